@@ -1,8 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
 
 use bevy::asset::AssetServer;
 use bevy::prelude::*;
 use bevy_kira_audio::AudioSource;
+use itertools::Itertools;
 use strum::IntoEnumIterator;
 
 use crate::core::map::planet::PlanetKind;
@@ -20,6 +22,8 @@ pub struct WorldAssets {
     pub fonts: HashMap<&'static str, Handle<Font>>,
     pub images: HashMap<&'static str, Handle<Image>>,
     pub textures: HashMap<&'static str, TextureInfo>,
+    /// Images requested without an asset, to warn only once per name
+    missing: Mutex<HashSet<String>>,
 }
 
 impl WorldAssets {
@@ -41,8 +45,20 @@ impl WorldAssets {
         self.get_asset(&self.fonts, name, "font").clone()
     }
 
+    /// Missing images fall back to Bevy's default (white) image instead of panicking
     pub fn image(&self, name: impl Into<String>) -> Handle<Image> {
-        self.get_asset(&self.images, name, "image").clone()
+        let name = name.into();
+        self.images.get(name.as_str()).cloned().unwrap_or_else(|| {
+            let mut missing = self.missing.lock().unwrap();
+            if missing.insert(name.clone()) {
+                warn!(
+                    "No asset for image {name}. Using a placeholder. Missing images: {}.",
+                    missing.iter().sorted().join(", ")
+                );
+            }
+
+            Handle::default()
+        })
     }
 
     pub fn texture(&self, name: impl Into<String>) -> TextureInfo {
@@ -151,10 +167,12 @@ impl FromWorld for WorldAssets {
             // Ships
             ("probe", assets.load("images/ships/probe.png")),
             ("colony ship", assets.load("images/ships/colony ship.png")),
+            ("tanker", assets.load("images/ships/tanker.png")),
             ("light fighter", assets.load("images/ships/light fighter.png")),
             ("heavy fighter", assets.load("images/ships/heavy fighter.png")),
             ("destroyer", assets.load("images/ships/destroyer.png")),
             ("cruiser", assets.load("images/ships/cruiser.png")),
+            ("ion frigate", assets.load("images/ships/ion frigate.png")),
             ("bomber", assets.load("images/ships/bomber.png")),
             ("battleship", assets.load("images/ships/battleship.png")),
             ("dreadnought", assets.load("images/ships/dreadnought.png")),
@@ -285,6 +303,27 @@ impl FromWorld for WorldAssets {
             fonts,
             images,
             textures,
+            missing: Mutex::new(HashSet::new()),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_images_return_the_placeholder() {
+        let assets = WorldAssets {
+            audio: HashMap::new(),
+            fonts: HashMap::new(),
+            images: HashMap::new(),
+            textures: HashMap::new(),
+            missing: Mutex::new(HashSet::new()),
+        };
+
+        assert_eq!(assets.image("unknown"), Handle::default());
+        assert_eq!(assets.image("unknown"), Handle::default());
+        assert_eq!(assets.missing.lock().unwrap().len(), 1);
+    }
+}
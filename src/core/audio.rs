@@ -5,7 +5,7 @@ use bevy::prelude::*;
 use bevy_kira_audio::prelude::*;
 
 use crate::core::assets::WorldAssets;
-use crate::core::constants::{NORMAL_BUTTON_COLOR, PRESSED_BUTTON_COLOR};
+use crate::core::constants::{MAX_COMBAT_SOUNDS, NORMAL_BUTTON_COLOR, PRESSED_BUTTON_COLOR};
 use crate::core::menu::settings::SettingsBtn;
 use crate::core::settings::Settings;
 use crate::core::states::{AudioState, GameState};
@@ -23,6 +23,10 @@ pub struct PlayAudioMsg {
     pub name: &'static str,
     pub volume: f32,
     pub is_background: bool,
+    /// Sound to play if there's no asset for `name`
+    pub fallback: Option<&'static str>,
+    /// Effects are dropped when too many of them are already playing
+    pub is_effect: bool,
 }
 
 impl PlayAudioMsg {
@@ -31,6 +35,8 @@ impl PlayAudioMsg {
             name,
             volume: PlayingAudio::DEFAULT_VOLUME,
             is_background: false,
+            fallback: None,
+            is_effect: false,
         }
     }
 
@@ -38,6 +44,16 @@ impl PlayAudioMsg {
         self.is_background = true;
         self
     }
+
+    pub fn or(mut self, fallback: &'static str) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+
+    pub fn effect(mut self) -> Self {
+        self.is_effect = true;
+        self
+    }
 }
 
 #[derive(Message, Clone)]
@@ -182,14 +198,34 @@ pub fn play_audio(
     audio_state: Res<State<AudioState>>,
     mut playing_audio: ResMut<PlayingAudio>,
     mut audio_instances: ResMut<Assets<AudioInstance>>,
+    mut effects: Local<Vec<Handle<AudioInstance>>>,
     audio: Res<Audio>,
     assets: Local<WorldAssets>,
 ) {
+    // Forget the effects that finished playing
+    effects.retain(|h| {
+        audio_instances.get(h).is_some_and(|i| !matches!(i.state(), PlaybackState::Stopped))
+    });
+
     for message in play_audio_msg.read() {
         if *audio_state.get() != AudioState::Mute {
+            // Use the fallback sound if the requested one has no asset
+            let name = if assets.audio.contains_key(message.name) {
+                message.name
+            } else if let Some(fallback) = message.fallback {
+                fallback
+            } else {
+                continue;
+            };
+
+            // Throttle effects so massive battles don't play hundreds of sounds at once
+            if message.is_effect && effects.len() >= MAX_COMBAT_SOUNDS {
+                continue;
+            }
+
             let mut new_sound = false;
 
-            if let Some(handle) = playing_audio.0.get(message.name) {
+            if let Some(handle) = playing_audio.0.get(name) {
                 if let Some(instance) = audio_instances.get_mut(handle) {
                     if matches!(
                         instance.state(),
@@ -212,9 +248,9 @@ pub fn play_audio(
             } else if message.is_background {
                 if *audio_state.get() != AudioState::NoMusic {
                     playing_audio.0.insert(
-                        message.name,
+                        name,
                         audio
-                            .play(assets.audio(message.name))
+                            .play(assets.audio(name))
                             .fade_in(PlayingAudio::TWEEN)
                             .with_volume(message.volume)
                             .looped()
@@ -226,10 +262,13 @@ pub fn play_audio(
             }
 
             if new_sound {
-                playing_audio.0.insert(
-                    message.name,
-                    audio.play(assets.audio(message.name)).with_volume(message.volume).handle(),
-                );
+                let handle = audio.play(assets.audio(name)).with_volume(message.volume).handle();
+
+                if message.is_effect {
+                    effects.push(handle.clone());
+                }
+
+                playing_audio.0.insert(name, handle);
             }
         }
     }
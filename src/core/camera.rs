@@ -5,6 +5,7 @@ use bevy_egui::EguiContexts;
 use crate::core::constants::{LERP_FACTOR, MAX_ZOOM, MIN_ZOOM, ZOOM_FACTOR};
 use crate::core::map::map::Map;
 use crate::core::map::systems::PlanetCmp;
+use crate::core::settings::Settings;
 use crate::core::ui::systems::UiState;
 
 #[derive(Component)]
@@ -39,6 +40,7 @@ pub fn move_camera(
     planet_q: Query<(&Transform, &PlanetCmp), (Without<MainCamera>, Without<ParallaxCmp>)>,
     mut parallax_q: Query<&mut Transform, (With<ParallaxCmp>, Without<MainCamera>)>,
     map: Res<Map>,
+    settings: Res<Settings>,
     mut state: ResMut<UiState>,
     mut scroll_msg: MessageReader<MouseWheel>,
     window: Single<&Window>,
@@ -85,6 +87,17 @@ pub fn move_camera(
                 position = position.lerp(pos.translation.truncate(), LERP_FACTOR);
             }
         }
+
+        if state.reset_zoom {
+            projection.scale = projection.scale.lerp(1., LERP_FACTOR);
+            if (projection.scale - 1.).abs() < 1e-3 {
+                projection.scale = 1.;
+                state.reset_zoom = false;
+            }
+        }
+    } else {
+        // The player took control of the camera
+        state.reset_zoom = false;
     }
 
     // Compute the camera's current view size based on projection
@@ -106,18 +119,30 @@ pub fn move_camera(
     camera_t.translation = position.extend(camera_t.translation.z);
 
     for mut parallax_t in parallax_q.iter_mut() {
-        parallax_t.translation.x = camera_t.translation.x / 1.2;
-        parallax_t.translation.y = camera_t.translation.y / 1.2;
+        let factor = if settings.parallax {
+            1.2
+        } else {
+            1.
+        };
+
+        parallax_t.translation.x = camera_t.translation.x / factor;
+        parallax_t.translation.y = camera_t.translation.y / factor;
 
         parallax_t.scale = 0.6 * camera_t.scale.powf(0.8);
     }
 }
 
 pub fn move_camera_keyboard(
+    context: EguiContexts,
     keyboard: Res<ButtonInput<KeyCode>>,
     mut camera_q: Query<(&mut Transform, &Projection), With<MainCamera>>,
     mut state: ResMut<UiState>,
 ) {
+    // Don't move while typing in a text field
+    if context.ctx().unwrap().wants_keyboard_input() {
+        return;
+    }
+
     let (mut camera_t, projection) = camera_q.single_mut().unwrap();
 
     let scale = if let Projection::Orthographic(projection) = projection {
@@ -5,10 +5,13 @@ use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 
 use crate::core::combat::report::{CombatReport, MissionReport, RoundReport, Side};
-use crate::core::constants::{CRAWLER_HEALING_PER_ROUND, PS_SHIELD_PER_LEVEL};
+use crate::core::constants::{
+    ANTIBALLISTIC_INTERCEPT_CHANCE, CRAWLER_HEALING_PER_ROUND, PS_SHIELD_PER_LEVEL, VETERANCY_BONUS,
+};
 use crate::core::map::icon::Icon;
-use crate::core::map::planet::Planet;
-use crate::core::missions::{BombingRaid, Mission};
+use crate::core::map::planet::{Planet, Stance};
+use crate::core::missions::{BombingRaid, Mission, Targeting};
+use crate::core::resources::Resources;
 use crate::core::units::ships::Ship;
 use crate::core::units::{Amount, Army, Combat, Unit};
 
@@ -46,7 +49,79 @@ impl CombatUnit {
     }
 }
 
-pub fn resolve_combat(turn: usize, mission: &Mission, destination: &Planet) -> MissionReport {
+/// Select the unit hit by a shot following the targeting policy
+fn select_target<'a>(
+    pool: impl Iterator<Item = &'a mut CombatUnit>,
+    targeting: Targeting,
+    rng: &mut impl Rng,
+) -> Option<&'a mut CombatUnit> {
+    if targeting == Targeting::Random {
+        return pool.choose(rng);
+    }
+
+    // Focused shots only miss when all units are already destroyed
+    let mut pool = pool.collect::<Vec<_>>();
+    if pool.iter().all(|cu| cu.hull == 0) {
+        return pool.into_iter().choose(rng);
+    }
+
+    pool.retain(|cu| cu.hull > 0);
+    match targeting {
+        Targeting::WeakestFirst => pool.into_iter().min_by_key(|cu| cu.hull + cu.shield),
+        Targeting::StrongestFirst => pool.into_iter().max_by_key(|cu| cu.hull + cu.shield),
+        _ => {
+            if pool.iter().any(|cu| cu.unit.is_defense()) {
+                pool.retain(|cu| cu.unit.is_defense());
+            }
+            pool.into_iter().choose(rng)
+        },
+    }
+}
+
+/// Damage of every shot of a unit. Veteran ships hit harder
+fn shot_damage(unit: &Unit, veterancy: usize) -> usize {
+    if unit.is_ship() {
+        unit.damage() * (100 + VETERANCY_BONUS * veterancy) / 100
+    } else {
+        unit.damage()
+    }
+}
+
+/// Fire the antiballistic missiles at an incoming interplanetary missile until
+/// one intercepts it, and return whether it was intercepted. Every antiballistic
+/// missile is fired at most once per combat
+fn intercept_missile(
+    defenders: &mut [CombatUnit],
+    used: &mut Vec<u64>,
+    rng: &mut impl Rng,
+) -> bool {
+    for cu in defenders.iter_mut() {
+        if cu.unit == Unit::antiballistic_missile() && !used.contains(&cu.id) {
+            used.push(cu.id);
+
+            let intercepted = rng.random::<f32>() < ANTIBALLISTIC_INTERCEPT_CHANCE;
+            cu.shots.push(ShotReport {
+                unit: Some(Unit::interplanetary_missile()),
+                killed: intercepted,
+                missed: !intercepted,
+                ..default()
+            });
+
+            if intercepted {
+                return true;
+            }
+        }
+    }
+
+    false
+}
+
+pub fn resolve_combat(
+    turn: usize,
+    mission: &Mission,
+    destination: &Planet,
+    max_rounds: usize,
+) -> MissionReport {
     if mission.objective == Icon::Deploy
         || (mission.objective == Icon::Colonize && destination.controlled == Some(mission.owner))
     {
@@ -56,6 +131,8 @@ pub fn resolve_combat(turn: usize, mission: &Mission, destination: &Planet) -> M
             mission: mission.clone(),
             planet: destination.clone(),
             scout_probes: 0,
+            intel_probes: 0,
+            leak_probes: (0, 0),
             surviving_attacker: mission.army.clone(),
             surviving_defender: destination.army.clone(),
             planet_colonized: mission.objective == Icon::Colonize,
@@ -63,6 +140,7 @@ pub fn resolve_combat(turn: usize, mission: &Mission, destination: &Planet) -> M
             destination_owned: destination.owned,
             destination_controlled: destination.controlled,
             combat_report: None,
+            plunder: Resources::default(),
             hidden: mission.origin_controlled != Some(mission.owner), // Hide returning probes or fleets
         };
     }
@@ -106,9 +184,17 @@ pub fn resolve_combat(turn: usize, mission: &Mission, destination: &Planet) -> M
 
     let mut round = 1;
     let mut returning_probes = 0;
+
+    // Spying probes gather intel on arrival and leave before the fight
+    if mission.objective == Icon::Spy {
+        returning_probes = attack_army.iter().filter(|u| u.unit == Unit::probe()).count();
+        attack_army.retain(|u| u.unit != Unit::probe());
+    }
     let mut used_antiballistic = vec![];
     let mut planet_destroyed = false;
-    while (!attack_army.is_empty() && !defend_army.is_empty()) || round == 1 {
+    while ((!attack_army.is_empty() && !defend_army.is_empty()) || round == 1)
+        && round <= max_rounds
+    {
         if attack_army.is_empty() && defend_army.is_empty() {
             // If there are no combat units, skip the battle
             break;
@@ -119,11 +205,23 @@ pub fn resolve_combat(turn: usize, mission: &Mission, destination: &Planet) -> M
                 continue;
             }
 
-            let (army, enemy_army) = match side {
-                Side::Attacker => (&mut attack_army, &mut defend_army),
-                Side::Defender => (&mut defend_army, &mut attack_army),
+            let (army, enemy_army, veterancy) = match side {
+                Side::Attacker => (&mut attack_army, &mut defend_army, mission.veterancy),
+                Side::Defender => (&mut defend_army, &mut attack_army, destination.veterancy),
+            };
+
+            // Only the attacker can choose a targeting policy
+            let targeting = match side {
+                Side::Attacker => mission.targeting,
+                Side::Defender => Targeting::Random,
             };
 
+            // Defenders can hold fire on probes that leave after the first round
+            let ignore_probes = side == Side::Defender
+                && destination.stance == Stance::IgnoreScouts
+                && round == 1
+                && !mission.combat_probes;
+
             // Reset all repairs, shots and defender's shields
             army.iter_mut().for_each(|u| {
                 u.repairs = vec![];
@@ -133,30 +231,13 @@ pub fn resolve_combat(turn: usize, mission: &Mission, destination: &Planet) -> M
 
             'unit: for unit in army {
                 // Intercept incoming missiles before resolving damage
-                if unit.unit == Unit::interplanetary_missile() {
-                    for cu in enemy_army.iter_mut() {
-                        if cu.unit == Unit::antiballistic_missile()
-                            && !used_antiballistic.contains(&cu.id)
-                        {
-                            used_antiballistic.push(cu.id);
-
-                            let mut shot = ShotReport::default();
-                            shot.unit = Some(Unit::interplanetary_missile());
-
-                            if rng.random::<f32>() < 0.5 {
-                                shot.killed = true;
-                                cu.shots.push(shot);
-                                continue 'unit;
-                            } else {
-                                shot.missed = true;
-                            }
-
-                            cu.shots.push(shot);
-                        }
-                    }
+                if unit.unit == Unit::interplanetary_missile()
+                    && intercept_missile(enemy_army, &mut used_antiballistic, &mut rng)
+                {
+                    continue 'unit;
                 }
 
-                let mut damage = unit.unit.damage();
+                let mut damage = shot_damage(&unit.unit, veterancy);
 
                 if damage == 0 {
                     // Skip the shooting (for probes or antiballistic missiles)
@@ -186,9 +267,13 @@ pub fn resolve_combat(turn: usize, mission: &Mission, destination: &Planet) -> M
                         planetary_shield -= shot.planetary_shield_damage;
                         shot.unit = Some(Unit::planetary_shield());
                         None
-                    } else if let Some(target) =
-                        enemy_army.iter_mut().filter(|cu| !cu.unit.is_missile()).choose(&mut rng)
-                    {
+                    } else if let Some(target) = select_target(
+                        enemy_army.iter_mut().filter(|cu| {
+                            !cu.unit.is_missile() && !(ignore_probes && cu.unit == Unit::probe())
+                        }),
+                        targeting,
+                        &mut rng,
+                    ) {
                         // If shooting on a defense, shoot on the planetary shield instead
                         if target.unit.is_defense()
                             && target.unit != Unit::space_dock()
@@ -218,7 +303,9 @@ pub fn resolve_combat(turn: usize, mission: &Mission, destination: &Planet) -> M
                     // Target could already been destroyed by another shot
                     if target.hull > 0 {
                         if target.shield > 0 {
-                            shot.shield_damage = damage.min(target.shield);
+                            // Shield-piercing damage can't be absorbed by the shield
+                            let pierced = damage * unit.unit.shield_piercing() / 100;
+                            shot.shield_damage = (damage - pierced).min(target.shield);
                             damage -= shot.shield_damage;
                             target.shield -= shot.shield_damage;
                         }
@@ -309,12 +396,9 @@ pub fn resolve_combat(turn: usize, mission: &Mission, destination: &Planet) -> M
         defend_army.retain(|u| u.hull > 0);
 
         if round == 1 {
-            // Send probes back if there are still remaining enemies or objective is spying
+            // Send probes back if there are still remaining enemies
             let probes = attack_army.iter().filter(|u| u.unit == Unit::probe()).count();
-            if ((!mission.combat_probes && !defend_army.is_empty())
-                || mission.objective == Icon::Spy)
-                && probes > 0
-            {
+            if !mission.combat_probes && !defend_army.is_empty() && probes > 0 {
                 attack_army.retain(|u| u.unit != Unit::probe());
                 returning_probes = probes;
             }
@@ -343,6 +427,9 @@ pub fn resolve_combat(turn: usize, mission: &Mission, destination: &Planet) -> M
         round += 1;
     }
 
+    // Both armies still standing after the last round results in a draw
+    combat_report.round_cap = !attack_army.is_empty() && !defend_army.is_empty();
+
     // Calculate the surviving units
     let mut surviving_attacker = attack_army.iter().fold(Army::new(), |mut army, cu| {
         *army.entry(cu.unit).or_insert(0) += 1;
@@ -358,7 +445,9 @@ pub fn resolve_combat(turn: usize, mission: &Mission, destination: &Planet) -> M
         // Add the non-combat ships to the attacker
         *surviving_attacker.entry(Unit::colony_ship()).or_insert(0) =
             mission.army.amount(&Unit::colony_ship());
-    } else {
+    }
+
+    if !surviving_defense.is_empty() && (attack_army.is_empty() || combat_report.round_cap) {
         // Add non-combat ships and the remaining missiles to the defender
         *surviving_defense.entry(Unit::colony_ship()).or_insert(0) =
             destination.army.amount(&Unit::colony_ship());
@@ -383,9 +472,13 @@ pub fn resolve_combat(turn: usize, mission: &Mission, destination: &Planet) -> M
         mission: mission.clone(),
         planet: destination.clone(),
         scout_probes: returning_probes,
+        intel_probes: returning_probes,
+        leak_probes: (0, 0), // Filled in turns.rs, depending on the game rules
         surviving_attacker,
         surviving_defender: surviving_defense,
-        planet_colonized: defend_army.is_empty() && mission.objective == Icon::Colonize,
+        planet_colonized: defend_army.is_empty()
+            && mission.objective == Icon::Colonize
+            && surviving_attacker.amount(&Unit::colony_ship()) > 0,
         planet_destroyed,
         destination_owned: None, // Filled in turns.rs after changes have been made to the planet
         destination_controlled: None, // Filled in turns.rs as well
@@ -396,6 +489,265 @@ pub fn resolve_combat(turn: usize, mission: &Mission, destination: &Planet) -> M
             .any(|cu| !cu.shots.is_empty())
             || mission.objective == Icon::Destroy)
             .then_some(combat_report),
+        plunder: Resources::default(),
         hidden: false,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::rngs::StdRng;
+    use rand::{RngCore, SeedableRng};
+
+    use super::*;
+    use crate::core::combat::fixtures::target_planet;
+    use crate::core::constants::MAX_VETERANCY;
+    use crate::core::units::defense::Defense;
+
+    /// Light Fighter (hull + shield of 32), Battleship (540), Rocket Launcher (82)
+    /// and Gauss Cannon (390)
+    fn targets() -> Vec<CombatUnit> {
+        [
+            Unit::Ship(Ship::LightFighter),
+            Unit::Ship(Ship::Battleship),
+            Unit::Defense(Defense::RocketLauncher),
+            Unit::Defense(Defense::GaussCannon),
+        ]
+        .iter()
+        .map(CombatUnit::new)
+        .collect()
+    }
+
+    fn target(units: &mut [CombatUnit], targeting: Targeting, seed: u64) -> Option<Unit> {
+        select_target(units.iter_mut(), targeting, &mut StdRng::seed_from_u64(seed))
+            .map(|cu| cu.unit)
+    }
+
+    #[test]
+    fn focused_targeting_picks_by_strength() {
+        let mut units = targets();
+        for seed in 0..20 {
+            let weakest = target(&mut units, Targeting::WeakestFirst, seed);
+            assert_eq!(weakest, Some(Unit::Ship(Ship::LightFighter)));
+            let strongest = target(&mut units, Targeting::StrongestFirst, seed);
+            assert_eq!(strongest, Some(Unit::Ship(Ship::Battleship)));
+        }
+
+        // Destroyed units are skipped, unless all of them are
+        units[0].hull = 0;
+        let weakest = target(&mut units, Targeting::WeakestFirst, 0);
+        assert_eq!(weakest, Some(Unit::Defense(Defense::RocketLauncher)));
+
+        units.iter_mut().for_each(|cu| cu.hull = 0);
+        assert!(target(&mut units, Targeting::WeakestFirst, 0).is_some());
+    }
+
+    #[test]
+    fn defenses_are_targeted_first() {
+        let mut units = targets();
+        for seed in 0..20 {
+            assert!(target(&mut units, Targeting::DefensesFirst, seed).unwrap().is_defense());
+        }
+
+        // Ships are targeted once the defenses are destroyed
+        units[2].hull = 0;
+        units[3].hull = 0;
+        for seed in 0..20 {
+            assert!(target(&mut units, Targeting::DefensesFirst, seed).unwrap().is_ship());
+        }
+    }
+
+    #[test]
+    fn random_targeting_hits_any_unit() {
+        let mut units = targets();
+        let hit = (0..100)
+            .filter_map(|seed| target(&mut units, Targeting::Random, seed))
+            .collect::<HashSet<_>>();
+        assert_eq!(hit.len(), units.len());
+
+        let first = target(&mut units, Targeting::Random, 7);
+        assert_eq!(first, target(&mut units, Targeting::Random, 7));
+    }
+
+    /// Rng that always draws the same number, to land on either side of a chance
+    struct FixedRng(u32);
+
+    impl RngCore for FixedRng {
+        fn next_u32(&mut self) -> u32 {
+            self.0
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            ((self.0 as u64) << 32) | self.0 as u64
+        }
+
+        fn fill_bytes(&mut self, dst: &mut [u8]) {
+            dst.fill(0);
+        }
+    }
+
+    #[test]
+    fn veteran_ships_hit_harder() {
+        // Every level of veterancy adds 5% damage
+        let cruiser = Unit::Ship(Ship::Cruiser);
+        assert_eq!(shot_damage(&cruiser, 0), 70);
+        assert_eq!(shot_damage(&cruiser, 2), 77);
+        assert_eq!(shot_damage(&cruiser, MAX_VETERANCY), 84);
+
+        // Defenses don't gain experience
+        let gauss = Unit::Defense(Defense::GaussCannon);
+        assert_eq!(shot_damage(&gauss, MAX_VETERANCY), gauss.damage());
+    }
+
+    #[test]
+    fn antiballistic_missiles_intercept_below_the_chance() {
+        let mut defenders = vec![CombatUnit::new(&Unit::antiballistic_missile()); 2];
+        defenders[1].id = defenders[0].id + 1;
+        let mut used = vec![];
+
+        // The lowest draw intercepts the missile with the first antiballistic
+        assert!(intercept_missile(&mut defenders, &mut used, &mut FixedRng(0)));
+        assert_eq!(used, vec![defenders[0].id]);
+        assert!(defenders[0].shots[0].killed);
+        assert!(defenders[1].shots.is_empty());
+
+        // The highest draw misses with every antiballistic left
+        assert!(!intercept_missile(&mut defenders, &mut used, &mut FixedRng(u32::MAX)));
+        assert!(defenders[1].shots[0].missed);
+
+        // Antiballistic missiles are only fired once
+        assert!(!intercept_missile(&mut defenders, &mut used, &mut FixedRng(0)));
+        assert_eq!(used.len(), 2);
+    }
+
+    #[test]
+    fn spy_probes_leave_before_combat() {
+        let planet = target_planet(Army::from([(Unit::Defense(Defense::GaussCannon), 20)]));
+
+        let mission = Mission {
+            owner: 1,
+            destination: 1,
+            objective: Icon::Spy,
+            army: Army::from([(Unit::probe(), 8)]),
+            ..Default::default()
+        };
+
+        let report = resolve_combat(1, &mission, &planet, 5);
+        assert_eq!(report.scout_probes, 8);
+        assert_eq!(report.intel_probes, 8);
+        assert_eq!(report.surviving_attacker.amount(&Unit::probe()), 8);
+        assert_eq!(report.surviving_defender.amount(&Unit::Defense(Defense::GaussCannon)), 20);
+    }
+
+    #[test]
+    fn scouts_are_ignored_in_the_first_round() {
+        let probe = Unit::probe();
+        let mut planet = Planet {
+            stance: Stance::IgnoreScouts,
+            ..target_planet(Army::from([(Unit::Defense(Defense::GaussCannon), 20)]))
+        };
+
+        let mut mission = Mission {
+            owner: 1,
+            destination: 1,
+            objective: Icon::Attack,
+            army: Army::from([(probe, 8)]),
+            ..Default::default()
+        };
+
+        let report = resolve_combat(1, &mission, &planet, 1);
+        assert_eq!(report.surviving_attacker.amount(&probe), 8);
+        assert_eq!(report.scout_probes, 8);
+
+        // A single Gauss Cannon shot destroys a probe, so any fire on them shows
+        mission.combat_probes = true;
+        let report = resolve_combat(1, &mission, &planet, 1);
+        assert!(report.surviving_attacker.amount(&probe) < 8);
+
+        mission.combat_probes = false;
+        planet.stance = Stance::FireAll;
+        let report = resolve_combat(1, &mission, &planet, 1);
+        assert!(report.surviving_attacker.amount(&probe) < 8);
+    }
+
+    #[test]
+    fn defenses_without_fleet_fight_back() {
+        let gauss = Unit::Defense(Defense::GaussCannon);
+        let mut planet = target_planet(Army::from([(gauss, 2), (Unit::planetary_shield(), 1)]));
+
+        let mut mission = Mission {
+            owner: 1,
+            destination: 1,
+            objective: Icon::Attack,
+            army: Army::from([(Unit::Ship(Ship::Cruiser), 3)]),
+            ..Default::default()
+        };
+
+        let hull = |round: &RoundReport| round.attacker.iter().map(|cu| cu.hull).sum::<usize>();
+
+        let report = resolve_combat(1, &mission, &planet, 1);
+        let round = &report.combat_report.unwrap().rounds[0];
+        assert!(round.planetary_shield < PS_SHIELD_PER_LEVEL);
+        assert!(round.defender.iter().all(|cu| !cu.shots.is_empty()));
+        assert!(hull(round) < 3 * Unit::Ship(Ship::Cruiser).hull());
+
+        // Turrets destroyed in the first round still fire before being removed
+        planet.army = Army::from([(gauss, 1)]);
+        mission.army = Army::from([(Unit::Ship(Ship::Cruiser), 20)]);
+        let report = resolve_combat(1, &mission, &planet, 1);
+        let round = &report.combat_report.unwrap().rounds[0];
+        assert_eq!(round.defender[0].hull, 0);
+        assert!(!round.defender[0].shots.is_empty());
+        assert!(hull(round) < 20 * Unit::Ship(Ship::Cruiser).hull());
+        assert_eq!(report.winner(), Some(1));
+    }
+
+    #[test]
+    fn stalemate_stops_at_the_round_cap() {
+        // The fighter's damage (5) never gets through the tanker's shield (10)
+        let planet = target_planet(Army::from([(Unit::Ship(Ship::Tanker), 1)]));
+
+        let mission = Mission {
+            owner: 1,
+            destination: 1,
+            objective: Icon::Attack,
+            army: Army::from([(Unit::Ship(Ship::LightFighter), 1)]),
+            ..Default::default()
+        };
+
+        let report = resolve_combat(1, &mission, &planet, 4);
+        let combat = report.combat_report.unwrap();
+        assert_eq!(combat.rounds.len(), 4);
+        assert!(combat.round_cap);
+        assert_eq!(report.surviving_attacker.amount(&Unit::Ship(Ship::LightFighter)), 1);
+        assert_eq!(report.surviving_defender.amount(&Unit::Ship(Ship::Tanker)), 1);
+    }
+
+    #[test]
+    fn shield_piercing_reaches_the_hull_of_a_shielded_target() {
+        // The Space Dock's shield (110) absorbs more than the frigate's damage (50)
+        let planet = target_planet(Army::from([(Unit::space_dock(), 1)]));
+
+        let mission = Mission {
+            owner: 1,
+            destination: 1,
+            objective: Icon::Attack,
+            army: Army::from([(Unit::Ship(Ship::IonFrigate), 1)]),
+            ..Default::default()
+        };
+
+        let report = resolve_combat(1, &mission, &planet, 1);
+        let round = &report.combat_report.unwrap().rounds[0];
+        let shots = &round.attacker[0].shots;
+        assert!(!shots.is_empty());
+
+        // 40% of the damage pierces the shield
+        for shot in shots {
+            assert_eq!(shot.shield_damage, 30);
+            assert_eq!(shot.hull_damage, 20);
+        }
+        assert!(round.defender[0].hull < Unit::space_dock().hull());
+    }
+}
@@ -2,3 +2,21 @@ pub mod combat;
 pub mod report;
 pub mod stats;
 pub mod systems;
+
+#[cfg(test)]
+mod fixtures {
+    use bevy::math::Vec2;
+
+    use crate::core::map::planet::Planet;
+    use crate::core::settings::ResourceBias;
+    use crate::core::units::Army;
+
+    /// Planet 1, controlled by player 2 and defended by `army`
+    pub fn target_planet(army: Army) -> Planet {
+        Planet {
+            controlled: Some(2),
+            army,
+            ..Planet::new(1, "Target".into(), Vec2::ZERO, false, 1., ResourceBias::None)
+        }
+    }
+}
@@ -3,10 +3,12 @@ use serde::{Deserialize, Serialize};
 use strum_macros::EnumIter;
 
 use crate::core::combat::combat::CombatUnit;
+use crate::core::constants::PROBES_PER_PRODUCTION_LEVEL;
 use crate::core::map::icon::Icon;
 use crate::core::map::planet::Planet;
 use crate::core::missions::Mission;
 use crate::core::player::Player;
+use crate::core::resources::Resources;
 use crate::core::units::{Army, Unit};
 
 #[derive(Clone, Serialize, Deserialize)]
@@ -26,6 +28,14 @@ pub struct MissionReport {
     /// Number of attacking probes that left after one round of combat
     pub scout_probes: usize,
 
+    /// Number of probes that gathered intel on the planet's starting units. On spy
+    /// missions, every probe that arrives gathers intel before combat starts
+    pub intel_probes: usize,
+
+    /// Intel gathered by the side that lost the combat, expressed in probes
+    /// (attacker, defender). Only non-zero with the intel leak game rule
+    pub leak_probes: (usize, usize),
+
     /// Surviving units from the attacker
     pub surviving_attacker: Army,
 
@@ -47,11 +57,73 @@ pub struct MissionReport {
     /// Combat report (if combat took place)
     pub combat_report: Option<CombatReport>,
 
+    /// Resources the attacker took from the defender's stockpile
+    pub plunder: Resources,
+
     /// Whether to show this report in the report mission tab
     pub hidden: bool,
 }
 
 impl MissionReport {
+    /// Whether the probes reveal the starting amount of `unit` on the planet. Every
+    /// production level requires 5 more probes: one probe reveals the units with
+    /// production 1, six probes those with production 2, eleven probes those with
+    /// production 3, etc. A Sensor Phalanx of level n works like 5n probes, which
+    /// reveals the units with production <= n. Attackers that lost the combat add the
+    /// probes of the intel leak
+    pub fn reveals(&self, unit: &Unit) -> bool {
+        let leak = if self.attacker_lost() {
+            self.leak_probes.0
+        } else {
+            0
+        };
+
+        self.intel_probes + leak > (unit.production() - 1) * PROBES_PER_PRODUCTION_LEVEL
+    }
+
+    /// Whether a defender that lost the combat learns the starting amount of the
+    /// attacker's `unit`. Follows the same production levels as the probes
+    pub fn leaks(&self, unit: &Unit) -> bool {
+        self.defender_lost()
+            && self.leak_probes.1 > (unit.production() - 1) * PROBES_PER_PRODUCTION_LEVEL
+    }
+
+    /// Fill the intel leak of the side that lost the combat, where `percentage` of
+    /// its units gather intel as probes. A losing attacker leaks through its scout
+    /// probes that left the combat, a losing defender through its units that fought
+    pub fn apply_intel_leak(&mut self, percentage: usize) {
+        self.leak_probes = (0, 0);
+
+        if self.attacker_lost() {
+            self.leak_probes.0 = self.scout_probes * percentage / 100;
+        } else if self.defender_lost() {
+            let fought = self
+                .planet
+                .army
+                .iter()
+                .filter(|(u, _)| !u.is_building() && **u != Unit::probe())
+                .map(|(_, c)| c)
+                .sum::<usize>();
+
+            self.leak_probes.1 = fought * percentage / 100;
+        }
+    }
+
+    /// Whether a combat took place that can be lost (no spy or missile strike)
+    fn is_battle(&self) -> bool {
+        self.combat_report.is_some()
+            && !self.is_draw()
+            && !matches!(self.mission.objective, Icon::Spy | Icon::MissileStrike)
+    }
+
+    fn attacker_lost(&self) -> bool {
+        self.is_battle() && self.winner() != Some(self.mission.owner)
+    }
+
+    fn defender_lost(&self) -> bool {
+        self.is_battle() && self.winner() == Some(self.mission.owner)
+    }
+
     pub fn winner(&self) -> Option<ClientId> {
         match self.mission.objective {
             Icon::Spy if self.scout_probes > 0 => None,
@@ -63,6 +135,7 @@ impl MissionReport {
                     None
                 }
             },
+            _ if self.is_draw() => None,
             _ => {
                 if self.surviving_attacker.iter().any(|(u, c)| {
                     if *u == Unit::probe() {
@@ -79,6 +152,11 @@ impl MissionReport {
         }
     }
 
+    /// Whether the combat ended with both sides standing after the last round
+    pub fn is_draw(&self) -> bool {
+        self.combat_report.as_ref().is_some_and(|c| c.round_cap)
+    }
+
     pub fn status(&self, player: &Player) -> &'static str {
         match self.winner() {
             None => "draw",
@@ -113,7 +191,7 @@ impl MissionReport {
 
 pub type ReportId = u64;
 
-#[derive(EnumIter, Clone, Debug, PartialEq)]
+#[derive(EnumIter, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Side {
     Attacker,
     Defender,
@@ -131,6 +209,8 @@ impl Side {
 #[derive(Clone, Default, Serialize, Deserialize)]
 pub struct CombatReport {
     pub rounds: Vec<RoundReport>,
+    /// Whether the combat was stopped by the maximum number of rounds
+    pub round_cap: bool,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize)]
@@ -168,3 +248,150 @@ impl RoundReport {
             .count()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::combat::fixtures::target_planet;
+    use crate::core::units::defense::Defense;
+    use crate::core::units::ships::Ship;
+
+    const GAUSS: Unit = Unit::Defense(Defense::GaussCannon);
+    const CRUISER: Unit = Unit::Ship(Ship::Cruiser);
+
+    /// Report of an attack by player 1 with 10 probes (that left after one round),
+    /// 10 light fighters and 2 cruisers on planet 1, controlled by player 2
+    fn report(attacker_won: bool) -> MissionReport {
+        let planet = target_planet(Army::from([
+            (Unit::Defense(Defense::RocketLauncher), 20),
+            (GAUSS, 2),
+            (Unit::Ship(Ship::Battleship), 1),
+        ]));
+
+        let mission = Mission {
+            owner: 1,
+            destination: 1,
+            objective: Icon::Attack,
+            army: Army::from([
+                (Unit::probe(), 10),
+                (Unit::Ship(Ship::LightFighter), 10),
+                (CRUISER, 2),
+            ]),
+            ..Default::default()
+        };
+
+        MissionReport {
+            id: 0,
+            turn: 1,
+            mission,
+            planet: planet.clone(),
+            scout_probes: 10,
+            intel_probes: 10,
+            leak_probes: (0, 0),
+            surviving_attacker: if attacker_won {
+                Army::from([(Unit::probe(), 10), (Unit::Ship(Ship::LightFighter), 5)])
+            } else {
+                Army::from([(Unit::probe(), 10)])
+            },
+            surviving_defender: if attacker_won {
+                Army::new()
+            } else {
+                planet.army.clone()
+            },
+            planet_colonized: false,
+            planet_destroyed: false,
+            destination_owned: None,
+            destination_controlled: Some(if attacker_won {
+                1
+            } else {
+                2
+            }),
+            combat_report: Some(CombatReport::default()),
+            plunder: Resources::default(),
+            hidden: false,
+        }
+    }
+
+    #[test]
+    fn one_more_probe_reveals_the_next_production_level() {
+        let mut report = report(true);
+        let destroyer = Unit::Ship(Ship::Destroyer);
+
+        report.intel_probes = 5;
+        assert!(report.reveals(&Unit::Ship(Ship::LightFighter)));
+        assert!(!report.reveals(&destroyer));
+
+        report.intel_probes = 6;
+        assert!(report.reveals(&destroyer));
+        assert!(!report.reveals(&CRUISER));
+
+        report.intel_probes = 11;
+        assert!(report.reveals(&CRUISER));
+    }
+
+    #[test]
+    fn losing_attacker_sees_more_with_the_intel_leak() {
+        let mut report = report(false);
+        assert!(!report.reveals(&GAUSS));
+
+        // 25% of the 10 scout probes add up to 12 probes, enough for production 3
+        report.apply_intel_leak(25);
+        assert_eq!(report.leak_probes, (2, 0));
+        assert!(report.reveals(&GAUSS));
+        assert!(!report.reveals(&Unit::Ship(Ship::Battleship)));
+        assert!(!report.leaks(&Unit::Ship(Ship::LightFighter)));
+    }
+
+    #[test]
+    fn intel_leak_reaches_the_last_info_of_the_attacker() {
+        let mut report = report(false);
+        let planet = report.planet.clone();
+        let mut player = Player {
+            id: 1,
+            ..Default::default()
+        };
+
+        player.reports.push(report.clone());
+        assert_eq!(player.last_info(&planet, &vec![]).unwrap().army.get(&GAUSS), None);
+
+        report.apply_intel_leak(25);
+        player.reports = vec![report];
+        assert_eq!(player.last_info(&planet, &vec![]).unwrap().army.get(&GAUSS), Some(&2));
+    }
+
+    #[test]
+    fn losing_defender_sees_part_of_the_attacker() {
+        let mut report = report(true);
+        let player = Player {
+            id: 2,
+            ..Default::default()
+        };
+        assert!(!report.can_see(&Side::Attacker, player.id));
+
+        // 25% of the 23 units that fought leak as 5 probes, enough for production 1
+        report.apply_intel_leak(25);
+        assert_eq!(report.leak_probes, (0, 5));
+        assert!(report.leaks(&Unit::Ship(Ship::LightFighter)));
+        assert!(!report.leaks(&CRUISER));
+        assert!(!report.reveals(&CRUISER));
+    }
+
+    #[test]
+    fn intel_leak_only_applies_to_the_losing_side() {
+        let mut report = report(true);
+        report.leak_probes = (50, 0);
+        assert!(!report.reveals(&Unit::Ship(Ship::Battleship)));
+
+        let mut report = self::report(false);
+        report.leak_probes = (0, 50);
+        assert!(!report.leaks(&CRUISER));
+
+        // A draw leaks nothing
+        report.combat_report = Some(CombatReport {
+            round_cap: true,
+            ..Default::default()
+        });
+        report.apply_intel_leak(25);
+        assert_eq!(report.leak_probes, (0, 0));
+    }
+}
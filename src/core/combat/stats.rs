@@ -28,13 +28,20 @@ impl Description for CombatStats {
                 The amount of damage a unit absorbs before it starts taking hull damage. The \
                 shield is regenerated every round."
             },
-            CombatStats::Damage => "The amount of damage a unit deals per round.",
+            CombatStats::Damage => {
+                "\
+                The amount of damage a unit deals per shot. A unit shoots once per round, plus \
+                the extra shots gained from rapid fire."
+            },
             CombatStats::Speed => "The speed at which a unit travels through space (in AU / turn).",
             CombatStats::FuelConsumption => {
                 "The amount of deuterium a unit requires to travel 1 AU."
             },
             CombatStats::RapidFire => {
-                "The percentage probability to fire again this round when targeting specific units."
+                "\
+                The percentage probability to stop firing after a shot on specific units. After \
+                every shot, the unit fires again with a probability of 100% minus this value. \
+                Against other units, it always stops after the shot."
             },
         }
     }
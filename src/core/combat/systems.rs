@@ -7,6 +7,7 @@ use bevy_tweening::{
     AnimCompletedEvent, CycleCompletedEvent, Delay, PlaybackState, RepeatCount, RepeatStrategy,
     Tween, TweenAnim,
 };
+use itertools::Itertools;
 use rand::{rng, Rng};
 use strum::IntoEnumIterator;
 
@@ -16,8 +17,9 @@ use crate::core::camera::MainCamera;
 use crate::core::combat::combat::ShotReport;
 use crate::core::combat::report::Side;
 use crate::core::constants::{
-    BG2_COLOR, COMBAT_BACKGROUND_Z, COMBAT_EXPLOSION_Z, COMBAT_SHIP_Z, ENEMY_COLOR, OWN_COLOR,
-    PS_SHIELD_PER_LEVEL, PS_WIDTH, SETUP_TIME, SHIELD_COLOR, UNIT_SIZE,
+    BG2_COLOR, COMBAT_BACKGROUND_Z, COMBAT_EXPLOSION_Z, COMBAT_SHIP_Z, HULL_DAMAGE_COLOR,
+    LITE_ROUND_DURATION, MAX_DAMAGE_NUMBERS, PS_SHIELD_PER_LEVEL, PS_WIDTH, SETUP_TIME,
+    SHIELD_COLOR, UNIT_SIZE,
 };
 use crate::core::map::icon::Icon;
 use crate::core::map::map::Map;
@@ -26,14 +28,14 @@ use crate::core::map::utils::{
 };
 use crate::core::menu::utils::{add_root_node, add_text};
 use crate::core::missions::BombingRaid;
-use crate::core::player::Player;
+use crate::core::player::{Player, Roster};
 use crate::core::settings::Settings;
 use crate::core::states::{CombatState, GameState};
 use crate::core::turns::StartTurnMsg;
 use crate::core::ui::systems::UiState;
 use crate::core::units::ships::Ship;
-use crate::core::units::{Amount, Combat, Unit};
-use crate::utils::{scale_duration, NameFromEnum};
+use crate::core::units::{Amount, Combat, Unit, WeaponClass};
+use crate::utils::{scale_duration, FmtNumb, NameFromEnum};
 
 #[derive(Component)]
 pub struct CombatMenuCmp;
@@ -100,10 +102,17 @@ pub struct DeathRayCmp;
 #[derive(Message)]
 pub struct SpawnShotMsg {
     shot: ShotReport,
+    kills: usize,
     repair: bool,
     side: Side,
+    /// Weapon of the firing unit, used for the impact sound
+    weapon: Option<WeaponClass>,
 }
 
+/// Number of kills of a (possibly merged) shot
+#[derive(Component)]
+pub struct ShotKillsCmp(pub usize);
+
 #[derive(Component)]
 pub struct RepairCmp {
     pub unit: Unit,
@@ -144,12 +153,41 @@ pub fn exit_combat_menu(
     stop_audio_msg.write(StopAudioMsg::new("drums"));
 }
 
+/// Run condition for the sprite battlefield (as opposed to the lite ticker)
+pub fn sprite_combat(settings: Res<Settings>) -> bool {
+    !settings.lite_combat
+}
+
+/// Advance the rounds shown by the lite combat ticker
+pub fn advance_lite_combat(
+    mut state: ResMut<UiState>,
+    player: Res<Player>,
+    settings: Res<Settings>,
+    mut timer: Local<Timer>,
+    time: Res<Time>,
+) {
+    let report = player.reports.iter().find(|r| r.id == state.in_combat.unwrap()).unwrap();
+    let n_rounds = report.combat_report.as_ref().map(|c| c.rounds.len()).unwrap_or(0);
+
+    if state.combat_round == 0 {
+        state.combat_round = 1;
+        *timer = Timer::from_seconds(LITE_ROUND_DURATION, TimerMode::Repeating);
+    }
+
+    timer.tick(time.delta().mul_f32(settings.speed()));
+
+    if timer.just_finished() && state.combat_round < n_rounds {
+        state.combat_round += 1;
+    }
+}
+
 pub fn setup_combat(
     mut commands: Commands,
     settings: Res<Settings>,
     state: Res<UiState>,
     map: Res<Map>,
     player: Res<Player>,
+    roster: Res<Roster>,
     mut play_audio_msg: MessageWriter<PlayAudioMsg>,
     camera: Single<(&Transform, &Projection), With<MainCamera>>,
     window: Single<&Window>,
@@ -275,7 +313,7 @@ pub fn setup_combat(
                 ],
                 TweenAnim::new(Tween::new(
                     EaseFunction::QuadraticInOut,
-                    Duration::from_secs(SETUP_TIME),
+                    settings.combat_setup.duration(),
                     TransformPositionLens {
                         start: Vec3::new(pos.x, y_start, COMBAT_SHIP_Z),
                         end: Vec3::new(pos.x + x, y_end, COMBAT_SHIP_Z),
@@ -287,11 +325,7 @@ pub fn setup_combat(
         }
     };
 
-    let (attack_c, defend_c) = if report.mission.owner == player.id {
-        (OWN_COLOR, ENEMY_COLOR)
-    } else {
-        (ENEMY_COLOR, OWN_COLOR)
-    };
+    let (attack_c, defend_c) = roster.report_colors(report, &player, &settings);
 
     let attacking = Unit::all()
         .into_iter()
@@ -426,7 +460,7 @@ pub fn setup_combat(
             ],
             TweenAnim::new(Tween::new(
                 EaseFunction::QuadraticInOut,
-                Duration::from_secs(SETUP_TIME),
+                settings.combat_setup.duration(),
                 TransformPositionLens {
                     start: Vec3::new(pos.x, pos.y - height * 0.7, COMBAT_SHIP_Z),
                     end: Vec3::new(pos.x, pos.y - height * 0.25, COMBAT_SHIP_Z),
@@ -502,7 +536,7 @@ pub fn setup_combat(
                 )],
                 TweenAnim::new(Tween::new(
                     EaseFunction::QuadraticInOut,
-                    Duration::from_secs(SETUP_TIME),
+                    settings.combat_setup.duration(),
                     TransformPositionLens {
                         start: Vec3::new(pos.x, pos.y - height * 0.7, COMBAT_SHIP_Z),
                         end: Vec3::new(
@@ -532,9 +566,64 @@ pub fn setup_combat(
 
     spawn_main_button(&mut commands, "Exit combat", &assets)
         .insert((ZIndex(6), CombatCmp))
-        .observe(|_: On<Pointer<Click>>, mut next_game_state: ResMut<NextState<GameState>>| {
-            next_game_state.set(GameState::CombatMenu);
-        });
+        .observe(
+            |_: On<Pointer<Click>>,
+             settings: Res<Settings>,
+             mut next_game_state: ResMut<NextState<GameState>>| {
+                // Auto-resolved combats are watched from the report, so return to the map
+                next_game_state.set(if settings.auto_resolve {
+                    GameState::Playing
+                } else {
+                    GameState::CombatMenu
+                });
+            },
+        );
+}
+
+/// Merge the shots of a volley so that at most `max` shots (per target and
+/// hit/miss) are animated. The total damage and kills remain the same
+fn batch_shots(shots: Vec<ShotReport>, max: Option<usize>) -> Vec<(ShotReport, usize)> {
+    let Some(max) = max.filter(|m| shots.len() > *m) else {
+        return shots
+            .into_iter()
+            .map(|s| {
+                let kills = s.killed as usize;
+                (s, kills)
+            })
+            .collect();
+    };
+
+    let size = shots.len().div_ceil(max.max(1));
+    shots
+        .into_iter()
+        .into_group_map_by(|s| (s.unit, s.missed))
+        .into_values()
+        .flat_map(|group| {
+            group
+                .chunks(size)
+                .map(|chunk| {
+                    chunk.iter().fold(
+                        (
+                            ShotReport {
+                                unit: chunk[0].unit,
+                                missed: chunk[0].missed,
+                                ..default()
+                            },
+                            0,
+                        ),
+                        |(mut shot, kills), s| {
+                            shot.shield_damage += s.shield_damage;
+                            shot.hull_damage += s.hull_damage;
+                            shot.planetary_shield_damage += s.planetary_shield_damage;
+                            shot.killed |= s.killed;
+                            shot.rapid_fire |= s.rapid_fire;
+                            (shot, kills + s.killed as usize)
+                        },
+                    )
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
 }
 
 pub fn animate_combat(
@@ -550,6 +639,7 @@ pub fn animate_combat(
     mut spawn_shot_msg: MessageWriter<SpawnShotMsg>,
     mut play_audio_msg: MessageWriter<PlayAudioMsg>,
     mut anim_completed_msg: MessageReader<AnimCompletedEvent>,
+    settings: Res<Settings>,
     camera: Single<(&Transform, &Projection), With<MainCamera>>,
     assets: Local<WorldAssets>,
 ) {
@@ -580,14 +670,16 @@ pub fn animate_combat(
     ) && unit_q.iter().all(|(_, _, cu)| matches!(cu.fire, FireState::Idle | FireState::Fired))
     {
         'side: for side in Side::iter() {
-            // If all enemy units are destroyed, end combat prematurely
-            // (to avoid all misses of the remainder of units)
+            // If all enemy units are destroyed, this side stops firing (to avoid all
+            // misses of the remainder of units). The other side still fires, since
+            // units destroyed this round shoot before being removed (e.g., defense
+            // turrets on a planet without fleet)
             if unit_q
                 .iter()
                 .filter(|(_, _, cu)| cu.side == side.opposite() && !cu.unit.is_building())
                 .all(|(_, _, cu)| cu.hull == 0)
             {
-                break 'side;
+                continue 'side;
             }
 
             // Select the next unit that should fire
@@ -814,22 +906,26 @@ pub fn animate_combat(
                         }
                     },
                     FireState::Firing if *combat_state.get() == CombatState::Repair => {
+                        // Hack the repair info into the shot report for code simplicity
                         let repaired = round
                             .units(&cu.side)
                             .iter()
-                            .flat_map(|cu2| cu2.repairs.iter().map(move |r| (cu2.unit, r)))
+                            .flat_map(|cu2| {
+                                cu2.repairs.iter().map(|r| ShotReport {
+                                    unit: Some(cu2.unit),
+                                    hull_damage: *r,
+                                    ..default()
+                                })
+                            })
                             .collect::<Vec<_>>();
 
-                        for (unit, repair) in repaired {
-                            // Hack the repair info into the shot report for code simplicity
+                        for (shot, kills) in batch_shots(repaired, settings.max_shots) {
                             spawn_shot_msg.write(SpawnShotMsg {
-                                shot: ShotReport {
-                                    unit: Some(unit),
-                                    hull_damage: *repair,
-                                    ..default()
-                                },
+                                shot,
+                                kills,
                                 repair: true,
                                 side: cu.side.clone(),
+                                weapon: None,
                             });
                         }
 
@@ -924,13 +1020,22 @@ pub fn animate_combat(
                                     u.is_building() && u != Unit::planetary_shield()
                                 }) == (*combat_state.get() == CombatState::Bomb)
                             })
+                            .cloned()
                             .collect::<Vec<_>>();
 
-                        for shot in shots {
+                        // One firing sound per volley, not per shot
+                        let weapon = cu.unit.weapon_class().filter(|_| settings.combat_sounds);
+                        if let Some(weapon) = weapon.filter(|_| !shots.is_empty()) {
+                            play_audio_msg.write(PlayAudioMsg::new(weapon.fire_sound()).effect());
+                        }
+
+                        for (shot, kills) in batch_shots(shots, settings.max_shots) {
                             spawn_shot_msg.write(SpawnShotMsg {
-                                shot: shot.clone(),
+                                shot,
+                                kills,
                                 repair: false,
                                 side: cu.side.opposite(),
+                                weapon,
                             });
                         }
 
@@ -1033,7 +1138,7 @@ pub fn update_combat_stats(
 
     // Update speed indicator
     anim_q.iter_mut().for_each(|mut t| {
-        if settings.combat_paused {
+        if settings.combat_paused || settings.paused {
             t.playback_state = PlaybackState::Paused;
         } else {
             t.playback_state = PlaybackState::Playing;
@@ -1044,7 +1149,7 @@ pub fn update_combat_stats(
     speed_q.as_mut().0 = format!(
         "{}x{}",
         settings.combat_speed,
-        if settings.combat_paused {
+        if settings.combat_paused || settings.paused {
             " - paused"
         } else {
             ""
@@ -1138,7 +1243,12 @@ pub fn update_combat_stats(
 
 pub fn run_combat_animations(
     mut commands: Commands,
-    mut animation_q: Query<(Entity, &mut Sprite, Option<&ShotReport>, &mut UnitExplosionCmp)>,
+    mut animation_q: Query<(
+        Entity,
+        &mut Sprite,
+        Option<(&ShotReport, &ShotKillsCmp)>,
+        &mut UnitExplosionCmp,
+    )>,
     mut unit_q: Query<(Entity, &Sprite, &Transform, &mut CombatUnitCmp), Without<UnitExplosionCmp>>,
     mut repair_q: Query<(Entity, &RepairCmp)>,
     ps_image_q: Query<
@@ -1161,6 +1271,9 @@ pub fn run_combat_animations(
 
     let short_explosion = assets.texture("short explosion");
 
+    // Throttle the damage numbers, so huge battles don't fill the screen with text
+    let mut n_numbers = 0;
+
     // Spawn shot/repair explosions
     for message in spawn_shot_msg.read() {
         let target = unit_q
@@ -1240,7 +1353,82 @@ pub fn run_combat_animations(
                     ))
                     .id()
             } else {
-                play_audio_msg.write(PlayAudioMsg::new("short explosion"));
+                let sound = match message.weapon {
+                    Some(weapon) => PlayAudioMsg::new(weapon.impact_sound()).or("short explosion"),
+                    None => PlayAudioMsg::new("short explosion"),
+                };
+                play_audio_msg.write(sound.effect());
+
+                let shot = &message.shot;
+                let is_building =
+                    shot.unit.is_some_and(|u| u.is_building() && u != Unit::planetary_shield());
+
+                if settings.damage_numbers && !is_building {
+                    let numbers = [
+                        (shot.shield_damage + shot.planetary_shield_damage, SHIELD_COLOR),
+                        (shot.hull_damage, HULL_DAMAGE_COLOR),
+                    ];
+
+                    for (i, (damage, color)) in
+                        numbers.into_iter().filter(|(d, _)| *d > 0).enumerate()
+                    {
+                        if n_numbers == MAX_DAMAGE_NUMBERS {
+                            break;
+                        }
+
+                        n_numbers += 1;
+
+                        let start = Vec3::new(
+                            target_t.translation.x + rng.random_range(-size * 0.3..size * 0.3),
+                            target_t.translation.y + size * (0.2 - 0.15 * i as f32),
+                            COMBAT_EXPLOSION_Z + 0.1,
+                        );
+
+                        commands.spawn((
+                            Text2d::new(format!("-{}", damage.fmt())),
+                            TextFont {
+                                font: assets.font("bold"),
+                                font_size: 400. * projection.scale,
+                                ..default()
+                            },
+                            TextColor(color),
+                            Transform {
+                                translation: start,
+                                scale: Vec3::splat(0.),
+                                ..default()
+                            },
+                            TweenAnim::new(
+                                Delay::new(Duration::from_millis(rng.random_range(1..500)))
+                                    .then(Tween::new(
+                                        EaseFunction::QuadraticOut,
+                                        Duration::from_millis(250),
+                                        TransformScaleLens {
+                                            start: Vec3::splat(0.),
+                                            end: Vec3::splat(0.05),
+                                        },
+                                    ))
+                                    .then(Tween::new(
+                                        EaseFunction::QuadraticOut,
+                                        Duration::from_millis(1000),
+                                        TransformPositionLens {
+                                            start,
+                                            end: start + Vec3::Y * size * 0.4,
+                                        },
+                                    ))
+                                    .then(Tween::new(
+                                        EaseFunction::QuadraticIn,
+                                        Duration::from_millis(250),
+                                        TransformScaleLens {
+                                            start: Vec3::splat(0.05),
+                                            end: Vec3::splat(0.),
+                                        },
+                                    )),
+                            ),
+                            CombatCmp,
+                        ));
+                    }
+                }
+
                 commands
                     .spawn((
                         Sprite {
@@ -1256,6 +1444,7 @@ pub fn run_combat_animations(
                             target_entity: target_e,
                         },
                         message.shot.clone(),
+                        ShotKillsCmp(message.kills),
                     ))
                     .id()
             };
@@ -1308,12 +1497,18 @@ pub fn run_combat_animations(
 
         animation.timer.tick(scale_duration(time.delta(), settings.speed()));
 
-        if animation.timer.just_finished() {
+        // Advance a frame per elapsed period, so the animations keep their speed at low
+        // frame rates (or high combat speeds) where a single tick spans multiple periods
+        for _ in 0..animation.timer.times_finished_this_tick() {
             if let Some(atlas) = &mut sprite.texture_atlas {
+                if atlas.index == animation.last_index {
+                    break;
+                }
+
                 atlas.index += 1;
 
                 // Resolve damage at 1/5 of the animation
-                if let Some(shot) = shot {
+                if let Some((shot, ShotKillsCmp(kills))) = shot {
                     if atlas.index == animation.last_index / 5 {
                         if let Ok((unit_e, _, _, mut cu)) = unit_q.get_mut(animation.target_entity)
                         {
@@ -1355,9 +1550,7 @@ pub fn run_combat_animations(
                                     play_audio_msg.write(PlayAudioMsg::new("explosion"));
                                 }
                             } else if cu.unit.is_building() {
-                                if shot.killed {
-                                    cu.hull -= 1;
-                                }
+                                cu.hull -= *kills;
                             } else {
                                 cu.shield -= shot.shield_damage;
                                 cu.hull -= shot.hull_damage;
@@ -1382,7 +1575,64 @@ pub fn exit_combat(
     mut next_combat_state: ResMut<NextState<CombatState>>,
     mut mute_audio_msg: MessageWriter<MuteAudioMsg>,
 ) {
+    state.in_combat = None;
     state.combat_round = 0;
     mute_audio_msg.write(MuteAudioMsg);
     next_combat_state.set(CombatState::default());
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Volley of 40 shots on two targets, a kill every third hit and some misses
+    fn volley() -> Vec<ShotReport> {
+        (0..40)
+            .map(|i| ShotReport {
+                unit: Some(Unit::Ship(if i % 2 == 0 {
+                    Ship::Cruiser
+                } else {
+                    Ship::Destroyer
+                })),
+                shield_damage: 10,
+                hull_damage: if i % 5 == 0 {
+                    0
+                } else {
+                    60
+                },
+                missed: i % 5 == 0,
+                killed: i % 5 != 0 && i % 3 == 0,
+                ..default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn batched_shots_keep_the_kills_and_damage() {
+        let shots = volley();
+        let kills = shots.iter().filter(|s| s.killed).count();
+        let hull = shots.iter().map(|s| s.hull_damage).sum::<usize>();
+        let shield = shots.iter().map(|s| s.shield_damage).sum::<usize>();
+
+        let batched = batch_shots(shots, Some(5));
+        assert!(batched.len() < 40);
+        assert_eq!(batched.iter().map(|(_, k)| k).sum::<usize>(), kills);
+        assert_eq!(batched.iter().map(|(s, _)| s.hull_damage).sum::<usize>(), hull);
+        assert_eq!(batched.iter().map(|(s, _)| s.shield_damage).sum::<usize>(), shield);
+
+        // Hits and misses are never merged together
+        assert!(batched.iter().all(|(s, k)| !s.missed || (*k == 0 && s.hull_damage == 0)));
+    }
+
+    #[test]
+    fn small_volleys_are_not_batched() {
+        let shots = volley();
+        let kills = shots.iter().filter(|s| s.killed).count();
+
+        let batched = batch_shots(shots, None);
+        assert_eq!(batched.len(), 40);
+        assert!(batched.iter().all(|(s, k)| *k == s.killed as usize));
+        assert_eq!(batched.iter().map(|(_, k)| k).sum::<usize>(), kills);
+        assert_eq!(batch_shots(volley(), Some(40)).len(), 40);
+    }
+}
@@ -5,6 +5,28 @@ pub const WIDTH: f32 = 1600.;
 pub const HEIGHT: f32 = 900.;
 pub const MESSAGE_DURATION: u64 = 5;
 
+/// Maximum number of messages kept in the notifications panel
+pub const MESSAGE_HISTORY: usize = 50;
+
+pub const REPLAY_TURN_DURATION: f32 = 2.;
+
+/// Seconds between broadcasts of the player list in multiplayer games
+pub const PLAYER_LIST_INTERVAL: f32 = 1.;
+
+/// Minimum seconds between two end-turn updates sent to the host
+pub const END_TURN_COOLDOWN: f32 = 0.5;
+
+/// Window size below which the panels are scaled down
+pub const MIN_UI_WIDTH: f32 = 1280.;
+pub const MIN_UI_HEIGHT: f32 = 720.;
+
+/// Window size below which non-essential panels are hidden
+pub const MIN_WINDOW_WIDTH: f32 = 800.;
+pub const MIN_WINDOW_HEIGHT: f32 = 450.;
+
+/// Factor by which the ui's text is enlarged in large text mode
+pub const LARGE_TEXT_SCALE: f32 = 1.25;
+
 /// Menu
 pub const SUBTITLE_TEXT_SIZE: f32 = 10.;
 pub const TITLE_TEXT_SIZE: f32 = 15.;
@@ -18,8 +40,23 @@ pub const DISABLED_BUTTON_COLOR: Color = Color::srgb(0.8, 0.5, 0.5);
 pub const BG_COLOR: Color = Color::srgb_u8(12, 16, 20);
 pub const BG2_COLOR: Color = Color::srgb_u8(40, 40, 40);
 pub const SHIELD_COLOR: Color = Color::srgb_u8(0, 255, 255);
+pub const HULL_DAMAGE_COLOR: Color = Color::srgb_u8(255, 120, 40);
 pub const OWN_COLOR: Color = Color::srgb_u8(102, 128, 255);
 pub const ENEMY_COLOR: Color = Color::srgb_u8(255, 64, 32);
+pub const ALLY_COLOR: Color = Color::srgb_u8(64, 200, 96);
+pub const WORMHOLE_COLOR: Color = Color::srgb_u8(180, 90, 255);
+pub const AUTO_ECONOMY_COLOR: Color = Color::srgb_u8(255, 215, 64);
+pub const STALE_INTEL_COLOR: Color = Color::srgb_u8(255, 160, 0);
+pub const EVEN_ODDS_COLOR: Color = Color::srgb_u8(255, 220, 40);
+pub const UNKNOWN_ODDS_COLOR: Color = Color::srgb_u8(150, 150, 150);
+pub const PLAYER_COLORS: [Color; 6] = [
+    ENEMY_COLOR,
+    Color::srgb_u8(255, 150, 40),
+    Color::srgb_u8(255, 80, 180),
+    Color::srgb_u8(230, 230, 80),
+    Color::srgb_u8(170, 120, 80),
+    Color::srgb_u8(230, 230, 230),
+];
 
 /// Camera
 pub const MIN_ZOOM: f32 = 0.5;
@@ -35,11 +72,40 @@ pub const PROBES_PER_PRODUCTION_LEVEL: usize = 5;
 pub const PS_SHIELD_PER_LEVEL: usize = 100;
 pub const NEXUS_FACTOR: f32 = 0.1;
 pub const PHALANX_DISTANCE: f32 = 0.8;
+pub const PHALANX_SCAN_DEUTERIUM: usize = 250;
 pub const RADAR_DISTANCE: f32 = 1.0;
+pub const WORMHOLE_DISTANCE: f32 = 1.0;
+pub const MOON_ORBIT_DISTANCE: f32 = 4.0;
+pub const ODDS_DISTANCE: f32 = 10.0;
+pub const FAVORABLE_ODDS: f32 = 1.5;
+pub const EVEN_ODDS: f32 = 0.75;
 pub const CRAWLER_HEALING_PER_ROUND: usize = 50;
+pub const ANTIBALLISTIC_INTERCEPT_CHANCE: f32 = 0.5;
+pub const TANKER_FUEL_FACTOR: f32 = 0.15;
+pub const SPECIALIZATION_BONUS: usize = 20;
+pub const SPECIALIZATION_COOLDOWN: usize = 5;
+pub const VETERANCY_BONUS: usize = 5;
+pub const MAX_VETERANCY: usize = 4;
+pub const UPKEEP_PER_PRODUCTION: usize = 1;
+pub const PLUNDER_PERCENTAGE: usize = 25;
+pub const CARGO_INVESTMENT_FACTOR: usize = 50;
+pub const MAX_NOTE_LENGTH: usize = 40;
+pub const MAX_PLANET_NAME_LENGTH: usize = 20;
+pub const MAX_BUY_WEIGHT: usize = 5;
+pub const ECONOMY_HISTORY: usize = 20;
+pub const SANDBOX_RESOURCES: usize = 1_000_000;
+pub const VICTORY_PLANETS: usize = 50;
+pub const VICTORY_SCORE: usize = 25_000;
+pub const VICTORY_TURN_LIMIT: usize = 50;
 
 /// Combat
 pub const SETUP_TIME: u64 = 2;
+pub const LITE_ROUND_DURATION: f32 = 1.5;
+pub const LOW_SHOTS_PER_VOLLEY: usize = 40;
+pub const HIGH_SHOTS_PER_VOLLEY: usize = 200;
+pub const MAX_COMBAT_SOUNDS: usize = 8;
+/// Maximum number of floating damage numbers spawned per frame
+pub const MAX_DAMAGE_NUMBERS: usize = 12;
 pub const UNIT_SIZE: f32 = 120.;
 pub const PS_WIDTH: f32 = 11.;
 pub const COMBAT_BACKGROUND_Z: f32 = 10.;
@@ -53,6 +119,10 @@ pub const PLANET_Z: f32 = 2.;
 pub const MISSION_Z: f32 = 3.;
 pub const EXPLOSION_Z: f32 = 4.;
 
+/// Distance outside the camera view where map entities are still drawn
+pub const CULLING_MARGIN: f32 = 150.;
+
+/// Planet names of the classic theme, also used to complete smaller themes
 pub const PLANET_NAMES: [&str; 162] = [
     "Abrax", "Aegis", "Aether", "Aleron", "Andros", "Arcadia", "Arctur", "Arvend", "Astrix",
     "Atreon", "Avalon", "Auralis", "Bastor", "Belion", "Bellax", "Boreal", "Brelix", "Caelum",
@@ -72,3 +142,40 @@ pub const PLANET_NAMES: [&str; 162] = [
     "Ularis", "Ulmar", "Ulyss", "Valen", "Vela", "Vesper", "Vortan", "Voryn", "Wyvern", "Xandar",
     "Xelra", "Xyra", "Yalen", "Ylros", "Ythra", "Zaryn", "Zaurak", "Zephyr",
 ];
+
+/// Planet names of the mythological theme
+pub const MYTHOLOGICAL_NAMES: [&str; 83] = [
+    "Achilles", "Aeolus", "Ajax", "Amaltea", "Anansi", "Anubis", "Apollo", "Ares", "Artemis",
+    "Asgard", "Athena", "Atlas", "Baldur", "Bastet", "Brahma", "Brigid", "Castor", "Cerberus",
+    "Chronos", "Circe", "Cronus", "Cybele", "Demeter", "Dionysus", "Durga", "Electra", "Eris",
+    "Fenrir", "Freya", "Frigg", "Gaia", "Hades", "Hathor", "Heimdall", "Helios", "Hera", "Hermes",
+    "Hestia", "Horus", "Idun", "Indra", "Isis", "Jormun", "Kali", "Loki", "Maat", "Medusa",
+    "Minerva", "Mjolnir", "Nemesis", "Neptune", "Nike", "Njord", "Nut", "Odin", "Olympus",
+    "Osiris", "Pan", "Pandora", "Perseus", "Pollux", "Ptah", "Quetzal", "Ra", "Selene", "Set",
+    "Shiva", "Sif", "Sobek", "Sol", "Styx", "Surya", "Tartarus", "Thanatos", "Themis", "Thor",
+    "Thoth", "Tyr", "Valhalla", "Vishnu", "Vulcan", "Ymir", "Zeus",
+];
+
+/// Planet names of the scientific theme: scientists and bright stars
+pub const SCIENTIFIC_NAMES: [&str; 71] = [
+    "Bohr", "Boyle", "Brahe", "Curie", "Darwin", "Dirac", "Doppler", "Einstein", "Euclid", "Euler",
+    "Faraday", "Fermat", "Fermi", "Feynman", "Gauss", "Galilei", "Goodall", "Halley", "Hawking",
+    "Herschel", "Hertz", "Hooke", "Hopper", "Hubble", "Huygens", "Joule", "Kelvin", "Kepler",
+    "Laplace", "Leavitt", "Leibniz", "Lorentz", "Lovelace", "Maxwell", "Meitner", "Mendel",
+    "Messier", "Newton", "Noether", "Ohm", "Oort", "Pascal", "Pasteur", "Pauli", "Planck",
+    "Ptolemy", "Raman", "Riemann", "Rubin", "Sagan", "Tesla", "Turing", "Volta", "Watt", "Wegener",
+    "Yukawa", "Zwicky", "Altair", "Antares", "Arcturus", "Canopus", "Capella", "Deneb", "Mira",
+    "Polaris", "Procyon", "Regulus", "Rigel", "Sirius", "Spica", "Vega",
+];
+
+/// Planet names of the sci-fi theme
+pub const SCIFI_NAMES: [&str; 70] = [
+    "Arrakis", "Caladan", "Giedi", "Kaitain", "Salusa", "Ix", "Tatooine", "Hoth", "Endor",
+    "Dagobah", "Naboo", "Mustafar", "Bespin", "Jakku", "Romulus", "Qonos", "Bajor", "Risa",
+    "Trill", "Solaris", "Terminus", "Trantor", "Gethen", "Anarres", "Urras", "Vortis", "Skaro",
+    "Mondas", "Krypton", "Thanagar", "Oa", "Xenex", "Reach", "Harvest", "Tuchanka", "Palaven",
+    "Thessia", "Rannoch", "Omega", "Illium", "Caprica", "Kobol", "Picon", "Arda", "Pern", "Komarr",
+    "Hegemon", "Eros", "Erewhon", "Acheron", "Fiorina", "Miranda", "Ariel", "Shadow", "Sihnon",
+    "Zeist", "Krikkit", "Damogran", "Metaluna", "Vogon", "Lumina", "Tralfama", "Chiron", "Dune",
+    "Zarkon", "Kessel", "Corellia", "Yavin", "Alderaan", "Ord",
+];
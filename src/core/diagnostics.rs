@@ -0,0 +1,98 @@
+use std::fmt::Write;
+
+use bevy::diagnostic::{
+    DiagnosticsStore, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin,
+};
+use bevy::prelude::*;
+use bevy_egui::egui::{Align2, Color32, Order, RichText};
+use bevy_egui::{egui, EguiContexts, EguiPrimaryContextPass};
+
+use crate::core::combat::systems::CombatCmp;
+use crate::core::map::systems::CulledEntities;
+
+/// Seconds between updates of the overlay's text
+const REFRESH_TIME: f32 = 0.25;
+
+#[derive(Resource)]
+struct DiagnosticsOverlay {
+    show: bool,
+    text: String,
+    timer: Timer,
+}
+
+impl Default for DiagnosticsOverlay {
+    fn default() -> Self {
+        Self {
+            show: false,
+            text: String::new(),
+            timer: Timer::from_seconds(REFRESH_TIME, TimerMode::Repeating),
+        }
+    }
+}
+
+fn toggle_diagnostics(
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<DiagnosticsOverlay>,
+) {
+    if keyboard.just_pressed(KeyCode::F3) {
+        overlay.show = !overlay.show;
+        overlay.text.clear();
+    }
+}
+
+fn draw_diagnostics(
+    mut contexts: EguiContexts,
+    mut overlay: ResMut<DiagnosticsOverlay>,
+    combat_q: Query<(), With<CombatCmp>>,
+    culled: Res<CulledEntities>,
+    store: Res<DiagnosticsStore>,
+    time: Res<Time>,
+) {
+    if !overlay.show {
+        return;
+    }
+
+    overlay.timer.tick(time.delta());
+
+    // Only rebuild the text a few times per second, reusing its buffer
+    if overlay.timer.just_finished() || overlay.text.is_empty() {
+        let value = |path| store.get(path).and_then(|d| d.smoothed()).unwrap_or_default();
+
+        let fps = value(&FrameTimeDiagnosticsPlugin::FPS);
+        let frame_time = value(&FrameTimeDiagnosticsPlugin::FRAME_TIME);
+        let entities = value(&EntityCountDiagnosticsPlugin::ENTITY_COUNT);
+
+        let text = &mut overlay.text;
+        text.clear();
+        let _ = write!(
+            text,
+            "FPS: {fps:.0}\nFrame time: {frame_time:.2} ms\nEntities: {entities:.0}\nCombat \
+            entities: {}\nCulled entities: {}",
+            combat_q.iter().count(),
+            culled.0,
+        );
+    }
+
+    egui::Area::new("diagnostics".into())
+        .order(Order::Foreground)
+        .anchor(Align2::LEFT_TOP, [5., 5.])
+        .interactable(false)
+        .show(contexts.ctx_mut().unwrap(), |ui| {
+            ui.label(RichText::new(overlay.text.as_str()).small().color(Color32::WHITE));
+        });
+}
+
+/// Overlay with performance statistics, toggled with F3
+pub struct DiagnosticsOverlayPlugin;
+
+impl Plugin for DiagnosticsOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            FrameTimeDiagnosticsPlugin::default(),
+            EntityCountDiagnosticsPlugin::default(),
+        ))
+        .init_resource::<DiagnosticsOverlay>()
+        .add_systems(Update, toggle_diagnostics)
+        .add_systems(EguiPrimaryContextPass, draw_diagnostics);
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use bevy_renet::renet::ClientId;
+use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
+
+use crate::core::messages::MessageMsg;
+use crate::core::network::{ClientMessage, ClientSendMsg, Host, ServerMessage, ServerSendMsg};
+use crate::core::player::Player;
+use crate::core::settings::{Settings, Teams};
+use crate::core::units::Description;
+use crate::utils::NameFromEnum;
+
+#[derive(EnumIter, Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Relation {
+    #[default]
+    War,
+    NonAggression,
+    Alliance,
+}
+
+impl Description for Relation {
+    fn description(&self) -> &str {
+        match self {
+            Relation::War => "No treaty. Both players can attack each other.",
+            Relation::NonAggression => {
+                "Missions towards planets of the other player are called off and return to \
+                their origin."
+            },
+            Relation::Alliance => {
+                "Like a non-aggression pact, but both players also see each other's territory."
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Treaty {
+    /// Players in the treaty (lowest id first)
+    pub players: (ClientId, ClientId),
+
+    /// Current relation between the players
+    pub relation: Relation,
+
+    /// Pending proposal and the player that made it
+    pub proposal: Option<(ClientId, Relation)>,
+
+    /// Turn in which the treaty was broken
+    pub broken: Option<usize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum DiplomacyAction {
+    Propose(ClientId, Relation),
+    Accept(ClientId),
+    Decline(ClientId),
+    Break(ClientId),
+}
+
+impl DiplomacyAction {
+    /// Player the action is directed at
+    pub fn target(&self) -> ClientId {
+        match self {
+            DiplomacyAction::Propose(to, _)
+            | DiplomacyAction::Accept(to)
+            | DiplomacyAction::Decline(to)
+            | DiplomacyAction::Break(to) => *to,
+        }
+    }
+}
+
+/// Relations between the players (authoritative on the host)
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+pub struct Diplomacy {
+    pub treaties: Vec<Treaty>,
+
+    /// Team of every player, fixed at the start of the game
+    pub teams: HashMap<ClientId, usize>,
+}
+
+impl Diplomacy {
+    /// Relations at the start of a game with the players in `roster`
+    pub fn new(roster: &[ClientId], teams: Teams) -> Self {
+        Self {
+            treaties: vec![],
+            teams: roster.iter().enumerate().map(|(i, id)| (*id, teams.team(i))).collect(),
+        }
+    }
+
+    /// Whether both players are in the same team. Teammates are always allied
+    pub fn are_teammates(&self, a: ClientId, b: ClientId) -> bool {
+        a != b && self.teams.get(&a).is_some_and(|t| self.teams.get(&b) == Some(t))
+    }
+
+    fn key(a: ClientId, b: ClientId) -> (ClientId, ClientId) {
+        (a.min(b), a.max(b))
+    }
+
+    pub fn get(&self, a: ClientId, b: ClientId) -> Option<&Treaty> {
+        self.treaties.iter().find(|t| t.players == Self::key(a, b))
+    }
+
+    fn get_mut(&mut self, a: ClientId, b: ClientId) -> &mut Treaty {
+        let key = Self::key(a, b);
+        if let Some(idx) = self.treaties.iter().position(|t| t.players == key) {
+            &mut self.treaties[idx]
+        } else {
+            self.treaties.push(Treaty {
+                players: key,
+                relation: Relation::War,
+                proposal: None,
+                broken: None,
+            });
+            self.treaties.last_mut().unwrap()
+        }
+    }
+
+    pub fn relation(&self, a: ClientId, b: ClientId) -> Relation {
+        if self.are_teammates(a, b) {
+            return Relation::Alliance;
+        }
+
+        self.get(a, b).map(|t| t.relation).unwrap_or_default()
+    }
+
+    pub fn is_allied(&self, a: ClientId, b: ClientId) -> bool {
+        a != b && self.relation(a, b) == Relation::Alliance
+    }
+
+    /// Whether the players can't attack each other this turn. Attacks are
+    /// only allowed one turn after a treaty was broken
+    pub fn is_protected(&self, a: ClientId, b: ClientId, turn: usize) -> bool {
+        a != b
+            && (self.are_teammates(a, b)
+                || self.get(a, b).is_some_and(|t| {
+                    t.relation != Relation::War || t.broken.is_some_and(|b| turn <= b + 1)
+                }))
+    }
+
+    /// Apply an action from a player and return the message to show
+    pub fn apply(&mut self, from: ClientId, action: DiplomacyAction, turn: usize) -> String {
+        if from == action.target() {
+            return "A player can't make treaties with themselves.".to_string();
+        } else if self.are_teammates(from, action.target()) {
+            return "The alliance between teammates can't be changed.".to_string();
+        }
+
+        match action {
+            DiplomacyAction::Propose(to, relation) => {
+                self.get_mut(from, to).proposal = Some((from, relation));
+                format!("Player {from} proposed a {} to player {to}.", relation.to_title())
+            },
+            DiplomacyAction::Accept(to) => {
+                let treaty = self.get_mut(from, to);
+                match treaty.proposal.take() {
+                    Some((proposer, relation)) if proposer == to => {
+                        treaty.relation = relation;
+                        treaty.broken = None;
+                        format!("Players {from} and {to} signed a {}.", relation.to_title())
+                    },
+                    _ => "There is no proposal to accept.".to_string(),
+                }
+            },
+            DiplomacyAction::Decline(to) => {
+                self.get_mut(from, to).proposal = None;
+                format!("Player {from} declined the proposal of player {to}.")
+            },
+            DiplomacyAction::Break(to) => {
+                if self.relation(from, to) == Relation::War {
+                    return "There is no treaty to break.".to_string();
+                }
+
+                let treaty = self.get_mut(from, to);
+                treaty.relation = Relation::War;
+                treaty.proposal = None;
+                treaty.broken = Some(turn);
+                format!("Player {from} broke the treaty with player {to}.")
+            },
+        }
+    }
+}
+
+#[derive(Message)]
+pub struct DiplomacyMsg {
+    pub action: DiplomacyAction,
+}
+
+impl DiplomacyMsg {
+    pub fn new(action: DiplomacyAction) -> Self {
+        Self {
+            action,
+        }
+    }
+}
+
+pub fn send_diplomacy(
+    mut diplomacy_msg: MessageReader<DiplomacyMsg>,
+    mut diplomacy: ResMut<Diplomacy>,
+    host: Option<Res<Host>>,
+    player: Res<Player>,
+    settings: Res<Settings>,
+    mut server_send_msg: MessageWriter<ServerSendMsg>,
+    mut client_send_msg: MessageWriter<ClientSendMsg>,
+    mut message: MessageWriter<MessageMsg>,
+) {
+    for DiplomacyMsg {
+        action,
+    } in diplomacy_msg.read()
+    {
+        if host.is_some() {
+            message.write(MessageMsg::diplomacy(diplomacy.apply(
+                player.id,
+                *action,
+                settings.turn,
+            )));
+            server_send_msg
+                .write(ServerSendMsg::new(ServerMessage::Diplomacy(diplomacy.clone()), None));
+        } else {
+            client_send_msg.write(ClientSendMsg::new(ClientMessage::Diplomacy(*action)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diplomacy() -> Diplomacy {
+        Diplomacy::new(&[1, 2, 3], Teams::default())
+    }
+
+    #[test]
+    fn accepted_proposal_signs_the_treaty() {
+        let mut diplomacy = diplomacy();
+        diplomacy.apply(1, DiplomacyAction::Propose(2, Relation::Alliance), 1);
+        diplomacy.apply(2, DiplomacyAction::Accept(1), 1);
+        assert_eq!(diplomacy.relation(1, 2), Relation::Alliance);
+        assert!(diplomacy.is_protected(1, 2, 1));
+    }
+
+    #[test]
+    fn breaking_without_treaty_is_ignored() {
+        let mut diplomacy = diplomacy();
+        diplomacy.apply(1, DiplomacyAction::Break(2), 3);
+        assert!(diplomacy.get(1, 2).is_none());
+        assert!(!diplomacy.is_protected(1, 2, 3));
+    }
+
+    #[test]
+    fn breaking_a_treaty_allows_attacks_after_one_turn() {
+        let mut diplomacy = diplomacy();
+        diplomacy.apply(1, DiplomacyAction::Propose(2, Relation::NonAggression), 1);
+        diplomacy.apply(2, DiplomacyAction::Accept(1), 1);
+        diplomacy.apply(1, DiplomacyAction::Break(2), 3);
+
+        assert_eq!(diplomacy.relation(1, 2), Relation::War);
+        assert!(diplomacy.is_protected(1, 2, 4));
+        assert!(!diplomacy.is_protected(1, 2, 5));
+    }
+
+    #[test]
+    fn actions_towards_oneself_are_rejected() {
+        let mut diplomacy = diplomacy();
+        diplomacy.apply(1, DiplomacyAction::Propose(1, Relation::Alliance), 1);
+        diplomacy.apply(1, DiplomacyAction::Accept(1), 1);
+        assert!(diplomacy.get(1, 1).is_none());
+        assert_eq!(diplomacy.relation(1, 1), Relation::War);
+    }
+
+    #[test]
+    fn teammates_cannot_attack_each_other() {
+        // Players 1 and 3 play against players 2 and 4
+        let mut diplomacy = Diplomacy::new(&[1, 2, 3, 4], Teams::TwoTeams);
+        assert!(diplomacy.is_protected(1, 3, 1));
+        assert!(diplomacy.is_protected(4, 2, 1));
+        assert!(!diplomacy.is_protected(1, 2, 1));
+
+        assert_eq!(
+            diplomacy.apply(1, DiplomacyAction::Break(3), 2),
+            "The alliance between teammates can't be changed."
+        );
+        assert_eq!(
+            diplomacy.apply(3, DiplomacyAction::Propose(1, Relation::NonAggression), 2),
+            "The alliance between teammates can't be changed."
+        );
+        assert_eq!(diplomacy.relation(1, 3), Relation::Alliance);
+        assert!(diplomacy.is_protected(1, 3, 5));
+    }
+}
@@ -5,20 +5,46 @@ use rand::seq::index::sample;
 use rand::{rng, Rng};
 use serde::{Deserialize, Serialize};
 
-use crate::core::constants::{HEIGHT, PLANET_NAMES, WIDTH};
+use crate::core::constants::{HEIGHT, MOON_ORBIT_DISTANCE, PLANET_NAMES, WIDTH, WORMHOLE_DISTANCE};
 use crate::core::map::planet::{Planet, PlanetId};
+use crate::core::settings::{NameTheme, ResourceBias};
 
 #[derive(Component)]
 pub struct MapCmp;
 
+/// Draw `n` unique planet names from the theme's list. Themes with fewer names
+/// than required are completed with the classic names
+pub fn planet_names(theme: NameTheme, n: usize, rng: &mut impl Rng) -> Vec<String> {
+    let mut names = theme.names().iter().choose_multiple(rng, n);
+
+    if names.len() < n {
+        let extra = PLANET_NAMES
+            .iter()
+            .filter(|name| !names.contains(name))
+            .choose_multiple(rng, n - names.len());
+        names.extend(extra);
+    }
+
+    names.into_iter().map(|name| name.to_string()).collect()
+}
+
 #[derive(Resource, Clone, Debug, Serialize, Deserialize)]
 pub struct Map {
     pub rect: Rect,
     pub planets: Vec<Planet>,
+
+    /// Pairs of linked planets fleets can travel between almost instantly
+    pub wormholes: Vec<(PlanetId, PlanetId)>,
 }
 
 impl Map {
-    pub fn new(n_planets: usize, p_moons: usize) -> Self {
+    pub fn new(
+        n_planets: usize,
+        p_moons: usize,
+        n_wormholes: usize,
+        bias: ResourceBias,
+        theme: NameTheme,
+    ) -> Self {
         let n_moons = (n_planets as f32 * p_moons as f32 / 100.) as usize;
         let n_total = n_planets + n_moons;
 
@@ -65,21 +91,73 @@ impl Map {
             .map(|td| (1. + (td - mean) / max_dev).clamp(1., 2.))
             .collect::<Vec<_>>();
 
-        let names = PLANET_NAMES.iter().choose_multiple(&mut rng(), n_total);
+        // Link distant planets (not moons) with wormholes, every planet has at most one
+        let mut wormholes: Vec<(PlanetId, PlanetId)> = vec![];
+        let mut attempts = 0;
+        while wormholes.len() < n_wormholes && attempts < 1000 {
+            attempts += 1;
+
+            let free = (0..n_total)
+                .filter(|i| {
+                    !moon_idx.contains(i) && wormholes.iter().all(|(a, b)| a != i && b != i)
+                })
+                .choose_multiple(&mut rng(), 2);
+
+            if let [a, b] = free[..] {
+                if positions[a].distance(positions[b]) > rect.width() * 0.4 {
+                    wormholes.push((a, b));
+                }
+            } else {
+                break;
+            }
+        }
+
+        let names = planet_names(theme, n_total, &mut rng());
         Self {
             rect,
             planets: names
-                .iter()
+                .into_iter()
                 .zip(positions)
                 .zip(factors)
                 .enumerate()
                 .map(|(id, ((name, pos), f))| {
-                    Planet::new(id, name.to_string(), pos, moon_idx.contains(&id), f)
+                    Planet::new(id, name, pos, moon_idx.contains(&id), f, bias)
                 })
                 .collect(),
+            wormholes,
         }
     }
 
+    /// Shortest path from a position to a planet. Returns the distance and,
+    /// if it's shorter to travel through a wormhole, its entrance and exit
+    pub fn path(
+        &self,
+        position: Vec2,
+        destination: PlanetId,
+    ) -> (f32, Option<(PlanetId, PlanetId)>) {
+        let target = self.get(destination).position;
+
+        self.wormholes
+            .iter()
+            .flat_map(|&(a, b)| [(a, b), (b, a)])
+            .filter(|(a, b)| !self.get(*a).is_destroyed && !self.get(*b).is_destroyed)
+            .map(|(entrance, exit)| {
+                (
+                    position.distance(self.get(entrance).position)
+                        + WORMHOLE_DISTANCE * Planet::SIZE
+                        + self.get(exit).position.distance(target),
+                    Some((entrance, exit)),
+                )
+            })
+            .fold((position.distance(target), None), |best, path| {
+                if path.0 < best.0 {
+                    path
+                } else {
+                    best
+                }
+            })
+    }
+
     pub fn get(&self, planet_id: PlanetId) -> &Planet {
         self.planets.iter().find(|p| p.id == planet_id).expect("Planet not found.")
     }
@@ -88,6 +166,33 @@ impl Map {
         self.planets.iter_mut().find(|p| p.id == planet_id).expect("Planet not found.")
     }
 
+    /// Moon orbiting a planet, or planet orbited by a moon. They are paired when
+    /// each one is the closest of the other type within orbit distance
+    pub fn companion(&self, planet_id: PlanetId) -> Option<&Planet> {
+        let closest = |planet: &Planet| {
+            self.planets
+                .iter()
+                .filter(|p| {
+                    p.is_moon() != planet.is_moon()
+                        && !p.is_destroyed
+                        && p.position.distance(planet.position)
+                            <= MOON_ORBIT_DISTANCE * Planet::SIZE
+                })
+                .min_by(|a, b| {
+                    a.position
+                        .distance(planet.position)
+                        .total_cmp(&b.position.distance(planet.position))
+                })
+        };
+
+        let planet = self.get(planet_id);
+        if planet.is_destroyed {
+            return None;
+        }
+
+        closest(planet).filter(|c| closest(c).is_some_and(|p| p.id == planet_id))
+    }
+
     pub fn planets(&self) -> Vec<&Planet> {
         self.planets.iter().filter(|p| !p.is_moon()).collect()
     }
@@ -96,3 +201,87 @@ impl Map {
         self.planets.iter().filter(|p| p.is_moon()).collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    fn map(planets: &[(PlanetId, f32, bool)]) -> Map {
+        Map {
+            rect: Rect::default(),
+            planets: planets
+                .iter()
+                .map(|&(id, x, is_moon)| {
+                    let name = format!("P{id}");
+                    Planet::new(id, name, Vec2::new(x, 0.), is_moon, 1., ResourceBias::None)
+                })
+                .collect(),
+            wormholes: vec![],
+        }
+    }
+
+    #[test]
+    fn planet_names_are_deterministic_and_unique() {
+        let themes =
+            [NameTheme::Classic, NameTheme::Mythological, NameTheme::Scientific, NameTheme::SciFi];
+
+        for theme in themes {
+            let names = |n| planet_names(theme, n, &mut StdRng::seed_from_u64(42));
+
+            let themed = names(50);
+            assert_eq!(themed, names(50));
+            assert_eq!(themed.iter().collect::<HashSet<_>>().len(), 50);
+            assert!(themed.iter().all(|name| theme.names().contains(&name.as_str())));
+
+            // Themes without enough names are completed with the classic ones
+            let completed = names(120);
+            assert_eq!(completed, names(120));
+            assert_eq!(completed.iter().collect::<HashSet<_>>().len(), 120);
+        }
+    }
+
+    #[test]
+    fn shortest_path_goes_through_the_wormhole() {
+        let mut map =
+            map(&[(0, 0., false), (1, 200., false), (2, 5000., false), (3, 5300., false)]);
+        assert_eq!(map.path(Vec2::ZERO, 3), (5300., None));
+
+        // Entering, crossing and leaving the wormhole is much shorter
+        map.wormholes.push((1, 2));
+        let distance = 200. + WORMHOLE_DISTANCE * Planet::SIZE + 300.;
+        assert_eq!(map.path(Vec2::ZERO, 3), (distance, Some((1, 2))));
+        assert_eq!(map.path(Vec2::new(5300., 0.), 0), (distance, Some((2, 1))));
+
+        // Nearby planets are still reached directly
+        assert_eq!(map.path(Vec2::ZERO, 1), (200., None));
+
+        // Wormholes collapse when one of their planets is destroyed
+        map.get_mut(2).is_destroyed = true;
+        assert_eq!(map.path(Vec2::ZERO, 3), (5300., None));
+    }
+
+    #[test]
+    fn moon_is_paired_with_the_closest_planet() {
+        let map = map(&[(0, 0., false), (1, 200., true), (2, 350., false)]);
+        assert_eq!(map.companion(0).map(|p| p.id), Some(1));
+        assert_eq!(map.companion(1).map(|p| p.id), Some(0));
+
+        // The moon is closer to the other planet, which stays unpaired
+        assert!(map.companion(2).is_none());
+    }
+
+    #[test]
+    fn distant_or_destroyed_moons_have_no_companion() {
+        let mut map = map(&[(0, 0., false), (1, 200., true), (2, 3000., true)]);
+        assert!(map.companion(2).is_none());
+
+        map.get_mut(1).is_destroyed = true;
+        assert!(map.companion(0).is_none());
+        assert!(map.companion(1).is_none());
+    }
+}
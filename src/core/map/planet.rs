@@ -1,21 +1,49 @@
-use std::ops::Range;
+use std::cmp::Reverse;
+use std::collections::HashMap;
 
 use bevy::math::Vec2;
 use bevy_renet::renet::ClientId;
+use itertools::Itertools;
 use rand::prelude::IteratorRandom;
 use rand::{rng, Rng};
 use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 use crate::core::constants::{
-    FACTORY_PRODUCTION_FACTOR, SHIPYARD_PRODUCTION_FACTOR, SILO_CAPACITY_FACTOR,
+    CARGO_INVESTMENT_FACTOR, FACTORY_PRODUCTION_FACTOR, MAX_PLANET_NAME_LENGTH, PHALANX_DISTANCE,
+    SHIPYARD_PRODUCTION_FACTOR, SILO_CAPACITY_FACTOR, SPECIALIZATION_BONUS,
+    SPECIALIZATION_COOLDOWN,
 };
-use crate::core::resources::Resources;
+use crate::core::missions::MissionId;
+use crate::core::resources::{ResourceName, Resources};
+use crate::core::settings::{BuildOrder, ResourceBias, StartPackage};
 use crate::core::units::buildings::Building;
-use crate::core::units::{Amount, Army, Unit};
+use crate::core::units::defense::Defense;
+use crate::core::units::{Amount, Army, Description, Price, Unit};
 
 pub type PlanetId = usize;
 
+/// Split a production `capacity` over items in proportion to their weights. The
+/// rest of the integer division goes to the largest remainders (ties to the first
+/// item), so the shares always sum to the capacity and the split is deterministic
+pub fn allocate_production(capacity: usize, weights: &[usize]) -> Vec<usize> {
+    let total = weights.iter().sum::<usize>();
+    if total == 0 {
+        return vec![0; weights.len()];
+    }
+
+    let mut shares = weights.iter().map(|w| capacity * w / total).collect::<Vec<_>>();
+    let left = capacity - shares.iter().sum::<usize>();
+
+    (0..weights.len())
+        .sorted_by_key(|&i| (Reverse(capacity * weights[i] % total), i))
+        .take(left)
+        .for_each(|i| shares[i] += 1);
+
+    shares
+}
+
 #[derive(EnumIter, Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum PlanetKind {
     // Planets
@@ -115,6 +143,17 @@ impl PlanetKind {
         }
     }
 
+    /// Abundance of metal, crystal and deuterium, from scarce (0) to abundant (2)
+    pub fn abundance(&self) -> [usize; 3] {
+        match self {
+            PlanetKind::Dry | PlanetKind::Metallic => [2, 0, 0],
+            PlanetKind::Gas => [0, 0, 2],
+            PlanetKind::Ice => [0, 2, 0],
+            PlanetKind::Water => [1, 1, 0],
+            _ => [0, 0, 0],
+        }
+    }
+
     pub fn description(&self) -> &str {
         match self {
             PlanetKind::Dry => {
@@ -147,6 +186,94 @@ impl PlanetKind {
     }
 }
 
+#[derive(EnumIter, Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Stance {
+    #[default]
+    FireAll,
+    IgnoreScouts,
+}
+
+impl Description for Stance {
+    fn description(&self) -> &str {
+        match self {
+            Stance::FireAll => "The defending units fire on every enemy unit.",
+            Stance::IgnoreScouts => {
+                "The defending units don't fire on enemy Probes during the first combat round, \
+                when they would leave the combat anyway, to not waste shots on them."
+            },
+        }
+    }
+}
+
+#[derive(EnumIter, Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Specialization {
+    #[default]
+    None,
+    Economy,
+    Military,
+}
+
+impl Specialization {
+    pub fn image(&self) -> &str {
+        match self {
+            Specialization::None => "overview",
+            Specialization::Economy => "metal mine",
+            Specialization::Military => "shipyard",
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Specialization::None => "The planet has no specialization.".to_string(),
+            Specialization::Economy => format!(
+                "The planet focuses on its economy, increasing its resource production by \
+                {SPECIALIZATION_BONUS}%."
+            ),
+            Specialization::Military => format!(
+                "The planet focuses on its military, increasing its fleet and defense production \
+                capacity by {SPECIALIZATION_BONUS}%."
+            ),
+        }
+    }
+}
+
+impl Price for Specialization {
+    fn price(&self) -> Resources {
+        match self {
+            Specialization::None => Resources::default(),
+            _ => Resources::new(500, 500, 0),
+        }
+    }
+}
+
+/// Automatic upgrades of a planet's resource buildings
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AutoEconomy {
+    /// Percentage of the planet's production saved for upgrades
+    pub share: usize,
+
+    /// Level up to which the resource buildings are upgraded
+    pub max_level: usize,
+
+    /// Resources saved so far for the next upgrade
+    pub budget: Resources,
+
+    /// Target metal:crystal:deuterium production ratio. If set, the building of
+    /// the resource furthest below the ratio is upgraded instead of the cheapest
+    pub ratio: Option<[usize; 3]>,
+}
+
+impl Default for AutoEconomy {
+    fn default() -> Self {
+        Self {
+            share: 50,
+            max_level: Building::MAX_LEVEL,
+            budget: Resources::default(),
+            ratio: None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Planet {
     // Planet characteristics
@@ -159,6 +286,7 @@ pub struct Planet {
     pub position: Vec2,
     pub resources: Resources,
     pub jump_gate: usize,
+    pub phalanx_scanned: bool,
     pub is_destroyed: bool,
 
     // Ownership and units
@@ -166,6 +294,31 @@ pub struct Planet {
     pub controlled: Option<ClientId>,
     pub army: Army,
     pub buy: Vec<Unit>,
+    /// Weights of the queued units. If the production capacity can't build all of
+    /// them, it's split over the units in proportion to their weights (default 1)
+    pub buy_weights: HashMap<Unit, usize>,
+    pub rally: Option<PlanetId>,
+    pub stance: Stance,
+    pub specialization: Specialization,
+    pub specialization_turn: Option<usize>,
+    pub intercept: Option<MissionId>,
+    /// Turn the last interception order failed and why, to notify the owner
+    pub intercept_failed: Option<(usize, String)>,
+    pub veterancy: usize,
+    pub auto_economy: Option<AutoEconomy>,
+    /// Unit built with the production capacity left idle at the end of the turn
+    pub overflow: Option<Unit>,
+    /// Name given by the owner, only shown to them
+    pub custom_name: Option<String>,
+    pub mothballed: bool,
+    /// Whether fleets deploying from this planet return when it's attacked
+    pub alert: bool,
+    /// Units kept home as defense when selecting all units for a new mission
+    pub reserved: Army,
+    /// Allied player this planet is offered to. It's transferred when the turn resolves
+    pub transfer: Option<ClientId>,
+    /// Player that gifted this planet and the turn it was transferred
+    pub gifted: Option<(ClientId, usize)>,
 }
 
 impl Planet {
@@ -178,29 +331,20 @@ impl Planet {
         position: Vec2,
         is_moon: bool,
         resource_factor: f32,
+        bias: ResourceBias,
     ) -> Self {
         let (kind, resources) = if !is_moon {
-            let low = 10.0..20.0;
-            let medium = 20.0..30.0;
-            let high = 30.0..40.0;
-
-            let configs: &[(PlanetKind, [&Range<f32>; 3])] = &[
-                (PlanetKind::Dry, [&high, &low, &low]),
-                (PlanetKind::Gas, [&low, &low, &high]),
-                (PlanetKind::Ice, [&low, &high, &low]),
-                (PlanetKind::Metallic, [&high, &low, &low]),
-                (PlanetKind::Water, [&medium, &medium, &low]),
-            ];
-
-            let (kind, ranges) = configs.iter().choose(&mut rng()).unwrap();
-
-            let resources = Resources::new(
-                (rng().random_range(ranges[0].clone()) * resource_factor).round() as usize * 10,
-                (rng().random_range(ranges[1].clone()) * resource_factor).round() as usize * 10,
-                (rng().random_range(ranges[2].clone()) * resource_factor).round() as usize * 10,
-            );
-
-            (kind.clone(), resources)
+            let kind = PlanetKind::iter()
+                .filter(|k| !PlanetKind::moons().contains(k))
+                .choose(&mut rng())
+                .unwrap();
+
+            let ranges = bias.ranges();
+            let [metal, crystal, deuterium] = kind.abundance().map(|i| {
+                (rng().random_range(ranges[i].clone()) * resource_factor).round() as usize * 10
+            });
+
+            (kind, Resources::new(metal, crystal, deuterium))
         } else {
             (PlanetKind::moons().into_iter().choose(&mut rng()).unwrap(), Resources::default())
         };
@@ -215,14 +359,47 @@ impl Planet {
             position,
             resources,
             jump_gate: 0,
+            phalanx_scanned: false,
             is_destroyed: false,
             owned: None,
             controlled: None,
             army: Army::new(),
             buy: vec![],
+            buy_weights: HashMap::new(),
+            rally: None,
+            stance: Stance::default(),
+            specialization: Specialization::default(),
+            specialization_turn: None,
+            intercept: None,
+            intercept_failed: None,
+            veterancy: 0,
+            auto_economy: None,
+            overflow: None,
+            custom_name: None,
+            mothballed: false,
+            alert: false,
+            reserved: Army::new(),
+            transfer: None,
+            gifted: None,
         }
     }
 
+    /// Set the name chosen by the owner, returning an error if it isn't valid
+    pub fn rename(&mut self, name: &str) -> Result<(), String> {
+        let name = name.trim();
+
+        if name.is_empty() {
+            return Err("The name can't be empty.".to_string());
+        } else if name.chars().count() > MAX_PLANET_NAME_LENGTH {
+            return Err(format!(
+                "The name can't be longer than {MAX_PLANET_NAME_LENGTH} characters."
+            ));
+        }
+
+        self.custom_name = (name != self.name).then(|| name.to_string());
+        Ok(())
+    }
+
     pub fn is_moon(&self) -> bool {
         PlanetKind::moons().contains(&self.kind)
     }
@@ -243,15 +420,26 @@ impl Planet {
         }
     }
 
-    pub fn make_home_planet(&mut self, client_id: ClientId) {
+    pub fn make_home_planet(&mut self, client_id: ClientId, start: StartPackage) {
         self.colonize(client_id);
-        self.army = Army::from([
-            (Unit::Building(Building::MetalMine), 1),
-            (Unit::Building(Building::CrystalMine), 1),
-            (Unit::Building(Building::DeuteriumSynthesizer), 1),
-            (Unit::Building(Building::Shipyard), 1),
-            (Unit::Building(Building::Factory), 1),
-        ]);
+        self.army = start.army();
+
+        // Drop whatever the planet couldn't have built itself (home planets
+        // are never moons, so there is no field limit to check)
+        let (shipyard, factory, max_missiles) = (
+            self.army.amount(&Unit::Building(Building::Shipyard)),
+            self.army.amount(&Unit::Building(Building::Factory)),
+            self.max_missile_capacity(),
+        );
+        self.army.retain(|u, c| match u {
+            Unit::Building(_) => {
+                *c = (*c).min(Building::MAX_LEVEL);
+                true
+            },
+            Unit::Ship(_) => u.production() <= shipyard,
+            Unit::Defense(_) if u.is_missile() => *c <= max_missiles,
+            Unit::Defense(_) => u.production() <= factory,
+        });
     }
 
     pub fn clean(&mut self) {
@@ -259,6 +447,33 @@ impl Planet {
         self.controlled = None;
         self.army.retain(|u, _| u.is_building());
         self.buy = Vec::new();
+        self.buy_weights.clear();
+        self.rally = None;
+        self.specialization = Specialization::None;
+        self.intercept = None;
+        self.veterancy = 0;
+        self.auto_economy = None;
+        self.overflow = None;
+        self.custom_name = None;
+        self.mothballed = false;
+        self.alert = false;
+        self.reserved = Army::new();
+        self.transfer = None;
+    }
+
+    /// Hand the planet, with all its units, over to another player
+    pub fn gift(&mut self, client_id: ClientId, turn: usize) {
+        self.gifted = self.owned.map(|o| (o, turn));
+        self.owned = Some(client_id);
+        self.controlled = Some(client_id);
+        self.transfer = None;
+        self.rally = None;
+        self.intercept = None;
+        self.auto_economy = None;
+        self.overflow = None;
+        self.custom_name = None;
+        self.alert = false;
+        self.reserved = Army::new();
     }
 
     pub fn colonize(&mut self, client_id: ClientId) {
@@ -269,6 +484,10 @@ impl Planet {
     pub fn control(&mut self, client_id: ClientId) {
         // Destroy buildings if Nexus built and new controller
         if self.controlled != Some(client_id) {
+            self.intercept = None;
+            self.mothballed = false;
+            self.alert = false;
+            self.reserved = Army::new();
             for _ in 0..self.army.amount(&Unit::Building(Building::DemolitionNexus)) {
                 let pool = self.army.iter_mut().filter(|(u, c)| u.consumes_field() && **c > 0);
                 if let Some((_, c)) = pool.choose(&mut rng()) {
@@ -280,11 +499,19 @@ impl Planet {
         self.controlled = Some(client_id);
         if self.owned != Some(client_id) {
             self.owned = None;
+            self.rally = None;
+            self.transfer = None;
+            self.specialization = Specialization::None;
         }
     }
 
     pub fn abandon(&mut self) {
         self.owned = None;
+        self.rally = None;
+        self.specialization = Specialization::None;
+        self.auto_economy = None;
+        self.overflow = None;
+        self.custom_name = None;
         self.army.retain(|u, _| !u.is_defense());
         if !self.has_fleet() {
             self.controlled = None;
@@ -307,19 +534,270 @@ impl Planet {
 
     /// Resources and production ===================================== >>
 
+    /// Remove a unit from the purchases, refund its price to `resources` and
+    /// return whether it was queued
+    pub fn cancel(&mut self, unit: &Unit, resources: &mut Resources) -> bool {
+        if let Some(idx) = self.buy.iter().position(|u| u == unit) {
+            self.buy.remove(idx);
+            *resources += unit.price();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Queue one level of every building in the order that the planet can build
+    /// and the resources can pay for, and return the queued buildings
+    pub fn queue_build_order(&mut self, order: BuildOrder, resources: &mut Resources) -> Vec<Unit> {
+        let mut queued = vec![];
+
+        for unit in order.buildings(self.is_moon()) {
+            if self.army.amount(&unit) < Building::MAX_LEVEL
+                && !self.buy.contains(&unit)
+                && (!self.is_moon()
+                    || !unit.consumes_field()
+                    || self.fields_consumed() < self.max_fields())
+                && *resources >= unit.price()
+            {
+                *resources -= unit.price();
+                self.buy.push(unit);
+                queued.push(unit);
+            }
+        }
+
+        queued
+    }
+
+    pub fn buy_weight(&self, unit: &Unit) -> usize {
+        self.buy_weights.get(unit).copied().unwrap_or(1)
+    }
+
+    /// Remove the queued units that exceed the production capacity, and return
+    /// their price. If the capacity dropped below the queued production (e.g., after
+    /// changing the specialization), it's split over the queued units by their weights
+    pub fn fit_production(&mut self) -> Resources {
+        let mut refund = Resources::default();
+
+        for (ships, capacity) in
+            [(true, self.max_fleet_production()), (false, self.max_battery_production())]
+        {
+            let queued = self
+                .buy
+                .iter()
+                .filter(|u| !u.is_building() && u.is_ship() == ships)
+                .copied()
+                .counts()
+                .into_iter()
+                .sorted()
+                .collect::<Vec<_>>();
+
+            if queued.iter().map(|(u, n)| u.production() * n).sum::<usize>() <= capacity {
+                continue;
+            }
+
+            let weights = queued.iter().map(|(u, _)| self.buy_weight(u)).collect::<Vec<_>>();
+            for ((unit, n), share) in
+                queued.into_iter().zip(allocate_production(capacity, &weights))
+            {
+                for _ in (share / unit.production()).min(n)..n {
+                    let idx = self.buy.iter().position(|u| *u == unit).unwrap();
+                    self.buy.remove(idx);
+                    refund += unit.price();
+                }
+            }
+        }
+
+        refund
+    }
+
     pub fn produce(&mut self) {
-        for unit in self.buy.drain(..) {
-            *self.army.entry(unit).or_default() += 1;
+        let new = self.buy.drain(..).counts();
+        self.dock(new, 0);
+    }
+
+    /// Resource building to upgrade next to bring the planet's production closer to
+    /// the target ratio, i.e., the one whose production is lowest relative to its weight
+    pub fn balanced_building(&self, ratio: [usize; 3], max_level: usize) -> Option<Unit> {
+        let production = self.resource_production();
+
+        Unit::resource_buildings()
+            .into_iter()
+            .zip(ResourceName::iter())
+            .zip(ratio)
+            .filter(|((u, _), w)| {
+                *w > 0 && self.army.amount(u) < max_level && !self.buy.contains(u)
+            })
+            .map(|((u, r), w)| (u, production.get(&r) as f32 / w as f32))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(u, _)| u)
+    }
+
+    /// Save part of the production and queue a resource building upgrade when the
+    /// savings (and the player's resources) allow it. The cheapest building is
+    /// upgraded, unless the planet targets a production ratio
+    pub fn auto_build(&mut self, resources: &mut Resources) {
+        let production = self.resource_production();
+        let Some((max_level, ratio)) = self.auto_economy.as_ref().map(|a| (a.max_level, a.ratio))
+        else {
+            return;
+        };
+
+        let unit = match ratio {
+            Some(ratio) => self.balanced_building(ratio, max_level),
+            None => Unit::resource_buildings()
+                .into_iter()
+                .filter(|u| self.army.amount(u) < max_level && !self.buy.contains(u))
+                .min_by_key(|u| {
+                    let price = u.price();
+                    price.metal + price.crystal + price.deuterium
+                }),
+        };
+
+        let (Some(unit), Some(auto)) = (unit, self.auto_economy.as_mut()) else {
+            return;
+        };
+
+        auto.budget += production * auto.share / 100usize;
+
+        let price = unit.price();
+        if auto.budget >= price && *resources >= price {
+            auto.budget -= price;
+            *resources -= price;
+            self.buy.push(unit);
         }
     }
 
+    /// Whether the planet has the buildings and production capacity to queue
+    /// one more `unit` (not considering the price)
+    pub fn can_queue(&self, unit: &Unit) -> bool {
+        let buildings = match unit {
+            Unit::Building(_) => false,
+            Unit::Ship(s) => {
+                s.production() <= self.army.amount(&Unit::Building(Building::Shipyard))
+            },
+            Unit::Defense(d) if d.is_missile() => {
+                d.production() <= self.army.amount(&Unit::Building(Building::MissileSilo))
+            },
+            Unit::Defense(d) => {
+                !self.is_moon()
+                    && *d != Defense::SpaceDock
+                    && d.production() <= self.army.amount(&Unit::Building(Building::Factory))
+            },
+        };
+
+        buildings && self.capacity_left(unit) > 0
+    }
+
+    /// Number of `unit`s that still fit in the planet's capacity this turn (not
+    /// considering the price): the production capacity of its category, the silo
+    /// capacity (shared by all stored and queued missiles), the single Space Dock
+    /// and, for buildings, one level per turn within the moon's fields
+    pub fn capacity_left(&self, unit: &Unit) -> usize {
+        match unit {
+            Unit::Building(_) => {
+                (!self.buy.contains(unit)
+                    && (!self.is_moon()
+                        || !unit.consumes_field()
+                        || self.fields_consumed() < self.max_fields())) as usize
+            },
+            Unit::Ship(s) => {
+                self.max_fleet_production().saturating_sub(self.fleet_production()) / s.production()
+            },
+            Unit::Defense(d) => {
+                let production =
+                    self.max_battery_production().saturating_sub(self.battery_production())
+                        / d.production();
+
+                if d.is_missile() {
+                    let queued = self.buy.iter().filter(|u| u.is_missile()).count();
+                    production.min(
+                        self.max_missile_capacity()
+                            .saturating_sub(self.missile_capacity() + queued),
+                    )
+                } else if *d == Defense::SpaceDock {
+                    production.min((!self.has(unit) && !self.buy.contains(unit)) as usize)
+                } else {
+                    production
+                }
+            },
+        }
+    }
+
+    /// Fill the idle production capacity with the overflow unit, as long as the
+    /// resources allow it, and return the number of units queued. Production is
+    /// only idle when nothing of the same category was bought this turn
+    pub fn overflow_build(&mut self, resources: &mut Resources) -> usize {
+        let Some(unit) = self.overflow else {
+            return 0;
+        };
+
+        if self.mothballed || self.buy.iter().any(|u| u.is_ship() == unit.is_ship()) {
+            return 0;
+        }
+
+        let mut queued = 0;
+        while self.can_queue(&unit) && *resources >= unit.price() {
+            *resources -= unit.price();
+            self.buy.push(unit);
+            queued += 1;
+        }
+
+        queued
+    }
+
     pub fn resource_production(&self) -> Resources {
-        Resources::new(
+        let production = Resources::new(
             self.resources.metal * self.army.amount(&Unit::Building(Building::MetalMine)),
             self.resources.crystal * self.army.amount(&Unit::Building(Building::CrystalMine)),
             self.resources.deuterium
                 * self.army.amount(&Unit::Building(Building::DeuteriumSynthesizer)),
-        )
+        );
+
+        production * self.specialization_bonus(Specialization::Economy) / 100usize
+    }
+
+    /// Invest delivered cargo in the planet, raising its production per mine level
+    pub fn invest(&mut self, cargo: Resources) {
+        self.resources += cargo / CARGO_INVESTMENT_FACTOR;
+    }
+
+    /// Extra production of a resource per turn when upgrading its building one level
+    pub fn production_increase(&self, resource: &ResourceName) -> usize {
+        self.resources.get(resource) * self.specialization_bonus(Specialization::Economy) / 100
+    }
+
+    /// Whether the specialization can be changed this turn
+    pub fn can_specialize(&self, turn: usize) -> bool {
+        self.specialization_turn.is_none_or(|t| turn >= t + SPECIALIZATION_COOLDOWN)
+    }
+
+    /// Change the specialization, paying its price, and return whether it changed
+    pub fn specialize(
+        &mut self,
+        specialization: Specialization,
+        turn: usize,
+        resources: &mut Resources,
+    ) -> bool {
+        if self.specialization == specialization
+            || !self.can_specialize(turn)
+            || !(*resources >= specialization.price())
+        {
+            return false;
+        }
+
+        *resources -= specialization.price();
+        self.specialization = specialization;
+        self.specialization_turn = Some(turn);
+        true
+    }
+
+    /// Percentage of production for the given specialization
+    fn specialization_bonus(&self, specialization: Specialization) -> usize {
+        if self.specialization == specialization {
+            100 + SPECIALIZATION_BONUS
+        } else {
+            100
+        }
     }
 
     pub fn fields_consumed(&self) -> usize {
@@ -336,7 +814,10 @@ impl Planet {
     }
 
     pub fn max_fleet_production(&self) -> usize {
-        SHIPYARD_PRODUCTION_FACTOR * self.army.amount(&Unit::Building(Building::Shipyard))
+        SHIPYARD_PRODUCTION_FACTOR
+            * self.army.amount(&Unit::Building(Building::Shipyard))
+            * self.specialization_bonus(Specialization::Military)
+            / 100
     }
 
     pub fn battery_production(&self) -> usize {
@@ -344,7 +825,10 @@ impl Planet {
     }
 
     pub fn max_battery_production(&self) -> usize {
-        FACTORY_PRODUCTION_FACTOR * self.army.amount(&Unit::Building(Building::Factory))
+        FACTORY_PRODUCTION_FACTOR
+            * self.army.amount(&Unit::Building(Building::Factory))
+            * self.specialization_bonus(Specialization::Military)
+            / 100
     }
 
     pub fn missile_capacity(&self) -> usize {
@@ -355,10 +839,61 @@ impl Planet {
         SILO_CAPACITY_FACTOR * self.army.amount(&Unit::Building(Building::MissileSilo))
     }
 
+    /// Deuterium required every turn to maintain the ships on this planet
+    pub fn upkeep(&self) -> usize {
+        self.army.iter().map(|(u, c)| u.upkeep() * c).sum()
+    }
+
+    /// Maximum number of units that can be queued at once, limited by the
+    /// resources and the planet's capacity
+    pub fn max_purchasable(&self, unit: &Unit, resources: &Resources) -> usize {
+        (*resources / unit.price()).min().min(self.capacity_left(unit))
+    }
+
+    /// Number of units that can be selected for a mission without touching
+    /// the units reserved for home defense
+    pub fn available(&self, unit: &Unit) -> usize {
+        self.army.amount(unit).saturating_sub(self.reserved.amount(unit))
+    }
+
+    /// Select `pct` percent (rounded) of every unit available for a mission,
+    /// leaving the reserved units behind
+    pub fn select_available(&self, units: &[Unit], pct: usize) -> Army {
+        units
+            .iter()
+            .map(|u| {
+                let n = self.available(u);
+                (*u, ((n * pct + 50) / 100).min(n))
+            })
+            .collect()
+    }
+
+    /// Reservation that keeps all the units that aren't selected for a mission
+    /// as home defense
+    pub fn reserve_unselected(&self, units: &[Unit], selected: &Army) -> Army {
+        let mut reserved = self.reserved.clone();
+        for u in units {
+            let n = self.army.amount(u);
+            reserved.insert(*u, n - selected.amount(u).min(n));
+        }
+
+        reserved.retain(|_, c| *c > 0);
+        reserved
+    }
+
     pub fn max_jump_capacity(&self) -> usize {
         FACTORY_PRODUCTION_FACTOR * self.army.amount(&Unit::Building(Building::JumpGate))
     }
 
+    /// Whether another planet is within reach of this planet's Sensor Phalanx
+    pub fn in_phalanx_range(&self, other: &Planet) -> bool {
+        let phalanx = self.army.amount(&Unit::Building(Building::SensorPhalanx));
+        phalanx > 0
+            && self.id != other.id
+            && PHALANX_DISTANCE * phalanx as f32 * Self::SIZE + self.size() * 0.5
+                >= self.position.distance(other.position)
+    }
+
     /// Units and combat ============================================= >>
 
     pub fn has(&self, unit: &Unit) -> bool {
@@ -378,7 +913,13 @@ impl Planet {
     }
 
     /// Merge a fleet into the planet's fleet
-    pub fn dock(&mut self, army: Army) {
+    /// Add an army to the planet, averaging the veterancy of the combat ships
+    pub fn dock(&mut self, army: Army, veterancy: usize) {
+        let (n_planet, n_army) = (self.army.combat_ships(), army.combat_ships());
+        self.veterancy = (self.veterancy * n_planet + veterancy * n_army)
+            .checked_div(n_planet + n_army)
+            .unwrap_or(0);
+
         for (unit, count) in army {
             *self.army.entry(unit).or_default() += count;
         }
@@ -390,6 +931,533 @@ impl Planet {
         self.controlled = None;
         self.army = Army::new();
         self.buy = Vec::new();
+        self.buy_weights.clear();
+        self.veterancy = 0;
+        self.auto_economy = None;
+        self.overflow = None;
+        self.custom_name = None;
+        self.mothballed = false;
         self.is_destroyed = true;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::units::ships::Ship;
+
+    const RICH: Resources = Resources {
+        metal: 1_000_000,
+        crystal: 1_000_000,
+        deuterium: 1_000_000,
+    };
+
+    fn planet(is_moon: bool, buildings: &[(Building, usize)]) -> Planet {
+        let mut planet = Planet::new(0, "Test".into(), Vec2::ZERO, is_moon, 1., ResourceBias::None);
+        for (building, level) in buildings {
+            planet.army.insert(Unit::Building(*building), *level);
+        }
+
+        planet
+    }
+
+    #[test]
+    fn home_planet_starts_with_the_package() {
+        for start in [StartPackage::Minimal, StartPackage::Standard, StartPackage::Advanced] {
+            let mut home = planet(false, &[]);
+            home.make_home_planet(1, start);
+            assert_eq!(home.owned, Some(1));
+            assert_eq!(home.controlled, Some(1));
+            assert_eq!(home.army, start.army());
+        }
+
+        // Only the advanced package comes with a fleet and defenses
+        let mut home = planet(false, &[]);
+        home.make_home_planet(1, StartPackage::Advanced);
+        assert_eq!(home.army.amount(&Unit::Ship(Ship::LightFighter)), 5);
+        assert_eq!(home.army.amount(&Unit::Defense(Defense::LightLaser)), 2);
+    }
+
+    #[test]
+    fn new_colonies_queue_the_build_order() {
+        let mut colony = planet(false, &[]);
+        let mut resources = RICH;
+        assert!(colony.queue_build_order(BuildOrder::Manual, &mut resources).is_empty());
+
+        let queued = colony.queue_build_order(BuildOrder::Industry, &mut resources);
+        assert_eq!(queued, BuildOrder::Industry.buildings(false));
+        assert_eq!(colony.buy, queued);
+        assert_eq!(resources, RICH - queued.iter().map(|u| u.price()).sum::<Resources>());
+
+        // Queued buildings aren't queued twice
+        assert!(colony.queue_build_order(BuildOrder::Economy, &mut resources).is_empty());
+
+        let mut moon = planet(true, &[]);
+        let mut resources = RICH;
+        let queued = moon.queue_build_order(BuildOrder::Economy, &mut resources);
+        assert_eq!(queued, vec![Unit::Building(Building::LunarBase)]);
+    }
+
+    #[test]
+    fn build_order_stops_at_the_resources() {
+        let mut colony = planet(false, &[]);
+        let first = BuildOrder::Economy.buildings(false)[0];
+        let mut resources = first.price();
+
+        assert_eq!(colony.queue_build_order(BuildOrder::Economy, &mut resources), vec![first]);
+        assert_eq!(resources, Resources::default());
+    }
+
+    #[test]
+    fn rename_sets_the_trimmed_name() {
+        let mut planet = planet(false, &[]);
+        assert_eq!(planet.rename("  New Terra "), Ok(()));
+        assert_eq!(planet.custom_name.as_deref(), Some("New Terra"));
+
+        // Renaming to the original name clears the custom one
+        assert_eq!(planet.rename("Test"), Ok(()));
+        assert_eq!(planet.custom_name, None);
+    }
+
+    #[test]
+    fn rename_rejects_empty_or_long_names() {
+        let mut planet = planet(false, &[]);
+        assert!(planet.rename("   ").is_err());
+
+        let max = "a".repeat(MAX_PLANET_NAME_LENGTH);
+        assert_eq!(planet.rename(&max), Ok(()));
+        assert_eq!(planet.custom_name, Some(max));
+
+        let long = "a".repeat(MAX_PLANET_NAME_LENGTH + 1);
+        assert!(planet.rename(&long).is_err());
+        assert_eq!(planet.custom_name.as_deref().map(str::len), Some(MAX_PLANET_NAME_LENGTH));
+    }
+
+    #[test]
+    fn selections_leave_the_reserved_units_behind() {
+        let cruiser = Unit::Ship(Ship::Cruiser);
+        let probe = Unit::Ship(Ship::Probe);
+        let units = [cruiser, probe];
+
+        let mut planet = planet(false, &[]);
+        planet.army.extend([(cruiser, 10), (probe, 4)]);
+        planet.reserved.insert(cruiser, 3);
+        assert_eq!(planet.available(&cruiser), 7);
+
+        let all = planet.select_available(&units, 100);
+        assert_eq!(all, Army::from([(cruiser, 7), (probe, 4)]));
+
+        let half = planet.select_available(&units, 50);
+        assert_eq!(half, Army::from([(cruiser, 4), (probe, 2)]));
+
+        // Reservations larger than the army (e.g. after losses) leave nothing
+        planet.army.insert(cruiser, 2);
+        assert_eq!(planet.available(&cruiser), 0);
+        assert_eq!(planet.select_available(&units, 100).amount(&cruiser), 0);
+    }
+
+    #[test]
+    fn unselected_units_are_reserved() {
+        let cruiser = Unit::Ship(Ship::Cruiser);
+        let probe = Unit::Ship(Ship::Probe);
+
+        let mut planet = planet(false, &[]);
+        planet.army.extend([(cruiser, 10), (probe, 4)]);
+
+        let selected = Army::from([(cruiser, 6), (probe, 4)]);
+        planet.reserved = planet.reserve_unselected(&[cruiser, probe], &selected);
+        assert_eq!(planet.reserved, Army::from([(cruiser, 4)]));
+        assert_eq!(planet.select_available(&[cruiser, probe], 100), selected);
+
+        // The reservation is lost when the planet changes hands
+        planet.control(2);
+        assert!(planet.reserved.is_empty());
+    }
+
+    #[test]
+    fn capacity_is_limited_by_the_production() {
+        let destroyer = Unit::Ship(Ship::Destroyer);
+        let mut planet = planet(false, &[(Building::Shipyard, 2)]);
+        assert_eq!(planet.capacity_left(&destroyer), 5);
+
+        planet.buy.push(destroyer);
+        assert_eq!(planet.capacity_left(&destroyer), 4);
+        assert_eq!(planet.max_purchasable(&destroyer, &RICH), 4);
+        assert!(planet.can_queue(&destroyer));
+
+        planet.buy.extend([destroyer; 4]);
+        assert_eq!(planet.max_purchasable(&destroyer, &RICH), 0);
+        assert!(!planet.can_queue(&destroyer));
+    }
+
+    #[test]
+    fn moons_only_build_lunar_units() {
+        let [buildings, ships] = &Unit::all_valid(true)[..] else {
+            panic!("Moons build buildings and ships only.");
+        };
+        assert!(buildings.iter().all(|b| Unit::lunar_buildings().contains(b)));
+        assert!(!buildings.contains(&Unit::Building(Building::MetalMine)));
+        assert_eq!(*ships, Unit::ships());
+
+        let moon = planet(true, &[(Building::Factory, 5)]);
+        assert!(!moon.can_queue(&Unit::Defense(Defense::RocketLauncher)));
+    }
+
+    #[test]
+    fn capacity_is_limited_by_the_resources() {
+        let destroyer = Unit::Ship(Ship::Destroyer);
+        let planet = planet(false, &[(Building::Shipyard, 2)]);
+        assert_eq!(planet.max_purchasable(&destroyer, &(destroyer.price() * 2usize)), 2);
+    }
+
+    #[test]
+    fn all_missiles_share_the_silo_capacity() {
+        let abm = Unit::Defense(Defense::AntiballisticMissile);
+        let ipm = Unit::Defense(Defense::InterplanetaryMissile);
+        let mut planet = planet(false, &[(Building::MissileSilo, 1), (Building::Factory, 10)]);
+        planet.army.insert(abm, 6);
+        planet.buy.extend([ipm; 3]);
+
+        // Queued interplanetary missiles also fill the silo
+        assert_eq!(planet.capacity_left(&abm), 1);
+        assert_eq!(planet.max_purchasable(&abm, &RICH), 1);
+        assert!(planet.can_queue(&abm));
+
+        planet.buy.push(abm);
+        assert_eq!(planet.max_purchasable(&abm, &RICH), 0);
+        assert_eq!(planet.max_purchasable(&ipm, &RICH), 0);
+        assert!(!planet.can_queue(&abm));
+    }
+
+    #[test]
+    fn only_one_space_dock_fits() {
+        let dock = Unit::Defense(Defense::SpaceDock);
+        let mut planet = planet(false, &[(Building::Factory, 5)]);
+        assert_eq!(planet.max_purchasable(&dock, &RICH), 1);
+
+        planet.buy.push(dock);
+        assert_eq!(planet.max_purchasable(&dock, &RICH), 0);
+
+        planet.produce();
+        assert_eq!(planet.max_purchasable(&dock, &RICH), 0);
+    }
+
+    #[test]
+    fn buildings_are_limited_by_the_queue_and_the_fields() {
+        let shipyard = Unit::Building(Building::Shipyard);
+        let mut moon = planet(true, &[(Building::LunarBase, 1)]);
+        assert_eq!(moon.max_purchasable(&shipyard, &RICH), 1);
+
+        moon.buy.push(shipyard);
+        assert_eq!(moon.max_purchasable(&shipyard, &RICH), 0);
+
+        // The only field is taken by the new Shipyard
+        moon.produce();
+        assert_eq!(moon.max_purchasable(&Unit::Building(Building::Factory), &RICH), 0);
+    }
+
+    #[test]
+    fn invested_cargo_raises_the_production() {
+        let mut planet = Planet::new(0, "Test".into(), Vec2::ZERO, false, 1., ResourceBias::None);
+        planet.resources = Resources::new(200, 200, 200);
+        planet.army.insert(Unit::Building(Building::MetalMine), 2);
+
+        planet.invest(Resources::new(CARGO_INVESTMENT_FACTOR * 10, 0, 0));
+        assert_eq!(planet.resources, Resources::new(210, 200, 200));
+        assert_eq!(planet.resource_production().metal, 420);
+    }
+
+    #[test]
+    fn queued_units_within_the_capacity_are_all_built() {
+        let mut planet = planet(false, &[(Building::Shipyard, 2)]);
+        planet.buy = vec![Unit::Ship(Ship::Destroyer); 5];
+        assert_eq!(planet.fit_production(), Resources::default());
+
+        planet.produce();
+        assert_eq!(planet.army.amount(&Unit::Ship(Ship::Destroyer)), 5);
+    }
+
+    #[test]
+    fn overflow_fills_the_idle_production() {
+        let fighter = Unit::Ship(Ship::LightFighter);
+        let mut planet = planet(false, &[(Building::Shipyard, 2)]);
+        planet.overflow = Some(fighter);
+
+        let mut resources = RICH;
+        assert_eq!(planet.overflow_build(&mut resources), 10);
+        assert_eq!(planet.fleet_production(), planet.max_fleet_production());
+        assert_eq!(resources, RICH - fighter.price() * 10usize);
+    }
+
+    #[test]
+    fn overflow_is_limited_by_the_resources() {
+        let destroyer = Unit::Ship(Ship::Destroyer);
+        let mut planet = planet(false, &[(Building::Shipyard, 2)]);
+        planet.overflow = Some(destroyer);
+
+        let mut resources = destroyer.price() * 3usize;
+        assert_eq!(planet.overflow_build(&mut resources), 3);
+        assert_eq!(resources, Resources::default());
+    }
+
+    #[test]
+    fn overflow_defenses_are_limited_by_the_battery() {
+        let launcher = Unit::Defense(Defense::RocketLauncher);
+        let mut planet = planet(false, &[(Building::Factory, 2)]);
+        planet.overflow = Some(launcher);
+
+        let mut resources = RICH;
+        assert_eq!(planet.overflow_build(&mut resources), 10);
+        assert_eq!(planet.battery_production(), planet.max_battery_production());
+        assert!(!planet.can_queue(&launcher));
+    }
+
+    #[test]
+    fn overflow_skips_busy_or_mothballed_planets() {
+        let fighter = Unit::Ship(Ship::LightFighter);
+        let mut planet = planet(false, &[(Building::Shipyard, 2)]);
+        planet.overflow = Some(fighter);
+        planet.buy.push(Unit::Ship(Ship::Destroyer));
+
+        let mut resources = RICH;
+        assert_eq!(planet.overflow_build(&mut resources), 0);
+
+        planet.buy.clear();
+        planet.mothballed = true;
+        assert_eq!(planet.overflow_build(&mut resources), 0);
+        assert_eq!(resources, RICH);
+    }
+
+    #[test]
+    fn exceeding_production_is_split_by_the_weights() {
+        let (fighter, destroyer) = (Unit::Ship(Ship::LightFighter), Unit::Ship(Ship::Destroyer));
+        let mut planet = planet(false, &[(Building::Shipyard, 2)]);
+        planet.buy = [vec![fighter; 10], vec![destroyer; 5]].concat();
+        planet.buy_weights.insert(fighter, 2);
+
+        // A capacity of 10 split 2:1 leaves room for 7 fighters and 1 destroyer
+        let refund = planet.fit_production();
+        assert_eq!(refund, fighter.price() * 3usize + destroyer.price() * 4usize);
+        assert_eq!(planet.buy.iter().filter(|u| **u == fighter).count(), 7);
+        assert_eq!(planet.buy.iter().filter(|u| **u == destroyer).count(), 1);
+        assert!(planet.fleet_production() <= planet.max_fleet_production());
+    }
+
+    #[test]
+    fn allocate_production_follows_the_weights() {
+        assert_eq!(allocate_production(9, &[2, 1]), vec![6, 3]);
+        assert_eq!(allocate_production(30, &[2, 1]), vec![20, 10]);
+    }
+
+    #[test]
+    fn allocate_production_conserves_the_capacity() {
+        for capacity in 0..50 {
+            for weights in [vec![2, 1], vec![1, 1, 1], vec![5, 3, 2, 1], vec![0, 4]] {
+                let shares = allocate_production(capacity, &weights);
+                assert_eq!(shares.iter().sum::<usize>(), capacity);
+                assert_eq!(shares, allocate_production(capacity, &weights));
+            }
+        }
+
+        assert_eq!(allocate_production(10, &[1, 1, 1]), vec![4, 3, 3]);
+        assert_eq!(allocate_production(10, &[0, 0]), vec![0, 0]);
+    }
+
+    #[test]
+    fn auto_economy_upgrades_when_affordable() {
+        let mines = [(Building::MetalMine, 1), (Building::CrystalMine, 1)];
+        let mut planet = planet(false, &mines);
+        planet.auto_economy = Some(AutoEconomy {
+            budget: RICH,
+            ..Default::default()
+        });
+
+        let mut resources = RICH;
+        planet.auto_build(&mut resources);
+        assert_eq!(planet.buy.len(), 1);
+        assert!(Unit::resource_buildings().contains(&planet.buy[0]));
+        assert_eq!(resources, RICH - planet.buy[0].price());
+
+        // Without automatic economy, nothing is queued
+        let mut planet = self::planet(false, &mines);
+        planet.auto_build(&mut resources);
+        assert!(planet.buy.is_empty());
+    }
+
+    #[test]
+    fn auto_economy_waits_when_broke() {
+        let mut planet = planet(false, &[(Building::MetalMine, 1)]);
+        planet.auto_economy = Some(AutoEconomy {
+            budget: RICH,
+            ..Default::default()
+        });
+
+        let mut resources = Resources::default();
+        planet.auto_build(&mut resources);
+        assert!(planet.buy.is_empty());
+        assert_eq!(resources, Resources::default());
+        assert!(planet.auto_economy.unwrap().budget >= RICH);
+    }
+
+    /// Planet producing 50 of every resource with level 5 mines
+    fn mining_planet() -> Planet {
+        let mines = [
+            (Building::MetalMine, 5),
+            (Building::CrystalMine, 5),
+            (Building::DeuteriumSynthesizer, 5),
+        ];
+
+        Planet {
+            resources: Resources::new(10, 10, 10),
+            ..planet(false, &mines)
+        }
+    }
+
+    #[test]
+    fn balanced_building_moves_toward_the_ratio() {
+        let metal = Unit::Building(Building::MetalMine);
+        let crystal = Unit::Building(Building::CrystalMine);
+        let deuterium = Unit::Building(Building::DeuteriumSynthesizer);
+
+        let mut planet = mining_planet();
+        assert_eq!(planet.balanced_building([2, 1, 1], 10), Some(metal));
+        assert_eq!(planet.balanced_building([1, 3, 2], 10), Some(crystal));
+        assert_eq!(planet.balanced_building([1, 0, 2], 10), Some(deuterium));
+
+        // The next furthest resource is chosen when the building is maxed or queued
+        assert_eq!(planet.balanced_building([3, 2, 1], 5), None);
+        planet.army.insert(metal, 4);
+        assert_eq!(planet.balanced_building([1, 1, 1], 5), Some(metal));
+        planet.buy.push(metal);
+        assert_eq!(planet.balanced_building([3, 2, 1], 10), Some(crystal));
+    }
+
+    #[test]
+    fn auto_economy_follows_the_ratio() {
+        let mut planet = mining_planet();
+        planet.auto_economy = Some(AutoEconomy {
+            budget: RICH,
+            ratio: Some([1, 1, 4]),
+            ..Default::default()
+        });
+
+        let mut resources = RICH;
+        planet.auto_build(&mut resources);
+        assert_eq!(planet.buy, vec![Unit::Building(Building::DeuteriumSynthesizer)]);
+    }
+
+    #[test]
+    fn cancelled_buildings_are_refunded() {
+        let shipyard = Unit::Building(Building::Shipyard);
+        let mut planet = planet(false, &[(Building::Shipyard, 1)]);
+        let mut resources = RICH;
+        let fields = planet.fields_consumed();
+
+        resources -= shipyard.price();
+        planet.buy.push(shipyard);
+        assert_eq!(planet.fields_consumed(), fields + 1);
+        assert_eq!(planet.capacity_left(&shipyard), 0);
+
+        assert!(planet.cancel(&shipyard, &mut resources));
+        assert_eq!(resources, RICH);
+        assert_eq!(planet.fields_consumed(), fields);
+        assert_eq!(planet.capacity_left(&shipyard), 1);
+
+        // Nothing left to cancel
+        assert!(!planet.cancel(&shipyard, &mut resources));
+        assert_eq!(resources, RICH);
+    }
+
+    #[test]
+    fn specialization_describes_the_bonus() {
+        let bonus = format!("{SPECIALIZATION_BONUS}%");
+        assert!(Specialization::Economy.description().contains(&bonus));
+        assert!(Specialization::Military.description().contains(&bonus));
+    }
+
+    #[test]
+    fn economy_raises_the_resource_production() {
+        let mut planet = planet(
+            false,
+            &[
+                (Building::MetalMine, 5),
+                (Building::CrystalMine, 5),
+                (Building::DeuteriumSynthesizer, 5),
+            ],
+        );
+        planet.resources = Resources::new(10, 20, 30);
+        assert_eq!(planet.resource_production(), Resources::new(50, 100, 150));
+
+        planet.specialization = Specialization::Economy;
+        assert_eq!(planet.resource_production(), Resources::new(60, 120, 180));
+        assert_eq!(planet.max_fleet_production(), 0);
+    }
+
+    #[test]
+    fn military_raises_the_production_capacity() {
+        let mut planet = planet(false, &[(Building::Shipyard, 5), (Building::Factory, 5)]);
+        assert_eq!(planet.max_fleet_production(), 25);
+        assert_eq!(planet.max_battery_production(), 25);
+
+        planet.specialization = Specialization::Military;
+        assert_eq!(planet.max_fleet_production(), 30);
+        assert_eq!(planet.max_battery_production(), 30);
+    }
+
+    #[test]
+    fn specializing_costs_resources_and_starts_the_cooldown() {
+        let mut planet = planet(false, &[]);
+        let mut resources = RICH;
+
+        assert!(planet.specialize(Specialization::Economy, 3, &mut resources));
+        assert_eq!(planet.specialization, Specialization::Economy);
+        assert_eq!(resources, RICH - Specialization::Economy.price());
+
+        // The cooldown blocks any change until it expires
+        let paid = resources;
+        assert!(!planet.can_specialize(3 + SPECIALIZATION_COOLDOWN - 1));
+        assert!(!planet.specialize(Specialization::Military, 4, &mut resources));
+        assert_eq!(planet.specialization, Specialization::Economy);
+        assert_eq!(resources, paid);
+
+        assert!(planet.can_specialize(3 + SPECIALIZATION_COOLDOWN));
+        assert!(planet.specialize(
+            Specialization::Military,
+            3 + SPECIALIZATION_COOLDOWN,
+            &mut resources
+        ));
+        assert_eq!(planet.specialization_turn, Some(3 + SPECIALIZATION_COOLDOWN));
+    }
+
+    #[test]
+    fn specializing_requires_the_price() {
+        let mut planet = planet(false, &[]);
+        let mut resources = Resources::default();
+
+        assert!(!planet.specialize(Specialization::Economy, 0, &mut resources));
+        assert_eq!(planet.specialization, Specialization::None);
+        assert_eq!(planet.specialization_turn, None);
+    }
+
+    #[test]
+    fn resource_bias_follows_the_planet_kind() {
+        for bias in [ResourceBias::None, ResourceBias::Normal, ResourceBias::Strong] {
+            let ranges = bias.ranges();
+            for _ in 0..50 {
+                let planet = Planet::new(0, "Test".into(), Vec2::ZERO, false, 1., bias);
+                let resources =
+                    [planet.resources.metal, planet.resources.crystal, planet.resources.deuterium];
+                for (amount, i) in resources.into_iter().zip(planet.kind.abundance()) {
+                    let range = &ranges[i];
+                    assert!(
+                        (range.start * 10.) as usize <= amount
+                            && amount <= (range.end * 10.) as usize
+                    );
+                }
+            }
+        }
+
+        let moon = Planet::new(0, "Test".into(), Vec2::ZERO, true, 1., ResourceBias::Strong);
+        assert_eq!(moon.resources, Resources::default());
+    }
+}
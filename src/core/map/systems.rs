@@ -7,8 +7,8 @@ use bevy::color::palettes::css::WHITE;
 use bevy::mesh::{Indices, PrimitiveTopology};
 use bevy::prelude::*;
 use bevy::window::{CursorIcon, SystemCursorIcon};
-use bevy_tweening::lens::ColorMaterialColorLens;
-use bevy_tweening::{AnimTarget, RepeatCount, RepeatStrategy, Tween, TweenAnim};
+use bevy_tweening::lens::{ColorMaterialColorLens, TransformPositionLens};
+use bevy_tweening::{AnimTarget, PlaybackState, RepeatCount, RepeatStrategy, Tween, TweenAnim};
 use itertools::Itertools;
 use rand::{rng, Rng};
 use strum::IntoEnumIterator;
@@ -17,23 +17,27 @@ use voronator::VoronoiDiagram;
 
 use crate::core::assets::WorldAssets;
 use crate::core::camera::{MainCamera, ParallaxCmp};
+use crate::core::combat::systems::CombatCmp;
 use crate::core::constants::{
-    BACKGROUND_Z, BUTTON_TEXT_SIZE, ENEMY_COLOR, OWN_COLOR, PHALANX_DISTANCE, PLANET_Z,
-    RADAR_DISTANCE, TITLE_TEXT_SIZE, VORONOI_Z,
+    ALLY_COLOR, AUTO_ECONOMY_COLOR, BACKGROUND_Z, BUTTON_TEXT_SIZE, CULLING_MARGIN, ENEMY_COLOR,
+    EVEN_ODDS, EVEN_ODDS_COLOR, FAVORABLE_ODDS, MISSION_Z, ODDS_DISTANCE, OWN_COLOR,
+    PHALANX_DISTANCE, PLANET_Z, RADAR_DISTANCE, STALE_INTEL_COLOR, TITLE_TEXT_SIZE,
+    UNKNOWN_ODDS_COLOR, VORONOI_Z, WORMHOLE_COLOR,
 };
+use crate::core::diplomacy::Diplomacy;
 use crate::core::map::icon::Icon;
 use crate::core::map::map::{Map, MapCmp};
-use crate::core::map::planet::{Planet, PlanetId};
+use crate::core::map::planet::{Planet, PlanetId, Specialization};
 use crate::core::map::utils::{cursor, spawn_main_button, MainButtonLabelCmp, TransformOrbitLens};
 use crate::core::missions::{Mission, MissionId, Missions};
-use crate::core::player::Player;
+use crate::core::player::{NoteTag, Player, Roster};
 use crate::core::resources::ResourceName;
 use crate::core::settings::Settings;
 use crate::core::states::GameState;
 use crate::core::ui::systems::{MissionTab, UiState};
 use crate::core::units::buildings::Building;
 use crate::core::units::ships::Ship;
-use crate::core::units::{Amount, Army, Unit};
+use crate::core::units::{Amount, Army, Combat, Unit};
 use crate::utils::NameFromEnum;
 
 #[derive(Component)]
@@ -49,19 +53,27 @@ impl PlanetCmp {
     }
 }
 
+/// Component for a mission on the map. It stores the position the
+/// sprite is moving to in order to animate the fleet between turns
 #[derive(Component)]
 pub struct MissionCmp {
     pub id: MissionId,
+    pub position: Vec2,
 }
 
 impl MissionCmp {
-    pub fn new(id: MissionId) -> Self {
+    pub fn new(id: MissionId, position: Vec2) -> Self {
         Self {
             id,
+            position,
         }
     }
 }
 
+/// Label showing the turns remaining for a mission to reach its destination
+#[derive(Component)]
+pub struct MissionEtaCmp;
+
 #[derive(Component)]
 pub struct ExplosionCmp {
     pub timer: Timer,
@@ -70,7 +82,7 @@ pub struct ExplosionCmp {
 }
 
 #[derive(Component)]
-pub struct PlanetNameCmp;
+pub struct PlanetNameCmp(pub PlanetId);
 
 #[derive(Component)]
 pub struct PlanetResourcesCmp;
@@ -107,6 +119,18 @@ impl PlanetaryShieldCmp {
 #[derive(Component)]
 pub struct SpaceDockCmp;
 
+/// Icon showing the specialization of an owned planet
+#[derive(Component)]
+pub struct SpecializationCmp(pub PlanetId);
+
+/// Ring marking owned planets that upgrade their economy automatically
+#[derive(Component)]
+pub struct AutoEconomyCmp(pub PlanetId);
+
+/// Ring showing the color tag the player gave to a planet
+#[derive(Component)]
+pub struct NoteTagCmp(pub PlanetId);
+
 #[derive(Component)]
 pub struct ScannerCmp(pub bool);
 
@@ -119,6 +143,14 @@ pub struct VoronoiEdgeCmp {
     pub key: (i32, i32, i32, i32),
 }
 
+/// World-space bounds of an entity drawn without a transform (e.g., Voronoi meshes)
+#[derive(Component)]
+pub struct CullBoundsCmp(pub Rect);
+
+/// Number of map entities hidden this frame because they are off-screen
+#[derive(Resource, Default)]
+pub struct CulledEntities(pub usize);
+
 #[derive(Component)]
 pub struct EndTurnLabelCmp;
 
@@ -128,6 +160,9 @@ pub struct EndTurnButtonCmp;
 #[derive(Component)]
 pub struct SpectatorLabelCmp;
 
+#[derive(Component)]
+pub struct SandboxLabelCmp;
+
 fn edge_key(v1: Vec2, v2: Vec2) -> (i32, i32, i32, i32) {
     let precision = 5.0;
     let mut a = ((v1.x / precision).round() as i32, (v1.y / precision).round() as i32);
@@ -264,7 +299,7 @@ pub fn draw_map(
             )
             .with_children(|parent| {
                 parent.spawn((
-                    Text2d::new(&planet.name),
+                    Text2d::new(player.planet_name(planet)),
                     TextFont {
                         font: assets.font("bold"),
                         font_size: TITLE_TEXT_SIZE,
@@ -273,7 +308,7 @@ pub fn draw_map(
                     TextColor(WHITE.into()),
                     Transform::from_xyz(0., planet.size() * 0.7, 0.9),
                     Pickable::IGNORE,
-                    PlanetNameCmp,
+                    PlanetNameCmp(planet.id),
                 ));
 
                 // Destroyed planets have no resources nor icons
@@ -460,6 +495,43 @@ pub fn draw_map(
                         }
                     }
 
+                    if !planet.is_moon() {
+                        parent.spawn((
+                            Sprite {
+                                custom_size: Some(Vec2::splat(Icon::SIZE)),
+                                ..default()
+                            },
+                            Transform::from_xyz(-planet.size() * 0.45, planet.size() * 0.4, 0.8),
+                            Pickable::IGNORE,
+                            Visibility::Hidden,
+                            SpecializationCmp(planet.id),
+                        ));
+                    }
+
+                    // Draw the note's color tag
+                    parent.spawn((
+                        Mesh2d(meshes.add(Annulus::new(planet.size() * 0.6, planet.size() * 0.63))),
+                        MeshMaterial2d(materials.add(ColorMaterial::from(Color::NONE))),
+                        Transform::from_xyz(0., 0., 0.5),
+                        Pickable::IGNORE,
+                        Visibility::Hidden,
+                        NoteTagCmp(planet.id),
+                    ));
+
+                    if !planet.is_moon() {
+                        parent.spawn((
+                            Mesh2d(
+                                meshes
+                                    .add(Annulus::new(planet.size() * 0.65, planet.size() * 0.66)),
+                            ),
+                            MeshMaterial2d(materials.add(ColorMaterial::from(AUTO_ECONOMY_COLOR))),
+                            Transform::from_xyz(0., 0., 0.5),
+                            Pickable::IGNORE,
+                            Visibility::Hidden,
+                            AutoEconomyCmp(planet.id),
+                        ));
+                    }
+
                     // Draw planetary shield
                     let material = materials.add(ColorMaterial::from(OWN_COLOR));
                     parent.spawn((
@@ -552,11 +624,15 @@ pub fn draw_map(
                     .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
                     .with_inserted_indices(Indices::U32(indices));
 
+                let bounds =
+                    positions.iter().fold(Rect::EMPTY, |rect, p| rect.union_point(p.truncate()));
+
                 commands.spawn((
                     Mesh2d(meshes.add(mesh)),
                     MeshMaterial2d(materials.add(OWN_COLOR.with_alpha(0.5))),
                     Visibility::Hidden,
                     VoronoiCmp(map.planets[i].id),
+                    CullBoundsCmp(bounds),
                     MapCmp,
                 ));
 
@@ -581,6 +657,7 @@ pub fn draw_map(
                             planet: planet_id,
                             key: edge_key(v1, v2),
                         },
+                        CullBoundsCmp(Rect::from_corners(v1, v2)),
                         MapCmp,
                     ));
                 }
@@ -588,6 +665,28 @@ pub fn draw_map(
         }
     }
 
+    // Draw wormholes
+    for (a, b) in &map.wormholes {
+        let (a, b) = (map.get(*a), map.get(*b));
+        let direction = (b.position - a.position).normalize();
+
+        let mesh = Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::default())
+            .with_inserted_attribute(
+                Mesh::ATTRIBUTE_POSITION,
+                vec![
+                    (a.position + direction * a.size() * 0.6).extend(VORONOI_Z + 0.2),
+                    (b.position - direction * b.size() * 0.6).extend(VORONOI_Z + 0.2),
+                ],
+            )
+            .with_inserted_indices(Indices::U32(vec![0, 1]));
+
+        commands.spawn((
+            Mesh2d(meshes.add(mesh)),
+            MeshMaterial2d(materials.add(WORMHOLE_COLOR.with_alpha(0.6))),
+            MapCmp,
+        ));
+    }
+
     // Spawn end turn button
     commands.spawn((
         Node {
@@ -635,6 +734,26 @@ pub fn draw_map(
         SpectatorLabelCmp,
         MapCmp,
     ));
+
+    // Spawn sandbox mode label
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(30.),
+            left: Val::Px(60.),
+            ..default()
+        },
+        Text::new("Sandbox Mode"),
+        TextFont {
+            font: assets.font("bold"),
+            font_size: 30.,
+            ..default()
+        },
+        TextColor(AUTO_ECONOMY_COLOR.into()),
+        Visibility::Hidden,
+        SandboxLabelCmp,
+        MapCmp,
+    ));
 }
 
 pub fn update_planet_info(
@@ -706,12 +825,34 @@ pub fn update_planet_info(
 ) {
     let (n_owned, n_max_owned) = player.planets_owned(&map, &settings);
 
+    // Distance the fleet of the mission being composed can reach with the available fuel
+    let fuel_range = if state.mission && state.mission_info.army.has_army() {
+        let range = state.mission_info.fuel_range(&map, player.resources.deuterium);
+        if range < map.rect.width() / Planet::SIZE {
+            // The mission starts and ends at the edge of the planets
+            (range + 1.4) * Planet::SIZE - map.get(state.mission_info.origin).size() * 0.5
+        } else {
+            0.
+        }
+    } else {
+        0.
+    };
+
     for (planet_e, mut planet_s, planet_c) in &mut planet_q {
         let planet = map.get(planet_c.id);
 
         // Update destroyed planet image
         planet_s.image = assets.image(planet.image());
 
+        // Fade enemy planets whose intel is stale, so they stand out for re-scouting
+        let stale = player.has_stale_intel(planet, &missions.0, &settings)
+            && player.last_info(planet, &missions.0).is_some_and(|i| i.controlled);
+        planet_s.color = Color::WHITE.with_alpha(if stale {
+            0.5
+        } else {
+            1.
+        });
+
         let selected =
             state.planet_hover.or(state.planet_selected).map(|id| id == planet.id).unwrap_or(false);
 
@@ -870,6 +1011,13 @@ pub fn update_planet_info(
                     RADAR_DISTANCE
                         * Planet::SIZE
                         * planet.army.amount(&Unit::Building(Building::OrbitalRadar)) as f32
+                } else if fuel_range > 0. && state.mission_info.origin == planet.id {
+                    // Only the ring is shown for the fuel range
+                    if scanner.0 {
+                        0.
+                    } else {
+                        fuel_range
+                    }
                 } else {
                     0.
                 };
@@ -891,6 +1039,57 @@ pub fn update_planet_info(
     }
 }
 
+/// Estimated threat to the player around every planet, normalized to [0, 1]. The
+/// threat comes from detected incoming missions and from known enemy armies,
+/// weighted by their distance to the planet
+fn threat_map(
+    map: &Map,
+    player: &Player,
+    missions: &Missions,
+    diplomacy: &Diplomacy,
+    settings: &Settings,
+) -> HashMap<PlanetId, f32> {
+    let power = |army: &Army| army.iter().map(|(u, c)| u.production() * c).sum::<usize>() as f32;
+
+    let mut threat: HashMap<PlanetId, f32> = HashMap::new();
+
+    for enemy in map.planets.iter().filter(|p| {
+        !p.is_destroyed
+            && !player.controls(p)
+            && !p.controlled.is_some_and(|c| diplomacy.is_allied(player.id, c))
+    }) {
+        let army = if settings.sandbox.reveal() && enemy.controlled.is_some() {
+            enemy.army.clone()
+        } else if let Some(info) = player.last_info(enemy, &missions.0).filter(|i| i.controlled) {
+            info.army
+        } else {
+            continue;
+        };
+
+        let power = power(&army);
+        for planet in map.planets.iter() {
+            let distance = planet.position.distance(enemy.position) / Planet::SIZE;
+            *threat.entry(planet.id).or_default() += power / (1. + distance);
+        }
+    }
+
+    for mission in missions.iter().filter(|m| {
+        m.owner != player.id
+            && !diplomacy.is_allied(player.id, m.owner)
+            && (m.is_seen_by_phalanx(map, player).is_some()
+                || m.is_seen_by_radar(map, player).is_some())
+    }) {
+        *threat.entry(mission.destination).or_default() += power(&mission.army);
+    }
+
+    let max = threat.values().copied().fold(0., f32::max);
+    if max > 0. {
+        threat.values_mut().for_each(|t| *t /= max);
+    }
+
+    threat
+}
+
 pub fn update_voronoi(
     mut cell_q: Query<(&mut Visibility, &mut MeshMaterial2d<ColorMaterial>, &VoronoiCmp)>,
     mut edge_q: Query<
@@ -901,22 +1100,69 @@ pub fn update_voronoi(
     map: Res<Map>,
     player: Res<Player>,
     missions: Res<Missions>,
+    diplomacy: Res<Diplomacy>,
+    roster: Res<Roster>,
     mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
+    // Only compute the threat when the overlay is shown
+    let threat =
+        settings.show_threat.then(|| threat_map(&map, &player, &missions, &diplomacy, &settings));
+
     for (mut cell_v, cell_m, cell) in &mut cell_q {
         let planet = map.get(cell.0);
+        let allied = planet.controlled.is_some_and(|c| diplomacy.is_allied(player.id, c));
+
+        if let Some(threat) = &threat {
+            if let Some(material) = materials.get_mut(&*cell_m) {
+                material.color = match threat.get(&planet.id) {
+                    Some(t) if *t > 0. => ENEMY_COLOR.with_alpha(0.01 + 0.2 * t),
+                    _ => Color::WHITE.with_alpha(0.005),
+                };
+            }
+
+            *cell_v = if planet.is_destroyed {
+                Visibility::Hidden
+            } else {
+                Visibility::Inherited
+            };
+
+            continue;
+        }
+
+        if settings.show_intel {
+            if let Some(material) = materials.get_mut(&*cell_m) {
+                material.color =
+                    if player.has_stale_intel(planet, &missions.0, &settings) && !allied {
+                        STALE_INTEL_COLOR.with_alpha(0.15)
+                    } else {
+                        Color::WHITE.with_alpha(0.005)
+                    };
+            }
+
+            *cell_v = if planet.is_destroyed {
+                Visibility::Hidden
+            } else {
+                Visibility::Inherited
+            };
+
+            continue;
+        }
 
         let visible = settings.show_cells
             && !planet.is_destroyed
             && (player.controls(planet)
+                || allied
+                || (settings.sandbox.reveal() && planet.controlled.is_some())
                 || player.last_info(planet, &missions.0).is_some_and(|i| i.controlled));
 
         if visible {
             if let Some(material) = materials.get_mut(&*cell_m) {
                 material.color = if player.controls(planet) {
                     OWN_COLOR.with_alpha(0.005)
+                } else if allied {
+                    ALLY_COLOR.with_alpha(0.005)
                 } else {
-                    ENEMY_COLOR.with_alpha(0.005)
+                    roster.color(planet.controlled, &settings).with_alpha(0.005)
                 };
             }
         }
@@ -930,13 +1176,20 @@ pub fn update_voronoi(
 
     let mut counts_enemy = HashMap::new();
     let mut counts_own = HashMap::new();
+    let mut counts_ally = HashMap::new();
+    let mut enemies = HashMap::new();
 
     for (_, _, edge) in &edge_q {
         let planet = map.get(edge.planet);
         if player.controls(planet) {
             *counts_own.entry(edge.key).or_default() += 1;
-        } else if player.last_info(planet, &missions.0).is_some_and(|i| i.controlled) {
+        } else if planet.controlled.is_some_and(|c| diplomacy.is_allied(player.id, c)) {
+            *counts_ally.entry(edge.key).or_default() += 1;
+        } else if (settings.sandbox.reveal() && planet.controlled.is_some())
+            || player.last_info(planet, &missions.0).is_some_and(|i| i.controlled)
+        {
             *counts_enemy.entry(edge.key).or_default() += 1;
+            enemies.insert(edge.key, planet.controlled);
         }
     }
 
@@ -948,8 +1201,11 @@ pub fn update_voronoi(
 
         let (visible, color) = if *counts_own.get(&edge.key).unwrap_or(&2) <= 1 {
             (true, OWN_COLOR.with_alpha(0.5))
+        } else if *counts_ally.get(&edge.key).unwrap_or(&2) <= 1 {
+            (true, ALLY_COLOR.with_alpha(0.5))
         } else if *counts_enemy.get(&edge.key).unwrap_or(&2) <= 1 {
-            (true, ENEMY_COLOR.with_alpha(0.5))
+            let enemy = enemies.get(&edge.key).copied().flatten();
+            (true, roster.color(enemy, &settings).with_alpha(0.5))
         } else {
             (false, Color::default())
         };
@@ -971,6 +1227,15 @@ pub fn update_voronoi(
 pub fn update_end_turn(
     mut button_c: Query<&mut Visibility, With<EndTurnButtonCmp>>,
     mut spectator_q: Query<&mut Visibility, (With<SpectatorLabelCmp>, Without<EndTurnButtonCmp>)>,
+    mut sandbox_q: Query<
+        &mut Visibility,
+        (
+            With<SandboxLabelCmp>,
+            Without<SpectatorLabelCmp>,
+            Without<EndTurnButtonCmp>,
+            Without<EndTurnLabelCmp>,
+        ),
+    >,
     mut button_q: Query<&mut Text, With<MainButtonLabelCmp>>,
     mut label_q: Query<
         &mut Visibility,
@@ -979,6 +1244,7 @@ pub fn update_end_turn(
     game_state: Res<State<GameState>>,
     state: Res<UiState>,
     player: Res<Player>,
+    settings: Res<Settings>,
 ) {
     for mut button_v in &mut button_c {
         *button_v = if !player.spectator {
@@ -996,6 +1262,14 @@ pub fn update_end_turn(
         };
     }
 
+    for mut label_v in &mut sandbox_q {
+        *label_v = if settings.sandbox.reveal() && *game_state.get() == GameState::Playing {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+
     if *game_state.get() == GameState::Playing {
         for mut button_t in &mut button_q {
             button_t.0 = if state.end_turn {
@@ -1018,9 +1292,68 @@ pub fn update_end_turn(
 pub fn run_map_animations(
     mut commands: Commands,
     mut animation_q: Query<(Entity, &mut Sprite, &mut ExplosionCmp)>,
+    mut mission_q: Query<(Entity, &mut Transform, &mut MissionCmp)>,
+    mut anim_q: Query<(Entity, &mut TweenAnim), Without<CombatCmp>>,
     mut map: ResMut<Map>,
+    missions: Res<Missions>,
+    settings: Res<Settings>,
     time: Res<Time>,
+    mut paused: Local<Option<Vec<Entity>>>,
 ) {
+    // Pause the running animations with the game and resume only those afterwards
+    if settings.paused != paused.is_some() {
+        if settings.paused {
+            *paused = Some(
+                anim_q
+                    .iter_mut()
+                    .filter(|(_, t)| t.playback_state == PlaybackState::Playing)
+                    .map(|(anim_e, mut t)| {
+                        t.playback_state = PlaybackState::Paused;
+                        anim_e
+                    })
+                    .collect(),
+            );
+        } else if let Some(entities) = paused.take() {
+            for anim_e in entities {
+                if let Ok((_, mut t)) = anim_q.get_mut(anim_e) {
+                    t.playback_state = PlaybackState::Playing;
+                }
+            }
+        }
+    }
+
+    // Move the fleets towards their new position after a turn is resolved
+    for (mission_e, mut mission_t, mut mission_c) in &mut mission_q {
+        if let Some(mission) = missions.iter().find(|m| m.id == mission_c.id) {
+            if mission.position != mission_c.position {
+                // Fleets that went through a wormhole moved further than their speed
+                let wormhole = mission_c.position.distance(mission.position)
+                    > mission.speed() * Planet::SIZE + 1.;
+
+                if mission.jump_gate || wormhole {
+                    // Jump gate missions don't travel, they appear at the destination
+                    commands.entity(mission_e).remove::<TweenAnim>();
+                    mission_t.translation = mission.position.extend(MISSION_Z);
+                } else {
+                    commands.entity(mission_e).insert(TweenAnim::new(Tween::new(
+                        EaseFunction::QuadraticInOut,
+                        Duration::from_millis(1000),
+                        TransformPositionLens {
+                            start: mission_t.translation,
+                            end: mission.position.extend(MISSION_Z),
+                        },
+                    )));
+                }
+
+                mission_c.position = mission.position;
+            }
+        }
+    }
+
+    if settings.paused {
+        return;
+    }
+
     for (animation_e, mut sprite, mut animation) in &mut animation_q {
         animation.timer.tick(time.delta());
 
@@ -1040,3 +1373,294 @@ pub fn run_map_animations(
         }
     }
 }
+
+/// Hide the map entities outside the camera view. Planets and missions are only
+/// shown/hidden here, while the Voronoi cells are shown by `update_voronoi` and
+/// this system only hides the ones off-screen afterwards
+pub fn cull_offscreen(
+    camera_q: Single<(&Transform, &Projection), With<MainCamera>>,
+    mut entity_q: Query<
+        (&mut Visibility, &Transform),
+        (Or<(With<PlanetCmp>, With<MissionCmp>)>, Without<MainCamera>),
+    >,
+    mut bounds_q: Query<
+        (&mut Visibility, &CullBoundsCmp),
+        (Without<PlanetCmp>, Without<MissionCmp>),
+    >,
+    mut culled: ResMut<CulledEntities>,
+) {
+    let (camera_t, projection) = camera_q.into_inner();
+
+    let Projection::Orthographic(projection) = projection else {
+        panic!("Expected Orthographic projection.");
+    };
+
+    let center = camera_t.translation.truncate();
+    let view = Rect::from_corners(center + projection.area.min, center + projection.area.max)
+        .inflate(CULLING_MARGIN);
+
+    culled.0 = 0;
+
+    for (mut entity_v, entity_t) in &mut entity_q {
+        *entity_v = if view.contains(entity_t.translation.truncate()) {
+            Visibility::Inherited
+        } else {
+            culled.0 += 1;
+            Visibility::Hidden
+        };
+    }
+
+    for (mut entity_v, bounds) in &mut bounds_q {
+        if *entity_v != Visibility::Hidden && view.intersect(bounds.0).is_empty() {
+            culled.0 += 1;
+            *entity_v = Visibility::Hidden;
+        }
+    }
+}
+
+pub fn draw_rally_links(mut gizmos: Gizmos, map: Res<Map>, player: Res<Player>) {
+    for planet in map.planets.iter().filter(|p| player.owns(p)) {
+        if let Some(rally) = planet.rally {
+            let rally = map.get(rally);
+
+            // Draw the link from the edge of one planet to the other
+            let direction = (rally.position - planet.position).normalize();
+            gizmos.arrow_2d(
+                planet.position + direction * planet.size() * 0.7,
+                rally.position - direction * rally.size() * 0.7,
+                OWN_COLOR.with_alpha(0.6),
+            );
+        }
+    }
+}
+
+/// Pulse a ring around the planets a detected enemy mission reaches next turn
+pub fn highlight_imminent_attacks(
+    mut gizmos: Gizmos,
+    state: Res<UiState>,
+    map: Res<Map>,
+    time: Res<Time>,
+) {
+    let pulse = 1. + 0.15 * (time.elapsed_secs() * 6.).sin();
+    for id in &state.imminent_attacks {
+        let planet = map.get(*id);
+        gizmos.circle_2d(planet.position, planet.size() * 0.8 * pulse, ENEMY_COLOR);
+    }
+}
+
+/// Rough strength of an army, following Lanchester's square law: the total
+/// damage times the total durability (hull + shield) of its combat units
+fn combat_strength(army: &Army) -> f32 {
+    let (damage, durability) = army.iter().filter(|(u, _)| !u.is_building()).fold(
+        (0., 0.),
+        |(damage, durability), (u, c)| {
+            (damage + (u.damage() * c) as f32, durability + ((u.hull() + u.shield()) * c) as f32)
+        },
+    );
+
+    damage * durability
+}
+
+/// Draw a badge next to the enemy planets with intel, colored by the odds of the
+/// strongest owned fleet nearby winning an attack on it. The estimate only uses
+/// the aggregated stats of both armies, so it's no substitute for a combat report
+pub fn draw_combat_odds(
+    mut gizmos: Gizmos,
+    settings: Res<Settings>,
+    map: Res<Map>,
+    player: Res<Player>,
+    missions: Res<Missions>,
+    diplomacy: Res<Diplomacy>,
+) {
+    if !settings.show_odds || player.spectator {
+        return;
+    }
+
+    // Available (not reserved) combat ships on every controlled planet
+    let fleets = map
+        .planets
+        .iter()
+        .filter(|p| player.controls(p))
+        .filter_map(|p| {
+            let fleet: Army = p
+                .army
+                .keys()
+                .filter(|u| u.is_combat_ship())
+                .map(|u| (*u, p.available(u)))
+                .collect();
+
+            fleet.has_army().then(|| (p.position, combat_strength(&fleet)))
+        })
+        .collect::<Vec<_>>();
+
+    for planet in map.planets.iter().filter(|p| {
+        !p.is_destroyed
+            && !player.controls(p)
+            && !p.controlled.is_some_and(|c| diplomacy.is_allied(player.id, c))
+    }) {
+        let Some(strength) = fleets
+            .iter()
+            .filter(|(pos, _)| pos.distance(planet.position) <= ODDS_DISTANCE * Planet::SIZE)
+            .map(|(_, s)| *s)
+            .reduce(f32::max)
+        else {
+            continue;
+        };
+
+        let (army, complete) = if settings.sandbox.reveal() && planet.controlled.is_some() {
+            (planet.army.clone(), true)
+        } else if let Some(info) = player.last_info(planet, &missions.0).filter(|i| i.controlled) {
+            // Units not revealed by the probes are missing from the intel
+            let complete = settings.turn.saturating_sub(info.turn) < settings.stale_intel
+                && Unit::all()
+                    .iter()
+                    .flatten()
+                    .all(|u| u.is_building() || info.army.contains_key(u));
+            (info.army, complete)
+        } else {
+            continue;
+        };
+
+        let color = if !complete {
+            UNKNOWN_ODDS_COLOR
+        } else {
+            match strength / combat_strength(&army).max(1.) {
+                o if o >= FAVORABLE_ODDS => ALLY_COLOR,
+                o if o >= EVEN_ODDS => EVEN_ODDS_COLOR,
+                _ => ENEMY_COLOR,
+            }
+        };
+
+        // Fill the badge with concentric circles, uncertain badges only get the ring
+        let size = planet.size() * 0.12;
+        let position = planet.position + Vec2::splat(planet.size() * 0.45);
+        let rings = if complete {
+            4
+        } else {
+            1
+        };
+        for i in 0..rings {
+            gizmos.circle_2d(position, size * (1. - i as f32 / rings as f32), color);
+        }
+    }
+}
+
+pub fn draw_mission_paths(
+    mut gizmos: Gizmos,
+    settings: Res<Settings>,
+    map: Res<Map>,
+    player: Res<Player>,
+    roster: Res<Roster>,
+    missions: Res<Missions>,
+) {
+    if !settings.show_paths {
+        return;
+    }
+
+    for mission in missions.iter() {
+        // The destination of enemy missions is only known when detected
+        let own = mission.owner == player.id;
+        if !own
+            && mission.is_seen_by_phalanx(&map, &player).is_none()
+            && mission.is_seen_by_radar(&map, &player).is_none()
+        {
+            continue;
+        }
+
+        let color = if own {
+            OWN_COLOR
+        } else {
+            roster.color(Some(mission.owner), &settings)
+        };
+
+        let origin = map.get(mission.origin);
+        let destination = map.get(mission.destination);
+
+        // The traveled part is faint, the arrow covers the remaining distance
+        let direction = (destination.position - mission.position).normalize_or_zero();
+        gizmos.line_2d(origin.position, mission.position, color.with_alpha(0.15));
+        gizmos.arrow_2d(
+            mission.position,
+            destination.position - direction * destination.size() * 0.7,
+            color.with_alpha(0.6),
+        );
+    }
+}
+
+pub fn update_auto_economy(
+    mut auto_q: Query<(&mut Visibility, &AutoEconomyCmp)>,
+    map: Res<Map>,
+    player: Res<Player>,
+) {
+    for (mut auto_v, auto_c) in &mut auto_q {
+        let planet = map.get(auto_c.0);
+
+        *auto_v = if player.owns(planet) && planet.auto_economy.is_some() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// Keep the target ratio of the player's balanced auto-economy planets in sync
+/// with the settings. The ratio is stored on the planets, since the host resolves
+/// the upgrades of every player
+pub fn sync_resource_ratio(settings: Res<Settings>, mut map: ResMut<Map>, player: Res<Player>) {
+    for planet in map.planets.iter_mut().filter(|p| player.owns(p)) {
+        if let Some(ratio) = planet.auto_economy.as_mut().and_then(|a| a.ratio.as_mut()) {
+            if *ratio != settings.resource_ratio {
+                *ratio = settings.resource_ratio;
+            }
+        }
+    }
+}
+
+pub fn update_planet_names(
+    mut name_q: Query<(&mut Text2d, &PlanetNameCmp)>,
+    map: Res<Map>,
+    player: Res<Player>,
+) {
+    for (mut name_t, name_c) in &mut name_q {
+        let name = player.planet_name(map.get(name_c.0));
+        if name_t.0 != name {
+            name_t.0 = name.to_string();
+        }
+    }
+}
+
+pub fn update_note_tags(
+    mut tag_q: Query<(&mut Visibility, &MeshMaterial2d<ColorMaterial>, &NoteTagCmp)>,
+    player: Res<Player>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for (mut tag_v, tag_m, tag_c) in &mut tag_q {
+        match player.notes.get(&tag_c.0).map(|n| n.tag).unwrap_or_default() {
+            NoteTag::None => *tag_v = Visibility::Hidden,
+            tag => {
+                if let Some(material) = materials.get_mut(&tag_m.0) {
+                    material.color = tag.color();
+                }
+                *tag_v = Visibility::Inherited;
+            },
+        }
+    }
+}
+
+pub fn update_specialization_icons(
+    mut spec_q: Query<(&mut Sprite, &mut Visibility, &SpecializationCmp)>,
+    map: Res<Map>,
+    player: Res<Player>,
+    assets: Local<WorldAssets>,
+) {
+    for (mut spec_s, mut spec_v, spec_c) in &mut spec_q {
+        let planet = map.get(spec_c.0);
+
+        if player.owns(planet) && planet.specialization != Specialization::None {
+            spec_s.image = assets.image(planet.specialization.image());
+            *spec_v = Visibility::Inherited;
+        } else {
+            *spec_v = Visibility::Hidden;
+        }
+    }
+}
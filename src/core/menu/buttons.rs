@@ -7,6 +7,7 @@ use rand::rng;
 
 use crate::core::assets::WorldAssets;
 use crate::core::constants::*;
+use crate::core::diplomacy::Diplomacy;
 use crate::core::map::map::Map;
 use crate::core::map::planet::{Planet, PlanetId};
 use crate::core::map::utils::cursor;
@@ -15,11 +16,16 @@ use crate::core::missions::Missions;
 use crate::core::network::{
     new_renet_client, new_renet_server, Host, Ip, ServerMessage, ServerSendMsg,
 };
-use crate::core::persistence::{LoadGameMsg, SaveGameMsg};
-use crate::core::player::Player;
-use crate::core::settings::Settings;
+use crate::core::persistence::{
+    ExportTimelapseMsg, LoadGameMsg, LoadReplayMsg, LoadScenarioMsg, LoadTimelapseMsg, SaveGameMsg,
+    SaveScenarioMsg,
+};
+use crate::core::player::{Player, Roster, Standings};
+use crate::core::resources::Resources;
+use crate::core::settings::{Sandbox, Settings};
 use crate::core::states::{AppState, GameState};
 use crate::core::turns::{PreviousEndTurnState, StartTurnMsg};
+use crate::core::tutorial::Tutorial;
 use crate::core::ui::systems::UiState;
 use crate::utils::NameFromEnum;
 
@@ -32,12 +38,18 @@ pub enum MenuBtn {
     StartGame,
     NewGame,
     LoadGame,
+    LoadScenario,
+    SaveScenario,
+    Replay,
+    Timelapse,
     HostGame,
     FindGame,
     Back,
     Continue,
+    Encyclopedia,
     Spectate,
     SaveGame,
+    ExportTimelapse,
     Settings,
     Quit,
 }
@@ -60,7 +72,10 @@ pub fn on_click_menu_button(
     mut settings: ResMut<Settings>,
     ip: Res<Ip>,
     mut load_game_msg: MessageWriter<LoadGameMsg>,
+    mut load_replay_msg: MessageWriter<LoadReplayMsg>,
     mut save_game_msg: MessageWriter<SaveGameMsg>,
+    mut load_scenario_msg: MessageWriter<LoadScenarioMsg>,
+    mut save_scenario_msg: MessageWriter<SaveScenarioMsg>,
     mut start_turn_msg: MessageWriter<StartTurnMsg>,
     mut server_send_msg: MessageWriter<ServerSendMsg>,
     app_state: Res<State<AppState>>,
@@ -83,21 +98,52 @@ pub fn on_click_menu_button(
         },
         MenuBtn::NewGame => {
             if *app_state.get() == AppState::SinglePlayerMenu {
-                let mut map = Map::new(settings.n_planets, settings.p_moons);
+                let mut map = Map::new(
+                    settings.n_planets,
+                    settings.p_moons,
+                    settings.wormholes,
+                    settings.resource_bias,
+                    settings.name_theme,
+                );
 
                 // Alter home planet's stats
                 let home_planet = map.planets().iter().choose(&mut rng()).map(|p| p.id).unwrap();
-                map.planets.iter_mut().find(|p| p.id == home_planet).unwrap().make_home_planet(0);
+                map.planets
+                    .iter_mut()
+                    .find(|p| p.id == home_planet)
+                    .unwrap()
+                    .make_home_planet(0, settings.start);
+
+                let mut player = Player::new(0, home_planet, settings.difficulty);
+                if settings.sandbox == Sandbox::Infinite {
+                    player.resources =
+                        Resources::new(SANDBOX_RESOURCES, SANDBOX_RESOURCES, SANDBOX_RESOURCES);
+                }
 
                 commands.insert_resource(map);
-                commands.insert_resource(Player::new(0, home_planet));
+                commands.insert_resource(player);
+                commands.insert_resource(Roster(vec![0]));
+                commands.insert_resource(Diplomacy::default());
+
+                if settings.tutorial {
+                    commands.insert_resource(Tutorial::default());
+                }
             } else {
+                // The sandbox mode is not allowed in multiplayer games
+                settings.sandbox = Sandbox::Off;
+
                 let server = server.unwrap();
 
                 let clients = server.clients_id();
                 let n_players = clients.len() + 1;
 
-                let mut map = Map::new(settings.n_planets * n_players, settings.p_moons);
+                let mut map = Map::new(
+                    settings.n_planets * n_players,
+                    settings.p_moons,
+                    settings.wormholes * n_players,
+                    settings.resource_bias,
+                    settings.name_theme,
+                );
 
                 // Determine home planets
                 let mut home_planets: Vec<(PlanetId, Vec2)> = vec![];
@@ -118,7 +164,7 @@ pub fn on_click_menu_button(
                 let players = std::iter::once(&0).chain(&clients).collect::<Vec<_>>();
                 home_planets.iter().zip(players).for_each(|((planet_id, _), client_id)| {
                     if let Some(planet) = map.planets.iter_mut().find(|p| p.id == *planet_id) {
-                        planet.make_home_planet(*client_id);
+                        planet.make_home_planet(*client_id, settings.start);
                     }
                 });
 
@@ -128,20 +174,41 @@ pub fn on_click_menu_button(
                         message: ServerMessage::StartGame {
                             id: *client_id,
                             home_planet: *planet_id,
+                            difficulty: settings.difficulty,
+                            start: settings.start,
+                            upkeep: settings.upkeep,
+                            victory: settings.victory,
                             map: map.clone(),
                         },
                         client: Some(*client_id),
                     });
                 }
 
+                // Fix the order of the players to assign them a color
+                let roster = Roster(std::iter::once(0).chain(clients).collect());
+                server_send_msg
+                    .write(ServerSendMsg::new(ServerMessage::Roster(roster.clone()), None));
+
+                // Teammates start allied for the whole game
+                let diplomacy = Diplomacy::new(&roster.0, settings.teams);
+                server_send_msg
+                    .write(ServerSendMsg::new(ServerMessage::Diplomacy(diplomacy.clone()), None));
+
+                commands.insert_resource(roster);
+                commands.insert_resource(diplomacy);
                 commands.insert_resource(map);
-                commands.insert_resource(Player::new(0, home_planets.first().unwrap().0));
+                commands.insert_resource(Player::new(
+                    0,
+                    home_planets.first().unwrap().0,
+                    settings.difficulty,
+                ));
             }
 
             settings.turn = 1;
             commands.insert_resource(UiState::default());
             commands.insert_resource(PreviousEndTurnState::default());
             commands.insert_resource(Missions::default());
+            commands.insert_resource(Standings::default());
             commands.insert_resource(Host::default());
 
             next_app_state.set(AppState::Game);
@@ -149,6 +216,20 @@ pub fn on_click_menu_button(
         MenuBtn::LoadGame => {
             load_game_msg.write(LoadGameMsg);
         },
+        MenuBtn::LoadScenario => {
+            load_scenario_msg.write(LoadScenarioMsg);
+        },
+        MenuBtn::SaveScenario => {
+            save_scenario_msg.write(SaveScenarioMsg);
+        },
+        MenuBtn::Replay => {
+            load_replay_msg.write(LoadReplayMsg);
+        },
+        MenuBtn::Timelapse => {
+            commands.queue(|w: &mut World| {
+                w.write_message(LoadTimelapseMsg);
+            });
+        },
         MenuBtn::HostGame => {
             // Remove client resources if they exist
             if client.is_some() {
@@ -193,6 +274,10 @@ pub fn on_click_menu_button(
         MenuBtn::Continue => {
             next_game_state.set(GameState::Playing);
         },
+        MenuBtn::Encyclopedia => {
+            commands.queue(|w: &mut World| w.resource_mut::<UiState>().encyclopedia = true);
+            next_game_state.set(GameState::Playing);
+        },
         MenuBtn::Spectate => {
             start_turn_msg.write(StartTurnMsg::new(true, true));
             next_game_state.set(GameState::Playing);
@@ -200,6 +285,11 @@ pub fn on_click_menu_button(
         MenuBtn::SaveGame => {
             save_game_msg.write(SaveGameMsg(false));
         },
+        MenuBtn::ExportTimelapse => {
+            commands.queue(|w: &mut World| {
+                w.write_message(ExportTimelapseMsg);
+            });
+        },
         MenuBtn::Settings => {
             if *game_state.get() == GameState::GameMenu {
                 next_game_state.set(GameState::Settings);
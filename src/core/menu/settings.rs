@@ -6,7 +6,10 @@ use crate::core::assets::WorldAssets;
 use crate::core::audio::ChangeAudioMsg;
 use crate::core::constants::*;
 use crate::core::menu::utils::add_text;
-use crate::core::settings::Settings;
+use crate::core::settings::{
+    BuildOrder, Difficulty, NameTheme, ResourceBias, Sandbox, Settings, StartPackage, Teams,
+    TurnOrder, Victory,
+};
 use crate::core::states::AudioState;
 use crate::utils::NameFromEnum;
 
@@ -26,6 +29,87 @@ pub enum SettingsBtn {
     Sound,
     True,
     False,
+    Easy,
+    Normal,
+    Hard,
+    Animate,
+    AutoResolve,
+    Show,
+    Hide,
+    Enabled,
+    Disabled,
+    Minimal,
+    Standard,
+    Advanced,
+    Sprites,
+    Lite,
+    Low,
+    High,
+    Unlimited,
+    On,
+    Off,
+    Parallax,
+    Static,
+    Regular,
+    Reveal,
+    Infinite,
+    NoWormholes,
+    FewWormholes,
+    ManyWormholes,
+    Elimination,
+    Conquest,
+    Score,
+    TurnLimit,
+    Manual,
+    Economy,
+    Industry,
+    Territory,
+    Threat,
+    Intel,
+    PerPlayer,
+    Uniform,
+    Confirm,
+    Skip,
+    NormalText,
+    LargeText,
+    NoBias,
+    NormalBias,
+    StrongBias,
+    ShowPaths,
+    HidePaths,
+    RandomOrder,
+    Simultaneous,
+    Tutorial,
+    NoTutorial,
+    SixRounds,
+    FifteenRounds,
+    ThirtyRounds,
+    VariedSounds,
+    SimpleSounds,
+    ThreeTurns,
+    FiveTurns,
+    TenTurns,
+    Vsync,
+    NoVsync,
+    ThirtyFps,
+    SixtyFps,
+    NoCap,
+    EvenRatio,
+    MetalRatio,
+    SteppedRatio,
+    ClassicNames,
+    MythologicalNames,
+    ScientificNames,
+    SciFiNames,
+    FreeForAll,
+    TwoTeams,
+    ShowDamage,
+    HideDamage,
+    ShowOdds,
+    HideOdds,
+    NoLeak,
+    LowLeak,
+    HighLeak,
 }
 
 impl SettingsBtn {
@@ -37,6 +121,48 @@ impl SettingsBtn {
             SettingsBtn::Zero => "0%".to_string(),
             SettingsBtn::Thirty => "30%".to_string(),
             SettingsBtn::Sixty => "60%".to_string(),
+            SettingsBtn::NoWormholes => "None".to_string(),
+            SettingsBtn::FewWormholes => "Few".to_string(),
+            SettingsBtn::ManyWormholes => "Many".to_string(),
+            SettingsBtn::NormalText => "Normal".to_string(),
+            SettingsBtn::LargeText => "Large".to_string(),
+            SettingsBtn::NoBias => "None".to_string(),
+            SettingsBtn::NormalBias => "Normal".to_string(),
+            SettingsBtn::StrongBias => "Strong".to_string(),
+            SettingsBtn::ShowPaths => "Show".to_string(),
+            SettingsBtn::HidePaths => "Hide".to_string(),
+            SettingsBtn::ShowOdds => "Show".to_string(),
+            SettingsBtn::HideOdds => "Hide".to_string(),
+            SettingsBtn::RandomOrder => "Random".to_string(),
+            SettingsBtn::Tutorial => "On".to_string(),
+            SettingsBtn::NoTutorial => "Off".to_string(),
+            SettingsBtn::SixRounds => "6".to_string(),
+            SettingsBtn::NoLeak => "Off".to_string(),
+            SettingsBtn::LowLeak => "10%".to_string(),
+            SettingsBtn::HighLeak => "25%".to_string(),
+            SettingsBtn::FifteenRounds => "15".to_string(),
+            SettingsBtn::ThirtyRounds => "30".to_string(),
+            SettingsBtn::VariedSounds => "Varied".to_string(),
+            SettingsBtn::SimpleSounds => "Simple".to_string(),
+            SettingsBtn::ThreeTurns => "3".to_string(),
+            SettingsBtn::FiveTurns => "5".to_string(),
+            SettingsBtn::TenTurns => "10".to_string(),
+            SettingsBtn::Vsync => "On".to_string(),
+            SettingsBtn::NoVsync => "Off".to_string(),
+            SettingsBtn::ThirtyFps => "30".to_string(),
+            SettingsBtn::SixtyFps => "60".to_string(),
+            SettingsBtn::NoCap => "None".to_string(),
+            SettingsBtn::EvenRatio => "1:1:1".to_string(),
+            SettingsBtn::MetalRatio => "2:1:1".to_string(),
+            SettingsBtn::SteppedRatio => "3:2:1".to_string(),
+            SettingsBtn::ClassicNames => "Classic".to_string(),
+            SettingsBtn::MythologicalNames => "Myths".to_string(),
+            SettingsBtn::ScientificNames => "Science".to_string(),
+            SettingsBtn::SciFiNames => "Sci-fi".to_string(),
+            SettingsBtn::FreeForAll => "FFA".to_string(),
+            SettingsBtn::TwoTeams => "2 teams".to_string(),
+            SettingsBtn::ShowDamage => "Show".to_string(),
+            SettingsBtn::HideDamage => "Hide".to_string(),
             _ => self.to_title(),
         }
     }
@@ -58,6 +184,87 @@ fn match_setting(button: &SettingsBtn, settings: &Settings) -> bool {
         SettingsBtn::Sound => settings.audio == AudioState::Sound,
         SettingsBtn::True => settings.autosave == true,
         SettingsBtn::False => settings.autosave == false,
+        SettingsBtn::Easy => settings.difficulty == Difficulty::Easy,
+        SettingsBtn::Normal => settings.difficulty == Difficulty::Normal,
+        SettingsBtn::Hard => settings.difficulty == Difficulty::Hard,
+        SettingsBtn::Animate => settings.auto_resolve == false,
+        SettingsBtn::AutoResolve => settings.auto_resolve == true,
+        SettingsBtn::Show => settings.show_summary == true,
+        SettingsBtn::Hide => settings.show_summary == false,
+        SettingsBtn::Enabled => settings.veterancy == true,
+        SettingsBtn::Disabled => settings.veterancy == false,
+        SettingsBtn::Minimal => settings.start == StartPackage::Minimal,
+        SettingsBtn::Standard => settings.start == StartPackage::Standard,
+        SettingsBtn::Advanced => settings.start == StartPackage::Advanced,
+        SettingsBtn::Sprites => settings.lite_combat == false,
+        SettingsBtn::Lite => settings.lite_combat == true,
+        SettingsBtn::Low => settings.max_shots == Some(LOW_SHOTS_PER_VOLLEY),
+        SettingsBtn::High => settings.max_shots == Some(HIGH_SHOTS_PER_VOLLEY),
+        SettingsBtn::Unlimited => settings.max_shots.is_none(),
+        SettingsBtn::On => settings.upkeep == true,
+        SettingsBtn::Off => settings.upkeep == false,
+        SettingsBtn::Parallax => settings.parallax == true,
+        SettingsBtn::Static => settings.parallax == false,
+        SettingsBtn::Regular => settings.sandbox == Sandbox::Off,
+        SettingsBtn::Reveal => settings.sandbox == Sandbox::Reveal,
+        SettingsBtn::Infinite => settings.sandbox == Sandbox::Infinite,
+        SettingsBtn::NoWormholes => settings.wormholes == 0,
+        SettingsBtn::FewWormholes => settings.wormholes == 1,
+        SettingsBtn::ManyWormholes => settings.wormholes == 3,
+        SettingsBtn::Elimination => settings.victory == Victory::Elimination,
+        SettingsBtn::Conquest => settings.victory == Victory::Conquest,
+        SettingsBtn::Score => settings.victory == Victory::Score,
+        SettingsBtn::TurnLimit => settings.victory == Victory::TurnLimit,
+        SettingsBtn::Manual => settings.build_order == BuildOrder::Manual,
+        SettingsBtn::Economy => settings.build_order == BuildOrder::Economy,
+        SettingsBtn::Industry => settings.build_order == BuildOrder::Industry,
+        SettingsBtn::Territory => !settings.show_threat && !settings.show_intel,
+        SettingsBtn::Threat => settings.show_threat == true,
+        SettingsBtn::Intel => settings.show_intel == true,
+        SettingsBtn::PerPlayer => settings.player_colors == true,
+        SettingsBtn::Uniform => settings.player_colors == false,
+        SettingsBtn::Confirm => settings.confirm_abandon == true,
+        SettingsBtn::Skip => settings.confirm_abandon == false,
+        SettingsBtn::NormalText => settings.large_text == false,
+        SettingsBtn::LargeText => settings.large_text == true,
+        SettingsBtn::NoBias => settings.resource_bias == ResourceBias::None,
+        SettingsBtn::NormalBias => settings.resource_bias == ResourceBias::Normal,
+        SettingsBtn::StrongBias => settings.resource_bias == ResourceBias::Strong,
+        SettingsBtn::ClassicNames => settings.name_theme == NameTheme::Classic,
+        SettingsBtn::MythologicalNames => settings.name_theme == NameTheme::Mythological,
+        SettingsBtn::ScientificNames => settings.name_theme == NameTheme::Scientific,
+        SettingsBtn::SciFiNames => settings.name_theme == NameTheme::SciFi,
+        SettingsBtn::ShowPaths => settings.show_paths == true,
+        SettingsBtn::HidePaths => settings.show_paths == false,
+        SettingsBtn::ShowOdds => settings.show_odds == true,
+        SettingsBtn::HideOdds => settings.show_odds == false,
+        SettingsBtn::RandomOrder => settings.turn_order == TurnOrder::Random,
+        SettingsBtn::Simultaneous => settings.turn_order == TurnOrder::Simultaneous,
+        SettingsBtn::FreeForAll => settings.teams == Teams::FreeForAll,
+        SettingsBtn::TwoTeams => settings.teams == Teams::TwoTeams,
+        SettingsBtn::Tutorial => settings.tutorial == true,
+        SettingsBtn::NoTutorial => settings.tutorial == false,
+        SettingsBtn::SixRounds => settings.combat_rounds == 6,
+        SettingsBtn::FifteenRounds => settings.combat_rounds == 15,
+        SettingsBtn::ThirtyRounds => settings.combat_rounds == 30,
+        SettingsBtn::NoLeak => settings.intel_leak == 0,
+        SettingsBtn::LowLeak => settings.intel_leak == 10,
+        SettingsBtn::HighLeak => settings.intel_leak == 25,
+        SettingsBtn::VariedSounds => settings.combat_sounds == true,
+        SettingsBtn::SimpleSounds => settings.combat_sounds == false,
+        SettingsBtn::ShowDamage => settings.damage_numbers == true,
+        SettingsBtn::HideDamage => settings.damage_numbers == false,
+        SettingsBtn::ThreeTurns => settings.stale_intel == 3,
+        SettingsBtn::FiveTurns => settings.stale_intel == 5,
+        SettingsBtn::TenTurns => settings.stale_intel == 10,
+        SettingsBtn::Vsync => settings.vsync == true,
+        SettingsBtn::NoVsync => settings.vsync == false,
+        SettingsBtn::ThirtyFps => settings.frame_cap == Some(30),
+        SettingsBtn::SixtyFps => settings.frame_cap == Some(60),
+        SettingsBtn::NoCap => settings.frame_cap.is_none(),
+        SettingsBtn::EvenRatio => settings.resource_ratio == [1, 1, 1],
+        SettingsBtn::MetalRatio => settings.resource_ratio == [2, 1, 1],
+        SettingsBtn::SteppedRatio => settings.resource_ratio == [3, 2, 1],
     }
 }
 
@@ -104,6 +311,96 @@ pub fn on_click_label_button(
         },
         SettingsBtn::True => settings.autosave = true,
         SettingsBtn::False => settings.autosave = false,
+        SettingsBtn::Easy => settings.difficulty = Difficulty::Easy,
+        SettingsBtn::Normal => settings.difficulty = Difficulty::Normal,
+        SettingsBtn::Hard => settings.difficulty = Difficulty::Hard,
+        SettingsBtn::Animate => settings.auto_resolve = false,
+        SettingsBtn::AutoResolve => settings.auto_resolve = true,
+        SettingsBtn::Show => settings.show_summary = true,
+        SettingsBtn::Hide => settings.show_summary = false,
+        SettingsBtn::Enabled => settings.veterancy = true,
+        SettingsBtn::Disabled => settings.veterancy = false,
+        SettingsBtn::Minimal => settings.start = StartPackage::Minimal,
+        SettingsBtn::Standard => settings.start = StartPackage::Standard,
+        SettingsBtn::Advanced => settings.start = StartPackage::Advanced,
+        SettingsBtn::Sprites => settings.lite_combat = false,
+        SettingsBtn::Lite => settings.lite_combat = true,
+        SettingsBtn::Low => settings.max_shots = Some(LOW_SHOTS_PER_VOLLEY),
+        SettingsBtn::High => settings.max_shots = Some(HIGH_SHOTS_PER_VOLLEY),
+        SettingsBtn::Unlimited => settings.max_shots = None,
+        SettingsBtn::On => settings.upkeep = true,
+        SettingsBtn::Off => settings.upkeep = false,
+        SettingsBtn::Parallax => settings.parallax = true,
+        SettingsBtn::Static => settings.parallax = false,
+        SettingsBtn::Regular => settings.sandbox = Sandbox::Off,
+        SettingsBtn::Reveal => settings.sandbox = Sandbox::Reveal,
+        SettingsBtn::Infinite => settings.sandbox = Sandbox::Infinite,
+        SettingsBtn::NoWormholes => settings.wormholes = 0,
+        SettingsBtn::FewWormholes => settings.wormholes = 1,
+        SettingsBtn::ManyWormholes => settings.wormholes = 3,
+        SettingsBtn::Elimination => settings.victory = Victory::Elimination,
+        SettingsBtn::Conquest => settings.victory = Victory::Conquest,
+        SettingsBtn::Score => settings.victory = Victory::Score,
+        SettingsBtn::TurnLimit => settings.victory = Victory::TurnLimit,
+        SettingsBtn::Manual => settings.build_order = BuildOrder::Manual,
+        SettingsBtn::Economy => settings.build_order = BuildOrder::Economy,
+        SettingsBtn::Industry => settings.build_order = BuildOrder::Industry,
+        SettingsBtn::Territory => {
+            settings.show_threat = false;
+            settings.show_intel = false;
+        },
+        SettingsBtn::Threat => {
+            settings.show_threat = true;
+            settings.show_intel = false;
+        },
+        SettingsBtn::Intel => {
+            settings.show_threat = false;
+            settings.show_intel = true;
+        },
+        SettingsBtn::PerPlayer => settings.player_colors = true,
+        SettingsBtn::Uniform => settings.player_colors = false,
+        SettingsBtn::Confirm => settings.confirm_abandon = true,
+        SettingsBtn::Skip => settings.confirm_abandon = false,
+        SettingsBtn::NormalText => settings.large_text = false,
+        SettingsBtn::LargeText => settings.large_text = true,
+        SettingsBtn::NoBias => settings.resource_bias = ResourceBias::None,
+        SettingsBtn::NormalBias => settings.resource_bias = ResourceBias::Normal,
+        SettingsBtn::StrongBias => settings.resource_bias = ResourceBias::Strong,
+        SettingsBtn::ClassicNames => settings.name_theme = NameTheme::Classic,
+        SettingsBtn::MythologicalNames => settings.name_theme = NameTheme::Mythological,
+        SettingsBtn::ScientificNames => settings.name_theme = NameTheme::Scientific,
+        SettingsBtn::SciFiNames => settings.name_theme = NameTheme::SciFi,
+        SettingsBtn::ShowPaths => settings.show_paths = true,
+        SettingsBtn::HidePaths => settings.show_paths = false,
+        SettingsBtn::ShowOdds => settings.show_odds = true,
+        SettingsBtn::HideOdds => settings.show_odds = false,
+        SettingsBtn::RandomOrder => settings.turn_order = TurnOrder::Random,
+        SettingsBtn::Simultaneous => settings.turn_order = TurnOrder::Simultaneous,
+        SettingsBtn::FreeForAll => settings.teams = Teams::FreeForAll,
+        SettingsBtn::TwoTeams => settings.teams = Teams::TwoTeams,
+        SettingsBtn::Tutorial => settings.tutorial = true,
+        SettingsBtn::NoTutorial => settings.tutorial = false,
+        SettingsBtn::SixRounds => settings.combat_rounds = 6,
+        SettingsBtn::FifteenRounds => settings.combat_rounds = 15,
+        SettingsBtn::ThirtyRounds => settings.combat_rounds = 30,
+        SettingsBtn::NoLeak => settings.intel_leak = 0,
+        SettingsBtn::LowLeak => settings.intel_leak = 10,
+        SettingsBtn::HighLeak => settings.intel_leak = 25,
+        SettingsBtn::VariedSounds => settings.combat_sounds = true,
+        SettingsBtn::SimpleSounds => settings.combat_sounds = false,
+        SettingsBtn::ShowDamage => settings.damage_numbers = true,
+        SettingsBtn::HideDamage => settings.damage_numbers = false,
+        SettingsBtn::ThreeTurns => settings.stale_intel = 3,
+        SettingsBtn::FiveTurns => settings.stale_intel = 5,
+        SettingsBtn::TenTurns => settings.stale_intel = 10,
+        SettingsBtn::Vsync => settings.vsync = true,
+        SettingsBtn::NoVsync => settings.vsync = false,
+        SettingsBtn::ThirtyFps => settings.frame_cap = Some(30),
+        SettingsBtn::SixtyFps => settings.frame_cap = Some(60),
+        SettingsBtn::NoCap => settings.frame_cap = None,
+        SettingsBtn::EvenRatio => settings.resource_ratio = [1, 1, 1],
+        SettingsBtn::MetalRatio => settings.resource_ratio = [2, 1, 1],
+        SettingsBtn::SteppedRatio => settings.resource_ratio = [3, 2, 1],
     }
 
     // Reset the color of the other buttons
@@ -8,6 +8,7 @@ use crate::core::audio::{MuteAudioMsg, PlayAudioMsg};
 use crate::core::constants::{
     BUTTON_TEXT_SIZE, DISABLED_BUTTON_COLOR, NORMAL_BUTTON_COLOR, TITLE_TEXT_SIZE,
 };
+use crate::core::diplomacy::Diplomacy;
 use crate::core::map::map::Map;
 use crate::core::menu::buttons::{
     spawn_menu_button, DisabledButton, IpTextCmp, LobbyTextCmp, MenuBtn, MenuCmp,
@@ -15,10 +16,10 @@ use crate::core::menu::buttons::{
 use crate::core::menu::settings::{spawn_label, SettingsBtn};
 use crate::core::menu::utils::{add_root_node, add_text};
 use crate::core::network::{Host, Ip};
-use crate::core::player::Player;
+use crate::core::player::{Player, Roster, Standings};
 use crate::core::settings::Settings;
 use crate::core::states::AppState;
-use crate::utils::get_local_ip;
+use crate::utils::{get_local_ip, FmtNumb};
 use crate::TITLE;
 
 pub fn setup_menu(
@@ -72,6 +73,9 @@ pub fn setup_menu(
                     AppState::SinglePlayerMenu => {
                         spawn_menu_button(parent, MenuBtn::NewGame, &assets, &window);
                         spawn_menu_button(parent, MenuBtn::LoadGame, &assets, &window);
+                        spawn_menu_button(parent, MenuBtn::LoadScenario, &assets, &window);
+                        spawn_menu_button(parent, MenuBtn::Replay, &assets, &window);
+                        spawn_menu_button(parent, MenuBtn::Timelapse, &assets, &window);
                         spawn_menu_button(parent, MenuBtn::Back, &assets, &window);
                     }
                     AppState::MultiPlayerMenu => {
@@ -111,6 +115,7 @@ pub fn setup_menu(
                             if n_players > 1 {
                                 spawn_menu_button(parent, MenuBtn::NewGame, &assets, &window);
                                 spawn_menu_button(parent, MenuBtn::LoadGame, &assets, &window);
+                                spawn_menu_button(parent, MenuBtn::LoadScenario, &assets, &window);
                             }
                         } else {
                             parent.spawn((
@@ -174,6 +179,171 @@ pub fn setup_menu(
                                     &assets,
                                     &window,
                                 );
+                                spawn_label(
+                                    parent,
+                                    "Wormholes per player",
+                                    vec![
+                                        SettingsBtn::NoWormholes,
+                                        SettingsBtn::FewWormholes,
+                                        SettingsBtn::ManyWormholes,
+                                    ],
+                                    &settings,
+                                    &assets,
+                                    &window,
+                                );
+                                spawn_label(
+                                    parent,
+                                    "Planet kind resources",
+                                    vec![
+                                        SettingsBtn::NoBias,
+                                        SettingsBtn::NormalBias,
+                                        SettingsBtn::StrongBias,
+                                    ],
+                                    &settings,
+                                    &assets,
+                                    &window,
+                                );
+                                spawn_label(
+                                    parent,
+                                    "Planet names",
+                                    vec![
+                                        SettingsBtn::ClassicNames,
+                                        SettingsBtn::MythologicalNames,
+                                        SettingsBtn::ScientificNames,
+                                        SettingsBtn::SciFiNames,
+                                    ],
+                                    &settings,
+                                    &assets,
+                                    &window,
+                                );
+                                spawn_label(
+                                    parent,
+                                    "Difficulty",
+                                    vec![
+                                        SettingsBtn::Easy,
+                                        SettingsBtn::Normal,
+                                        SettingsBtn::Hard,
+                                    ],
+                                    &settings,
+                                    &assets,
+                                    &window,
+                                );
+                                spawn_label(
+                                    parent,
+                                    "Starting assets",
+                                    vec![
+                                        SettingsBtn::Minimal,
+                                        SettingsBtn::Standard,
+                                        SettingsBtn::Advanced,
+                                    ],
+                                    &settings,
+                                    &assets,
+                                    &window,
+                                );
+                                spawn_label(
+                                    parent,
+                                    "Veterancy",
+                                    vec![
+                                        SettingsBtn::Enabled,
+                                        SettingsBtn::Disabled,
+                                    ],
+                                    &settings,
+                                    &assets,
+                                    &window,
+                                );
+                                spawn_label(
+                                    parent,
+                                    "Victory",
+                                    vec![
+                                        SettingsBtn::Elimination,
+                                        SettingsBtn::Conquest,
+                                        SettingsBtn::Score,
+                                        SettingsBtn::TurnLimit,
+                                    ],
+                                    &settings,
+                                    &assets,
+                                    &window,
+                                );
+                                spawn_label(
+                                    parent,
+                                    "Fleet upkeep",
+                                    vec![SettingsBtn::On, SettingsBtn::Off],
+                                    &settings,
+                                    &assets,
+                                    &window,
+                                );
+                                spawn_label(
+                                    parent,
+                                    "Max. combat rounds",
+                                    vec![
+                                        SettingsBtn::SixRounds,
+                                        SettingsBtn::FifteenRounds,
+                                        SettingsBtn::ThirtyRounds,
+                                    ],
+                                    &settings,
+                                    &assets,
+                                    &window,
+                                );
+                                spawn_label(
+                                    parent,
+                                    "Intel leak",
+                                    vec![
+                                        SettingsBtn::NoLeak,
+                                        SettingsBtn::LowLeak,
+                                        SettingsBtn::HighLeak,
+                                    ],
+                                    &settings,
+                                    &assets,
+                                    &window,
+                                );
+                                spawn_label(
+                                    parent,
+                                    "Sandbox (single player)",
+                                    vec![
+                                        SettingsBtn::Regular,
+                                        SettingsBtn::Reveal,
+                                        SettingsBtn::Infinite,
+                                    ],
+                                    &settings,
+                                    &assets,
+                                    &window,
+                                );
+                                spawn_label(
+                                    parent,
+                                    "Tutorial (single player)",
+                                    vec![SettingsBtn::Tutorial, SettingsBtn::NoTutorial],
+                                    &settings,
+                                    &assets,
+                                    &window,
+                                );
+                                spawn_label(
+                                    parent,
+                                    "Turn resolution (multiplayer)",
+                                    vec![SettingsBtn::RandomOrder, SettingsBtn::Simultaneous],
+                                    &settings,
+                                    &assets,
+                                    &window,
+                                );
+                                spawn_label(
+                                    parent,
+                                    "Teams (multiplayer)",
+                                    vec![SettingsBtn::FreeForAll, SettingsBtn::TwoTeams],
+                                    &settings,
+                                    &assets,
+                                    &window,
+                                );
+                                spawn_label(
+                                    parent,
+                                    "Colony build order",
+                                    vec![
+                                        SettingsBtn::Manual,
+                                        SettingsBtn::Economy,
+                                        SettingsBtn::Industry,
+                                    ],
+                                    &settings,
+                                    &assets,
+                                    &window,
+                                );
                                 spawn_label(
                                     parent,
                                     "Audio",
@@ -199,6 +369,7 @@ pub fn setup_menu(
                                 );
                             });
 
+                        spawn_menu_button(parent, MenuBtn::SaveScenario, &assets, &window);
                         spawn_menu_button(parent, MenuBtn::Back, &assets, &window);
                     }
                     _ => (),
@@ -298,7 +469,9 @@ pub fn setup_game_menu(
         spawn_menu_button(parent, MenuBtn::Continue, &assets, &window);
         if host.is_some() {
             spawn_menu_button(parent, MenuBtn::SaveGame, &assets, &window);
+            spawn_menu_button(parent, MenuBtn::ExportTimelapse, &assets, &window);
         }
+        spawn_menu_button(parent, MenuBtn::Encyclopedia, &assets, &window);
         spawn_menu_button(parent, MenuBtn::Settings, &assets, &window);
         spawn_menu_button(parent, MenuBtn::Quit, &assets, &window);
     });
@@ -330,6 +503,159 @@ pub fn setup_game_settings(
                     &assets,
                     &window,
                 );
+                spawn_label(
+                    parent,
+                    "Combats",
+                    vec![SettingsBtn::Animate, SettingsBtn::AutoResolve],
+                    &settings,
+                    &assets,
+                    &window,
+                );
+                spawn_label(
+                    parent,
+                    "Combat view",
+                    vec![SettingsBtn::Sprites, SettingsBtn::Lite],
+                    &settings,
+                    &assets,
+                    &window,
+                );
+                spawn_label(
+                    parent,
+                    "Shot effects",
+                    vec![SettingsBtn::Low, SettingsBtn::High, SettingsBtn::Unlimited],
+                    &settings,
+                    &assets,
+                    &window,
+                );
+                spawn_label(
+                    parent,
+                    "Combat sounds",
+                    vec![SettingsBtn::VariedSounds, SettingsBtn::SimpleSounds],
+                    &settings,
+                    &assets,
+                    &window,
+                );
+                spawn_label(
+                    parent,
+                    "Damage numbers",
+                    vec![SettingsBtn::ShowDamage, SettingsBtn::HideDamage],
+                    &settings,
+                    &assets,
+                    &window,
+                );
+                spawn_label(
+                    parent,
+                    "Background",
+                    vec![SettingsBtn::Parallax, SettingsBtn::Static],
+                    &settings,
+                    &assets,
+                    &window,
+                );
+                spawn_label(
+                    parent,
+                    "Turn summary",
+                    vec![SettingsBtn::Show, SettingsBtn::Hide],
+                    &settings,
+                    &assets,
+                    &window,
+                );
+                spawn_label(
+                    parent,
+                    "Map cells",
+                    vec![SettingsBtn::Territory, SettingsBtn::Threat, SettingsBtn::Intel],
+                    &settings,
+                    &assets,
+                    &window,
+                );
+                spawn_label(
+                    parent,
+                    "Stale intel (turns)",
+                    vec![SettingsBtn::ThreeTurns, SettingsBtn::FiveTurns, SettingsBtn::TenTurns],
+                    &settings,
+                    &assets,
+                    &window,
+                );
+                spawn_label(
+                    parent,
+                    "Mission paths",
+                    vec![SettingsBtn::ShowPaths, SettingsBtn::HidePaths],
+                    &settings,
+                    &assets,
+                    &window,
+                );
+                spawn_label(
+                    parent,
+                    "Combat odds",
+                    vec![SettingsBtn::ShowOdds, SettingsBtn::HideOdds],
+                    &settings,
+                    &assets,
+                    &window,
+                );
+                spawn_label(
+                    parent,
+                    "Enemy colors",
+                    vec![SettingsBtn::PerPlayer, SettingsBtn::Uniform],
+                    &settings,
+                    &assets,
+                    &window,
+                );
+                spawn_label(
+                    parent,
+                    "Abandon planet",
+                    vec![SettingsBtn::Confirm, SettingsBtn::Skip],
+                    &settings,
+                    &assets,
+                    &window,
+                );
+                spawn_label(
+                    parent,
+                    "Text size",
+                    vec![SettingsBtn::NormalText, SettingsBtn::LargeText],
+                    &settings,
+                    &assets,
+                    &window,
+                );
+
+                // The frame rate is controlled by the browser on the web
+                #[cfg(not(target_arch = "wasm32"))]
+                {
+                    spawn_label(
+                        parent,
+                        "VSync",
+                        vec![SettingsBtn::Vsync, SettingsBtn::NoVsync],
+                        &settings,
+                        &assets,
+                        &window,
+                    );
+                    spawn_label(
+                        parent,
+                        "Frame rate cap",
+                        vec![SettingsBtn::ThirtyFps, SettingsBtn::SixtyFps, SettingsBtn::NoCap],
+                        &settings,
+                        &assets,
+                        &window,
+                    );
+                }
+                spawn_label(
+                    parent,
+                    "Colony build order",
+                    vec![SettingsBtn::Manual, SettingsBtn::Economy, SettingsBtn::Industry],
+                    &settings,
+                    &assets,
+                    &window,
+                );
+                spawn_label(
+                    parent,
+                    "Auto-economy ratio",
+                    vec![
+                        SettingsBtn::EvenRatio,
+                        SettingsBtn::MetalRatio,
+                        SettingsBtn::SteppedRatio,
+                    ],
+                    &settings,
+                    &assets,
+                    &window,
+                );
                 if host.is_some() {
                     spawn_label(
                         parent,
@@ -350,11 +676,19 @@ pub fn setup_end_game(
     mut commands: Commands,
     map: Res<Map>,
     player: Res<Player>,
+    standings: Res<Standings>,
+    roster: Res<Roster>,
+    diplomacy: Res<Diplomacy>,
     mut play_audio_msg: MessageWriter<PlayAudioMsg>,
     assets: Local<WorldAssets>,
     window: Single<&Window>,
 ) {
-    let (image, audio) = if player.owns(map.get(player.home_planet)) {
+    // A team wins together
+    let won = standings.winner.map_or(player.owns(map.get(player.home_planet)), |w| {
+        w == player.id || diplomacy.are_teammates(w, player.id)
+    });
+
+    let (image, audio) = if won {
         ("victory bg", "victory")
     } else {
         ("defeat bg", "defeat")
@@ -376,6 +710,66 @@ pub fn setup_end_game(
                     ..default()
                 })
                 .with_children(|parent| {
+                    // Scoreboard with the standings of all players
+                    parent
+                        .spawn(Node {
+                            flex_direction: FlexDirection::Column,
+                            row_gap: Val::Percent(1.),
+                            margin: UiRect::ZERO.with_bottom(Val::Percent(3.)),
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            let header =
+                                ["Player", "Planets", "Fleet", "Produced", "Battles won", "Score"]
+                                    .map(|s| s.to_string());
+
+                            let rows = standings.players.iter().map(|s| {
+                                [
+                                    if s.id == player.id {
+                                        "You".to_string()
+                                    } else {
+                                        roster.name(s.id)
+                                    },
+                                    s.planets.to_string(),
+                                    s.fleet.fmt(),
+                                    s.produced.fmt(),
+                                    s.battles_won.to_string(),
+                                    s.score().fmt(),
+                                ]
+                            });
+
+                            for (i, row) in std::iter::once(header).chain(rows).enumerate() {
+                                parent
+                                    .spawn(Node {
+                                        flex_direction: FlexDirection::Row,
+                                        ..default()
+                                    })
+                                    .with_children(|parent| {
+                                        for cell in row {
+                                            parent
+                                                .spawn(Node {
+                                                    width: Val::Vw(12.),
+                                                    justify_content: JustifyContent::Center,
+                                                    ..default()
+                                                })
+                                                .with_children(|parent| {
+                                                    parent.spawn(add_text(
+                                                        cell,
+                                                        if i == 0 {
+                                                            "bold"
+                                                        } else {
+                                                            "medium"
+                                                        },
+                                                        TITLE_TEXT_SIZE,
+                                                        &assets,
+                                                        &window,
+                                                    ));
+                                                });
+                                        }
+                                    });
+                            }
+                        });
+
                     spawn_menu_button(parent, MenuBtn::Spectate, &assets, &window);
                     spawn_menu_button(parent, MenuBtn::Quit, &assets, &window);
                 });
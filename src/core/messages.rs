@@ -1,23 +1,53 @@
+use std::collections::VecDeque;
 use std::time::Duration;
 
 use bevy::prelude::*;
-use bevy_egui::egui::RichText;
+use bevy_egui::egui::{Color32, RichText};
 use bevy_egui::EguiContexts;
 use egui_notify::{Anchor, Toast, Toasts};
 
 use crate::core::audio::PlayAudioMsg;
-use crate::core::constants::MESSAGE_DURATION;
+use crate::core::combat::report::ReportId;
+use crate::core::constants::{MESSAGE_DURATION, MESSAGE_HISTORY};
 
+#[derive(Clone, Copy, PartialEq)]
 pub enum MessageLevel {
     Info,
     Warning,
     Error,
+    Combat,
+    Diplomacy,
+}
+
+impl MessageLevel {
+    pub fn icon(&self) -> &'static str {
+        match self {
+            MessageLevel::Info => "ℹ",
+            MessageLevel::Warning => "⚠",
+            MessageLevel::Error => "❗",
+            MessageLevel::Combat => "⚔",
+            MessageLevel::Diplomacy => "🤝",
+        }
+    }
+
+    pub fn color(&self) -> Color32 {
+        match self {
+            MessageLevel::Info => Color32::from_rgb(136, 192, 208),
+            MessageLevel::Warning => Color32::from_rgb(235, 203, 139),
+            MessageLevel::Error => Color32::from_rgb(191, 97, 106),
+            MessageLevel::Combat => Color32::from_rgb(208, 135, 112),
+            MessageLevel::Diplomacy => Color32::from_rgb(180, 142, 173),
+        }
+    }
 }
 
 #[derive(Message)]
 pub struct MessageMsg {
     pub message: String,
     pub level: MessageLevel,
+
+    /// Mission report with a combat the message refers to
+    pub report: Option<ReportId>,
 }
 
 impl MessageMsg {
@@ -25,9 +55,21 @@ impl MessageMsg {
         Self {
             message: message.into(),
             level,
+            report: None,
         }
     }
 
+    pub fn combat(message: impl Into<String>, report: Option<ReportId>) -> Self {
+        Self {
+            report,
+            ..Self::new(message, MessageLevel::Combat)
+        }
+    }
+
+    pub fn diplomacy(message: impl Into<String>) -> Self {
+        Self::new(message, MessageLevel::Diplomacy)
+    }
+
     pub fn info(message: impl Into<String>) -> Self {
         Self::new(message, MessageLevel::Info)
     }
@@ -41,6 +83,17 @@ impl MessageMsg {
     }
 }
 
+/// Message shown earlier, kept to be read back in the notifications panel
+pub struct Notification {
+    pub message: String,
+    pub level: MessageLevel,
+    pub report: Option<ReportId>,
+}
+
+/// Most recent messages, newest first
+#[derive(Resource, Default)]
+pub struct MessageHistory(pub VecDeque<Notification>);
+
 #[derive(Resource)]
 pub struct Messages(pub Toasts);
 
@@ -62,11 +115,22 @@ impl Messages {
             .error(RichText::new(message).small())
             .duration(Some(Duration::from_secs(MESSAGE_DURATION)))
     }
+
+    pub fn custom(&mut self, message: &String, level: MessageLevel) -> &mut Toast {
+        self.0
+            .custom(
+                RichText::new(message).small().color(level.color()),
+                level.icon().to_string(),
+                level.color(),
+            )
+            .duration(Some(Duration::from_secs(MESSAGE_DURATION)))
+    }
 }
 
 fn check_messages(
     contexts: EguiContexts,
     mut messages: ResMut<Messages>,
+    mut history: ResMut<MessageHistory>,
     mut play_audio_msg: MessageWriter<PlayAudioMsg>,
     mut message_msg: MessageReader<MessageMsg>,
 ) {
@@ -96,7 +160,21 @@ fn check_messages(
                 }
                 messages.error(&message.message);
             },
+            MessageLevel::Combat | MessageLevel::Diplomacy => {
+                if info {
+                    play_audio_msg.write(PlayAudioMsg::new("message"));
+                    info = false;
+                }
+                messages.custom(&message.message, message.level);
+            },
         };
+
+        history.0.push_front(Notification {
+            message: message.message.clone(),
+            level: message.level,
+            report: message.report,
+        });
+        history.0.truncate(MESSAGE_HISTORY);
     }
 
     messages.0.show(contexts.ctx().unwrap());
@@ -122,6 +200,7 @@ impl Default for MessagesPlugin {
 impl Plugin for MessagesPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(Messages(self.builder.map(|f| f()).unwrap_or_default()))
+            .init_resource::<MessageHistory>()
             .add_systems(Update, check_messages);
     }
 }
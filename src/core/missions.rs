@@ -1,25 +1,36 @@
+use std::collections::HashMap;
 use std::f32::consts::PI;
 use std::time::Duration;
 
+use bevy::color::palettes::css::WHITE;
 use bevy::prelude::*;
 use bevy::window::SystemCursorIcon;
 use bevy_egui::egui::emath::OrderedFloat;
 use bevy_renet::renet::ClientId;
 use bevy_tweening::{RepeatCount, Tween, TweenAnim};
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 use crate::core::assets::WorldAssets;
-use crate::core::constants::{MISSION_Z, NEXUS_FACTOR, PHALANX_DISTANCE, RADAR_DISTANCE};
+use crate::core::constants::{
+    MISSION_Z, NEXUS_FACTOR, PHALANX_DISTANCE, RADAR_DISTANCE, TANKER_FUEL_FACTOR,
+    WORMHOLE_DISTANCE,
+};
+use crate::core::diplomacy::Diplomacy;
 use crate::core::map::icon::Icon;
 use crate::core::map::map::{Map, MapCmp};
 use crate::core::map::planet::{Planet, PlanetId};
-use crate::core::map::systems::MissionCmp;
+use crate::core::map::systems::{MissionCmp, MissionEtaCmp};
 use crate::core::map::utils::{cursor, SpriteFrameLens};
 use crate::core::messages::MessageMsg;
-use crate::core::player::Player;
+use crate::core::player::{Player, Roster};
+use crate::core::resources::{ResourceName, Resources};
+use crate::core::settings::Settings;
 use crate::core::ui::systems::{MissionTab, UiState};
 use crate::core::units::buildings::Building;
+use crate::core::units::ships::Ship;
 use crate::core::units::{Amount, Army, Combat, Description, Unit};
 use crate::utils::NameFromEnum;
 
@@ -36,17 +47,41 @@ impl Missions {
     pub fn iter(&self) -> std::slice::Iter<'_, Mission> {
         self.0.iter()
     }
+
+    /// Planets of the player that a detected enemy mission reaches next turn
+    pub fn imminent_attacks(
+        &self,
+        map: &Map,
+        player: &Player,
+        diplomacy: &Diplomacy,
+    ) -> Vec<PlanetId> {
+        self.iter()
+            .filter(|m| {
+                m.owner != player.id
+                    && !diplomacy.is_allied(player.id, m.owner)
+                    && player.controls(map.get(m.destination))
+                    && m.turns_to_destination(map) == 1
+                    && (m.is_seen_by_phalanx(map, player).is_some()
+                        || m.is_seen_by_radar(map, player).is_some())
+            })
+            .map(|m| m.destination)
+            .unique()
+            .collect()
+    }
 }
 
 #[derive(Message)]
 pub struct SendMissionMsg {
     pub mission: Mission,
+    /// Abandon the origin planet after the mission leaves
+    pub abandon: bool,
 }
 
 impl SendMissionMsg {
     pub fn new(mission: Mission) -> Self {
         Self {
             mission,
+            abandon: false,
         }
     }
 }
@@ -76,6 +111,35 @@ impl Description for BombingRaid {
     }
 }
 
+#[derive(EnumIter, Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Targeting {
+    #[default]
+    Random,
+    WeakestFirst,
+    StrongestFirst,
+    DefensesFirst,
+}
+
+impl Description for Targeting {
+    fn description(&self) -> &str {
+        match self {
+            Targeting::Random => "Every shot hits a random enemy unit.",
+            Targeting::WeakestFirst => {
+                "Shots focus on the enemy unit with the least hull and shield left, to destroy \
+                as many units as possible."
+            },
+            Targeting::StrongestFirst => {
+                "Shots focus on the enemy unit with the most hull and shield left, to take out \
+                the most dangerous units first."
+            },
+            Targeting::DefensesFirst => {
+                "Shots hit random enemy defenses first, and only target ships once all defenses \
+                are destroyed."
+            },
+        }
+    }
+}
+
 #[derive(Clone, Default, Serialize, Deserialize)]
 pub struct Mission {
     pub id: MissionId,
@@ -90,8 +154,16 @@ pub struct Mission {
     pub objective: Icon,
     pub army: Army,
     pub bombing: BombingRaid,
+    pub targeting: Targeting,
     pub combat_probes: bool,
     pub jump_gate: bool,
+    pub veterancy: usize,
+    /// Resources carried by the fleet, invested in the destination's production on arrival
+    pub cargo: Resources,
+    /// Whether the host took the cargo from the owner's stockpile
+    pub cargo_loaded: bool,
+    /// Planet the surviving fleet returns to instead of the origin, if still controlled
+    pub return_to: Option<PlanetId>,
     pub logs: String,
 }
 
@@ -125,8 +197,18 @@ impl Mission {
             objective,
             army,
             bombing,
+            targeting: Targeting::default(),
             combat_probes,
             jump_gate,
+            // The fleet keeps the veterancy of the planet it leaves from
+            veterancy: if origin.controlled == Some(owner) {
+                origin.veterancy
+            } else {
+                0
+            },
+            cargo: Resources::default(),
+            cargo_loaded: false,
+            return_to: None,
             logs: logs.unwrap_or(format!("- ({turn}) Mission send to {}.", destination.name)),
         }
     }
@@ -138,18 +220,22 @@ impl Mission {
         destination: &Planet,
         mission: &Mission,
     ) -> Self {
-        Self::new(
-            turn,
-            owner,
-            origin,
-            destination,
-            mission.objective,
-            mission.army.clone(),
-            mission.bombing.clone(),
-            mission.combat_probes,
-            mission.jump_gate,
-            None,
-        )
+        Self {
+            targeting: mission.targeting,
+            return_to: mission.return_to,
+            ..Self::new(
+                turn,
+                owner,
+                origin,
+                destination,
+                mission.objective,
+                mission.army.clone(),
+                mission.bombing.clone(),
+                mission.combat_probes,
+                mission.jump_gate,
+                None,
+            )
+        }
     }
 
     pub fn image(&self, player: &Player) -> &str {
@@ -162,7 +248,7 @@ impl Mission {
 
     pub fn distance(&self, map: &Map) -> f32 {
         // Minus 0.7 since the mission ends at the edge of the planet
-        (self.position.distance(map.get(self.destination).position) / Planet::SIZE - 0.7).max(0.)
+        (map.path(self.position, self.destination).0 / Planet::SIZE - 0.7).max(0.)
     }
 
     pub fn speed(&self) -> f32 {
@@ -185,8 +271,15 @@ impl Mission {
         (speed != 0.).then(|| (distance / speed).ceil() as usize).unwrap_or(0)
     }
 
-    pub fn fuel_consumption(&self, map: &Map) -> usize {
-        if self.jump_gate {
+    /// Whether the mission deploys a fleet between a planet and its moon
+    pub fn is_orbit_deploy(&self, map: &Map) -> bool {
+        self.objective == Icon::Deploy
+            && map.companion(self.origin).is_some_and(|p| p.id == self.destination)
+    }
+
+    /// Fuel consumption of the mission without the reduction from Tankers
+    pub fn base_fuel_consumption(&self, map: &Map) -> usize {
+        if self.jump_gate || self.is_orbit_deploy(map) {
             0
         } else {
             let origin = map.get(self.origin);
@@ -203,18 +296,148 @@ impl Mission {
         }
     }
 
+    /// Maximum distance the fleet can travel with the given amount of deuterium
+    pub fn fuel_range(&self, map: &Map, deuterium: usize) -> f32 {
+        let reactor = map.get(self.origin).army.amount(&Unit::Building(Building::Reactor)) as f32;
+        let tankers = self.army.amount(&Unit::Ship(Ship::Tanker)) as i32;
+
+        let fuel = self.army.iter().map(|(u, n)| (u.fuel_consumption() * n) as f32).sum::<f32>()
+            * (1. - NEXUS_FACTOR * reactor)
+            * (1. - TANKER_FUEL_FACTOR).powi(tankers);
+
+        if self.jump_gate || fuel <= 0. {
+            f32::MAX
+        } else {
+            deuterium as f32 / fuel
+        }
+    }
+
+    /// Whether both planets have a Jump Gate the mission can use, and whether the
+    /// origin's gate has capacity left for the army. Only deploys between planets
+    /// owned by the player can jump
+    pub fn jump_gate_access(&self, map: &Map, player: &Player) -> (bool, bool) {
+        let origin = map.get(self.origin);
+        let destination = map.get(self.destination);
+
+        let has_gates = self.objective == Icon::Deploy
+            && player.owns(origin)
+            && player.owns(destination)
+            && origin.army.amount(&Unit::Building(Building::JumpGate)) > 0
+            && destination.army.amount(&Unit::Building(Building::JumpGate)) > 0;
+
+        (has_gates, has_gates && origin.jump_gate + self.jump_cost() <= origin.max_jump_capacity())
+    }
+
+    /// Travel through the Jump Gate when possible and chosen for the route, and
+    /// return the resulting duration and fuel consumption of the mission
+    pub fn select_travel(
+        &mut self,
+        map: &Map,
+        player: &Player,
+        routes: &HashMap<(PlanetId, PlanetId), bool>,
+    ) -> (usize, usize) {
+        let (_, can_jump) = self.jump_gate_access(map, player);
+        self.jump_gate =
+            can_jump && routes.get(&(self.origin, self.destination)).copied().unwrap_or(false);
+
+        self.travel_cost(map, self.jump_gate)
+    }
+
+    /// Duration and fuel consumption of the mission when traveling through the
+    /// Jump Gate or at sublight speed
+    pub fn travel_cost(&self, map: &Map, jump_gate: bool) -> (usize, usize) {
+        let mission = Mission {
+            jump_gate,
+            ..self.clone()
+        };
+
+        (mission.duration(map), mission.fuel_consumption(map))
+    }
+
+    pub fn fuel_consumption(&self, map: &Map) -> usize {
+        // Every Tanker reduces the remaining consumption (diminishing returns)
+        let tankers = self.army.amount(&Unit::Ship(Ship::Tanker)) as i32;
+        let fuel = self.base_fuel_consumption(map) as f32;
+
+        (fuel * (1. - TANKER_FUEL_FACTOR).powi(tankers)).ceil() as usize
+    }
+
     pub fn total(&self) -> usize {
         self.army.values().sum()
     }
 
+    /// Amount of resources the fleet can carry
+    pub fn cargo_capacity(&self) -> usize {
+        self.army.iter().map(|(u, c)| u.cargo() * c).sum()
+    }
+
+    /// Take the cargo from the stockpile, limited to the cargo capacity of the fleet
+    /// (only deploy missions carry cargo) and to the available resources
+    pub fn load_cargo(&mut self, stockpile: &mut Resources) {
+        if self.cargo_loaded {
+            return;
+        }
+
+        let mut remaining = if self.objective == Icon::Deploy {
+            self.cargo_capacity()
+        } else {
+            0
+        };
+
+        for resource in ResourceName::iter() {
+            let amount = self.cargo.get_mut(&resource);
+            *amount = (*amount).min(stockpile.get(&resource)).min(remaining);
+            remaining -= *amount;
+        }
+
+        *stockpile -= self.cargo;
+        self.cargo_loaded = true;
+    }
+
+    /// Whether the army meets the ship requirements of the objective
+    pub fn meets_objective(&self) -> bool {
+        match self.objective {
+            Icon::Deploy => self.army.iter().any(|(u, c)| u.is_ship() && *c > 0),
+            Icon::Colonize => self.army.amount(&Unit::colony_ship()) > 0,
+            Icon::Attack => self.army.iter().any(|(u, n)| *n > 0 && u.is_combat_ship()),
+            Icon::Spy => self.army.amount(&Unit::probe()) == self.total(),
+            Icon::MissileStrike => {
+                self.army.amount(&Unit::interplanetary_missile()) == self.total()
+            },
+            Icon::Destroy => self.army.amount(&Unit::war_sun()) > 0,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Deuterium required every turn to maintain the ships of this mission
+    pub fn upkeep(&self) -> usize {
+        self.army.iter().map(|(u, c)| u.upkeep() * c).sum()
+    }
+
     pub fn advance(&mut self, map: &Map) {
         let destination = map.get(self.destination);
 
         if self.jump_gate {
             self.position = destination.position;
         } else {
-            let direction = (-self.position + destination.position).normalize();
-            self.position += direction * self.speed() * Planet::SIZE;
+            let mut step = self.speed() * Planet::SIZE;
+
+            // Travel towards the wormhole's entrance and continue from its exit
+            if let (_, Some((entrance, exit))) = map.path(self.position, self.destination) {
+                let entrance = map.get(entrance).position;
+                let to_entrance = self.position.distance(entrance);
+
+                if step < to_entrance {
+                    self.position += (entrance - self.position).normalize() * step;
+                    return;
+                }
+
+                step = (step - to_entrance - WORMHOLE_DISTANCE * Planet::SIZE).max(0.);
+                self.position = map.get(exit).position;
+            }
+
+            let direction = (-self.position + destination.position).normalize_or_zero();
+            self.position += direction * step;
         }
     }
 
@@ -234,17 +457,25 @@ impl Mission {
             self.origin_owned = other.origin_owned;
             self.origin_controlled = other.origin_controlled;
             self.origin_army = other.origin_army.clone();
+            self.targeting = other.targeting;
         }
 
         // Select objective based on priority
         self.objective =
             [self.objective, other.objective].into_iter().max_by_key(|o| o.priority()).unwrap();
 
+        let (n_self, n_other) = (self.army.combat_ships(), other.army.combat_ships());
+        self.veterancy = (self.veterancy * n_self + other.veterancy * n_other)
+            .checked_div(n_self + n_other)
+            .unwrap_or(0);
+
         for (u, c) in &other.army {
             *self.army.entry(*u).or_default() += c;
         }
 
         self.combat_probes = other.combat_probes || self.combat_probes;
+        self.cargo += other.cargo;
+        self.cargo_loaded = self.cargo_loaded || other.cargo_loaded;
 
         self.logs.push_str(
             format!("\n- Merged with other mission with objective {}.", other.objective.to_name())
@@ -252,9 +483,25 @@ impl Mission {
         );
     }
 
-    /// Return the origin planet if still controlled by the player,
-    /// else go to the nearest friendly planet
+    /// Whether the surviving fleet can return to `planet`. It must be controlled by
+    /// the owner and no farther from the destination than the origin, since the
+    /// return leg costs no fuel
+    pub fn can_return_to(&self, planet: &Planet, map: &Map) -> bool {
+        planet.controlled == Some(self.owner)
+            && planet.id != self.destination
+            && map.path(planet.position, self.destination).0
+                <= map.path(map.get(self.origin).position, self.destination).0
+    }
+
+    /// Return the planet to return to (or else the origin planet) if still
+    /// controlled by the player, else go to the nearest friendly planet
     pub fn check_origin(&self, map: &Map) -> PlanetId {
+        if let Some(planet) =
+            self.return_to.map(|id| map.get(id)).filter(|p| self.can_return_to(p, map))
+        {
+            return planet.id;
+        }
+
         let origin = map.get(self.origin);
         if origin.controlled == Some(self.owner) {
             origin.id
@@ -294,9 +541,16 @@ impl Mission {
 pub fn update_missions(
     mut commands: Commands,
     mut mission_q: Query<(Entity, &mut Sprite, &mut Transform, &MissionCmp)>,
+    mut eta_q: Query<
+        (&mut Text2d, &mut TextColor, &mut Transform, &mut Visibility),
+        (With<MissionEtaCmp>, Without<MissionCmp>),
+    >,
+    children_q: Query<&Children>,
     state: Res<UiState>,
+    settings: Res<Settings>,
     map: Res<Map>,
     player: Res<Player>,
+    roster: Res<Roster>,
     missions: Res<Missions>,
     assets: Local<WorldAssets>,
 ) {
@@ -326,24 +580,38 @@ pub fn update_missions(
                         ..default()
                     },
                     Pickable::default(),
-                    MissionCmp::new(id),
+                    MissionCmp::new(id, mission.position),
                     MapCmp,
-                    children![(
-                        Sprite::from_atlas_image(texture.image, texture.atlas),
-                        Transform {
-                            translation: Vec3::new(-25., 0., -0.1),
-                            scale: Vec3::splat(0.35),
-                            rotation: Quat::from_rotation_z(PI),
-                        },
-                        TweenAnim::new(
-                            Tween::new(
-                                EaseFunction::Linear,
-                                Duration::from_millis(1000),
-                                SpriteFrameLens(texture.last_index),
-                            )
-                            .with_repeat_count(RepeatCount::Infinite),
+                    children![
+                        (
+                            Sprite::from_atlas_image(texture.image, texture.atlas),
+                            Transform {
+                                translation: Vec3::new(-25., 0., -0.1),
+                                scale: Vec3::splat(0.35),
+                                rotation: Quat::from_rotation_z(PI),
+                            },
+                            TweenAnim::new(
+                                Tween::new(
+                                    EaseFunction::Linear,
+                                    Duration::from_millis(1000),
+                                    SpriteFrameLens(texture.last_index),
+                                )
+                                .with_repeat_count(RepeatCount::Infinite),
+                            ),
                         ),
-                    )],
+                        (
+                            Text2d::default(),
+                            TextFont {
+                                font: assets.font("bold"),
+                                font_size: 20.,
+                                ..default()
+                            },
+                            TextColor(WHITE.into()),
+                            Transform::from_xyz(0., 35., 0.1),
+                            Pickable::IGNORE,
+                            MissionEtaCmp,
+                        ),
+                    ],
                 ))
                 .observe(cursor::<Over>(SystemCursorIcon::Pointer))
                 .observe(cursor::<Out>(SystemCursorIcon::Default))
@@ -380,12 +648,44 @@ pub fn update_missions(
 
             if state.mission_hover.is_some_and(|id| id == mission.id) {
                 // Hovered missions show on top of all other components (e.g., planets)
-                mission_t.translation = mission.position.extend(MISSION_Z + 10.);
+                // The position itself is animated between turns in `run_map_animations`
+                mission_t.translation.z = MISSION_Z + 10.;
                 mission_s.image = assets.image(format!("{} hover", mission.image(&player)));
             } else {
-                mission_t.translation = mission.position.extend(MISSION_Z);
+                mission_t.translation.z = MISSION_Z;
                 mission_s.image = assets.image(mission.image(&player));
             }
+
+            // Update the turns remaining label, keeping it upright above the icon
+            let turns = mission.turns_to_destination(&map);
+            for child in children_q.iter_descendants(mission_e) {
+                if let Ok((mut eta_text, mut eta_c, mut eta_t, mut eta_v)) = eta_q.get_mut(child) {
+                    eta_t.translation = mission_t.rotation.inverse() * Vec3::new(0., 35., 0.1);
+                    eta_t.rotation = mission_t.rotation.inverse();
+
+                    let seen = mission.is_seen_by_phalanx(&map, &player).is_some()
+                        || mission.is_seen_by_radar(&map, &player).is_some();
+
+                    eta_text.0 = if mission.owner == player.id || seen {
+                        format!("+{turns}")
+                    } else {
+                        "?".to_string()
+                    };
+
+                    // Detected enemy missions show the color of their owner
+                    eta_c.0 = if mission.owner != player.id && seen {
+                        roster.color(Some(mission.owner), &settings)
+                    } else {
+                        WHITE.into()
+                    };
+
+                    *eta_v = if turns > 0 {
+                        Visibility::Inherited
+                    } else {
+                        Visibility::Hidden
+                    };
+                }
+            }
         } else {
             commands.entity(mission_e).despawn();
         }
@@ -401,6 +701,7 @@ pub fn send_mission(
 ) {
     for SendMissionMsg {
         mission,
+        abandon,
     } in send_mission.read()
     {
         player.resources.deuterium -= mission.fuel_consumption(&map);
@@ -424,5 +725,305 @@ pub fn send_mission(
         missions.0.push(mission.clone());
 
         message.write(MessageMsg::info("Mission sent."));
+
+        if *abandon {
+            player.abandon(origin, mission.send);
+            message.write(MessageMsg::info(format!("Planet {} abandoned.", origin.name)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::settings::{ResourceBias, Teams};
+
+    fn mission(objective: Icon, army: Army, cargo: Resources) -> Mission {
+        Mission {
+            objective,
+            army,
+            cargo,
+            ..default()
+        }
+    }
+
+    /// Planets of player 1 at x=0 (origin), x=800 and x=-500, and the target of
+    /// player 2 at x=1000
+    fn map() -> Map {
+        let planet = |id, x, owner| Planet {
+            controlled: Some(owner),
+            ..Planet::new(id, format!("P{id}"), Vec2::new(x, 0.), false, 1., ResourceBias::None)
+        };
+
+        Map {
+            rect: Rect::default(),
+            planets: vec![
+                planet(0, 0., 1),
+                planet(1, 800., 1),
+                planet(2, 1000., 2),
+                planet(3, -500., 1),
+            ],
+            wormholes: vec![],
+        }
+    }
+
+    fn attack(return_to: PlanetId) -> Mission {
+        Mission {
+            owner: 1,
+            origin: 0,
+            destination: 2,
+            position: Vec2::new(1000., 0.),
+            army: Army::from([(Unit::Ship(Ship::Cruiser), 3)]),
+            return_to: Some(return_to),
+            ..mission(Icon::Attack, Army::new(), Resources::default())
+        }
+    }
+
+    #[test]
+    fn victorious_fleet_docks_at_the_return_planet() {
+        let mut map = map();
+        let mission = attack(1);
+        assert_eq!(mission.check_origin(&map), 1);
+
+        // The surviving fleet flies back with a deploy mission and docks on arrival
+        let back = Mission::new(
+            2,
+            mission.owner,
+            map.get(mission.destination),
+            map.get(mission.check_origin(&map)),
+            Icon::Deploy,
+            mission.army.clone(),
+            BombingRaid::None,
+            false,
+            false,
+            None,
+        );
+        map.get_mut(back.destination).dock(back.army, 0);
+        assert_eq!(map.get(1).army.amount(&Unit::Ship(Ship::Cruiser)), 3);
+    }
+
+    #[test]
+    fn fleet_cannot_return_farther_than_the_origin() {
+        let map = map();
+        let mission = attack(3);
+        assert!(!mission.can_return_to(map.get(3), &map));
+        assert_eq!(mission.check_origin(&map), 0);
+    }
+
+    #[test]
+    fn fleet_returns_to_the_origin_if_the_planet_was_lost() {
+        let mut map = map();
+        map.get_mut(1).controlled = Some(2);
+        assert_eq!(attack(1).check_origin(&map), 0);
+    }
+
+    #[test]
+    fn tankers_reduce_the_fuel_consumption() {
+        let map = map();
+        let mut mission = Mission {
+            position: Vec2::ZERO,
+            ..attack(0)
+        };
+        assert!(mission.fuel_consumption(&map) > 0);
+
+        // Every tanker cuts the remaining consumption, including its own
+        for n in 1..=3 {
+            mission.army.insert(Unit::Ship(Ship::Tanker), n);
+            let base = mission.base_fuel_consumption(&map) as f32;
+            let reduced = mission.fuel_consumption(&map);
+            assert_eq!(reduced, (base * (1. - TANKER_FUEL_FACTOR).powi(n as i32)).ceil() as usize);
+            assert!(reduced < base as usize);
+        }
+    }
+
+    #[test]
+    fn deploying_to_the_moon_costs_no_fuel() {
+        let mut map = map();
+        map.planets.push(Planet {
+            controlled: Some(1),
+            ..Planet::new(4, "Moon".into(), Vec2::new(200., 0.), true, 1., ResourceBias::None)
+        });
+
+        let army = Army::from([(Unit::Ship(Ship::Cruiser), 3)]);
+        let mut mission = Mission {
+            owner: 1,
+            origin: 0,
+            destination: 4,
+            ..mission(Icon::Deploy, army, Resources::default())
+        };
+        assert!(mission.is_orbit_deploy(&map));
+        assert_eq!(mission.fuel_consumption(&map), 0);
+
+        // Only deploys are free, and only between the companions
+        mission.objective = Icon::Attack;
+        assert!(!mission.is_orbit_deploy(&map));
+        assert!(mission.fuel_consumption(&map) > 0);
+
+        mission.objective = Icon::Deploy;
+        mission.destination = 1;
+        assert!(!mission.is_orbit_deploy(&map));
+        assert!(mission.fuel_consumption(&map) > 0);
+    }
+
+    /// Deploy of 3 cruisers (jump cost 9) between planets 0 and 1, both with a
+    /// level 2 Jump Gate (capacity 10)
+    fn gate_deploy() -> (Map, Player, Mission) {
+        let mut map = map();
+        for id in [0, 1] {
+            let planet = map.get_mut(id);
+            planet.owned = Some(1);
+            planet.army.insert(Unit::Building(Building::JumpGate), 2);
+        }
+
+        let mission = Mission {
+            owner: 1,
+            origin: 0,
+            destination: 1,
+            ..mission(
+                Icon::Deploy,
+                Army::from([(Unit::Ship(Ship::Cruiser), 3)]),
+                Resources::default(),
+            )
+        };
+
+        let player = Player {
+            id: 1,
+            ..default()
+        };
+
+        (map, player, mission)
+    }
+
+    #[test]
+    fn jump_gate_travels_in_one_turn_without_fuel() {
+        let (map, player, mut mission) = gate_deploy();
+        let sublight = (mission.duration(&map), mission.fuel_consumption(&map));
+        assert!(sublight.1 > 0);
+        assert_eq!(mission.travel_cost(&map, false), sublight);
+        assert_eq!(mission.travel_cost(&map, true), (1, 0));
+        assert_eq!(mission.jump_gate_access(&map, &player), (true, true));
+
+        // Missions travel at sublight speed unless the gate was chosen for the route
+        let mut routes = HashMap::new();
+        assert_eq!(mission.select_travel(&map, &player, &routes), sublight);
+        assert!(!mission.jump_gate);
+
+        routes.insert((0, 1), true);
+        assert_eq!(mission.select_travel(&map, &player, &routes), (1, 0));
+        assert!(mission.jump_gate);
+
+        routes.insert((0, 1), false);
+        assert_eq!(mission.select_travel(&map, &player, &routes), sublight);
+        assert!(!mission.jump_gate);
+    }
+
+    #[test]
+    fn jump_gate_requires_capacity_left() {
+        let (mut map, player, mut mission) = gate_deploy();
+        map.get_mut(0).jump_gate = 2;
+        assert_eq!(mission.jump_gate_access(&map, &player), (true, false));
+
+        let routes = HashMap::from([((0, 1), true)]);
+        let sublight = mission.travel_cost(&map, false);
+        assert_eq!(mission.select_travel(&map, &player, &routes), sublight);
+        assert!(!mission.jump_gate);
+
+        // Only deploys can jump
+        mission.objective = Icon::Attack;
+        assert_eq!(mission.jump_gate_access(&map, &player), (false, false));
+    }
+
+    #[test]
+    fn detected_missions_arriving_next_turn_are_imminent() {
+        let mut map = map();
+        let home = map.get_mut(0);
+        home.owned = Some(1);
+        home.army.insert(Unit::Building(Building::SensorPhalanx), 5);
+
+        let player = Player {
+            id: 1,
+            ..default()
+        };
+        let diplomacy = Diplomacy::new(&[1, 2], Teams::default());
+
+        let incoming = |id, x| Mission {
+            id,
+            owner: 2,
+            origin: 2,
+            destination: 0,
+            position: Vec2::new(x, 0.),
+            ..mission(
+                Icon::Attack,
+                Army::from([(Unit::Ship(Ship::Cruiser), 3)]),
+                Resources::default(),
+            )
+        };
+
+        // Cruisers cover 2 planet sizes per turn, so only the closest one arrives next turn
+        let missions = Missions(vec![incoming(1, 150.), incoming(2, 280.)]);
+        assert_eq!(missions.iter().map(|m| m.turns_to_destination(&map)).collect_vec(), [1, 2]);
+        assert_eq!(missions.imminent_attacks(&map, &player, &diplomacy), vec![0]);
+
+        // Undetected missions don't raise the warning
+        map.get_mut(0).army.insert(Unit::Building(Building::SensorPhalanx), 0);
+        assert!(missions.imminent_attacks(&map, &player, &diplomacy).is_empty());
+    }
+
+    #[test]
+    fn merged_veterancy_is_weighted_by_the_combat_ships() {
+        let cruiser = Unit::Ship(Ship::Cruiser);
+        let mut veterans = Mission {
+            veterancy: 4,
+            ..mission(Icon::Deploy, Army::from([(cruiser, 1)]), Resources::default())
+        };
+        let recruits = mission(
+            Icon::Deploy,
+            Army::from([(cruiser, 3), (Unit::probe(), 10)]),
+            Resources::default(),
+        );
+
+        // Probes don't fight, so they don't dilute the veterancy
+        veterans.merge(&recruits);
+        assert_eq!(veterans.veterancy, 1);
+        assert_eq!(veterans.army.amount(&cruiser), 4);
+        assert_eq!(veterans.army.amount(&Unit::probe()), 10);
+    }
+
+    #[test]
+    fn delivered_cargo_is_capped_by_the_capacity() {
+        let army = Army::from([(Unit::Ship(Ship::Tanker), 2)]);
+        let mut mission = mission(Icon::Deploy, army, Resources::new(300, 300, 0));
+        let mut stockpile = Resources::new(1000, 1000, 1000);
+
+        mission.load_cargo(&mut stockpile);
+        assert_eq!(mission.cargo_capacity(), 400);
+        assert_eq!(mission.cargo, Resources::new(300, 100, 0));
+        assert_eq!(stockpile, Resources::new(700, 900, 1000));
+
+        // A loaded mission doesn't take from the stockpile again
+        mission.load_cargo(&mut stockpile);
+        assert_eq!(stockpile, Resources::new(700, 900, 1000));
+    }
+
+    #[test]
+    fn cargo_is_capped_by_the_stockpile() {
+        let army = Army::from([(Unit::Ship(Ship::Tanker), 2)]);
+        let mut mission = mission(Icon::Deploy, army, Resources::new(300, 0, 100));
+        let mut stockpile = Resources::new(50, 0, 500);
+
+        mission.load_cargo(&mut stockpile);
+        assert_eq!(mission.cargo, Resources::new(50, 0, 100));
+        assert_eq!(stockpile, Resources::new(0, 0, 400));
+    }
+
+    #[test]
+    fn only_deploy_missions_carry_cargo() {
+        let army = Army::from([(Unit::Ship(Ship::Tanker), 2)]);
+        let mut mission = mission(Icon::Attack, army, Resources::new(100, 0, 0));
+        let mut stockpile = Resources::new(1000, 1000, 1000);
+
+        mission.load_cargo(&mut stockpile);
+        assert_eq!(mission.cargo, Resources::default());
+        assert_eq!(stockpile, Resources::new(1000, 1000, 1000));
     }
 }
@@ -3,6 +3,9 @@ mod audio;
 mod camera;
 pub mod combat;
 pub mod constants;
+#[cfg(any(debug_assertions, feature = "diagnostics"))]
+pub mod diagnostics;
+mod diplomacy;
 mod map;
 mod menu;
 pub mod messages;
@@ -15,6 +18,7 @@ mod settings;
 mod states;
 mod systems;
 mod turns;
+mod tutorial;
 mod ui;
 mod units;
 mod utils;
@@ -28,12 +32,17 @@ use strum::IntoEnumIterator;
 use crate::core::audio::*;
 use crate::core::camera::{move_camera, move_camera_keyboard, reset_camera, setup_camera};
 use crate::core::combat::systems::{
-    animate_combat, exit_combat, exit_combat_menu, run_combat_animations, setup_combat,
-    setup_combat_menu, update_combat_stats, CombatCmp, CombatMenuCmp, SpawnShotMsg,
+    advance_lite_combat, animate_combat, exit_combat, exit_combat_menu, run_combat_animations,
+    setup_combat, setup_combat_menu, sprite_combat, update_combat_stats, CombatCmp, CombatMenuCmp,
+    SpawnShotMsg,
 };
+use crate::core::diplomacy::{send_diplomacy, Diplomacy, DiplomacyMsg};
 use crate::core::map::map::{Map, MapCmp};
 use crate::core::map::systems::{
-    draw_map, run_map_animations, update_end_turn, update_planet_info, update_voronoi,
+    cull_offscreen, draw_combat_odds, draw_map, draw_mission_paths, draw_rally_links,
+    highlight_imminent_attacks, run_map_animations, sync_resource_ratio, update_auto_economy,
+    update_end_turn, update_note_tags, update_planet_info, update_planet_names,
+    update_specialization_icons, update_voronoi, CulledEntities,
 };
 use crate::core::menu::buttons::MenuCmp;
 use crate::core::menu::systems::{
@@ -42,13 +51,28 @@ use crate::core::menu::systems::{
 use crate::core::messages::MessageMsg;
 use crate::core::missions::{update_missions, SendMissionMsg};
 use crate::core::network::*;
-use crate::core::persistence::{load_game, save_game, LoadGameMsg, SaveGameMsg};
+use crate::core::persistence::{
+    exit_replay, export_timelapse, load_game, load_replay, load_scenario, load_timelapse,
+    save_game, save_scenario, update_replay, ExportTimelapseMsg, LoadGameMsg, LoadReplayMsg,
+    LoadScenarioMsg, LoadTimelapseMsg, Replay, SaveGameMsg, SaveScenarioMsg,
+};
+use crate::core::player::{Roster, Standings};
 use crate::core::settings::Settings;
 use crate::core::states::{AppState, AudioState, CombatState, GameState};
-use crate::core::systems::{check_keys, check_keys_combat, check_keys_menu, on_resize_system};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::core::systems::limit_frame_rate;
+use crate::core::systems::{
+    check_keys, check_keys_combat, check_keys_menu, on_resize_system, update_present_mode,
+};
 use crate::core::turns::{check_turn_ended, resolve_turn, start_turn, StartTurnMsg};
-use crate::core::ui::systems::{add_ui_images, draw_ui, set_ui_style};
+use crate::core::tutorial::{draw_tutorial, exit_tutorial, highlight_tutorial, update_tutorial};
+use crate::core::ui::locale::load_locale;
+use crate::core::ui::systems::{
+    add_ui_images, draw_encyclopedia_panel, draw_home_button, draw_notification_history,
+    draw_pause_indicator, draw_player_list, draw_ui, set_ui_style, update_ui_style,
+};
 use crate::core::ui::utils::ImageIds;
+use crate::core::units::modding::load_unit_overrides;
 use crate::core::utils::despawn;
 
 pub struct GamePlugin;
@@ -75,17 +99,28 @@ impl Plugin for GamePlugin {
             .add_message::<ChangeAudioMsg>()
             .add_message::<SaveGameMsg>()
             .add_message::<LoadGameMsg>()
+            .add_message::<LoadReplayMsg>()
+            .add_message::<LoadTimelapseMsg>()
+            .add_message::<ExportTimelapseMsg>()
+            .add_message::<LoadScenarioMsg>()
+            .add_message::<SaveScenarioMsg>()
             .add_message::<ServerSendMsg>()
             .add_message::<ClientSendMsg>()
             .add_message::<MessageMsg>()
             .add_message::<StartTurnMsg>()
             .add_message::<SendMissionMsg>()
             .add_message::<SpawnShotMsg>()
+            .add_message::<DiplomacyMsg>()
             // Resources
             .init_resource::<Ip>()
             .init_resource::<Settings>()
             .init_resource::<ImageIds>()
             .init_resource::<PlayingAudio>()
+            .init_resource::<Diplomacy>()
+            .init_resource::<Standings>()
+            .init_resource::<Roster>()
+            .init_resource::<CulledEntities>()
+            .init_resource::<PlayerList>()
             // Sets
             .configure_sets(First, InGameSet.run_if(in_state(AppState::Game)))
             .configure_sets(PreUpdate, InGameSet.run_if(in_state(AppState::Game)))
@@ -113,6 +148,8 @@ impl Plugin for GamePlugin {
                 Last,
                 InPlayingGameSet.run_if(in_state(GameState::Playing)).in_set(InGameSet),
             )
+            // Mods
+            .add_systems(Startup, (load_unit_overrides, load_locale))
             // Camera
             .add_systems(Startup, setup_camera)
             .add_systems(Update, (move_camera, move_camera_keyboard).in_set(InPlayingGameSet))
@@ -132,6 +169,12 @@ impl Plugin for GamePlugin {
                 ),
             )
             .add_systems(Update, server_update.run_if(resource_exists::<RenetServer>))
+            .add_systems(
+                Update,
+                (update_player_list, join_observers.after(server_update))
+                    .run_if(resource_exists::<RenetServer>.and(resource_exists::<Host>))
+                    .in_set(InGameSet),
+            )
             .add_systems(
                 Last,
                 (
@@ -150,11 +193,34 @@ impl Plugin for GamePlugin {
         app
             // Ui
             .add_systems(OnExit(AppState::MainMenu), (add_ui_images, set_ui_style))
-            .add_systems(EguiPrimaryContextPass, draw_ui.in_set(InGameSet))
+            .add_systems(
+                EguiPrimaryContextPass,
+                (
+                    update_ui_style,
+                    draw_ui,
+                    draw_notification_history,
+                    draw_player_list,
+                    draw_encyclopedia_panel,
+                    draw_tutorial.run_if(in_state(GameState::Playing)),
+                    draw_pause_indicator.run_if(in_state(GameState::Playing)),
+                    draw_home_button.run_if(in_state(GameState::Playing)),
+                )
+                    .chain()
+                    .in_set(InGameSet),
+            )
             // Persistence
             .add_systems(
                 Update,
-                (load_game, save_game.run_if(resource_exists::<Host>).in_set(InGameSet)),
+                (
+                    load_game,
+                    load_replay,
+                    load_timelapse,
+                    load_scenario,
+                    save_scenario,
+                    save_game.run_if(resource_exists::<Host>).in_set(InGameSet),
+                    export_timelapse.run_if(resource_exists::<Host>).in_set(InGameSet),
+                    update_replay.run_if(resource_exists::<Replay>).in_set(InGameSet),
+                ),
             )
             // Utilities
             .add_systems(
@@ -167,7 +233,10 @@ impl Plugin for GamePlugin {
                         .in_set(InGameSet),
                 ),
             )
-            .add_systems(PostUpdate, on_resize_system)
+            .add_systems(
+                PostUpdate,
+                (on_resize_system, update_present_mode.run_if(resource_changed::<Settings>)),
+            )
             // In-game states
             .add_systems(OnEnter(AppState::Game), draw_map)
             .add_systems(First, start_turn.run_if(resource_exists::<Map>).in_set(InPlayingGameSet))
@@ -175,7 +244,25 @@ impl Plugin for GamePlugin {
                 Update,
                 (
                     (update_end_turn, run_map_animations).in_set(InGameSet),
-                    (update_voronoi, update_planet_info, send_mission, update_missions)
+                    (
+                        update_voronoi,
+                        update_planet_info,
+                        update_planet_names,
+                        update_specialization_icons,
+                        update_note_tags,
+                        update_auto_economy,
+                        sync_resource_ratio.run_if(resource_changed::<Settings>),
+                        draw_rally_links,
+                        draw_mission_paths,
+                        highlight_imminent_attacks,
+                        draw_combat_odds,
+                        send_mission,
+                        send_diplomacy,
+                        update_missions,
+                        cull_offscreen.after(update_voronoi).after(update_missions),
+                        update_tutorial,
+                        highlight_tutorial,
+                    )
                         .in_set(InPlayingGameSet),
                 ),
             )
@@ -184,17 +271,32 @@ impl Plugin for GamePlugin {
                 check_turn_ended.run_if(resource_exists::<RenetClient>).in_set(InGameSet),
             )
             .add_systems(Last, resolve_turn.run_if(resource_exists::<Host>).in_set(InGameSet))
-            .add_systems(OnExit(AppState::Game), (despawn::<MapCmp>, reset_camera))
+            .add_systems(
+                OnExit(AppState::Game),
+                (
+                    despawn::<MapCmp>,
+                    despawn::<CombatCmp>,
+                    despawn::<CombatMenuCmp>,
+                    reset_camera,
+                    exit_replay,
+                    clear_player_list,
+                    exit_tutorial,
+                ),
+            )
             .add_systems(OnEnter(GameState::CombatMenu), setup_combat_menu)
             .add_systems(
                 OnExit(GameState::CombatMenu),
                 (despawn::<CombatMenuCmp>, exit_combat_menu),
             )
-            .add_systems(OnEnter(GameState::Combat), setup_combat)
+            .add_systems(OnEnter(GameState::Combat), setup_combat.run_if(sprite_combat))
             .add_systems(
                 Update,
-                (animate_combat, run_combat_animations, update_combat_stats)
-                    .chain()
+                (
+                    (animate_combat, run_combat_animations, update_combat_stats)
+                        .chain()
+                        .run_if(sprite_combat),
+                    advance_lite_combat.run_if(not(sprite_combat)),
+                )
                     .run_if(in_state(GameState::Combat)),
             )
             .add_systems(OnExit(GameState::Combat), (despawn::<CombatCmp>, exit_combat))
@@ -204,5 +306,9 @@ impl Plugin for GamePlugin {
             .add_systems(OnExit(GameState::Settings), despawn::<MenuCmp>)
             .add_systems(OnEnter(GameState::EndGame), setup_end_game)
             .add_systems(OnExit(GameState::EndGame), (despawn::<MenuCmp>, exit_end_game));
+
+        // The browser controls the frame rate on the web
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_systems(Last, limit_frame_rate);
     }
 }
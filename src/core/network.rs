@@ -7,15 +7,19 @@ use bevy_renet::netcode::*;
 use bevy_renet::renet::*;
 use bincode::config::standard;
 use bincode::serde::{decode_from_slice, encode_to_vec};
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
+use crate::core::constants::PLAYER_LIST_INTERVAL;
+use crate::core::diplomacy::{Diplomacy, DiplomacyAction};
 use crate::core::map::map::Map;
 use crate::core::map::planet::PlanetId;
 use crate::core::menu::buttons::LobbyTextCmp;
 use crate::core::messages::MessageMsg;
 use crate::core::missions::{Mission, Missions};
-use crate::core::player::Player;
-use crate::core::settings::Settings;
+use crate::core::persistence::{ReplayTurn, Scenario};
+use crate::core::player::{Player, Roster, Standings};
+use crate::core::settings::{Difficulty, Settings, StartPackage, Victory};
 use crate::core::states::{AppState, GameState};
 use crate::core::turns::{PreviousEndTurnState, StartTurnMsg};
 use crate::core::ui::systems::UiState;
@@ -45,8 +49,40 @@ pub struct Host {
 
     /// Keeps track of which clients send an update
     pub received: HashSet<ClientId>,
+
+    /// Snapshots of the game at the start of every turn, used for replays
+    pub history: Vec<ReplayTurn>,
+
+    /// Clients that joined the running game to watch it. They never play
+    pub observers: HashSet<ClientId>,
+}
+
+impl Host {
+    /// Whether every playing client ended its turn, so the host can resolve it once
+    /// it ended its own. Spectators and disconnected clients aren't waited for
+    pub fn all_turns_ended(&self, is_connected: impl Fn(ClientId) -> bool) -> bool {
+        self.clients
+            .values()
+            .filter(|c| !c.spectator && is_connected(c.id))
+            .all(|c| self.turn_ended.contains(&c.id))
+    }
+}
+
+/// Connection and turn status of a player, shown in the player list
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PlayerStatus {
+    pub id: ClientId,
+    pub connected: bool,
+    pub end_turn: bool,
+    /// Round-trip time to the host in milliseconds (none for the host itself)
+    pub latency: Option<f64>,
+    pub planets: usize,
 }
 
+/// Status of all players, periodically broadcast by the host. It's never saved
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+pub struct PlayerList(pub Vec<PlayerStatus>);
+
 #[derive(Message)]
 pub struct ServerSendMsg {
     pub message: ServerMessage,
@@ -80,6 +116,9 @@ pub enum ServerMessage {
     LoadGame {
         turn: usize,
         p_colonizable: usize,
+        difficulty: Difficulty,
+        upkeep: bool,
+        victory: Victory,
         map: Map,
         player: Player,
         missions: Missions,
@@ -88,6 +127,10 @@ pub enum ServerMessage {
     StartGame {
         id: ClientId,
         home_planet: PlanetId,
+        difficulty: Difficulty,
+        start: StartPackage,
+        upkeep: bool,
+        victory: Victory,
         map: Map,
     },
     StartTurn {
@@ -97,16 +140,23 @@ pub enum ServerMessage {
         missions: Missions,
     },
     RequestUpdate,
+    Diplomacy(Diplomacy),
+    Standings(Standings),
+    Scenario(Scenario),
+    Roster(Roster),
+    PlayerList(PlayerList),
 }
 
 #[derive(Serialize, Deserialize)]
 pub enum ClientMessage {
     EndTurn {
+        turn: usize,
         end_turn: bool,
         map: Map,
         player: Player,
         missions: Missions,
     },
+    Diplomacy(DiplomacyAction),
 }
 
 pub fn new_renet_client(ip: &String) -> (RenetClient, NetcodeClientTransport) {
@@ -151,6 +201,7 @@ pub fn server_update(
     mut n_players_q: Query<&mut Text, With<LobbyTextCmp>>,
     mut server: ResMut<RenetServer>,
     mut server_msg: MessageReader<ServerEvent>,
+    host: Option<Res<Host>>,
     app_state: Res<State<AppState>>,
     mut next_app_state: ResMut<NextState<AppState>>,
     mut next_game_state: ResMut<NextState<GameState>>,
@@ -171,7 +222,10 @@ pub fn server_update(
                     "Client {client_id} disconnected. Reason: {reason}."
                 )));
 
-                if *app_state == AppState::Game {
+                // Spectators can leave without interrupting the game
+                if *app_state == AppState::Game
+                    && !host.as_ref().is_some_and(|h| h.observers.contains(client_id))
+                {
                     next_game_state.set(GameState::GameMenu);
                 }
             },
@@ -197,6 +251,121 @@ pub fn server_update(
     }
 }
 
+/// Let clients that connect to a running game watch it as spectators. They receive
+/// a full copy of the game but take no slot in it, so they never block a turn
+pub fn join_observers(
+    mut server_msg: MessageReader<ServerEvent>,
+    mut host: ResMut<Host>,
+    settings: Res<Settings>,
+    map: Res<Map>,
+    player: Res<Player>,
+    missions: Res<Missions>,
+    diplomacy: Res<Diplomacy>,
+    roster: Res<Roster>,
+    standings: Res<Standings>,
+    mut server_send_msg: MessageWriter<ServerSendMsg>,
+    mut message: MessageWriter<MessageMsg>,
+) {
+    for ev in server_msg.read() {
+        match ev {
+            ServerEvent::ClientConnected {
+                client_id,
+            } => {
+                // Skip connections of the players from before the game started
+                if roster.0.contains(client_id) {
+                    continue;
+                }
+
+                host.observers.insert(*client_id);
+
+                // Spectators see all missions, the host's and those of every client
+                let all_missions = missions
+                    .iter()
+                    .filter(|m| m.owner == player.id)
+                    .chain(host.missions.iter())
+                    .cloned()
+                    .collect();
+
+                server_send_msg.write(ServerSendMsg::new(
+                    ServerMessage::LoadGame {
+                        turn: settings.turn,
+                        p_colonizable: settings.p_colonizable,
+                        difficulty: settings.difficulty,
+                        upkeep: settings.upkeep,
+                        victory: settings.victory,
+                        map: map.clone(),
+                        player: Player::observer(*client_id, player.home_planet),
+                        missions: Missions(all_missions),
+                    },
+                    Some(*client_id),
+                ));
+                server_send_msg.write(ServerSendMsg::new(
+                    ServerMessage::Roster(roster.clone()),
+                    Some(*client_id),
+                ));
+                server_send_msg.write(ServerSendMsg::new(
+                    ServerMessage::Diplomacy(diplomacy.clone()),
+                    Some(*client_id),
+                ));
+                server_send_msg.write(ServerSendMsg::new(
+                    ServerMessage::Standings(standings.clone()),
+                    Some(*client_id),
+                ));
+
+                message.write(MessageMsg::info(format!("Client {client_id} joined as spectator.")));
+            },
+            ServerEvent::ClientDisconnected {
+                client_id,
+                ..
+            } => {
+                host.observers.remove(client_id);
+            },
+        }
+    }
+}
+
+pub fn update_player_list(
+    server: Res<RenetServer>,
+    host: Res<Host>,
+    map: Res<Map>,
+    player: Res<Player>,
+    state: Res<UiState>,
+    mut player_list: ResMut<PlayerList>,
+    mut server_send_msg: MessageWriter<ServerSendMsg>,
+    mut elapsed: Local<f32>,
+    time: Res<Time>,
+) {
+    *elapsed += time.delta_secs();
+    if *elapsed < PLAYER_LIST_INTERVAL {
+        return;
+    }
+    *elapsed = 0.;
+
+    let planets = |id: ClientId| map.planets.iter().filter(|p| p.owned == Some(id)).count();
+
+    player_list.0 = std::iter::once(PlayerStatus {
+        id: player.id,
+        connected: true,
+        end_turn: state.end_turn,
+        latency: None,
+        planets: planets(player.id),
+    })
+    .chain(host.clients.keys().sorted().map(|&id| PlayerStatus {
+        id,
+        connected: server.is_connected(id),
+        end_turn: host.turn_ended.contains(&id),
+        latency: server.network_info(id).ok().map(|info| info.rtt * 1000.),
+        planets: planets(id),
+    }))
+    .collect();
+
+    server_send_msg.write(ServerSendMsg::new(ServerMessage::PlayerList(player_list.clone()), None));
+}
+
+pub fn clear_player_list(mut player_list: ResMut<PlayerList>) {
+    player_list.0.clear();
+}
+
 pub fn server_send_message(
     mut server_send_msg: MessageReader<ServerSendMsg>,
     mut server: ResMut<RenetServer>,
@@ -215,17 +384,31 @@ pub fn server_receive_message(
     mut server: ResMut<RenetServer>,
     mut map: Option<ResMut<Map>>,
     mut host: Option<ResMut<Host>>,
+    mut diplomacy: ResMut<Diplomacy>,
+    player: Option<Res<Player>>,
+    settings: Res<Settings>,
+    mut server_send_msg: MessageWriter<ServerSendMsg>,
+    mut message: MessageWriter<MessageMsg>,
 ) {
     for id in server.clients_id() {
         while let Some(message) = server.receive_message(id, DefaultChannel::ReliableOrdered) {
             let (d, _) = decode_from_slice(&message, standard()).unwrap();
             match d {
                 ClientMessage::EndTurn {
+                    turn,
                     end_turn,
                     map: new_map,
                     player: new_player,
                     missions: new_missions,
                 } => {
+                    // Updates sent before the turn was resolved are outdated, and
+                    // spectators that joined the running game don't play
+                    if turn != settings.turn
+                        || host.as_ref().is_some_and(|h| h.observers.contains(&id))
+                    {
+                        continue;
+                    }
+
                     if let Some(host) = &mut host {
                         let map = map.as_mut().unwrap();
 
@@ -259,6 +442,21 @@ pub fn server_receive_message(
                         host.received.insert(id);
                     }
                 },
+                ClientMessage::Diplomacy(_)
+                    if host.as_ref().is_some_and(|h| h.observers.contains(&id)) => {},
+                ClientMessage::Diplomacy(action) => {
+                    let text = diplomacy.apply(id, action, settings.turn);
+
+                    // Only notify the host of actions directed at them
+                    if player.as_ref().is_some_and(|p| p.id == action.target()) {
+                        message.write(MessageMsg::diplomacy(text));
+                    }
+
+                    server_send_msg.write(ServerSendMsg::new(
+                        ServerMessage::Diplomacy(diplomacy.clone()),
+                        None,
+                    ));
+                },
             }
         }
     }
@@ -286,9 +484,14 @@ pub fn client_receive_message(
     mut map: Option<ResMut<Map>>,
     mut player: Option<ResMut<Player>>,
     mut missions: Option<ResMut<Missions>>,
+    mut diplomacy: ResMut<Diplomacy>,
+    mut standings: ResMut<Standings>,
+    mut roster: ResMut<Roster>,
+    mut player_list: ResMut<PlayerList>,
+    mut message: MessageWriter<MessageMsg>,
 ) {
-    while let Some(message) = client.receive_message(DefaultChannel::ReliableOrdered) {
-        let (d, _) = decode_from_slice(&message, standard()).unwrap();
+    while let Some(bytes) = client.receive_message(DefaultChannel::ReliableOrdered) {
+        let (d, _) = decode_from_slice(&bytes, standard()).unwrap();
         match d {
             ServerMessage::NPlayers(i) => {
                 if let Ok(mut text) = n_players_q.single_mut() {
@@ -298,27 +501,46 @@ pub fn client_receive_message(
             ServerMessage::StartGame {
                 id,
                 home_planet,
+                difficulty,
+                start,
+                upkeep,
+                victory,
                 map,
             } => {
                 *settings = settings.clone();
+                settings.turn = 1;
+                settings.difficulty = difficulty;
+                settings.start = start;
+                settings.upkeep = upkeep;
+                settings.victory = victory;
 
                 commands.insert_resource(UiState::default());
                 commands.insert_resource(PreviousEndTurnState::default());
-                commands.insert_resource(Player::new(id, home_planet));
+                commands.insert_resource(Player::new(id, home_planet, difficulty));
                 commands.insert_resource(map);
                 commands.insert_resource(Missions::default());
+                commands.insert_resource(Standings::default());
+
+                // Reset in place, so the teams sent right after this message are kept
+                *diplomacy = Diplomacy::default();
 
                 next_app_state.set(AppState::Game);
             },
             ServerMessage::LoadGame {
                 turn,
                 p_colonizable,
+                difficulty,
+                upkeep,
+                victory,
                 map,
                 player,
                 missions,
             } => {
                 settings.turn = turn;
                 settings.p_colonizable = p_colonizable;
+                settings.difficulty = difficulty;
+                settings.upkeep = upkeep;
+                settings.victory = victory;
 
                 commands.insert_resource(UiState::default());
                 commands.insert_resource(PreviousEndTurnState::default());
@@ -344,12 +566,84 @@ pub fn client_receive_message(
             },
             ServerMessage::RequestUpdate => {
                 client_send_msg.write(ClientSendMsg::new(ClientMessage::EndTurn {
+                    turn: settings.turn,
                     end_turn: state.as_ref().unwrap().end_turn,
                     map: (*map.as_ref().unwrap()).clone(),
                     player: (*player.as_ref().unwrap()).clone(),
                     missions: (*missions.as_ref().unwrap()).clone(),
                 }));
             },
+            ServerMessage::Diplomacy(new_diplomacy) => {
+                *diplomacy = new_diplomacy;
+            },
+            ServerMessage::Standings(new_standings) => {
+                *standings = new_standings;
+            },
+            ServerMessage::Scenario(scenario) => {
+                scenario.apply(&mut settings);
+                message.write(MessageMsg::info("The host loaded a scenario."));
+            },
+            ServerMessage::Roster(new_roster) => {
+                *roster = new_roster;
+            },
+            ServerMessage::PlayerList(new_player_list) => {
+                *player_list = new_player_list;
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host(clients: &[(ClientId, bool)], turn_ended: &[ClientId]) -> Host {
+        Host {
+            clients: clients
+                .iter()
+                .map(|(id, spectator)| {
+                    (
+                        *id,
+                        Player {
+                            id: *id,
+                            spectator: *spectator,
+                            ..Default::default()
+                        },
+                    )
+                })
+                .collect(),
+            turn_ended: turn_ended.iter().copied().collect(),
+            ..default()
         }
     }
+
+    #[test]
+    fn waits_for_every_connected_player() {
+        let host = host(&[(1, false), (2, false), (3, false)], &[1, 2]);
+        assert!(!host.all_turns_ended(|_| true));
+
+        // A disconnected player isn't waited for
+        assert!(host.all_turns_ended(|id| id != 3));
+
+        // The connected players that didn't end their turn are still waited for
+        assert!(!host.all_turns_ended(|id| id != 1));
+    }
+
+    #[test]
+    fn resolves_when_all_players_ended() {
+        let host = host(&[(1, false), (2, false)], &[1, 2]);
+        assert!(host.all_turns_ended(|_| true));
+        assert!(host.all_turns_ended(|_| false));
+    }
+
+    #[test]
+    fn spectators_are_not_waited_for() {
+        let host = self::host(&[(1, false), (2, true)], &[1]);
+        assert!(host.all_turns_ended(|_| true));
+
+        // With only spectators, the host resolves the turn on its own
+        let host = self::host(&[(1, true), (2, true)], &[]);
+        assert!(host.all_turns_ended(|_| true));
+        assert!(self::host(&[], &[]).all_turns_ended(|_| true));
+    }
 }
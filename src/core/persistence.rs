@@ -10,17 +10,35 @@ use bincode::serde::{decode_from_slice, encode_to_vec};
 use rfd::FileDialog;
 use serde::{Deserialize, Serialize};
 
+use crate::core::combat::report::{MissionReport, ReportId};
+use crate::core::constants::{PLANET_NAMES, REPLAY_TURN_DURATION};
+use crate::core::diplomacy::Diplomacy;
 use crate::core::map::map::Map;
 use crate::core::messages::MessageMsg;
 use crate::core::missions::{Mission, Missions};
 use crate::core::network::{Host, ServerMessage, ServerSendMsg};
-use crate::core::player::Player;
-use crate::core::settings::Settings;
-use crate::core::states::{AppState, AudioState};
+use crate::core::player::{Player, Roster, Standings};
+use crate::core::settings::{
+    Difficulty, NameTheme, Sandbox, Settings, StartPackage, Teams, Victory,
+};
+use crate::core::states::{AppState, AudioState, GameState};
 use crate::core::turns::{filter_missions, PreviousEndTurnState};
 use crate::core::ui::systems::UiState;
 use crate::TITLE;
 
+/// Combat (and round) being watched, which is cleared when the combat is exited
+fn saved_combat(ui: &UiState) -> Option<(ReportId, usize)> {
+    ui.in_combat.map(|id| (id, ui.combat_round))
+}
+
+/// Combat to resume after loading a game, only if its report still exists
+fn resume_combat(
+    combat: Option<(ReportId, usize)>,
+    mut reports: impl Iterator<Item = ReportId>,
+) -> Option<(ReportId, usize)> {
+    combat.filter(|(id, _)| reports.any(|r| r == *id))
+}
+
 #[derive(Default)]
 pub enum SaveState {
     #[default]
@@ -29,6 +47,39 @@ pub enum SaveState {
     WaitingForClients,
 }
 
+/// State of the game at the start of a turn
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ReplayTurn {
+    pub turn: usize,
+    pub map: Map,
+    /// The host without its reports, which only grow. They are stored once (in the
+    /// final state of the game) and restored with [`ReplayTurn::host`]
+    pub host: Player,
+    pub missions: Vec<Mission>,
+}
+
+impl ReplayTurn {
+    pub fn new(turn: usize, map: &Map, host: &Player, missions: &[Mission]) -> Self {
+        Self {
+            turn,
+            map: map.clone(),
+            host: Player {
+                reports: vec![],
+                ..host.clone()
+            },
+            missions: missions.to_vec(),
+        }
+    }
+
+    /// The host at this turn, with the `reports` it had received by then
+    pub fn host(&self, reports: &[MissionReport]) -> Player {
+        Player {
+            reports: reports.iter().filter(|r| r.turn <= self.turn).cloned().collect(),
+            ..self.host.clone()
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct SaveAll {
     pub settings: Settings,
@@ -36,14 +87,235 @@ pub struct SaveAll {
     pub host: Player,
     pub clients: Vec<Player>,
     pub missions: Vec<Mission>,
+    pub diplomacy: Diplomacy,
+    pub history: Vec<ReplayTurn>,
+    /// Combat being watched when the game was saved and its round
+    pub combat: Option<(ReportId, usize)>,
+}
+
+/// Ownership of a planet at the start of a turn
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PlanetState {
+    pub owned: Option<ClientId>,
+    pub controlled: Option<ClientId>,
+    pub is_destroyed: bool,
+}
+
+/// Lightweight recording of how the galaxy's ownership changed over a game,
+/// without armies nor missions so it's small enough to share
+#[derive(Serialize, Deserialize)]
+pub struct Timelapse {
+    /// Layout of the galaxy, stripped of all units
+    pub map: Map,
+    pub roster: Roster,
+    /// Turn number and state of every planet (in the map's order) per turn
+    pub turns: Vec<(usize, Vec<PlanetState>)>,
+}
+
+impl Timelapse {
+    pub fn new(history: &[ReplayTurn], roster: &Roster) -> Option<Self> {
+        let mut map = history.first()?.map.clone();
+        map.planets.iter_mut().for_each(|p| {
+            p.army.clear();
+            p.buy.clear();
+            p.custom_name = None;
+        });
+
+        let turns = history
+            .iter()
+            .map(|t| {
+                let planets = t
+                    .map
+                    .planets
+                    .iter()
+                    .map(|p| PlanetState {
+                        owned: p.owned,
+                        controlled: p.controlled,
+                        is_destroyed: p.is_destroyed,
+                    })
+                    .collect();
+
+                (t.turn, planets)
+            })
+            .collect();
+
+        Some(Self {
+            map,
+            roster: roster.clone(),
+            turns,
+        })
+    }
+
+    /// Rebuild the full turns to show them in the replay viewer
+    pub fn into_turns(self) -> Vec<ReplayTurn> {
+        // A viewer that doesn't play, so every player gets its roster color
+        let viewer = Player {
+            id: ClientId::MAX,
+            spectator: true,
+            ..default()
+        };
+
+        self.turns
+            .into_iter()
+            .map(|(turn, states)| {
+                let mut map = self.map.clone();
+                for (planet, state) in map.planets.iter_mut().zip(states) {
+                    planet.owned = state.owned;
+                    planet.controlled = state.controlled;
+                    planet.is_destroyed = state.is_destroyed;
+                }
+
+                ReplayTurn {
+                    turn,
+                    map,
+                    host: viewer.clone(),
+                    missions: vec![],
+                }
+            })
+            .collect()
+    }
+}
+
+/// Game configuration that can be shared between players to start new games
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    pub difficulty: Difficulty,
+    pub start: StartPackage,
+    pub n_planets: usize,
+    pub p_colonizable: usize,
+    pub p_moons: usize,
+    pub wormholes: usize,
+    pub veterancy: bool,
+    pub upkeep: bool,
+    pub victory: Victory,
+    pub sandbox: Sandbox,
+    pub name_theme: NameTheme,
+    pub teams: Teams,
+}
+
+impl Scenario {
+    pub fn new(settings: &Settings) -> Self {
+        Self {
+            difficulty: settings.difficulty,
+            start: settings.start,
+            n_planets: settings.n_planets,
+            p_colonizable: settings.p_colonizable,
+            p_moons: settings.p_moons,
+            wormholes: settings.wormholes,
+            veterancy: settings.veterancy,
+            upkeep: settings.upkeep,
+            victory: settings.victory,
+            sandbox: settings.sandbox,
+            name_theme: settings.name_theme,
+            teams: settings.teams,
+        }
+    }
+
+    /// Check whether a game with `n_players` players can be started from this scenario
+    pub fn validate(&self, n_players: usize) -> Result<(), String> {
+        if self.n_planets == 0 {
+            return Err("The scenario has no planets per player.".to_string());
+        }
+
+        if !(1..=100).contains(&self.p_colonizable) {
+            return Err(format!(
+                "The percentage of colonizable planets must be between 1 and 100, got {}.",
+                self.p_colonizable
+            ));
+        }
+
+        if self.p_moons > 100 {
+            return Err(format!(
+                "The percentage of moons must be between 0 and 100, got {}.",
+                self.p_moons
+            ));
+        }
+
+        if self.wormholes * 2 > self.n_planets {
+            return Err(format!(
+                "{} wormholes per player don't fit in {} planets per player.",
+                self.wormholes, self.n_planets
+            ));
+        }
+
+        let n_planets = self.n_planets * n_players;
+        let n_total = n_planets + n_planets * self.p_moons / 100;
+        if n_total > PLANET_NAMES.len() {
+            return Err(format!(
+                "The scenario needs {n_total} planets and moons for {n_players} players, but \
+                the maximum is {}.",
+                PLANET_NAMES.len()
+            ));
+        }
+
+        if n_players > 1 && self.sandbox != Sandbox::Off {
+            return Err("The sandbox mode is only available in single player games.".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Overwrite the game configuration in the settings, keeping the user's preferences
+    pub fn apply(&self, settings: &mut Settings) {
+        settings.difficulty = self.difficulty;
+        settings.start = self.start;
+        settings.n_planets = self.n_planets;
+        settings.p_colonizable = self.p_colonizable;
+        settings.p_moons = self.p_moons;
+        settings.wormholes = self.wormholes;
+        settings.veterancy = self.veterancy;
+        settings.upkeep = self.upkeep;
+        settings.victory = self.victory;
+        settings.sandbox = self.sandbox;
+        settings.name_theme = self.name_theme;
+        settings.teams = self.teams;
+    }
+}
+
+/// Read-only viewer that steps through the recorded turns of a saved game
+#[derive(Resource)]
+pub struct Replay {
+    pub turns: Vec<ReplayTurn>,
+    /// Reports of the host at the end of the game
+    pub reports: Vec<MissionReport>,
+    pub index: usize,
+    pub playing: bool,
+    pub timer: Timer,
+}
+
+impl Replay {
+    pub fn new(turns: Vec<ReplayTurn>, reports: Vec<MissionReport>) -> Self {
+        Self {
+            turns,
+            reports,
+            index: 0,
+            playing: false,
+            timer: Timer::from_seconds(REPLAY_TURN_DURATION, TimerMode::Repeating),
+        }
+    }
 }
 
 #[derive(Message)]
 pub struct LoadGameMsg;
 
+#[derive(Message)]
+pub struct LoadReplayMsg;
+
 #[derive(Message)]
 pub struct SaveGameMsg(pub bool);
 
+#[derive(Message)]
+pub struct ExportTimelapseMsg;
+
+#[derive(Message)]
+pub struct LoadTimelapseMsg;
+
+#[derive(Message)]
+pub struct LoadScenarioMsg;
+
+#[derive(Message)]
+pub struct SaveScenarioMsg;
+
 fn save_to_bin(file_path: &str, data: &SaveAll) -> io::Result<()> {
     let mut file = File::create(file_path)?;
 
@@ -63,11 +335,32 @@ fn load_from_bin(file_path: &str) -> io::Result<SaveAll> {
     Ok(data)
 }
 
+fn load_timelapse_from_bin(file_path: &str) -> Result<Timelapse, String> {
+    let buffer =
+        std::fs::read(file_path).map_err(|e| format!("Failed to read the timelapse: {e}."))?;
+
+    match decode_from_slice::<Timelapse, _>(&buffer, standard()) {
+        Ok((timelapse, n)) if n == buffer.len() && !timelapse.turns.is_empty() => Ok(timelapse),
+        _ => Err("The selected file is not a valid timelapse.".to_string()),
+    }
+}
+
+fn load_scenario_from_bin(file_path: &str) -> Result<Scenario, String> {
+    let buffer =
+        std::fs::read(file_path).map_err(|e| format!("Failed to read the scenario: {e}."))?;
+
+    match decode_from_slice::<Scenario, _>(&buffer, standard()) {
+        Ok((scenario, n)) if n == buffer.len() => Ok(scenario),
+        _ => Err("The selected file is not a valid scenario.".to_string()),
+    }
+}
+
 pub fn load_game(
     mut commands: Commands,
     mut load_game_msg: MessageReader<LoadGameMsg>,
     server: Option<Res<RenetServer>>,
     mut next_app_state: ResMut<NextState<AppState>>,
+    mut next_game_state: ResMut<NextState<GameState>>,
     mut next_audio_state: ResMut<NextState<AudioState>>,
     mut message: MessageWriter<MessageMsg>,
     mut server_send_msg: MessageWriter<ServerSendMsg>,
@@ -126,11 +419,24 @@ pub fn load_game(
                                 upd_id(&mut m.owner);
                                 upd(&mut m.origin_owned);
                             }
+                            if let Some(team) = data.diplomacy.teams.remove(&old_id) {
+                                data.diplomacy.teams.insert(*new_id, team);
+                            }
+                            for t in data.diplomacy.treaties.iter_mut() {
+                                upd_id(&mut t.players.0);
+                                upd_id(&mut t.players.1);
+                                if let Some((proposer, _)) = &mut t.proposal {
+                                    upd_id(proposer);
+                                }
+                            }
 
                             server_send_msg.write(ServerSendMsg::new(
                                 ServerMessage::LoadGame {
                                     turn: data.settings.turn,
                                     p_colonizable: data.settings.p_colonizable,
+                                    difficulty: data.settings.difficulty,
+                                    upkeep: data.settings.upkeep,
+                                    victory: data.settings.victory,
                                     map: data.map.clone(),
                                     player: player.clone(),
                                     missions: if !player.spectator {
@@ -156,7 +462,29 @@ pub fn load_game(
             if start_game {
                 next_audio_state.set(data.settings.audio);
 
-                commands.insert_resource(UiState::default());
+                let roster = Roster(
+                    std::iter::once(data.host.id)
+                        .chain(data.clients.iter().map(|p| p.id))
+                        .collect(),
+                );
+
+                // Resume watching the combat the game was saved in (if its report still
+                // exists), else return to the map so no combat state is left behind
+                match resume_combat(data.combat, data.host.reports.iter().map(|r| r.id)) {
+                    Some((id, round)) => {
+                        commands.insert_resource(UiState {
+                            in_combat: Some(id),
+                            combat_round: round,
+                            ..default()
+                        });
+                        next_game_state.set(GameState::Combat);
+                    },
+                    None => {
+                        commands.insert_resource(UiState::default());
+                        next_game_state.set(GameState::Playing);
+                    },
+                }
+
                 commands.insert_resource(PreviousEndTurnState::default());
                 commands.insert_resource(data.settings);
                 commands.insert_resource(if !data.host.spectator {
@@ -166,7 +494,21 @@ pub fn load_game(
                 });
                 commands.insert_resource(data.map);
                 commands.insert_resource(data.host);
-                commands.insert_resource(Host::default());
+                commands.insert_resource(Host {
+                    history: data.history,
+                    ..default()
+                });
+
+                // Send the relations and colors once all player ids have been updated
+                server_send_msg.write(ServerSendMsg::new(
+                    ServerMessage::Diplomacy(data.diplomacy.clone()),
+                    None,
+                ));
+                server_send_msg
+                    .write(ServerSendMsg::new(ServerMessage::Roster(roster.clone()), None));
+                commands.insert_resource(roster);
+                commands.insert_resource(data.diplomacy);
+                commands.insert_resource(Standings::default());
 
                 next_app_state.set(AppState::Game);
 
@@ -176,6 +518,220 @@ pub fn load_game(
     }
 }
 
+pub fn load_replay(
+    mut commands: Commands,
+    mut load_replay_msg: MessageReader<LoadReplayMsg>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut message: MessageWriter<MessageMsg>,
+) {
+    for _ in load_replay_msg.read() {
+        if let Some(file_path) = FileDialog::new().pick_file() {
+            let file_path_str = file_path.to_string_lossy().to_string();
+            let data = load_from_bin(&file_path_str).expect("Failed to load the game.");
+
+            let Some(first) = data.history.first().cloned() else {
+                message.write(MessageMsg::error("The loaded game has no recorded turns."));
+                continue;
+            };
+
+            // No Host resource is inserted, so turns are never resolved nor saved
+            commands.insert_resource(UiState::default());
+            commands.insert_resource(PreviousEndTurnState::default());
+            commands.insert_resource(Settings {
+                turn: first.turn,
+                ..data.settings
+            });
+            commands.insert_resource(first.map);
+            commands.insert_resource(Player {
+                spectator: true,
+                ..first.host(&data.host.reports)
+            });
+            commands.insert_resource(Missions(first.missions));
+            commands.insert_resource(data.diplomacy);
+            commands.insert_resource(Roster(
+                std::iter::once(data.host.id).chain(data.clients.iter().map(|p| p.id)).collect(),
+            ));
+            commands.insert_resource(Replay::new(data.history, data.host.reports));
+
+            next_app_state.set(AppState::Game);
+
+            message.write(MessageMsg::info("Replay loaded."));
+        }
+    }
+}
+
+pub fn load_timelapse(
+    mut commands: Commands,
+    mut load_timelapse_msg: MessageReader<LoadTimelapseMsg>,
+    settings: Res<Settings>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut message: MessageWriter<MessageMsg>,
+) {
+    for _ in load_timelapse_msg.read() {
+        if let Some(file_path) = FileDialog::new().pick_file() {
+            let timelapse = match load_timelapse_from_bin(&file_path.to_string_lossy()) {
+                Ok(timelapse) => timelapse,
+                Err(err) => {
+                    message.write(MessageMsg::error(err));
+                    continue;
+                },
+            };
+
+            let roster = timelapse.roster.clone();
+            let turns = timelapse.into_turns();
+            let first = turns[0].clone();
+
+            // Reveal all cells, so the Voronoi diagram shows every player's territory
+            commands.insert_resource(UiState::default());
+            commands.insert_resource(PreviousEndTurnState::default());
+            commands.insert_resource(Settings {
+                turn: first.turn,
+                sandbox: Sandbox::Reveal,
+                show_cells: true,
+                show_threat: false,
+                show_intel: false,
+                ..settings.clone()
+            });
+            commands.insert_resource(first.map);
+            commands.insert_resource(first.host);
+            commands.insert_resource(Missions::default());
+            commands.insert_resource(Diplomacy::default());
+            commands.insert_resource(roster);
+            commands.insert_resource(Replay::new(turns, vec![]));
+
+            next_app_state.set(AppState::Game);
+
+            message.write(MessageMsg::info("Timelapse loaded."));
+        }
+    }
+}
+
+pub fn export_timelapse(
+    mut export_timelapse_msg: MessageReader<ExportTimelapseMsg>,
+    host: Res<Host>,
+    roster: Res<Roster>,
+    mut message: MessageWriter<MessageMsg>,
+) {
+    for _ in export_timelapse_msg.read() {
+        let Some(timelapse) = Timelapse::new(&host.history, &roster) else {
+            message.write(MessageMsg::warning("There are no finished turns to export yet."));
+            continue;
+        };
+
+        if let Some(mut file_path) = FileDialog::new().save_file() {
+            if !file_path.extension().map(|e| e == "bin").unwrap_or(false) {
+                file_path.set_extension("bin");
+            }
+
+            let buffer = encode_to_vec(timelapse, standard()).expect("Failed to serialize data.");
+
+            match File::create(&file_path).and_then(|mut file| file.write_all(&buffer)) {
+                Ok(_) => message.write(MessageMsg::info("Timelapse exported.")),
+                Err(e) => message
+                    .write(MessageMsg::error(format!("Failed to export the timelapse: {e}."))),
+            };
+        }
+    }
+}
+
+pub fn load_scenario(
+    mut load_scenario_msg: MessageReader<LoadScenarioMsg>,
+    server: Option<Res<RenetServer>>,
+    mut settings: ResMut<Settings>,
+    mut message: MessageWriter<MessageMsg>,
+    mut server_send_msg: MessageWriter<ServerSendMsg>,
+) {
+    for _ in load_scenario_msg.read() {
+        if let Some(file_path) = FileDialog::new().pick_file() {
+            let file_path_str = file_path.to_string_lossy().to_string();
+            let n_players = server.as_ref().map(|s| s.clients_id().len() + 1).unwrap_or(1);
+
+            match load_scenario_from_bin(&file_path_str)
+                .and_then(|scenario| scenario.validate(n_players).map(|_| scenario))
+            {
+                Ok(scenario) => {
+                    scenario.apply(&mut settings);
+
+                    if server.is_some() {
+                        server_send_msg
+                            .write(ServerSendMsg::new(ServerMessage::Scenario(scenario), None));
+                    }
+
+                    message.write(MessageMsg::info("Scenario loaded."));
+                },
+                Err(err) => {
+                    message.write(MessageMsg::error(err));
+                },
+            }
+        }
+    }
+}
+
+pub fn save_scenario(
+    mut save_scenario_msg: MessageReader<SaveScenarioMsg>,
+    settings: Res<Settings>,
+    mut message: MessageWriter<MessageMsg>,
+) {
+    for _ in save_scenario_msg.read() {
+        if let Some(mut file_path) = FileDialog::new().save_file() {
+            if !file_path.extension().map(|e| e == "bin").unwrap_or(false) {
+                file_path.set_extension("bin");
+            }
+
+            let buffer = encode_to_vec(Scenario::new(&settings), standard())
+                .expect("Failed to serialize data.");
+
+            match File::create(&file_path).and_then(|mut file| file.write_all(&buffer)) {
+                Ok(_) => message.write(MessageMsg::info("Scenario saved.")),
+                Err(e) => {
+                    message.write(MessageMsg::error(format!("Failed to save the scenario: {e}.")))
+                },
+            };
+        }
+    }
+}
+
+/// Show the recorded turn selected in the replay
+pub fn update_replay(
+    mut replay: ResMut<Replay>,
+    mut settings: ResMut<Settings>,
+    mut map: ResMut<Map>,
+    mut player: ResMut<Player>,
+    mut missions: ResMut<Missions>,
+    time: Res<Time>,
+    mut shown: Local<Option<usize>>,
+) {
+    if replay.playing && !settings.paused {
+        replay.timer.tick(time.delta());
+
+        if replay.timer.just_finished() {
+            if replay.index + 1 < replay.turns.len() {
+                replay.index += 1;
+            } else {
+                replay.playing = false;
+            }
+        }
+    }
+
+    if *shown != Some(replay.index) {
+        let snapshot = &replay.turns[replay.index];
+
+        settings.turn = snapshot.turn;
+        *map = snapshot.map.clone();
+        *player = Player {
+            spectator: true,
+            ..snapshot.host(&replay.reports)
+        };
+        missions.0 = snapshot.missions.clone();
+
+        *shown = Some(replay.index);
+    }
+}
+
+pub fn exit_replay(mut commands: Commands) {
+    commands.remove_resource::<Replay>();
+}
+
 pub fn save_game(
     server: Option<Res<RenetServer>>,
     mut save_game_msg: MessageReader<SaveGameMsg>,
@@ -184,6 +740,8 @@ pub fn save_game(
     map: Res<Map>,
     player: Res<Player>,
     missions: Res<Missions>,
+    diplomacy: Res<Diplomacy>,
+    ui: Res<UiState>,
     mut host: ResMut<Host>,
     mut message: MessageWriter<MessageMsg>,
     mut state: Local<SaveState>,
@@ -218,6 +776,9 @@ pub fn save_game(
                 host: player.clone(),
                 clients: host.clients.values().cloned().collect(),
                 missions: all_missions,
+                diplomacy: diplomacy.clone(),
+                history: host.history.clone(),
+                combat: saved_combat(&ui),
             };
 
             save_to_bin(&file_path_str, &data).expect("Failed to save the game.");
@@ -258,3 +819,104 @@ pub fn save_game(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::combat::combat::resolve_combat;
+    use crate::core::map::icon::Icon;
+    use crate::core::map::planet::Planet;
+    use crate::core::settings::ResourceBias;
+
+    #[test]
+    fn a_game_saved_during_combat_resumes_it_on_load() {
+        let ui = UiState {
+            in_combat: Some(7),
+            combat_round: 3,
+            ..default()
+        };
+
+        let combat = saved_combat(&ui);
+        assert_eq!(combat, Some((7, 3)));
+        assert_eq!(resume_combat(combat, [5, 7].into_iter()), Some((7, 3)));
+    }
+
+    #[test]
+    fn a_game_saved_outside_combat_loads_on_the_map() {
+        assert_eq!(saved_combat(&UiState::default()), None);
+        assert_eq!(resume_combat(None, [7].into_iter()), None);
+    }
+
+    #[test]
+    fn replay_turns_store_the_reports_once() {
+        let planet = Planet::new(0, "Home".into(), Vec2::ZERO, false, 1., ResourceBias::None);
+        let map = Map {
+            rect: Rect::default(),
+            planets: vec![planet.clone()],
+            wormholes: vec![],
+        };
+        let mission = Mission {
+            objective: Icon::Deploy,
+            ..default()
+        };
+
+        let mut player = Player::default();
+        player.reports = (1..=3).map(|turn| resolve_combat(turn, &mission, &planet, 15)).collect();
+
+        let snapshot = ReplayTurn::new(2, &map, &player, &[]);
+        assert!(snapshot.host.reports.is_empty());
+
+        let host = snapshot.host(&player.reports);
+        assert_eq!(host.reports.iter().map(|r| r.turn).collect::<Vec<_>>(), vec![1, 2]);
+    }
+
+    #[test]
+    fn exported_scenarios_restore_the_setup() {
+        let settings = Settings {
+            difficulty: Difficulty::Hard,
+            start: StartPackage::Advanced,
+            n_planets: 12,
+            wormholes: 2,
+            victory: Victory::Conquest,
+            ..default()
+        };
+
+        let buffer = encode_to_vec(Scenario::new(&settings), standard()).unwrap();
+        let (scenario, _) = decode_from_slice::<Scenario, _>(&buffer, standard()).unwrap();
+        assert_eq!(scenario.validate(2), Ok(()));
+
+        let mut loaded = Settings::default();
+        scenario.apply(&mut loaded);
+        assert_eq!(loaded.difficulty, Difficulty::Hard);
+        assert_eq!(loaded.start, StartPackage::Advanced);
+        assert_eq!(loaded.n_planets, 12);
+        assert_eq!(loaded.wormholes, 2);
+        assert_eq!(loaded.victory, Victory::Conquest);
+    }
+
+    #[test]
+    fn invalid_scenarios_are_rejected() {
+        let scenario = |change: fn(&mut Settings)| {
+            let mut settings = Settings::default();
+            change(&mut settings);
+            Scenario::new(&settings)
+        };
+
+        assert!(scenario(|s| s.n_planets = 0).validate(2).is_err());
+        assert!(scenario(|s| s.p_colonizable = 0).validate(2).is_err());
+        assert!(scenario(|s| s.wormholes = s.n_planets).validate(2).is_err());
+
+        // There must be enough names for all planets and moons
+        assert!(scenario(|s| s.n_planets = 100).validate(1).is_ok());
+        assert!(scenario(|s| s.n_planets = 100).validate(2).is_err());
+
+        // The sandbox is only for single player games
+        assert!(scenario(|s| s.sandbox = Sandbox::Reveal).validate(1).is_ok());
+        assert!(scenario(|s| s.sandbox = Sandbox::Reveal).validate(2).is_err());
+    }
+
+    #[test]
+    fn a_combat_without_report_is_not_resumed() {
+        assert_eq!(resume_combat(Some((7, 3)), [5].into_iter()), None);
+    }
+}
@@ -1,16 +1,23 @@
+use std::collections::{HashMap, VecDeque};
+
 use bevy::prelude::*;
 use bevy_renet::renet::ClientId;
 use serde::{Deserialize, Serialize};
+use strum_macros::EnumIter;
 
 use crate::core::combat::report::{MissionReport, Side};
-use crate::core::constants::PROBES_PER_PRODUCTION_LEVEL;
+use crate::core::constants::{
+    ECONOMY_HISTORY, ENEMY_COLOR, OWN_COLOR, PHALANX_SCAN_DEUTERIUM, PLAYER_COLORS,
+    PROBES_PER_PRODUCTION_LEVEL,
+};
 use crate::core::map::icon::Icon;
 use crate::core::map::map::Map;
 use crate::core::map::planet::{Planet, PlanetId};
-use crate::core::missions::Mission;
+use crate::core::missions::{BombingRaid, Mission};
 use crate::core::resources::Resources;
-use crate::core::settings::Settings;
-use crate::core::units::{Amount, Army, Unit};
+use crate::core::settings::{Difficulty, Settings};
+use crate::core::units::buildings::Building;
+use crate::core::units::{Amount, Army, Price, Unit};
 
 #[derive(Clone)]
 pub struct PlanetInfo {
@@ -24,13 +31,67 @@ pub struct PlanetInfo {
     pub army: Army,
 }
 
+#[derive(EnumIter, Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum NoteTag {
+    #[default]
+    None,
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+}
+
+impl NoteTag {
+    pub fn color(&self) -> Color {
+        match self {
+            NoteTag::None => Color::NONE,
+            NoteTag::Red => Color::srgb_u8(230, 60, 60),
+            NoteTag::Orange => Color::srgb_u8(240, 150, 40),
+            NoteTag::Yellow => Color::srgb_u8(240, 220, 60),
+            NoteTag::Green => Color::srgb_u8(80, 200, 90),
+            NoteTag::Blue => Color::srgb_u8(70, 150, 240),
+            NoteTag::Purple => Color::srgb_u8(170, 90, 220),
+        }
+    }
+}
+
+/// Personal annotation of the player on a planet
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct PlanetNote {
+    pub text: String,
+    pub tag: NoteTag,
+}
+
+impl PlanetNote {
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty() && self.tag == NoteTag::None
+    }
+}
+
+/// State of a player's economy at the end of a turn
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct EconomyRecord {
+    pub turn: usize,
+    pub production: Resources,
+    pub stockpile: Resources,
+}
+
 #[derive(Resource, Clone, Serialize, Deserialize)]
 pub struct Player {
     pub id: ClientId,
     pub home_planet: PlanetId,
     pub resources: Resources,
     pub reports: Vec<MissionReport>,
+    pub notes: HashMap<PlanetId, PlanetNote>,
     pub spectator: bool,
+
+    /// Total amount of resources produced during the game
+    pub produced: usize,
+
+    /// Production and stockpile of the last turns (oldest first)
+    pub economy: VecDeque<EconomyRecord>,
 }
 
 impl Default for Player {
@@ -44,30 +105,166 @@ impl Default for Player {
                 deuterium: 1000,
             },
             reports: Vec::new(),
+            notes: HashMap::new(),
             spectator: false,
+            produced: 0,
+            economy: VecDeque::new(),
         }
     }
 }
 
 impl Player {
-    pub fn new(id: ClientId, home_planet: PlanetId) -> Self {
+    pub fn new(id: ClientId, home_planet: PlanetId, difficulty: Difficulty) -> Self {
+        let player = Self::default();
+
+        Self {
+            id,
+            home_planet,
+            resources: player.resources * difficulty.resources() / 100usize,
+            ..player
+        }
+    }
+
+    /// A spectator that joined a running game. It owns nothing, so it's only
+    /// given a valid planet to center the camera on
+    pub fn observer(id: ClientId, home_planet: PlanetId) -> Self {
         Self {
             id,
             home_planet,
+            resources: Resources::default(),
+            spectator: true,
             ..default()
         }
     }
 
+    /// Store the economy of a turn, forgetting the oldest turns
+    pub fn record_economy(&mut self, turn: usize, production: Resources) {
+        if self.economy.len() == ECONOMY_HISTORY {
+            self.economy.pop_front();
+        }
+
+        self.economy.push_back(EconomyRecord {
+            turn,
+            production,
+            stockpile: self.resources,
+        });
+    }
+
     pub fn owns(&self, planet: &Planet) -> bool {
         planet.owned == Some(self.id)
     }
 
+    /// Name of the planet as seen by this player. Only the owner sees a custom name,
+    /// everyone else keeps seeing the generated one
+    pub fn planet_name<'a>(&self, planet: &'a Planet) -> &'a str {
+        match &planet.custom_name {
+            Some(name) if self.owns(planet) => name,
+            _ => &planet.name,
+        }
+    }
+
     pub fn controls(&self, planet: &Planet) -> bool {
         planet.controlled == Some(self.id)
     }
 
-    pub fn resource_production(&self, planets: &Vec<Planet>) -> Resources {
-        planets.iter().filter(|p| p.owned == Some(self.id)).map(|p| p.resource_production()).sum()
+    /// Abandon an owned planet
+    pub fn abandon(&mut self, planet: &mut Planet, turn: usize) {
+        planet.abandon();
+
+        // Inject hidden report to show last_info that the planet is abandoned
+        if planet.controlled == None {
+            let mission = Mission::from_mission(turn, self.id, planet, planet, &Mission::default());
+
+            self.reports.push(MissionReport {
+                id: rand::random(),
+                turn,
+                mission,
+                planet: planet.clone(),
+                scout_probes: 0,
+                intel_probes: 0,
+                leak_probes: (0, 0),
+                surviving_attacker: Army::new(),
+                surviving_defender: Army::new(),
+                planet_colonized: false,
+                planet_destroyed: false,
+                destination_owned: None,
+                destination_controlled: None,
+                combat_report: None,
+                plunder: Resources::default(),
+                hidden: true,
+            });
+        }
+    }
+
+    /// Scan an enemy planet with the Sensor Phalanx of an owned planet. The scan
+    /// reveals the units with production <= the Phalanx's level. Returns whether
+    /// the scan was performed
+    pub fn phalanx_scan(&mut self, origin: &mut Planet, target: &Planet, turn: usize) -> bool {
+        if !self.owns(origin)
+            || self.controls(target)
+            || target.is_destroyed
+            || origin.phalanx_scanned
+            || !origin.in_phalanx_range(target)
+            || self.resources.deuterium < PHALANX_SCAN_DEUTERIUM
+        {
+            return false;
+        }
+
+        self.resources.deuterium -= PHALANX_SCAN_DEUTERIUM;
+        origin.phalanx_scanned = true;
+
+        // Inject a hidden report that works like a spy mission with probes
+        let phalanx = origin.army.amount(&Unit::Building(Building::SensorPhalanx));
+        self.reports.push(MissionReport {
+            id: rand::random(),
+            turn,
+            mission: Mission {
+                origin_controlled: None,
+                ..Mission::new(
+                    turn,
+                    self.id,
+                    origin,
+                    target,
+                    Icon::Spy,
+                    Army::new(),
+                    BombingRaid::None,
+                    false,
+                    false,
+                    None,
+                )
+            },
+            planet: target.clone(),
+            scout_probes: phalanx * PROBES_PER_PRODUCTION_LEVEL,
+            intel_probes: phalanx * PROBES_PER_PRODUCTION_LEVEL,
+            leak_probes: (0, 0),
+            surviving_attacker: Army::new(),
+            surviving_defender: Army::new(),
+            planet_colonized: false,
+            planet_destroyed: false,
+            destination_owned: target.owned,
+            destination_controlled: target.controlled,
+            combat_report: None,
+            plunder: Resources::default(),
+            hidden: true,
+        });
+
+        true
+    }
+
+    pub fn resource_production(&self, planets: &Vec<Planet>, settings: &Settings) -> Resources {
+        let production: Resources = planets
+            .iter()
+            .filter(|p| p.owned == Some(self.id))
+            .map(|p| p.resource_production())
+            .sum();
+
+        production * settings.difficulty.production() / 100usize
+    }
+
+    /// Total deuterium required every turn to maintain the player's fleets
+    pub fn upkeep(&self, planets: &Vec<Planet>, missions: &Vec<Mission>) -> usize {
+        planets.iter().filter(|p| self.controls(p)).map(|p| p.upkeep()).sum::<usize>()
+            + missions.iter().filter(|m| m.owner == self.id).map(|m| m.upkeep()).sum::<usize>()
     }
 
     pub fn planets_owned(&self, map: &Map, settings: &Settings) -> (usize, usize) {
@@ -78,6 +275,23 @@ impl Player {
         (n_owned, n_max)
     }
 
+    /// Whether the intel about a planet the player doesn't control is missing or
+    /// older than the staleness threshold in the settings
+    pub fn has_stale_intel(
+        &self,
+        planet: &Planet,
+        missions: &Vec<Mission>,
+        settings: &Settings,
+    ) -> bool {
+        !self.controls(planet)
+            && !planet.is_destroyed
+            && !self.spectator
+            && !settings.sandbox.reveal()
+            && self
+                .last_info(planet, missions)
+                .is_none_or(|i| settings.turn.saturating_sub(i.turn) >= settings.stale_intel)
+    }
+
     pub fn last_info(&self, planet: &Planet, missions: &Vec<Mission>) -> Option<PlanetInfo> {
         let mut reports = vec![];
 
@@ -125,6 +339,7 @@ impl Player {
                         .filter_map(|u| {
                             if can_see {
                                 if r.winner() == r.planet.controlled
+                                    || r.is_draw()
                                     || r.mission.objective == Icon::Destroy
                                 {
                                     Some((*u, r.surviving_defender.amount(u)))
@@ -140,10 +355,7 @@ impl Player {
                                         },
                                     ))
                                 }
-                            } else if r.mission.owner == self.id
-                                && r.scout_probes
-                                    > (u.production() - 1) * PROBES_PER_PRODUCTION_LEVEL
-                            {
+                            } else if r.mission.owner == self.id && r.reveals(u) {
                                 Some((*u, r.planet.army.amount(u)))
                             } else {
                                 None
@@ -198,3 +410,207 @@ impl Player {
         })
     }
 }
+
+/// Results of a player shown on the end game screen
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Standing {
+    pub id: ClientId,
+    pub planets: usize,
+    pub fleet: usize,
+    pub produced: usize,
+    pub battles_won: usize,
+}
+
+impl Standing {
+    pub fn new(player: &Player, map: &Map, missions: &Vec<Mission>) -> Self {
+        // Value of the ships as the sum of their price
+        let value = |army: &Army| {
+            army.iter()
+                .filter(|(u, _)| u.is_ship())
+                .map(|(u, c)| {
+                    let price = u.price();
+                    (price.metal + price.crystal + price.deuterium) * c
+                })
+                .sum::<usize>()
+        };
+
+        Self {
+            id: player.id,
+            planets: map.planets.iter().filter(|p| player.controls(p)).count(),
+            fleet: map
+                .planets
+                .iter()
+                .filter(|p| player.controls(p))
+                .map(|p| value(&p.army))
+                .sum::<usize>()
+                + missions
+                    .iter()
+                    .filter(|m| m.owner == player.id)
+                    .map(|m| value(&m.army))
+                    .sum::<usize>(),
+            produced: player.produced,
+            battles_won: player
+                .reports
+                .iter()
+                .filter(|r| r.combat_report.is_some() && r.winner() == Some(player.id))
+                .count(),
+        }
+    }
+
+    pub fn score(&self) -> usize {
+        self.planets * 1000 + self.battles_won * 500 + (self.fleet + self.produced) / 100
+    }
+}
+
+/// Final standings of all players, sorted by score (computed by the host)
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+pub struct Standings {
+    pub players: Vec<Standing>,
+    pub winner: Option<ClientId>,
+}
+
+/// Players of the game in a fixed order, so every opponent has the same color
+/// and name for all players (broadcast by the host at the start of the game)
+#[derive(Resource, Clone, Default, Serialize, Deserialize)]
+pub struct Roster(pub Vec<ClientId>);
+
+impl Roster {
+    /// Color of a player, or the generic enemy color for unknown players
+    pub fn color(&self, id: Option<ClientId>, settings: &Settings) -> Color {
+        match id.and_then(|id| self.0.iter().position(|p| *p == id)) {
+            Some(i) if settings.player_colors => PLAYER_COLORS[i % PLAYER_COLORS.len()],
+            _ => ENEMY_COLOR,
+        }
+    }
+
+    /// Colors of the attacking and defending sides of a mission report
+    pub fn report_colors(
+        &self,
+        report: &MissionReport,
+        player: &Player,
+        settings: &Settings,
+    ) -> (Color, Color) {
+        if report.mission.owner == player.id {
+            (OWN_COLOR, self.color(report.destination_controlled, settings))
+        } else {
+            (self.color(Some(report.mission.owner), settings), OWN_COLOR)
+        }
+    }
+
+    pub fn name(&self, id: ClientId) -> String {
+        match self.0.iter().position(|p| *p == id) {
+            Some(i) => format!("Player {}", i + 1),
+            None => format!("Player {id}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::settings::ResourceBias;
+
+    fn planet(id: PlanetId, owned: Option<ClientId>) -> Planet {
+        Planet {
+            owned,
+            controlled: owned,
+            ..Planet::new(
+                id,
+                format!("P{id}"),
+                Vec2::new(id as f32 * 500., 0.),
+                false,
+                1.,
+                ResourceBias::None,
+            )
+        }
+    }
+
+    #[test]
+    fn difficulty_scales_the_starting_resources() {
+        assert_eq!(Player::new(1, 0, Difficulty::Normal).resources, Player::default().resources);
+        assert_eq!(Player::new(1, 0, Difficulty::Easy).resources, Resources::new(2250, 1800, 1500));
+        assert_eq!(Player::new(1, 0, Difficulty::Hard).resources, Resources::new(1125, 900, 750));
+    }
+
+    #[test]
+    fn difficulty_scales_the_production() {
+        let mut home = planet(0, Some(1));
+        home.resources = Resources::new(10, 10, 10);
+        for mine in [Building::MetalMine, Building::CrystalMine, Building::DeuteriumSynthesizer] {
+            home.army.insert(Unit::Building(mine), 10);
+        }
+
+        // Planets of other players don't count
+        let mut enemy = planet(1, Some(2));
+        enemy.resources = home.resources;
+        enemy.army = home.army.clone();
+
+        let player = Player::new(1, 0, Difficulty::Normal);
+        let planets = vec![home, enemy];
+        for (difficulty, n) in
+            [(Difficulty::Easy, 125), (Difficulty::Normal, 100), (Difficulty::Hard, 80)]
+        {
+            let settings = Settings {
+                difficulty,
+                ..default()
+            };
+            assert_eq!(player.resource_production(&planets, &settings), Resources::new(n, n, n));
+        }
+    }
+
+    #[test]
+    fn phalanx_scan_reveals_an_enemy_planet_once_per_turn() {
+        let mut origin = planet(0, Some(1));
+        origin.army.insert(Unit::Building(Building::SensorPhalanx), 6);
+        let target = planet(1, Some(2));
+
+        let mut player = Player::new(1, 0, Difficulty::Normal);
+        let deuterium = player.resources.deuterium;
+
+        assert!(player.phalanx_scan(&mut origin, &target, 3));
+        assert_eq!(player.resources.deuterium, deuterium - PHALANX_SCAN_DEUTERIUM);
+
+        let report = player.reports.last().unwrap();
+        assert!(report.hidden);
+        assert_eq!(report.mission.destination, target.id);
+        assert_eq!(report.intel_probes, 6 * PROBES_PER_PRODUCTION_LEVEL);
+
+        // The Phalanx only scans once per turn
+        assert!(!player.phalanx_scan(&mut origin, &target, 3));
+        assert_eq!(player.reports.len(), 1);
+    }
+
+    #[test]
+    fn phalanx_scan_requires_range_and_deuterium() {
+        // A level 5 Phalanx doesn't reach the target 5 planet sizes away
+        let mut origin = planet(0, Some(1));
+        origin.army.insert(Unit::Building(Building::SensorPhalanx), 5);
+        let target = planet(1, Some(2));
+
+        let mut player = Player::new(1, 0, Difficulty::Normal);
+        assert!(!player.phalanx_scan(&mut origin, &target, 3));
+
+        origin.army.insert(Unit::Building(Building::SensorPhalanx), 6);
+        player.resources.deuterium = PHALANX_SCAN_DEUTERIUM - 1;
+        assert!(!player.phalanx_scan(&mut origin, &target, 3));
+        assert!(player.reports.is_empty());
+        assert!(!origin.phalanx_scanned);
+    }
+
+    #[test]
+    fn economy_history_keeps_the_latest_turns() {
+        let mut player = Player::default();
+        for turn in 1..=ECONOMY_HISTORY + 5 {
+            player.resources = Resources::new(turn, 0, 0);
+            player.record_economy(turn, Resources::new(0, turn, 0));
+        }
+
+        assert_eq!(player.economy.len(), ECONOMY_HISTORY);
+        assert_eq!(player.economy.front().unwrap().turn, 6);
+
+        let last = player.economy.back().unwrap();
+        assert_eq!(last.turn, ECONOMY_HISTORY + 5);
+        assert_eq!(last.production, Resources::new(0, ECONOMY_HISTORY + 5, 0));
+        assert_eq!(last.stockpile, Resources::new(ECONOMY_HISTORY + 5, 0, 0));
+    }
+}
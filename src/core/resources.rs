@@ -6,6 +6,7 @@ use bevy::prelude::Component;
 use serde::{Deserialize, Serialize};
 use strum_macros::EnumIter;
 
+use crate::core::constants::PLUNDER_PERCENTAGE;
 use crate::core::units::Description;
 
 #[derive(Component, EnumIter, Clone, Copy, Debug, Default, PartialEq)]
@@ -91,6 +92,28 @@ impl Resources {
     pub fn min(&self) -> usize {
         self.metal.min(self.crystal).min(self.deuterium)
     }
+
+    pub fn total(&self) -> usize {
+        self.metal + self.crystal + self.deuterium
+    }
+
+    /// Resources a fleet with `cargo` capacity takes from this stockpile. Only a
+    /// fraction of the stockpile can be plundered, and if the cargo can't hold
+    /// all of it, every resource is reduced in the same proportion
+    pub fn plunder(&self, cargo: usize) -> Resources {
+        let available = *self * PLUNDER_PERCENTAGE / 100usize;
+        let total = available.total();
+
+        if total <= cargo {
+            available
+        } else {
+            Resources::new(
+                available.metal * cargo / total,
+                available.crystal * cargo / total,
+                available.deuterium * cargo / total,
+            )
+        }
+    }
 }
 
 impl PartialOrd for Resources {
@@ -221,3 +244,32 @@ resources_assignment_ops!(
     MulAssign, mul_assign, *=;
     DivAssign, div_assign, /=;
 );
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STOCKPILE: Resources = Resources {
+        metal: 1000,
+        crystal: 2000,
+        deuterium: 400,
+    };
+
+    #[test]
+    fn plunder_is_capped_by_the_percentage() {
+        let plunder = STOCKPILE.plunder(usize::MAX);
+        assert_eq!(plunder, STOCKPILE * PLUNDER_PERCENTAGE / 100usize);
+        assert_eq!(plunder, Resources::new(250, 500, 100));
+    }
+
+    #[test]
+    fn plunder_is_capped_by_the_cargo() {
+        // Half of the available 850 fits, and every resource is halved
+        assert_eq!(STOCKPILE.plunder(425), Resources::new(125, 250, 50));
+        assert_eq!(STOCKPILE.plunder(0), Resources::default());
+
+        for cargo in [1, 100, 333, 849, 850, 851] {
+            assert!(STOCKPILE.plunder(cargo).total() <= cargo);
+        }
+    }
+}
@@ -1,27 +1,345 @@
+use std::ops::Range;
+use std::time::Duration;
+
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 
+use crate::core::constants::{
+    HIGH_SHOTS_PER_VOLLEY, MYTHOLOGICAL_NAMES, PLANET_NAMES, SCIENTIFIC_NAMES, SCIFI_NAMES,
+    SETUP_TIME,
+};
 use crate::core::states::AudioState;
+use crate::core::units::buildings::Building;
+use crate::core::units::defense::Defense;
+use crate::core::units::ships::Ship;
+use crate::core::units::{Army, Description, Unit};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    /// Percentage of the default starting resources
+    pub fn resources(&self) -> usize {
+        match self {
+            Difficulty::Easy => 150,
+            Difficulty::Normal => 100,
+            Difficulty::Hard => 75,
+        }
+    }
+
+    /// Percentage of the resource production per turn
+    pub fn production(&self) -> usize {
+        match self {
+            Difficulty::Easy => 125,
+            Difficulty::Normal => 100,
+            Difficulty::Hard => 80,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum StartPackage {
+    Minimal,
+    #[default]
+    Standard,
+    Advanced,
+}
+
+impl StartPackage {
+    /// Units the home planet starts with
+    pub fn army(&self) -> Army {
+        match self {
+            StartPackage::Minimal => Army::from([
+                (Unit::Building(Building::MetalMine), 1),
+                (Unit::Building(Building::CrystalMine), 1),
+                (Unit::Building(Building::Shipyard), 1),
+            ]),
+            StartPackage::Standard => Army::from([
+                (Unit::Building(Building::MetalMine), 1),
+                (Unit::Building(Building::CrystalMine), 1),
+                (Unit::Building(Building::DeuteriumSynthesizer), 1),
+                (Unit::Building(Building::Shipyard), 1),
+                (Unit::Building(Building::Factory), 1),
+            ]),
+            StartPackage::Advanced => Army::from([
+                (Unit::Building(Building::MetalMine), 2),
+                (Unit::Building(Building::CrystalMine), 2),
+                (Unit::Building(Building::DeuteriumSynthesizer), 2),
+                (Unit::Building(Building::Shipyard), 2),
+                (Unit::Building(Building::Factory), 2),
+                (Unit::Ship(Ship::Probe), 3),
+                (Unit::Ship(Ship::ColonyShip), 1),
+                (Unit::Ship(Ship::LightFighter), 5),
+                (Unit::Ship(Ship::HeavyFighter), 2),
+                (Unit::Defense(Defense::RocketLauncher), 5),
+                (Unit::Defense(Defense::LightLaser), 2),
+            ]),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Victory {
+    #[default]
+    Elimination,
+    Conquest,
+    Score,
+    TurnLimit,
+}
+
+impl Description for Victory {
+    fn description(&self) -> &str {
+        match self {
+            Victory::Elimination => "The last player that owns its home planet wins the game.",
+            Victory::Conquest => {
+                "The first player that controls 50% of all planets and moons wins the game."
+            },
+            Victory::Score => {
+                "The first player that reaches a score of 25,000 wins the game. If nobody \
+                reaches it by turn 50, the player with the highest score wins."
+            },
+            Victory::TurnLimit => {
+                "The last player standing wins the game. If there are still multiple players \
+                at turn 50, the player with the highest score wins."
+            },
+        }
+    }
+}
+
+/// Order in which the host resolves the missions of all players
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum TurnOrder {
+    /// Players are resolved one after the other in a random order
+    #[default]
+    Random,
+    /// Missions are resolved per planet in order of arrival
+    Simultaneous,
+}
+
+/// Fixed alliances between the players of a multiplayer game
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Teams {
+    /// Every player for themselves
+    #[default]
+    FreeForAll,
+    /// Players alternate between two teams in the order they joined
+    TwoTeams,
+}
+
+impl Teams {
+    /// Team of the player at position `index` in the roster
+    pub fn team(&self, index: usize) -> usize {
+        match self {
+            Teams::FreeForAll => index,
+            Teams::TwoTeams => index % 2,
+        }
+    }
+}
+
+/// Buildings queued automatically on newly colonized planets
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum BuildOrder {
+    #[default]
+    Manual,
+    Economy,
+    Industry,
+}
+
+impl BuildOrder {
+    /// Buildings to queue on a new colony. Moons can't have resource
+    /// buildings, so they get a Lunar Base to gain fields instead
+    pub fn buildings(&self, is_moon: bool) -> Vec<Unit> {
+        match (self, is_moon) {
+            (BuildOrder::Manual, _) => vec![],
+            (BuildOrder::Economy, false) => Unit::resource_buildings(),
+            (BuildOrder::Industry, false) => Unit::resource_buildings()
+                .into_iter()
+                .chain([Unit::Building(Building::Shipyard), Unit::Building(Building::Factory)])
+                .collect(),
+            (BuildOrder::Economy, true) => vec![Unit::Building(Building::LunarBase)],
+            (BuildOrder::Industry, true) => {
+                vec![Unit::Building(Building::LunarBase), Unit::Building(Building::Shipyard)]
+            },
+        }
+    }
+}
+
+/// Single player mode to learn the game or design maps
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Sandbox {
+    #[default]
+    Off,
+    /// Reveal the full information of every planet
+    Reveal,
+    /// Reveal every planet and grant unlimited resources
+    Infinite,
+}
+
+impl Sandbox {
+    pub fn reveal(&self) -> bool {
+        *self != Sandbox::Off
+    }
+}
+
+/// How strongly a planet's kind determines its resource reserves
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum ResourceBias {
+    /// Every kind has the same reserves
+    None,
+    #[default]
+    Normal,
+    Strong,
+}
+
+impl ResourceBias {
+    /// Ranges of the base production per building level of scarce, balanced
+    /// and abundant resources (before the map's resource factor)
+    pub fn ranges(&self) -> [Range<f32>; 3] {
+        match self {
+            ResourceBias::None => [20.0..30.0, 20.0..30.0, 20.0..30.0],
+            ResourceBias::Normal => [10.0..20.0, 20.0..30.0, 30.0..40.0],
+            ResourceBias::Strong => [5.0..15.0, 20.0..30.0, 35.0..50.0],
+        }
+    }
+}
+
+/// Themed list the planet names of a new galaxy are drawn from
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum NameTheme {
+    #[default]
+    Classic,
+    Mythological,
+    Scientific,
+    SciFi,
+}
+
+impl NameTheme {
+    pub fn names(&self) -> &'static [&'static str] {
+        match self {
+            NameTheme::Classic => &PLANET_NAMES,
+            NameTheme::Mythological => &MYTHOLOGICAL_NAMES,
+            NameTheme::Scientific => &SCIENTIFIC_NAMES,
+            NameTheme::SciFi => &SCIFI_NAMES,
+        }
+    }
+}
+
+/// Length of the fly-in animation at the start of a combat
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum CombatSetup {
+    #[default]
+    Full,
+    Short,
+    Skip,
+}
+
+impl CombatSetup {
+    /// Skipped setups still run a one-frame animation, so the combat's state
+    /// machine waits for its completion like with any other length
+    pub fn duration(&self) -> Duration {
+        match self {
+            CombatSetup::Full => Duration::from_secs(SETUP_TIME),
+            CombatSetup::Short => Duration::from_secs(SETUP_TIME) / 4,
+            CombatSetup::Skip => Duration::from_millis(1),
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            CombatSetup::Full => CombatSetup::Short,
+            CombatSetup::Short => CombatSetup::Skip,
+            CombatSetup::Skip => CombatSetup::Full,
+        }
+    }
+}
 
 #[derive(Resource, Clone, Serialize, Deserialize)]
 pub struct Settings {
     pub audio: AudioState,
+    pub difficulty: Difficulty,
+    pub start: StartPackage,
     pub n_planets: usize,
     pub p_colonizable: usize,
     pub p_moons: usize,
+    pub resource_bias: ResourceBias,
+    pub name_theme: NameTheme,
+    /// Number of wormholes per player
+    pub wormholes: usize,
+    pub veterancy: bool,
+    pub upkeep: bool,
+    /// Maximum number of rounds in a combat before it ends in a draw
+    pub combat_rounds: usize,
+    /// Percentage of the scout probes (losing attacker) or units that fought (losing
+    /// defender) that gather extra intel on the enemy (in probes)
+    pub intel_leak: usize,
+    pub victory: Victory,
+    /// Only available in single player games
+    pub sandbox: Sandbox,
+    /// Guided first game, only available in single player games
+    pub tutorial: bool,
+    /// Only relevant in multiplayer games
+    pub turn_order: TurnOrder,
+    /// Only relevant in multiplayer games
+    pub teams: Teams,
+    pub build_order: BuildOrder,
+    /// Target metal:crystal:deuterium production ratio of balanced auto-economy planets
+    pub resource_ratio: [usize; 3],
     pub autosave: bool,
+    pub auto_resolve: bool,
+    pub lite_combat: bool,
+    /// Maximum number of shot animations spawned per volley. Shots above the cap are
+    /// merged with each other, so only the visuals change, never the combat result
+    pub max_shots: Option<usize>,
+    /// Whether shots play sounds based on the firing unit's weapon. When disabled,
+    /// all shots use the generic explosion sound
+    pub combat_sounds: bool,
+    /// Whether the damage of every shot floats over its target in the sprite combat
+    pub damage_numbers: bool,
+    /// Whether the background moves slower than the camera. When disabled, the
+    /// background stays fixed on the screen
+    pub parallax: bool,
+    pub show_summary: bool,
     pub show_cells: bool,
+    /// Whether the cells are tinted by the estimated threat to the player
+    pub show_threat: bool,
+    /// Whether the cells of planets with stale or missing intel are highlighted
+    pub show_intel: bool,
+    /// Number of turns after which the intel about a planet is considered stale
+    pub stale_intel: usize,
+    /// Whether all visible missions are drawn as arrows to their destination
+    pub show_paths: bool,
+    /// Whether enemy planets with intel show a badge with the odds of attacking them
+    pub show_odds: bool,
+    /// Whether every opponent is shown with its own color (else all enemies share one)
+    pub player_colors: bool,
+    /// Whether abandoning a planet asks for confirmation
+    pub confirm_abandon: bool,
+    /// Accessibility mode with larger text and stronger contrast in the ui
+    pub large_text: bool,
+    /// Whether frames are synchronized with the monitor's refresh rate
+    pub vsync: bool,
+    /// Maximum number of frames per second (None for no limit). Has no effect on the web
+    pub frame_cap: Option<usize>,
     pub show_info: bool,
     pub show_hover: bool,
     pub show_menu: bool,
     pub combat_paused: bool,
+    /// Whether all animations and timers are halted. Only the local rendering is
+    /// paused, so turns keep being resolved in multiplayer
+    pub paused: bool,
     pub combat_speed: f32,
+    pub combat_setup: CombatSetup,
     pub turn: usize,
 }
 
 impl Settings {
     pub fn speed(&self) -> f32 {
-        if self.combat_paused {
+        if self.combat_paused || self.paused {
             0.
         } else {
             self.combat_speed
@@ -33,16 +351,51 @@ impl Default for Settings {
     fn default() -> Self {
         Self {
             audio: AudioState::default(),
+            difficulty: Difficulty::default(),
+            start: StartPackage::default(),
             n_planets: 10,
             p_colonizable: 25,
             p_moons: 30,
+            resource_bias: ResourceBias::default(),
+            name_theme: NameTheme::default(),
+            wormholes: 0,
+            veterancy: false,
+            upkeep: false,
+            combat_rounds: 15,
+            intel_leak: 0,
+            victory: Victory::default(),
+            sandbox: Sandbox::default(),
+            tutorial: false,
+            turn_order: TurnOrder::default(),
+            teams: Teams::default(),
+            build_order: BuildOrder::default(),
+            resource_ratio: [2, 1, 1],
             autosave: false,
+            auto_resolve: false,
+            lite_combat: false,
+            max_shots: Some(HIGH_SHOTS_PER_VOLLEY),
+            combat_sounds: true,
+            damage_numbers: false,
+            parallax: true,
+            show_summary: true,
             show_cells: true,
+            show_threat: false,
+            show_intel: false,
+            stale_intel: 5,
+            show_paths: false,
+            show_odds: false,
+            player_colors: true,
+            confirm_abandon: true,
+            large_text: false,
+            vsync: true,
+            frame_cap: None,
             show_info: false,
             show_hover: true,
             show_menu: true,
             combat_paused: false,
+            paused: false,
             combat_speed: 1.0,
+            combat_setup: CombatSetup::default(),
             turn: 1,
         }
     }
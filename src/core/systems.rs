@@ -1,20 +1,28 @@
 use bevy::prelude::*;
-use bevy::window::WindowResized;
+use bevy::window::{PresentMode, PrimaryWindow, WindowResized};
+use bevy_egui::EguiContexts;
 use bevy_renet::netcode::NetcodeServerTransport;
 use bevy_renet::renet::{RenetClient, RenetServer};
 use itertools::Itertools;
 
 use crate::core::camera::MainCamera;
 use crate::core::combat::systems::BackgroundImageCmp;
+use crate::core::map::icon::Icon;
 use crate::core::map::map::Map;
+use crate::core::map::planet::PlanetId;
 use crate::core::menu::utils::TextSize;
+use crate::core::messages::MessageMsg;
+use crate::core::missions::{Mission, SendMissionMsg};
+use crate::core::network::Host;
+use crate::core::persistence::SaveGameMsg;
 use crate::core::player::Player;
 use crate::core::settings::Settings;
 use crate::core::states::{AppState, GameState};
 use crate::core::turns::StartTurnMsg;
 use crate::core::ui::systems::{MissionTab, Shop, UiState};
 use crate::core::units::buildings::Building;
-use crate::core::units::Unit;
+use crate::core::units::{Amount, Unit};
+use crate::utils::NameFromEnum;
 
 pub fn on_resize_system(
     mut resize_reader: MessageReader<WindowResized>,
@@ -40,6 +48,36 @@ pub fn on_resize_system(
     }
 }
 
+/// Apply the vsync setting to the window when it changes
+pub fn update_present_mode(
+    settings: Res<Settings>,
+    mut window: Single<&mut Window, With<PrimaryWindow>>,
+) {
+    let mode = if settings.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+
+    if window.present_mode != mode {
+        window.present_mode = mode;
+    }
+}
+
+/// Sleep at the end of every frame to not exceed the frame rate cap. Animations
+/// are driven by the frame's delta time, so their speed doesn't change
+#[cfg(not(target_arch = "wasm32"))]
+pub fn limit_frame_rate(settings: Res<Settings>, mut last: Local<Option<std::time::Instant>>) {
+    if let (Some(fps), Some(last)) = (settings.frame_cap, *last) {
+        let target = std::time::Duration::from_secs_f32(1. / fps as f32);
+        if let Some(remaining) = target.checked_sub(last.elapsed()) {
+            std::thread::sleep(remaining);
+        }
+    }
+
+    *last = Some(std::time::Instant::now());
+}
+
 pub fn check_keys_menu(
     mut commands: Commands,
     app_state: Res<State<AppState>>,
@@ -49,6 +87,7 @@ pub fn check_keys_menu(
     server: Option<ResMut<RenetServer>>,
     mut client: Option<ResMut<RenetClient>>,
     mut state: Option<ResMut<UiState>>,
+    settings: Res<Settings>,
     mut start_turn_msg: MessageWriter<StartTurnMsg>,
     keyboard: Res<ButtonInput<KeyCode>>,
 ) {
@@ -76,7 +115,9 @@ pub fn check_keys_menu(
                 match game_state.get() {
                     GameState::Playing => {
                         let state = state.as_mut().unwrap();
-                        if state.planet_selected.is_some() || state.mission {
+                        if state.turn_summary.is_some() {
+                            state.turn_summary = None;
+                        } else if state.planet_selected.is_some() || state.mission {
                             state.planet_selected = None;
                             state.mission = false;
                             state.combat_report = None;
@@ -87,6 +128,9 @@ pub fn check_keys_menu(
                     GameState::CombatMenu | GameState::GameMenu => {
                         next_game_state.set(GameState::Playing)
                     },
+                    GameState::Combat if settings.auto_resolve => {
+                        next_game_state.set(GameState::Playing)
+                    },
                     GameState::Combat => next_game_state.set(GameState::CombatMenu),
                     GameState::EndGame => next_app_state.set(AppState::MainMenu),
                     GameState::Settings => next_game_state.set(GameState::GameMenu),
@@ -112,8 +156,20 @@ pub fn check_keys_menu(
     }
 }
 
-pub fn check_keys_combat(mut settings: ResMut<Settings>, keyboard: Res<ButtonInput<KeyCode>>) {
-    if keyboard.just_pressed(KeyCode::Space) {
+pub fn check_keys_combat(
+    host: Option<Res<Host>>,
+    mut settings: ResMut<Settings>,
+    mut save_game_msg: MessageWriter<SaveGameMsg>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+) {
+    let ctrl_pressed = keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+
+    if ctrl_pressed && keyboard.just_pressed(KeyCode::KeyP) {
+        settings.paused = !settings.paused;
+    } else if ctrl_pressed && keyboard.just_pressed(KeyCode::KeyS) && host.is_some() {
+        // The game menu can't be reached during a combat, so save from here
+        save_game_msg.write(SaveGameMsg(false));
+    } else if keyboard.just_pressed(KeyCode::Space) {
         settings.combat_paused = !settings.combat_paused;
     } else if keyboard.just_released(KeyCode::ArrowRight) {
         settings.combat_speed = (settings.combat_speed * 2.).min(64.0);
@@ -122,14 +178,88 @@ pub fn check_keys_combat(mut settings: ResMut<Settings>, keyboard: Res<ButtonInp
     }
 }
 
+/// Compose the last sent mission towards `destination` from the origin that can
+/// send the largest part of its army
+fn resend_mission(
+    last: &Mission,
+    destination: PlanetId,
+    map: &Map,
+    player: &Player,
+    settings: &Settings,
+) -> Result<Mission, String> {
+    let destination = map.get(destination);
+
+    let (n_owned, n_max_owned) = player.planets_owned(map, settings);
+    if !Icon::objectives(player.owns(destination), player.controls(destination))
+        .contains(&last.objective)
+        || (destination.is_moon() && last.objective.on_planet_only())
+        || (last.objective == Icon::Colonize && n_owned >= n_max_owned)
+        || destination.is_destroyed
+    {
+        return Err(format!(
+            "The objective {} is not valid for planet {}.",
+            last.objective.to_name(),
+            destination.name
+        ));
+    }
+
+    map.planets
+        .iter()
+        .filter(|p| p.id != destination.id && player.controls(p))
+        .filter_map(|origin| {
+            // Fraction of the last army that this origin can send
+            let ratio = last
+                .army
+                .iter()
+                .filter(|(_, c)| **c > 0)
+                .map(|(u, c)| origin.army.amount(u) as f32 / *c as f32)
+                .fold(1., f32::min);
+
+            let army = last
+                .army
+                .iter()
+                .map(|(u, c)| (*u, ((*c as f32 * ratio) as usize).min(origin.army.amount(u))))
+                .filter(|(_, c)| *c > 0)
+                .collect();
+
+            // Jump Gates are not used since the new origin may not have one
+            let mission = Mission {
+                army,
+                jump_gate: false,
+                ..Mission::from_mission(settings.turn, player.id, origin, destination, last)
+            };
+
+            let mothball_check =
+                !origin.mothballed || !mission.army.iter().any(|(u, c)| u.is_ship() && *c > 0);
+
+            (mission.meets_objective()
+                && mothball_check
+                && mission.fuel_consumption(map) <= player.resources.deuterium)
+                .then_some((ratio, mission))
+        })
+        .max_by(|(r1, m1), (r2, m2)| {
+            r1.total_cmp(r2).then(m2.distance(map).total_cmp(&m1.distance(map)))
+        })
+        .map(|(_, mission)| mission)
+        .ok_or(format!("No planet can send the last mission to {}.", destination.name))
+}
+
 pub fn check_keys(
+    context: EguiContexts,
     keyboard: Res<ButtonInput<KeyCode>>,
     mouse: Res<ButtonInput<MouseButton>>,
     mut map: ResMut<Map>,
     mut player: ResMut<Player>,
     mut state: ResMut<UiState>,
     mut settings: ResMut<Settings>,
+    mut send_mission: MessageWriter<SendMissionMsg>,
+    mut message: MessageWriter<MessageMsg>,
 ) {
+    // Don't trigger shortcuts while typing in a text field
+    if context.ctx().unwrap().wants_keyboard_input() {
+        return;
+    }
+
     let ctrl_pressed = keyboard.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
     let shift_pressed = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
 
@@ -160,6 +290,15 @@ pub fn check_keys(
         settings.show_cells = !settings.show_cells;
     }
 
+    // Pause all animations and timers, or toggle show mission paths
+    if keyboard.just_pressed(KeyCode::KeyP) {
+        if ctrl_pressed {
+            settings.paused = !settings.paused;
+        } else {
+            settings.show_paths = !settings.show_paths;
+        }
+    }
+
     // Toggle show planet info
     if keyboard.just_pressed(KeyCode::KeyI) {
         settings.show_info = !settings.show_info;
@@ -182,10 +321,50 @@ pub fn check_keys(
         state.combat_report = None;
     }
 
-    // Go back to home planet
-    if keyboard.just_pressed(KeyCode::Space) {
+    // Toggle diplomacy panel
+    if keyboard.just_pressed(KeyCode::KeyR) {
+        state.diplomacy = !state.diplomacy;
+    }
+
+    // Toggle player list panel
+    if keyboard.just_pressed(KeyCode::KeyU) {
+        state.player_list = !state.player_list;
+    }
+
+    // Toggle encyclopedia panel
+    if keyboard.just_pressed(KeyCode::KeyE) {
+        state.encyclopedia = !state.encyclopedia;
+    }
+
+    // Toggle notifications panel
+    if keyboard.just_pressed(KeyCode::KeyN) {
+        state.notifications = !state.notifications;
+    }
+
+    // Resend the last mission to the hovered planet
+    if keyboard.just_pressed(KeyCode::KeyL) && !state.mission && !player.spectator {
+        if let Some(destination) = state.planet_hover {
+            if let Some(last) = &state.last_mission {
+                match resend_mission(last, destination, &map, &player, &settings) {
+                    Ok(mission) => {
+                        state.last_mission = Some(mission.clone());
+                        send_mission.write(SendMissionMsg::new(mission));
+                    },
+                    Err(err) => {
+                        message.write(MessageMsg::warning(err));
+                    },
+                }
+            } else {
+                message.write(MessageMsg::warning("No mission has been sent yet."));
+            }
+        }
+    }
+
+    // Go back to home planet, home also resets the zoom
+    if keyboard.any_just_pressed([KeyCode::Space, KeyCode::Home]) {
         state.planet_selected = Some(player.home_planet);
         state.to_selected = true;
+        state.reset_zoom = keyboard.just_pressed(KeyCode::Home);
         state.mission = false;
     }
 
@@ -275,5 +454,14 @@ pub fn check_keys(
                 Shop::Defenses => Shop::Buildings,
             };
         }
+
+        // Jump directly to a shop category
+        if keyboard.just_pressed(KeyCode::Digit1) {
+            state.shop = Shop::Buildings;
+        } else if keyboard.just_pressed(KeyCode::Digit2) {
+            state.shop = Shop::Fleet;
+        } else if keyboard.just_pressed(KeyCode::Digit3) && !planet.is_moon() {
+            state.shop = Shop::Defenses;
+        }
     }
 }
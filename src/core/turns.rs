@@ -1,4 +1,7 @@
+use std::collections::{HashMap, HashSet};
+
 use bevy::prelude::*;
+use bevy_renet::renet::{ClientId, RenetServer};
 use itertools::Itertools;
 use rand::rng;
 use rand::seq::SliceRandom;
@@ -7,20 +10,27 @@ use crate::core::assets::WorldAssets;
 use crate::core::audio::PlayAudioMsg;
 use crate::core::combat::combat::resolve_combat;
 use crate::core::combat::report::Side;
-use crate::core::constants::EXPLOSION_Z;
+use crate::core::constants::{
+    END_TURN_COOLDOWN, EXPLOSION_Z, MAX_VETERANCY, SANDBOX_RESOURCES, VICTORY_PLANETS,
+    VICTORY_SCORE, VICTORY_TURN_LIMIT,
+};
+use crate::core::diplomacy::Diplomacy;
 use crate::core::map::icon::Icon;
 use crate::core::map::map::Map;
+use crate::core::map::planet::{Planet, PlanetId};
 use crate::core::map::systems::{ExplosionCmp, PlanetCmp};
 use crate::core::messages::MessageMsg;
 use crate::core::missions::{BombingRaid, Mission, Missions};
 use crate::core::network::{ClientMessage, ClientSendMsg, Host, ServerMessage, ServerSendMsg};
-use crate::core::persistence::SaveGameMsg;
-use crate::core::player::Player;
-use crate::core::settings::Settings;
+use crate::core::persistence::{ReplayTurn, SaveGameMsg};
+use crate::core::player::{Player, Standing, Standings};
+use crate::core::resources::Resources;
+use crate::core::settings::{BuildOrder, Sandbox, Settings, TurnOrder, Victory};
 use crate::core::states::GameState;
+use crate::core::ui::locale::trf;
 use crate::core::ui::systems::{MissionTab, UiState};
 use crate::core::units::buildings::Building;
-use crate::core::units::{Army, Unit};
+use crate::core::units::{Amount, Army, Unit};
 use crate::utils::NameFromEnum;
 
 #[derive(Message)]
@@ -38,6 +48,24 @@ impl StartTurnMsg {
     }
 }
 
+/// Planets owned by the player at the start of a turn
+#[derive(Clone, Default)]
+pub struct TurnSnapshot {
+    pub turn: usize,
+    pub planets: HashMap<PlanetId, Army>,
+}
+
+/// Changes since the player's previous turn
+#[derive(Clone, Default)]
+pub struct TurnSummary {
+    pub resources: Resources,
+    pub buildings: Vec<(PlanetId, Building, usize)>,
+    pub missions: usize,
+    pub combats: usize,
+    pub gained: Vec<PlanetId>,
+    pub lost: Vec<PlanetId>,
+}
+
 #[derive(Resource)]
 pub struct PreviousEndTurnState(bool);
 
@@ -48,29 +76,281 @@ impl Default for PreviousEndTurnState {
     }
 }
 
-/// Merge missions per objective and return ordered by objective priority
+/// Merge missions per objective and return ordered by objective priority. Missions
+/// are merged in order of id, so the result doesn't depend on the order of arrival
 fn regroup_missions(missions: &Vec<Mission>) -> Vec<Mission> {
-    let mut deploy: Option<Mission> = None;
-    let mut missile: Option<Mission> = None;
-    let mut spy: Option<Mission> = None;
-    let mut rest: Option<Mission> = None;
-
-    for m in missions.iter() {
-        let target = match m.objective {
-            Icon::MissileStrike => &mut missile,
-            Icon::Spy => &mut spy,
-            Icon::Deploy => &mut deploy,
-            _ => &mut rest,
+    let mut groups: [Vec<&Mission>; 4] = Default::default();
+
+    for m in missions.iter().sorted_by_key(|m| m.id) {
+        let idx = match m.objective {
+            Icon::Deploy => 0,
+            Icon::MissileStrike => 1,
+            Icon::Spy => 2,
+            _ => 3,
         };
 
-        if let Some(t) = target {
-            t.merge(m);
-        } else {
-            *target = Some(m.clone());
+        groups[idx].push(m);
+    }
+
+    groups
+        .into_iter()
+        .filter_map(|group| {
+            let (first, others) = group.split_first()?;
+
+            let mut mission = (*first).clone();
+            others.iter().for_each(|m| mission.merge(m));
+
+            if !others.is_empty() {
+                // Compute the veterancy over all fleets at once to avoid rounding per merge
+                let n = group.iter().map(|m| m.army.combat_ships()).sum::<usize>();
+                mission.veterancy = group
+                    .iter()
+                    .map(|m| m.veterancy * m.army.combat_ships())
+                    .sum::<usize>()
+                    .checked_div(n)
+                    .unwrap_or(0);
+
+                // Fleets deployed together get a single log entry
+                if mission.objective == Icon::Deploy {
+                    mission.logs = first.logs.clone()
+                        + format!("\n- Merged with {} other Deploy missions.", others.len())
+                            .as_str();
+                }
+            }
+
+            Some(mission)
+        })
+        .collect()
+}
+
+/// Reason why a planet's fleet couldn't intercept a mission
+#[derive(Debug, PartialEq)]
+enum MissedInterception {
+    TooLate,
+    NoFleet,
+    Outran,
+}
+
+impl MissedInterception {
+    fn message(&self, planet: &Planet) -> String {
+        match self {
+            MissedInterception::TooLate => format!(
+                "Too late to intercept the mission arriving at planet {} this turn.",
+                planet.name
+            ),
+            MissedInterception::NoFleet => {
+                format!("Planet {} has no fleet to intercept the mission.", planet.name)
+            },
+            MissedInterception::Outran => {
+                format!("The mission outran the interceptors of planet {}.", planet.name)
+            },
         }
     }
+}
+
+/// Fleet of `planet` that intercepts `mission`, arriving in `turns` turns, or the
+/// reason why it can't. The fleet sorties the turn before the mission arrives
+fn interceptors(
+    planet: &Planet,
+    mission: &Mission,
+    turns: usize,
+) -> Result<Army, MissedInterception> {
+    if turns < 2 {
+        return Err(MissedInterception::TooLate);
+    }
+
+    let fleet: Army = planet
+        .army
+        .iter()
+        .filter_map(|(u, c)| (u.is_combat_ship() && *c > 0).then_some((*u, *c)))
+        .collect();
+
+    // Mothballed fleets can't sortie
+    if !fleet.has_army() || planet.mothballed {
+        return Err(MissedInterception::NoFleet);
+    }
+
+    // Faster fleets can't be caught by the interceptors
+    let speed = fleet.keys().map(|u| u.speed()).fold(f32::MAX, f32::min);
+    if mission.speed() > speed {
+        return Err(MissedInterception::Outran);
+    }
 
-    [deploy, missile, spy, rest].into_iter().flatten().collect()
+    Ok(fleet)
+}
+
+/// Colony ships arriving at a planet that was colonized earlier this turn. Those of
+/// the same player deploy instead, those of other players return to `new_origin`
+/// with the returned mission
+fn check_colonized(
+    mission: &mut Mission,
+    colonized: &HashMap<PlanetId, ClientId>,
+    destination: &Planet,
+    new_origin: &Planet,
+    turn: usize,
+) -> Option<Mission> {
+    let first =
+        colonized.get(&destination.id).copied().filter(|_| mission.objective == Icon::Colonize)?;
+
+    if first == mission.owner {
+        mission.objective = Icon::Deploy;
+        mission.logs.push_str(
+            format!(
+                "\n- ({turn}) Planet {} was already colonized by another mission. Deploying \
+                the fleet instead.",
+                destination.name
+            )
+            .as_str(),
+        );
+
+        None
+    } else {
+        Some(Mission {
+            veterancy: mission.veterancy,
+            ..Mission::new(
+                turn,
+                mission.owner,
+                destination,
+                new_origin,
+                Icon::Deploy,
+                mission.army.clone(),
+                BombingRaid::None,
+                false,
+                false,
+                Some(
+                    mission.logs.clone()
+                        + format!(
+                            "\n- ({turn}) Planet {} was colonized first by player {first}. \
+                            Returning to planet {}.",
+                            destination.name, new_origin.name
+                        )
+                        .as_str(),
+                ),
+            )
+        })
+    }
+}
+
+/// Veterancy of a surviving fleet, which gains experience if it fought
+fn promote(veterancy: usize, fought: bool) -> usize {
+    if fought {
+        (veterancy + 1).min(MAX_VETERANCY)
+    } else {
+        veterancy
+    }
+}
+
+/// Charge the fleet upkeep of a player. Missions always pay, and planets whose
+/// upkeep can't be paid are mothballed until there is enough deuterium again
+fn charge_upkeep(player: &mut Player, planets: &mut [Planet], missions: &[Mission]) {
+    let missions_upkeep =
+        missions.iter().filter(|m| m.owner == player.id).map(|m| m.upkeep()).sum::<usize>();
+    player.resources.deuterium = player.resources.deuterium.saturating_sub(missions_upkeep);
+
+    for planet in planets.iter_mut().filter(|p| player.controls(p)).sorted_by_key(|p| p.upkeep()) {
+        let upkeep = planet.upkeep();
+        planet.mothballed = upkeep > player.resources.deuterium;
+        if !planet.mothballed {
+            player.resources.deuterium -= upkeep;
+        }
+    }
+}
+
+/// Transfer the planets offered to allies with room for them. Offers to players
+/// that are no longer allied, or of their home planet, are withdrawn, the others
+/// wait until there is room
+fn transfer_planets(
+    map: &mut Map,
+    all_players: &[Player],
+    all_missions: &mut [Mission],
+    diplomacy: &Diplomacy,
+    settings: &Settings,
+) {
+    let n_max_owned =
+        (map.planets().len() as f32 * settings.p_colonizable as f32 / 100.).ceil() as usize;
+    let offers = map
+        .planets
+        .iter()
+        .filter_map(|p| p.transfer.zip(p.owned).map(|(to, from)| (p.id, from, to)))
+        .collect::<Vec<_>>();
+
+    for (planet_id, from, to) in offers {
+        let is_home = all_players.iter().any(|p| p.id == from && p.home_planet == planet_id);
+        if !diplomacy.is_allied(from, to) || is_home {
+            map.get_mut(planet_id).transfer = None;
+            continue;
+        }
+
+        if map.planets().iter().filter(|p| p.owned == Some(to)).count() >= n_max_owned {
+            continue;
+        }
+
+        let planet = map.get_mut(planet_id);
+        planet.gift(to, settings.turn);
+
+        // Deploy missions of the previous owner towards the planet are called off
+        // when they arrive, since attacks on allies aren't allowed
+        for mission in
+            all_missions.iter_mut().filter(|m| m.owner == from && m.destination == planet_id)
+        {
+            mission.logs.push_str(
+                format!(
+                    "\n- ({}) Planet {} was transferred to player {to}.",
+                    settings.turn, planet.name
+                )
+                .as_str(),
+            );
+        }
+    }
+}
+
+/// Recall the deploying fleets of a player from planets with the defensive alert
+/// enabled when a detected enemy mission targets them. The fuel for the way back
+/// is paid again, and fleets that can't pay it continue
+fn recall_alerted_fleets(
+    player: &mut Player,
+    map: &Map,
+    all_missions: &mut [Mission],
+    diplomacy: &Diplomacy,
+    turn: usize,
+) {
+    let threatened = all_missions
+        .iter()
+        .filter(|m| {
+            m.owner != player.id
+                && !diplomacy.is_allied(player.id, m.owner)
+                && (m.is_seen_by_phalanx(map, player).is_some()
+                    || m.is_seen_by_radar(map, player).is_some())
+        })
+        .map(|m| m.destination)
+        .filter(|id| {
+            let planet = map.get(*id);
+            planet.alert && player.controls(planet)
+        })
+        .collect::<HashSet<_>>();
+
+    for mission in all_missions.iter_mut().filter(|m| {
+        m.owner == player.id
+            && m.objective == Icon::Deploy
+            && !m.jump_gate
+            && m.destination != m.origin
+            && threatened.contains(&m.origin)
+    }) {
+        let recalled = Mission {
+            destination: mission.origin,
+            ..mission.clone()
+        };
+
+        let fuel = recalled.fuel_consumption(map);
+        if fuel <= player.resources.deuterium {
+            player.resources.deuterium -= fuel;
+            *mission = recalled;
+            mission.logs.push_str(
+                format!("\n- ({turn}) Recalled to defend planet {}.", map.get(mission.origin).name)
+                    .as_str(),
+            );
+        }
+    }
 }
 
 /// Check if a mission objective has to change because the destination
@@ -89,8 +369,27 @@ fn check_mission(mission: &mut Mission, map: &Map, turn: usize, settings: &Setti
         mission.objective = Icon::Deploy;
     }
 
-    // If deploying to a planet that's no longer under control, convert to attack
-    if destination.controlled != Some(mission.owner) && mission.objective == Icon::Deploy {
+    // The unit required for the objective could have been lost on the way
+    if mission.objective == Icon::Colonize && mission.army.amount(&Unit::colony_ship()) == 0 {
+        mission.objective = Icon::Deploy;
+        mission
+            .logs
+            .push_str(format!("\n- ({turn}) No Colony Ship left to colonize the planet.").as_str());
+    } else if mission.objective == Icon::Destroy && mission.army.amount(&Unit::war_sun()) == 0 {
+        mission.objective = Icon::Deploy;
+        mission
+            .logs
+            .push_str(format!("\n- ({turn}) No War Sun left to destroy the planet.").as_str());
+    }
+
+    // If deploying to a planet that's no longer under control, convert to attack. Planets
+    // that were abandoned (not controlled and without defenders) are still deployed to
+    if destination.controlled != Some(mission.owner)
+        && mission.objective == Icon::Deploy
+        && (destination.controlled.is_some()
+            || destination.has_fleet()
+            || destination.has_defense())
+    {
         mission.objective = Icon::Attack;
     }
 
@@ -99,6 +398,9 @@ fn check_mission(mission: &mut Mission, map: &Map, turn: usize, settings: &Setti
     let n_max_owned =
         (map.planets.len() as f32 * settings.p_colonizable as f32 / 100.).ceil() as usize;
     if mission.objective == Icon::Colonize && n_owned >= n_max_owned {
+        mission.logs.push_str(
+            format!("\n- ({turn}) Maximum number of colonized planets reached.").as_str(),
+        );
         mission.objective = if destination.controlled != Some(mission.owner) {
             Icon::Attack
         } else {
@@ -142,16 +444,68 @@ pub fn filter_missions(missions: &Vec<Mission>, map: &Map, player: &Player) -> V
         .collect::<Vec<_>>()
 }
 
+/// Return the player that fulfilled the victory condition, if any. The planets
+/// and score of teammates count towards the whole team, and every teammate of
+/// the returned player also wins the game
+fn check_victory(
+    settings: &Settings,
+    map: &Map,
+    players: &Vec<Player>,
+    missions: &Vec<Mission>,
+    diplomacy: &Diplomacy,
+) -> Option<ClientId> {
+    let standings = players
+        .iter()
+        .filter(|p| !p.spectator)
+        .map(|p| Standing::new(p, map, missions))
+        .collect::<Vec<_>>();
+
+    // Sum a statistic over the player and its teammates
+    let team = |s: &Standing, stat: fn(&Standing) -> usize| {
+        standings
+            .iter()
+            .filter(|t| t.id == s.id || diplomacy.are_teammates(s.id, t.id))
+            .map(stat)
+            .sum::<usize>()
+    };
+
+    let planets = |s: &Standing| team(s, |t| t.planets);
+    let score = |s: &Standing| team(s, Standing::score);
+
+    let turn_limit = settings.turn >= VICTORY_TURN_LIMIT;
+
+    match settings.victory {
+        Victory::Elimination => None,
+        Victory::Conquest => standings
+            .iter()
+            .filter(|s| planets(s) * 100 >= map.planets.len() * VICTORY_PLANETS)
+            .max_by_key(|s| planets(s)),
+        Victory::Score => standings
+            .iter()
+            .filter(|s| turn_limit || score(s) >= VICTORY_SCORE)
+            .max_by_key(|s| score(s)),
+        Victory::TurnLimit => standings.iter().filter(|_| turn_limit).max_by_key(|s| score(s)),
+    }
+    .map(|s| s.id)
+}
+
 pub fn check_turn_ended(
     state: Res<UiState>,
     mut prev_state: ResMut<PreviousEndTurnState>,
+    settings: Res<Settings>,
     map: Res<Map>,
     player: Res<Player>,
     missions: Res<Missions>,
     mut client_send_msg: MessageWriter<ClientSendMsg>,
+    mut last_sent: Local<Option<f32>>,
+    time: Res<Time<Real>>,
 ) {
-    if prev_state.0 != state.end_turn {
+    // Rapid toggling is debounced, so only the state it settles on reaches the host
+    let cooldown = last_sent.is_some_and(|t| time.elapsed_secs() - t < END_TURN_COOLDOWN);
+
+    if prev_state.0 != state.end_turn && !cooldown {
         client_send_msg.write(ClientSendMsg::new(ClientMessage::EndTurn {
+            turn: settings.turn,
             end_turn: state.end_turn,
             map: map.clone(),
             player: player.clone(),
@@ -159,16 +513,20 @@ pub fn check_turn_ended(
         }));
 
         prev_state.0 = state.end_turn;
+        *last_sent = Some(time.elapsed_secs());
     }
 }
 
 pub fn resolve_turn(
     mut host: ResMut<Host>,
+    server: Option<Res<RenetServer>>,
     mut settings: ResMut<Settings>,
     state: Res<UiState>,
     mut map: ResMut<Map>,
     mut player: ResMut<Player>,
     mut missions: ResMut<Missions>,
+    diplomacy: Res<Diplomacy>,
+    mut standings: ResMut<Standings>,
     mut server_send_msg: MessageWriter<ServerSendMsg>,
     mut start_turn_msg: MessageWriter<StartTurnMsg>,
 ) {
@@ -183,78 +541,463 @@ pub fn resolve_turn(
         .cloned()
         .collect::<Vec<_>>();
 
-    let clients_playing = host.clients.values().filter(|c| !c.spectator).count();
+    let is_connected = |id| server.as_ref().is_none_or(|s| s.is_connected(id));
+
+    if (state.end_turn || player.spectator) && host.all_turns_ended(is_connected) {
+        // Record the initial state of the game for replays
+        if host.history.is_empty() {
+            host.history.push(ReplayTurn::new(settings.turn, &map, &player, &all_missions));
+        }
+
+        // The host takes the cargo of newly sent missions from the owner's stockpile
+        for mission in all_missions.iter_mut().filter(|m| m.cargo.total() > 0) {
+            if let Some(player) = all_players.iter_mut().find(|p| p.id == mission.owner) {
+                mission.load_cargo(&mut player.resources);
+            }
+        }
 
-    if (state.end_turn || player.spectator)
-        && host.turn_ended.len() == clients_playing
-        && (clients_playing > 0 || host.clients.len() == 0)
-    {
         settings.turn += 1;
 
-        // Apply purchases and reset jump gates
+        // Fill the production left idle with the planet's overflow unit
+        for planet in &mut map.planets {
+            if let Some(player) = all_players.iter_mut().find(|p| planet.owned == Some(p.id)) {
+                planet.overflow_build(&mut player.resources);
+            }
+        }
+
+        // Apply purchases and reset jump gates and phalanx scans
+        let mut rallies = vec![];
         map.planets.iter_mut().for_each(|p| {
+            // Purchases that no longer fit in the production capacity are refunded
+            let refund = p.fit_production();
+            if let Some(player) = all_players.iter_mut().find(|pl| p.owned == Some(pl.id)) {
+                player.resources += refund;
+            }
+
+            if let Some(rally) = p.rally.filter(|_| !p.mothballed) {
+                rallies.push((p.id, rally, p.buy.iter().filter(|u| u.is_ship()).copied().counts()));
+            }
+
             p.produce();
             p.jump_gate = 0;
+            p.phalanx_scanned = false;
         });
 
         // Produce resources
+        let mut productions = vec![];
         for player in &mut all_players {
-            let production = player.resource_production(&map.planets);
+            let production = player.resource_production(&map.planets, &settings);
             player.resources += production;
+            player.produced += production.metal + production.crystal + production.deuterium;
+            productions.push(production);
         }
 
-        // Resolve missions in random player order
-        let mut players_shuffled = all_players.clone();
-        players_shuffled.shuffle(&mut rng());
+        // Refill the resources in sandbox mode (only the host plays)
+        if settings.sandbox == Sandbox::Infinite {
+            all_players.iter_mut().for_each(|p| {
+                p.resources =
+                    Resources::new(SANDBOX_RESOURCES, SANDBOX_RESOURCES, SANDBOX_RESOURCES)
+            });
+        }
 
-        let planet_ids = map.planets.iter().map(|p| p.id).collect::<Vec<_>>();
+        if settings.upkeep {
+            for player in &mut all_players {
+                charge_upkeep(player, &mut map.planets, &all_missions);
+            }
+        }
 
-        let mut new_missions = vec![];
-        for player in players_shuffled {
-            for planet_id in &planet_ids {
-                // We loop since a player can change a destination which affects other of its own missions
-                loop {
-                    // Select only arriving missions owned by this player
-                    let arrived = all_missions
-                        .iter()
-                        .filter(|m| {
-                            m.owner == player.id
-                                && m.destination == *planet_id
-                                && m.turns_to_destination(&map) < 2
-                        })
-                        .cloned()
-                        .collect::<Vec<_>>();
+        // Keep track of the economy's history for the production graphs
+        for (player, production) in all_players.iter_mut().zip(productions) {
+            player.record_economy(settings.turn - 1, production);
+        }
 
-                    if arrived.is_empty() {
-                        break; // No more missions to check for this player
-                    }
+        // Queue resource building upgrades on auto-economy planets
+        for planet in &mut map.planets {
+            if let Some(player) = all_players.iter_mut().find(|p| planet.owned == Some(p.id)) {
+                planet.auto_build(&mut player.resources);
+            }
+        }
 
-                    // Resolve missions that reached destination
-                    for mission in regroup_missions(&arrived) {
-                        let new_origin = map.get(mission.check_origin(&map)).clone();
-                        let destination = map.get_mut(mission.destination);
+        transfer_planets(&mut map, &all_players, &mut all_missions, &diplomacy, &settings);
 
-                        let mut report = resolve_combat(settings.turn, &mission, destination);
+        // Deploy the newly built ships to their rally point
+        let mut rally_missions = vec![];
+        for (origin_id, rally_id, army) in rallies.into_iter().filter(|(_, _, a)| a.has_army()) {
+            let origin = map.get(origin_id);
+            let destination = map.get(rally_id);
+
+            let Some(owner) = origin.owned.filter(|&o| {
+                origin_id != rally_id && !destination.is_destroyed && destination.owned == Some(o)
+            }) else {
+                map.get_mut(origin_id).rally = None;
+                continue;
+            };
 
-                        report.mission.logs.push_str(
+            let mission = Mission::new(
+                settings.turn,
+                owner,
+                origin,
+                destination,
+                Icon::Deploy,
+                army,
+                BombingRaid::None,
+                false,
+                false,
+                None,
+            );
+
+            // Ships stay on the planet if there isn't enough fuel
+            let fuel = mission.fuel_consumption(&map);
+            if let Some(player) =
+                all_players.iter_mut().find(|p| p.id == owner && p.resources.deuterium >= fuel)
+            {
+                player.resources.deuterium -= fuel;
+
+                map.get_mut(origin_id).army.iter_mut().for_each(|(u, c)| {
+                    *c -= mission.army.amount(u);
+                });
+
+                rally_missions.push(mission);
+            }
+        }
+
+        // Resolve interceptions of detected enemy missions that arrive next turn
+        let interceptions =
+            map.planets.iter().filter_map(|p| p.intercept.map(|id| (p.id, id))).collect::<Vec<_>>();
+
+        for (planet_id, mission_id) in interceptions {
+            let planet = map.get(planet_id).clone();
+
+            let Some(idx) = all_missions.iter().position(|m| {
+                m.id == mission_id
+                    && m.destination == planet_id
+                    && m.objective != Icon::MissileStrike
+                    && all_players.iter().any(|p| {
+                        p.id != m.owner
+                            && p.controls(&planet)
+                            && !diplomacy.is_protected(p.id, m.owner, settings.turn)
+                            && m.is_seen_by_radar(&map, p).is_some()
+                    })
+            }) else {
+                // The mission is no longer incoming or detected
+                map.get_mut(planet_id).intercept = None;
+                continue;
+            };
+
+            let turns = all_missions[idx].turns_to_destination(&map);
+            if turns > 2 {
+                continue;
+            }
+
+            map.get_mut(planet_id).intercept = None;
+
+            let mission = &mut all_missions[idx];
+
+            let fleet = match interceptors(&planet, mission, turns) {
+                Ok(fleet) => fleet,
+                Err(missed) => {
+                    if missed == MissedInterception::Outran {
+                        mission.logs.push_str(
                             format!(
-                                "\n- ({}) Mission arrived in {}.",
-                                settings.turn, destination.name
+                                "\n- ({}) Outran the interceptors of planet {}.",
+                                settings.turn, planet.name
                             )
                             .as_str(),
                         );
+                    }
+
+                    map.get_mut(planet_id).intercept_failed =
+                        Some((settings.turn, missed.message(&planet)));
+                    continue;
+                },
+            };
+
+            mission.logs.push_str(
+                format!(
+                    "\n- ({}) Intercepted by the fleet of planet {}.",
+                    settings.turn, planet.name
+                )
+                .as_str(),
+            );
+
+            // The interception is fought in transit, without defenses or buildings
+            let mut interceptors = planet.clone();
+            interceptors.army = fleet;
+
+            let intercepted = Mission {
+                objective: Icon::Attack,
+                bombing: BombingRaid::None,
+                ..mission.clone()
+            };
+
+            let mut report =
+                resolve_combat(settings.turn, &intercepted, &interceptors, settings.combat_rounds);
+            report.destination_owned = planet.owned;
+            report.destination_controlled = planet.controlled;
+
+            mission.army = report.surviving_attacker.clone();
+            check_mission(mission, &map, settings.turn, &settings);
+
+            let planet = map.get_mut(planet_id);
+            planet.army.retain(|u, _| !u.is_combat_ship());
+            planet.dock(
+                report
+                    .surviving_defender
+                    .iter()
+                    .filter_map(|(u, c)| u.is_combat_ship().then_some((*u, *c)))
+                    .collect(),
+                planet.veterancy,
+            );
+
+            all_players
+                .iter_mut()
+                .filter(|p| report.planet.controlled == Some(p.id) || report.mission.owner == p.id)
+                .for_each(|p| p.reports.push(report.clone()));
+
+            if !all_missions[idx].army.has_army() {
+                all_missions.remove(idx);
+            }
+        }
+
+        // Resolve missions in random player order. With simultaneous turns, the missions
+        // are resolved per planet in order of arrival (ties broken by player id), which
+        // makes the result independent of the order in which players ended their turn
+        let planet_ids = map.planets.iter().map(|p| p.id).collect::<Vec<_>>();
+
+        let order = if settings.turn_order == TurnOrder::Simultaneous {
+            planet_ids
+                .iter()
+                .flat_map(|planet_id| {
+                    let arrival = |id: &ClientId| {
+                        all_missions
+                            .iter()
+                            .filter(|m| {
+                                m.owner == *id
+                                    && m.destination == *planet_id
+                                    && m.turns_to_destination(&map) < 2
+                            })
+                            .map(|m| m.distance(&map) / m.speed())
+                            .fold(f32::MAX, f32::min)
+                    };
+
+                    all_players
+                        .iter()
+                        .map(|p| p.id)
+                        .sorted_by(|a, b| arrival(a).total_cmp(&arrival(b)).then(a.cmp(b)))
+                        .map(|id| (id, *planet_id))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        } else {
+            let mut players_shuffled = all_players.iter().map(|p| p.id).collect::<Vec<_>>();
+            players_shuffled.shuffle(&mut rng());
+
+            players_shuffled
+                .into_iter()
+                .flat_map(|id| planet_ids.iter().map(move |planet_id| (id, *planet_id)))
+                .collect::<Vec<_>>()
+        };
+
+        // Planets colonized this turn and the player that colonized them
+        let mut colonized: HashMap<PlanetId, ClientId> = HashMap::new();
+
+        let mut new_missions = vec![];
+        for (player_id, planet_id) in order {
+            // We loop since a player can change a destination which affects other of its own missions
+            loop {
+                // Select only arriving missions owned by this player
+                let arrived = all_missions
+                    .iter()
+                    .filter(|m| {
+                        m.owner == player_id
+                            && m.destination == planet_id
+                            && m.turns_to_destination(&map) < 2
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                if arrived.is_empty() {
+                    break; // No more missions to check for this player
+                }
 
-                        if report.scout_probes > 0 {
-                            if mission.objective == Icon::Spy {
+                // Resolve missions that reached destination
+                for mut mission in regroup_missions(&arrived) {
+                    let new_origin = map.get(mission.check_origin(&map)).clone();
+                    let destination = map.get_mut(mission.destination);
+
+                    // Attacks on players with a treaty are called off
+                    if let Some(owner) = destination.controlled.filter(|&c| {
+                        mission.objective != Icon::Deploy
+                            && diplomacy.is_protected(mission.owner, c, settings.turn)
+                    }) {
+                        new_missions.push(Mission {
+                            veterancy: mission.veterancy,
+                            cargo: mission.cargo,
+                            cargo_loaded: true,
+                            ..Mission::new(
+                                settings.turn,
+                                mission.owner,
+                                destination,
+                                &new_origin,
+                                Icon::Deploy,
+                                mission.army.clone(),
+                                BombingRaid::None,
+                                false,
+                                false,
+                                Some(
+                                    mission.logs.clone()
+                                        + format!(
+                                            "\n- ({}) Mission called off due to the treaty \
+                                            with player {owner}. Returning to planet {}.",
+                                            settings.turn, new_origin.name
+                                        )
+                                        .as_str(),
+                                ),
+                            )
+                        });
+                        continue;
+                    }
+
+                    // The first colony ship that's resolved claims the planet
+                    if let Some(bounced) = check_colonized(
+                        &mut mission,
+                        &colonized,
+                        destination,
+                        &new_origin,
+                        settings.turn,
+                    ) {
+                        new_missions.push(bounced);
+                        continue;
+                    }
+
+                    let mut report = resolve_combat(
+                        settings.turn,
+                        &mission,
+                        destination,
+                        settings.combat_rounds,
+                    );
+
+                    // The side that lost the combat still learns something about the enemy
+                    report.apply_intel_leak(settings.intel_leak);
+
+                    // Surviving fleets gain experience after a combat
+                    let fought = settings.veterancy && report.combat_report.is_some();
+
+                    report.mission.logs.push_str(
+                        format!("\n- ({}) Mission arrived in {}.", settings.turn, destination.name)
+                            .as_str(),
+                    );
+
+                    // The cargo is invested in the owner's planet, or lost with the fleet
+                    let cargo = report.mission.cargo;
+                    if cargo.total() > 0 {
+                        if report.winner() == Some(mission.owner)
+                            && report.destination_owned == Some(mission.owner)
+                        {
+                            destination.invest(cargo);
+
+                            report.mission.logs.push_str(
+                                format!(
+                                    "\n- ({}) Invested {} metal, {} crystal and {} deuterium in \
+                                    the production of {}.",
+                                    settings.turn,
+                                    cargo.metal,
+                                    cargo.crystal,
+                                    cargo.deuterium,
+                                    destination.name
+                                )
+                                .as_str(),
+                            );
+                        } else {
+                            report.mission.logs.push_str(
+                                format!("\n- ({}) The cargo was lost.", settings.turn).as_str(),
+                            );
+                        }
+                    }
+
+                    if report.scout_probes > 0 {
+                        if mission.objective == Icon::Spy {
+                            report.mission.logs.push_str(
+                                format!(
+                                    "\n- ({}) Spied on planet {}.",
+                                    settings.turn, destination.name
+                                )
+                                .as_str(),
+                            );
+
+                            new_missions.push(Mission::new(
+                                settings.turn,
+                                report.mission.owner,
+                                destination,
+                                &new_origin,
+                                Icon::Deploy,
+                                report.surviving_attacker.clone(),
+                                BombingRaid::None,
+                                false,
+                                false,
+                                Some(
+                                    report.mission.logs.clone()
+                                        + format!(
+                                            "\n- ({}) Returning to planet {}.",
+                                            settings.turn, new_origin.name
+                                        )
+                                        .as_str(),
+                                ),
+                            ));
+                        } else if report.mission.objective != Icon::Destroy
+                            || report.winner() != Some(mission.owner)
+                        {
+                            // Send probes back that left combat after one round. If the
+                            // mission was a destroy mission and won, the probes are sent
+                            // back with the returning fleet
+                            new_missions.push(Mission::new(
+                                settings.turn,
+                                mission.owner,
+                                destination,
+                                &new_origin,
+                                Icon::Deploy,
+                                Army::from([(Unit::probe(), report.scout_probes)]),
+                                BombingRaid::None,
+                                false,
+                                false,
+                                None,
+                            ));
+                        }
+                    }
+
+                    if report.winner() == Some(mission.owner) {
+                        if report.mission.objective == Icon::Destroy {
+                            if report.planet_destroyed {
+                                destination.destroy();
                                 report.mission.logs.push_str(
                                     format!(
-                                        "\n- ({}) Spied on planet {}.",
+                                        "\n- ({}) Planet {} destroyed.",
                                         settings.turn, destination.name
                                     )
                                     .as_str(),
                                 );
+                            } else if report.surviving_attacker.amount(&Unit::war_sun()) == 0 {
+                                report.mission.logs.push_str(
+                                    format!(
+                                        "\n- ({}) No War Sun survived the combat to destroy \
+                                        planet {}.",
+                                        settings.turn, destination.name
+                                    )
+                                    .as_str(),
+                                );
+                            } else {
+                                report.mission.logs.push_str(
+                                    format!(
+                                        "\n- ({}) Failed to destroy planet {}.",
+                                        settings.turn, destination.name
+                                    )
+                                    .as_str(),
+                                );
+                            }
 
-                                new_missions.push(Mission::new(
+                            new_missions.push(Mission {
+                                veterancy: promote(mission.veterancy, fought),
+                                ..Mission::new(
                                     settings.turn,
                                     report.mission.owner,
                                     destination,
@@ -272,56 +1015,138 @@ pub fn resolve_turn(
                                             )
                                             .as_str(),
                                     ),
-                                ));
-                            } else if report.mission.objective != Icon::Destroy
-                                || report.winner() != Some(mission.owner)
+                                )
+                            });
+                        } else if report.planet_colonized {
+                            *report.surviving_attacker.entry(Unit::colony_ship()).or_insert(1) -= 1;
+                            destination.colonize(mission.owner);
+                            colonized.insert(destination.id, mission.owner);
+
+                            report.mission.logs.push_str(
+                                format!(
+                                    "\n- ({}) Planet {} colonized.",
+                                    settings.turn, destination.name
+                                )
+                                .as_str(),
+                            );
+
+                            // If the planet has no buildings, build level 1 resource buildings
+                            if !destination.has_buildings() {
+                                destination.army.insert(Unit::Building(Building::MetalMine), 1);
+                                destination.army.insert(Unit::Building(Building::CrystalMine), 1);
+                                destination
+                                    .army
+                                    .insert(Unit::Building(Building::DeuteriumSynthesizer), 1);
+                            }
+                        } else if report.mission.objective == Icon::Colonize {
+                            report.mission.logs.push_str(
+                                format!(
+                                    "\n- ({}) No Colony Ship survived the combat to colonize \
+                                    planet {}.",
+                                    settings.turn, destination.name
+                                )
+                                .as_str(),
+                            );
+                        }
+
+                        // Clear defenders from planet
+                        if !(mission.objective == Icon::Deploy
+                            || (mission.objective == Icon::Colonize
+                                && destination.controlled == Some(mission.owner)))
+                        {
+                            destination.army.retain(|u, _| u.is_building());
+                        }
+
+                        // Plunder the defender's stockpile with the fleet's cargo
+                        if let Some(defender) = all_players.iter_mut().find(|p| {
+                            mission.objective != Icon::Destroy
+                                && report.planet.controlled == Some(p.id)
+                                && p.id != mission.owner
+                        }) {
+                            let cargo = report
+                                .surviving_attacker
+                                .iter()
+                                .map(|(u, c)| u.cargo() * c)
+                                .sum::<usize>();
+
+                            report.plunder = defender.resources.plunder(cargo);
+                            defender.resources -= report.plunder;
+                        }
+
+                        if report.plunder.total() > 0 {
+                            if let Some(attacker) =
+                                all_players.iter_mut().find(|p| p.id == mission.owner)
                             {
-                                // Send probes back that left combat after one round. If the
-                                // mission was a destroy mission and won, the probes are sent
-                                // back with the returning fleet
-                                new_missions.push(Mission::new(
-                                    settings.turn,
-                                    mission.owner,
-                                    destination,
-                                    &new_origin,
-                                    Icon::Deploy,
-                                    Army::from([(Unit::probe(), report.scout_probes)]),
-                                    BombingRaid::None,
-                                    false,
-                                    false,
-                                    None,
-                                ));
+                                attacker.resources += report.plunder;
                             }
+
+                            report.mission.logs.push_str(
+                                format!(
+                                    "\n- ({}) Plundered {} metal, {} crystal and {} \
+                                    deuterium from planet {}.",
+                                    settings.turn,
+                                    report.plunder.metal,
+                                    report.plunder.crystal,
+                                    report.plunder.deuterium,
+                                    destination.name
+                                )
+                                .as_str(),
+                            );
                         }
 
-                        if report.winner() == Some(mission.owner) {
-                            if report.mission.objective == Icon::Destroy {
-                                if report.planet_destroyed {
-                                    destination.destroy();
-                                    report.mission.logs.push_str(
-                                        format!(
-                                            "\n- ({}) Planet {} destroyed.",
-                                            settings.turn, destination.name
-                                        )
-                                        .as_str(),
-                                    );
-                                } else {
-                                    report.mission.logs.push_str(
-                                        format!(
-                                            "\n- ({}) Failed to destroy planet {}.",
-                                            settings.turn, destination.name
+                        // Take control of the planet and dock the surviving fleet
+                        if mission.objective != Icon::Destroy {
+                            destination.control(mission.owner);
+                            destination.dock(
+                                report
+                                    .surviving_attacker
+                                    .iter()
+                                    .map(|(u, c)| {
+                                        (
+                                            *u,
+                                            if *u == Unit::probe() {
+                                                c - report.scout_probes
+                                            } else {
+                                                *c
+                                            },
                                         )
-                                        .as_str(),
-                                    );
-                                }
+                                    })
+                                    .collect(),
+                                promote(mission.veterancy, fought),
+                            );
+                        }
+                    } else {
+                        // Merge surviving defenders with planet
+                        destination.army = report.surviving_defender.clone();
+                        destination.veterancy = if destination.army.combat_ships() > 0 {
+                            promote(destination.veterancy, fought)
+                        } else {
+                            0
+                        };
 
-                                new_missions.push(Mission::new(
+                        // After a draw, the surviving attackers return to their origin
+                        if report.is_draw() {
+                            report.mission.logs.push_str(
+                                format!(
+                                    "\n- ({}) Combat ended in a draw after {} rounds.",
+                                    settings.turn, settings.combat_rounds
+                                )
+                                .as_str(),
+                            );
+
+                            let mut army = report.surviving_attacker.clone();
+                            *army.entry(Unit::probe()).or_insert(0) -= report.scout_probes;
+                            army.retain(|_, c| *c > 0);
+
+                            new_missions.push(Mission {
+                                veterancy: promote(mission.veterancy, fought),
+                                ..Mission::new(
                                     settings.turn,
                                     report.mission.owner,
                                     destination,
                                     &new_origin,
                                     Icon::Deploy,
-                                    report.surviving_attacker.clone(),
+                                    army,
                                     BombingRaid::None,
                                     false,
                                     false,
@@ -333,110 +1158,87 @@ pub fn resolve_turn(
                                             )
                                             .as_str(),
                                     ),
-                                ));
-                            } else if report.planet_colonized {
-                                *report
-                                    .surviving_attacker
-                                    .entry(Unit::colony_ship())
-                                    .or_insert(1) -= 1;
-                                destination.colonize(mission.owner);
-
-                                report.mission.logs.push_str(
-                                    format!(
-                                        "\n- ({}) Planet {} colonized.",
-                                        settings.turn, destination.name
-                                    )
-                                    .as_str(),
-                                );
-
-                                // If the planet has no buildings, build level 1 resource buildings
-                                if !destination.has_buildings() {
-                                    destination.army.insert(Unit::Building(Building::MetalMine), 1);
-                                    destination
-                                        .army
-                                        .insert(Unit::Building(Building::CrystalMine), 1);
-                                    destination
-                                        .army
-                                        .insert(Unit::Building(Building::DeuteriumSynthesizer), 1);
-                                }
-                            }
-
-                            // Clear defenders from planet
-                            if !(mission.objective == Icon::Deploy
-                                || (mission.objective == Icon::Colonize
-                                    && destination.controlled == Some(mission.owner)))
-                            {
-                                destination.army.retain(|u, _| u.is_building());
-                            }
-
-                            // Take control of the planet and dock the surviving fleet
-                            if mission.objective != Icon::Destroy {
-                                destination.control(mission.owner);
-                                destination.dock(
-                                    report
-                                        .surviving_attacker
-                                        .iter()
-                                        .map(|(u, c)| {
-                                            (
-                                                *u,
-                                                if *u == Unit::probe() {
-                                                    c - report.scout_probes
-                                                } else {
-                                                    *c
-                                                },
-                                            )
-                                        })
-                                        .collect(),
-                                );
-                            }
-                        } else {
-                            // Merge surviving defenders with planet
-                            destination.army = report.surviving_defender.clone();
+                                )
+                            });
                         }
-
-                        // Update the ownership in the report
-                        report.destination_owned = destination.owned;
-                        report.destination_controlled = destination.controlled;
-
-                        // Attach mission report to relevant players
-                        all_players
-                            .iter_mut()
-                            .filter(|p| {
-                                report.planet.controlled == Some(p.id)
-                                    || report.mission.owner == p.id
-                            })
-                            .for_each(|p| p.reports.push(report.clone()));
                     }
 
-                    // Update all missions whose destination changed
-                    all_missions.retain_mut(|mission| {
-                        check_mission(mission, &map, settings.turn, &settings);
-                        !arrived.iter().map(|m| m.id).contains(&mission.id)
-                    });
+                    // Update the ownership in the report
+                    report.destination_owned = destination.owned;
+                    report.destination_controlled = destination.controlled;
+
+                    // Attach mission report to relevant players
+                    all_players
+                        .iter_mut()
+                        .filter(|p| {
+                            report.planet.controlled == Some(p.id) || report.mission.owner == p.id
+                        })
+                        .for_each(|p| p.reports.push(report.clone()));
                 }
+
+                // Update all missions whose destination changed
+                all_missions.retain_mut(|mission| {
+                    check_mission(mission, &map, settings.turn, &settings);
+                    !arrived.iter().map(|m| m.id).contains(&mission.id)
+                });
             }
         }
 
+        // Clear rally points towards planets that are no longer owned by the same player
+        let owners = map.planets.iter().map(|p| (p.id, p.owned)).collect::<HashMap<_, _>>();
+        map.planets.iter_mut().for_each(|p| {
+            if p.rally.is_some_and(|r| p.owned.is_none() || owners[&r] != p.owned) {
+                p.rally = None;
+            }
+        });
+
         // After all missions that arrived have been resolved, advance all remaining missions
         // and add the new missions
         all_missions.iter_mut().for_each(|m| m.advance(&map));
         all_missions.extend(new_missions);
+        all_missions.extend(rally_missions);
+
+        // Recall fleets deploying from planets with the defensive alert enabled
+        for player in &mut all_players {
+            recall_alerted_fleets(player, &map, &mut all_missions, &diplomacy, settings.turn);
+        }
 
         // Reset missions in the host
         host.missions = vec![];
 
         // Update which players lost the game
+        let mut lost = false;
         let n_playing = all_players
             .iter_mut()
             .map(|p| {
-                p.spectator = !p.owns(map.get(p.home_planet));
+                let spectator = !p.owns(map.get(p.home_planet));
+                lost |= spectator && !p.spectator;
+                p.spectator = spectator;
                 p
             })
             .filter(|p| !p.spectator)
             .count();
 
+        // Check whether a player (or team) won the game
+        let playing = all_players.iter().filter(|p| !p.spectator).map(|p| p.id).collect::<Vec<_>>();
+        let winner = if all_players.len() > 1
+            && !playing.is_empty()
+            && playing
+                .iter()
+                .all(|id| *id == playing[0] || diplomacy.are_teammates(*id, playing[0]))
+        {
+            playing.first().copied()
+        } else {
+            check_victory(&settings, &map, &all_players, &all_missions, &diplomacy)
+        };
+
+        if let Some(winner) = winner.filter(|_| standings.winner.is_none()) {
+            standings.winner = Some(winner);
+            lost = true;
+        }
+
         // If there are still players playing, cleanup resources from players that lost
-        if n_playing > 1 {
+        if n_playing > 1 && winner.is_none() {
             // Remove all units, buys and missions from this player
             all_players.iter_mut().filter(|p| p.spectator).for_each(|p| {
                 map.planets.iter_mut().filter(|pl| pl.controlled == Some(p.id)).for_each(|p| {
@@ -444,11 +1246,23 @@ pub fn resolve_turn(
                 });
                 all_missions.retain(|m| m.owner != p.id);
             });
-        } else if all_players.len() > 1 {
+        } else if winner.is_some() || all_players.len() > 1 {
             // Game is over -> convert everyone to spectator
             all_players.iter_mut().for_each(|p| p.spectator = true);
         }
 
+        // Update the standings shown on the end game screen of the players that lost
+        if lost {
+            standings.players = all_players
+                .iter()
+                .map(|p| Standing::new(p, &map, &all_missions))
+                .sorted_by_key(|s| std::cmp::Reverse(s.score()))
+                .collect();
+
+            server_send_msg
+                .write(ServerSendMsg::new(ServerMessage::Standings(standings.clone()), None));
+        }
+
         for p in &mut all_players {
             let new_missions = if p.spectator {
                 all_missions.clone()
@@ -477,6 +1291,21 @@ pub fn resolve_turn(
             }
         }
 
+        // Spectators that joined the running game see everything
+        for id in &host.observers {
+            server_send_msg.write(ServerSendMsg::new(
+                ServerMessage::StartTurn {
+                    turn: settings.turn,
+                    map: map.clone(),
+                    player: Player::observer(*id, player.home_planet),
+                    missions: Missions(all_missions.clone()),
+                },
+                Some(*id),
+            ));
+        }
+
+        host.history.push(ReplayTurn::new(settings.turn, &map, &player, &all_missions));
+
         host.turn_ended.clear();
         host.received.clear();
 
@@ -490,8 +1319,10 @@ pub fn start_turn(
     planet_q: Query<(&Transform, &PlanetCmp)>,
     settings: Res<Settings>,
     mut state: ResMut<UiState>,
-    map: Res<Map>,
-    player: Res<Player>,
+    mut map: ResMut<Map>,
+    mut player: ResMut<Player>,
+    missions: Res<Missions>,
+    diplomacy: Res<Diplomacy>,
     mut play_audio_msg: MessageWriter<PlayAudioMsg>,
     mut message: MessageWriter<MessageMsg>,
     mut save_game_msg: MessageWriter<SaveGameMsg>,
@@ -503,9 +1334,38 @@ pub fn start_turn(
             mission_hover: None, // Reset hover since missions can no longer exist
             lab: state.lab,
             mission_report: state.mission_report,
+            snapshot: state.snapshot.take(),
+            last_mission: state.last_mission.take(),
+            jump_gate_routes: std::mem::take(&mut state.jump_gate_routes),
             ..default()
         };
 
+        // Queue the default build order on the planets colonized this turn
+        if settings.build_order != BuildOrder::Manual && !player.spectator {
+            let colonized = player
+                .reports
+                .iter()
+                .filter(|r| {
+                    r.turn == settings.turn && r.planet_colonized && r.mission.owner == player.id
+                })
+                .map(|r| r.planet.id)
+                .collect::<Vec<_>>();
+
+            for id in colonized {
+                let planet = map.get_mut(id);
+                if player.owns(planet)
+                    && !planet
+                        .queue_build_order(settings.build_order, &mut player.resources)
+                        .is_empty()
+                {
+                    message.write(MessageMsg::info(format!(
+                        "Build order queued on planet {}.",
+                        planet.name
+                    )));
+                }
+            }
+        }
+
         let new_reports = player
             .reports
             .iter()
@@ -513,6 +1373,7 @@ pub fn start_turn(
             .collect::<Vec<_>>();
 
         if !msg.skip_battle
+            && !settings.auto_resolve
             && new_reports.iter().any(|r| {
                 r.combat_report.is_some()
                     && r.can_see(&Side::Attacker, player.id)
@@ -526,11 +1387,103 @@ pub fn start_turn(
             break;
         }
 
+        // Summarize the changes since the previous turn
+        let planets = map
+            .planets
+            .iter()
+            .filter(|p| player.owns(p))
+            .map(|p| (p.id, p.army.clone()))
+            .collect::<HashMap<_, _>>();
+
+        if let Some(previous) = state.snapshot.take().filter(|s| s.turn + 1 == settings.turn) {
+            if settings.show_summary && !player.spectator {
+                state.turn_summary = Some(TurnSummary {
+                    resources: player.resource_production(&map.planets, &settings),
+                    buildings: planets
+                        .iter()
+                        .filter_map(|(id, army)| previous.planets.get(id).map(|a| (id, army, a)))
+                        .flat_map(|(id, army, old)| {
+                            army.iter().filter_map(move |(u, c)| match u {
+                                Unit::Building(b) if old.amount(u) < *c => Some((*id, *b, *c)),
+                                _ => None,
+                            })
+                        })
+                        .sorted_by_key(|(id, _, _)| *id)
+                        .collect(),
+                    missions: new_reports.len(),
+                    combats: new_reports
+                        .iter()
+                        .filter(|r| {
+                            r.combat_report.is_some()
+                                && r.can_see(&Side::Attacker, player.id)
+                                && r.can_see(&Side::Defender, player.id)
+                        })
+                        .count(),
+                    gained: planets
+                        .keys()
+                        .filter(|id| !previous.planets.contains_key(id))
+                        .copied()
+                        .sorted()
+                        .collect(),
+                    lost: previous
+                        .planets
+                        .keys()
+                        .filter(|id| !planets.contains_key(id))
+                        .copied()
+                        .sorted()
+                        .collect(),
+                });
+            }
+        }
+
+        state.snapshot = Some(TurnSnapshot {
+            turn: settings.turn,
+            planets,
+        });
+
         if settings.autosave {
             save_game_msg.write(SaveGameMsg(true));
         }
 
-        message.write(MessageMsg::info(format!("Turn {} started.", settings.turn)));
+        message.write(MessageMsg::info(trf("turn.started", &[&settings.turn])));
+
+        // Warn about the detected enemy missions that arrive next turn
+        if !player.spectator {
+            state.imminent_attacks = missions.imminent_attacks(&map, &player, &diplomacy);
+            for id in &state.imminent_attacks {
+                message.write(MessageMsg::warning(format!(
+                    "A detected enemy mission reaches planet {} next turn!",
+                    map.get(*id).name
+                )));
+            }
+        }
+
+        // Notify the interception orders that failed this turn
+        for planet in map.planets.iter().filter(|p| player.controls(p)) {
+            if let Some((_, text)) =
+                planet.intercept_failed.as_ref().filter(|(t, _)| *t == settings.turn)
+            {
+                message.write(MessageMsg::warning(text.clone()));
+            }
+        }
+
+        // Notify the planets transferred between allies this turn
+        for planet in &map.planets {
+            if let Some((from, _)) = planet.gifted.filter(|(_, t)| *t == settings.turn) {
+                if planet.owned == Some(player.id) {
+                    message.write(MessageMsg::diplomacy(format!(
+                        "Player {from} transferred planet {} to you.",
+                        planet.name
+                    )));
+                } else if from == player.id {
+                    message.write(MessageMsg::diplomacy(format!(
+                        "Planet {} transferred to player {}.",
+                        planet.name,
+                        planet.owned.unwrap()
+                    )));
+                }
+            }
+        }
 
         // Spawn explosion animation for newly destroyed planets
         map.planets.iter().filter(|p| p.is_destroyed && p.image != 0).for_each(|p| {
@@ -560,6 +1513,20 @@ pub fn start_turn(
                 let origin = map.get(report.mission.origin);
                 let destination = map.get(report.mission.destination);
 
+                if settings.auto_resolve
+                    && report.combat_report.is_some()
+                    && report.can_see(&Side::Attacker, player.id)
+                    && report.can_see(&Side::Defender, player.id)
+                {
+                    message.write(MessageMsg::combat(
+                        format!(
+                            "Battle of {} resolved. Check the mission reports for details.",
+                            destination.name
+                        ),
+                        Some(report.id),
+                    ));
+                }
+
                 match report.mission.objective {
                     Icon::Deploy if report.mission.origin_controlled != Some(player.id) => {
                         if report.mission.army.len() == 1
@@ -611,7 +1578,7 @@ pub fn start_turn(
                                 )));
                             } else {
                                 message.write(MessageMsg::warning(format!(
-                                    "All probes lost while spying planet {}.",
+                                    "All probes lost after spying planet {}.",
                                     destination.name
                                 )));
                             }
@@ -642,16 +1609,27 @@ pub fn start_turn(
                         )));
                     },
                     _ => {
-                        if report.winner() == Some(player.id) {
-                            message.write(MessageMsg::info(format!(
-                                "Battle won at planet {}.",
-                                destination.name
-                            )));
+                        // Only link the combat when the player may see both sides of it
+                        let id = (report.combat_report.is_some()
+                            && report.can_see(&Side::Attacker, player.id)
+                            && report.can_see(&Side::Defender, player.id))
+                        .then_some(report.id);
+
+                        if report.is_draw() {
+                            message.write(MessageMsg::combat(
+                                format!("Battle at planet {} ended in a draw.", destination.name),
+                                id,
+                            ));
+                        } else if report.winner() == Some(player.id) {
+                            message.write(MessageMsg::combat(
+                                format!("Battle won at planet {}.", destination.name),
+                                id,
+                            ));
                         } else {
-                            message.write(MessageMsg::warning(format!(
-                                "Battle lost at planet {}.",
-                                destination.name
-                            )));
+                            message.write(MessageMsg::combat(
+                                format!("Battle lost at planet {}.", destination.name),
+                                id,
+                            ));
                         }
                     },
                 }
@@ -662,3 +1640,455 @@ pub fn start_turn(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::diplomacy::{DiplomacyAction, Relation};
+    use crate::core::missions::MissionId;
+    use crate::core::settings::{ResourceBias, Teams};
+    use crate::core::units::defense::Defense;
+    use crate::core::units::ships::Ship;
+
+    fn planet(army: Army) -> Planet {
+        Planet {
+            army,
+            ..Planet::new(0, "Home".into(), Vec2::ZERO, false, 1., ResourceBias::None)
+        }
+    }
+
+    fn mission(army: Army) -> Mission {
+        Mission {
+            objective: Icon::Attack,
+            army,
+            ..default()
+        }
+    }
+
+    fn deploy(id: MissionId, origin: PlanetId, army: Army, veterancy: usize) -> Mission {
+        Mission {
+            id,
+            origin,
+            destination: 0,
+            objective: Icon::Deploy,
+            army,
+            veterancy,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn simultaneous_deploys_sum_into_the_destination() {
+        let missions = vec![
+            deploy(1, 1, Army::from([(Unit::Ship(Ship::Cruiser), 2)]), 0),
+            deploy(2, 2, Army::from([(Unit::Ship(Ship::Battleship), 3)]), 2),
+            deploy(3, 3, Army::from([(Unit::Ship(Ship::Cruiser), 1), (Unit::probe(), 4)]), 4),
+        ];
+
+        let regrouped = regroup_missions(&missions);
+        assert_eq!(regrouped.len(), 1);
+
+        let mut planet = planet(Army::from([(Unit::Ship(Ship::Cruiser), 5)]));
+        planet.dock(regrouped[0].army.clone(), regrouped[0].veterancy);
+        assert_eq!(planet.army.amount(&Unit::Ship(Ship::Cruiser)), 8);
+        assert_eq!(planet.army.amount(&Unit::Ship(Ship::Battleship)), 3);
+        assert_eq!(planet.army.amount(&Unit::probe()), 4);
+    }
+
+    #[test]
+    fn regrouped_missions_do_not_depend_on_the_order() {
+        let missions = vec![
+            deploy(1, 1, Army::from([(Unit::Ship(Ship::Cruiser), 2)]), 0),
+            deploy(2, 2, Army::from([(Unit::Ship(Ship::Battleship), 3)]), 2),
+            deploy(3, 3, Army::from([(Unit::Ship(Ship::Cruiser), 1), (Unit::probe(), 4)]), 4),
+            Mission {
+                objective: Icon::Attack,
+                ..deploy(4, 1, Army::from([(Unit::Ship(Ship::Destroyer), 6)]), 1)
+            },
+        ];
+
+        let expected = regroup_missions(&missions);
+        for order in missions.iter().cloned().permutations(missions.len()) {
+            let regrouped = regroup_missions(&order);
+            assert_eq!(regrouped.len(), expected.len());
+            for (m, e) in regrouped.iter().zip(&expected) {
+                assert_eq!(m.id, e.id);
+                assert_eq!(m.origin, e.origin);
+                assert_eq!(m.objective, e.objective);
+                assert_eq!(m.army, e.army);
+                assert_eq!(m.veterancy, e.veterancy);
+                assert_eq!(m.logs, e.logs);
+            }
+        }
+    }
+
+    /// Home planet of player 1, an empty planet and a planet of player 2
+    fn map() -> Map {
+        let planet = |id, controlled| Planet {
+            owned: controlled,
+            controlled,
+            ..Planet::new(
+                id,
+                format!("P{id}"),
+                Vec2::new(id as f32 * 500., 0.),
+                false,
+                1.,
+                ResourceBias::None,
+            )
+        };
+
+        Map {
+            rect: Rect::default(),
+            planets: vec![planet(0, Some(1)), planet(1, None), planet(2, Some(2))],
+            wormholes: vec![],
+        }
+    }
+
+    fn check(objective: Icon, destination: PlanetId, army: Army, p_colonizable: usize) -> Mission {
+        let mut mission = Mission {
+            owner: 1,
+            destination,
+            objective,
+            army,
+            ..default()
+        };
+
+        let settings = Settings {
+            p_colonizable,
+            ..default()
+        };
+
+        check_mission(&mut mission, &map(), 3, &settings);
+        mission
+    }
+
+    #[test]
+    fn colonize_without_colony_ship_deploys() {
+        let mission = check(Icon::Colonize, 1, Army::from([(Unit::Ship(Ship::Cruiser), 2)]), 100);
+        assert_eq!(mission.objective, Icon::Deploy);
+        assert!(mission.logs.contains("No Colony Ship left to colonize the planet."));
+        assert!(mission.logs.contains("Objective changed to Deploy."));
+
+        let mission = check(Icon::Colonize, 1, Army::from([(Unit::colony_ship(), 1)]), 100);
+        assert_eq!(mission.objective, Icon::Colonize);
+    }
+
+    #[test]
+    fn colonize_over_the_planet_cap_attacks() {
+        // A third of the planets can be owned, and player 1 already owns one
+        let mission = check(Icon::Colonize, 1, Army::from([(Unit::colony_ship(), 1)]), 33);
+        assert_eq!(mission.objective, Icon::Attack);
+        assert!(mission.logs.contains("Maximum number of colonized planets reached."));
+    }
+
+    #[test]
+    fn destroy_without_war_sun_attacks() {
+        let mission = check(Icon::Destroy, 2, Army::from([(Unit::Ship(Ship::Cruiser), 2)]), 100);
+        assert_eq!(mission.objective, Icon::Attack);
+        assert!(mission.logs.contains("No War Sun left to destroy the planet."));
+        assert!(mission.logs.contains("Objective changed to Attack."));
+    }
+
+    #[test]
+    fn only_the_first_colony_ship_claims_the_planet() {
+        let origin = planet(Army::new());
+        let target =
+            Planet::new(1, "Target".into(), Vec2::new(500., 0.), false, 1., ResourceBias::None);
+        let colonize = |owner| Mission {
+            owner,
+            origin: origin.id,
+            destination: target.id,
+            objective: Icon::Colonize,
+            army: Army::from([(Unit::colony_ship(), 1)]),
+            ..default()
+        };
+
+        let mut colonized = HashMap::new();
+
+        let mut first = colonize(1);
+        assert!(check_colonized(&mut first, &colonized, &target, &origin, 3).is_none());
+        assert!(resolve_combat(3, &first, &target, 4).planet_colonized);
+        colonized.insert(target.id, first.owner);
+
+        // The colony ship of the other player returns home and explains why
+        let mut second = colonize(2);
+        let bounced = check_colonized(&mut second, &colonized, &target, &origin, 3).unwrap();
+        assert_eq!(bounced.objective, Icon::Deploy);
+        assert_eq!(bounced.destination, origin.id);
+        assert_eq!(bounced.army, second.army);
+        assert!(bounced.logs.contains("Planet Target was colonized first by player 1."));
+
+        // Another colony ship of the same player deploys instead
+        let mut third = colonize(1);
+        assert!(check_colonized(&mut third, &colonized, &target, &origin, 3).is_none());
+        assert_eq!(third.objective, Icon::Deploy);
+        assert!(third.logs.contains("was already colonized by another mission"));
+    }
+
+    #[test]
+    fn slower_missions_are_caught() {
+        let planet = planet(Army::from([(Unit::Ship(Ship::Cruiser), 5)]));
+        let mission = mission(Army::from([(Unit::Ship(Ship::Battleship), 2)]));
+        assert_eq!(
+            interceptors(&planet, &mission, 2),
+            Ok(Army::from([(Unit::Ship(Ship::Cruiser), 5)]))
+        );
+    }
+
+    #[test]
+    fn faster_missions_outrun_the_interceptors() {
+        let planet = planet(Army::from([(Unit::Ship(Ship::Cruiser), 5)]));
+        let mission = mission(Army::from([(Unit::Ship(Ship::LightFighter), 20)]));
+        assert_eq!(interceptors(&planet, &mission, 2), Err(MissedInterception::Outran));
+    }
+
+    #[test]
+    fn missions_arriving_this_turn_are_too_late_to_intercept() {
+        let planet = planet(Army::from([(Unit::Ship(Ship::Cruiser), 5)]));
+        let mission = mission(Army::from([(Unit::Ship(Ship::Battleship), 2)]));
+        assert_eq!(interceptors(&planet, &mission, 1), Err(MissedInterception::TooLate));
+        assert!(MissedInterception::TooLate.message(&planet).starts_with("Too late to intercept"));
+    }
+
+    #[test]
+    fn planets_without_fleet_cannot_intercept() {
+        let mut planet = planet(Army::from([(Unit::Defense(Defense::RocketLauncher), 10)]));
+        let mission = mission(Army::from([(Unit::Ship(Ship::Battleship), 2)]));
+        assert_eq!(interceptors(&planet, &mission, 2), Err(MissedInterception::NoFleet));
+
+        planet.army.insert(Unit::Ship(Ship::Cruiser), 5);
+        planet.mothballed = true;
+        assert_eq!(interceptors(&planet, &mission, 2), Err(MissedInterception::NoFleet));
+    }
+
+    /// Player 1 owns its home planet 0 and offers planet 1 to player 2
+    fn transfer(allied: bool, p_colonizable: usize, planet_id: PlanetId) -> (Map, Mission) {
+        let mut map = map();
+        map.get_mut(1).owned = Some(1);
+        map.get_mut(1).controlled = Some(1);
+        map.get_mut(planet_id).transfer = Some(2);
+
+        let mut diplomacy = Diplomacy::new(&[1, 2], Teams::default());
+        if allied {
+            diplomacy.apply(1, DiplomacyAction::Propose(2, Relation::Alliance), 1);
+            diplomacy.apply(2, DiplomacyAction::Accept(1), 1);
+        }
+
+        let players = [1, 2].map(|id| Player {
+            id,
+            home_planet: if id == 1 {
+                0
+            } else {
+                2
+            },
+            ..default()
+        });
+
+        let mut missions = [Mission {
+            owner: 1,
+            destination: planet_id,
+            ..deploy(1, 0, Army::from([(Unit::Ship(Ship::Cruiser), 2)]), 0)
+        }];
+
+        let settings = Settings {
+            p_colonizable,
+            turn: 3,
+            ..default()
+        };
+
+        transfer_planets(&mut map, &players, &mut missions, &diplomacy, &settings);
+        let [mission] = missions;
+        (map, mission)
+    }
+
+    #[test]
+    fn planet_is_transferred_to_an_ally() {
+        let (map, mission) = transfer(true, 100, 1);
+        let planet = map.get(1);
+        assert_eq!(planet.owned, Some(2));
+        assert_eq!(planet.controlled, Some(2));
+        assert_eq!(planet.gifted, Some((1, 3)));
+        assert_eq!(planet.transfer, None);
+        assert!(mission.logs.contains("Planet P1 was transferred to player 2."));
+    }
+
+    #[test]
+    fn transfer_to_a_non_ally_is_withdrawn() {
+        let (map, mission) = transfer(false, 100, 1);
+        assert_eq!(map.get(1).owned, Some(1));
+        assert_eq!(map.get(1).transfer, None);
+        assert!(mission.logs.is_empty());
+    }
+
+    #[test]
+    fn transfer_waits_until_the_ally_has_room() {
+        // A third of the planets can be owned, and player 2 already owns one
+        let (map, _) = transfer(true, 33, 1);
+        assert_eq!(map.get(1).owned, Some(1));
+        assert_eq!(map.get(1).transfer, Some(2));
+    }
+
+    #[test]
+    fn home_planet_cannot_be_transferred() {
+        let (map, _) = transfer(true, 100, 0);
+        assert_eq!(map.get(0).owned, Some(1));
+        assert_eq!(map.get(0).transfer, None);
+    }
+
+    #[test]
+    fn surviving_fleets_are_promoted_up_to_the_cap() {
+        assert_eq!(promote(0, true), 1);
+        assert_eq!(promote(MAX_VETERANCY - 1, true), MAX_VETERANCY);
+        assert_eq!(promote(MAX_VETERANCY, true), MAX_VETERANCY);
+
+        // Fleets that didn't fight keep their veterancy
+        assert_eq!(promote(2, false), 2);
+    }
+
+    #[test]
+    fn unpaid_fleets_are_mothballed() {
+        let mut planets = map().planets;
+        planets[0].army = Army::from([(Unit::Ship(Ship::Cruiser), 10)]);
+        planets[1].controlled = Some(1);
+        planets[1].army = Army::from([(Unit::Ship(Ship::LightFighter), 10)]);
+        let (big, small) = (planets[0].upkeep(), planets[1].upkeep());
+
+        let missions = [Mission {
+            owner: 1,
+            ..mission(Army::from([(Unit::Ship(Ship::Destroyer), 5)]))
+        }];
+        let on_mission = missions[0].upkeep();
+
+        // Missions pay first, then the cheapest planets while the deuterium lasts
+        let mut player = Player {
+            id: 1,
+            resources: Resources::new(0, 0, on_mission + small + big - 1),
+            ..default()
+        };
+        charge_upkeep(&mut player, &mut planets, &missions);
+        assert!(!planets[1].mothballed);
+        assert!(planets[0].mothballed);
+        assert_eq!(player.resources.deuterium, big - 1);
+
+        // The fleet is reactivated once its upkeep can be paid again
+        player.resources.deuterium = on_mission + small + big;
+        charge_upkeep(&mut player, &mut planets, &missions);
+        assert!(!planets[0].mothballed);
+        assert_eq!(player.resources.deuterium, 0);
+
+        // Other players' planets are never charged
+        assert!(!planets[2].mothballed);
+    }
+
+    fn victory(victory: Victory, turn: usize, map: &Map) -> Option<ClientId> {
+        let settings = Settings {
+            victory,
+            turn,
+            ..default()
+        };
+        let players = [1, 2]
+            .into_iter()
+            .map(|id| Player {
+                id,
+                ..default()
+            })
+            .collect();
+
+        check_victory(&settings, map, &players, &vec![], &Diplomacy::new(&[1, 2], Teams::default()))
+    }
+
+    #[test]
+    fn conquest_requires_half_of_the_planets() {
+        let mut map = map();
+        assert_eq!(victory(Victory::Conquest, 3, &map), None);
+
+        map.get_mut(1).controlled = Some(1);
+        assert_eq!(victory(Victory::Conquest, 3, &map), Some(1));
+        assert_eq!(victory(Victory::Elimination, 3, &map), None);
+    }
+
+    #[test]
+    fn turn_limit_crowns_the_highest_score() {
+        let mut map = map();
+        map.get_mut(1).controlled = Some(2);
+
+        for condition in [Victory::Score, Victory::TurnLimit] {
+            assert_eq!(victory(condition, VICTORY_TURN_LIMIT - 1, &map), None);
+            assert_eq!(victory(condition, VICTORY_TURN_LIMIT, &map), Some(2));
+        }
+    }
+
+    #[test]
+    fn deploys_to_abandoned_planets_still_arrive() {
+        let army = Army::from([(Unit::Ship(Ship::Cruiser), 2)]);
+        assert_eq!(check(Icon::Deploy, 1, army.clone(), 100).objective, Icon::Deploy);
+        assert_eq!(check(Icon::Deploy, 2, army.clone(), 100).objective, Icon::Attack);
+
+        // Uncontrolled planets that kept their defenses must be attacked
+        let mut map = map();
+        map.planets[1].army = Army::from([(Unit::Defense(Defense::RocketLauncher), 3)]);
+        let mut mission = Mission {
+            owner: 1,
+            destination: 1,
+            objective: Icon::Deploy,
+            army,
+            ..default()
+        };
+
+        check_mission(&mut mission, &map, 3, &Settings::default());
+        assert_eq!(mission.objective, Icon::Attack);
+    }
+
+    /// Player 1 deploys from its home planet while player 2 attacks it
+    fn recall(alert: bool, deuterium: usize) -> (Player, Mission) {
+        let mut map = map();
+        let home = map.get_mut(0);
+        home.alert = alert;
+        home.army.insert(Unit::Building(Building::SensorPhalanx), 1);
+
+        let mut player = Player {
+            id: 1,
+            resources: Resources::new(0, 0, deuterium),
+            ..default()
+        };
+
+        let mut missions = [
+            Mission {
+                owner: 1,
+                destination: 2,
+                position: Vec2::new(400., 0.),
+                ..deploy(1, 0, Army::from([(Unit::Ship(Ship::Cruiser), 2)]), 0)
+            },
+            Mission {
+                owner: 2,
+                origin: 2,
+                destination: 0,
+                position: Vec2::new(50., 0.),
+                ..mission(Army::from([(Unit::Ship(Ship::Cruiser), 5)]))
+            },
+        ];
+
+        let diplomacy = Diplomacy::new(&[1, 2], Teams::default());
+        recall_alerted_fleets(&mut player, &map, &mut missions, &diplomacy, 3);
+        let [recalled, _] = missions;
+        (player, recalled)
+    }
+
+    #[test]
+    fn alert_recalls_the_deploying_fleets() {
+        let (player, mission) = recall(true, 1000);
+        assert_eq!(mission.destination, 0);
+        assert!(mission.logs.contains("Recalled to defend planet P0."));
+        assert_eq!(player.resources.deuterium, 1000 - mission.fuel_consumption(&map()));
+
+        let (_, mission) = recall(false, 1000);
+        assert_eq!(mission.destination, 2);
+    }
+
+    #[test]
+    fn fleets_without_fuel_are_not_recalled() {
+        let (player, mission) = recall(true, 0);
+        assert_eq!(mission.destination, 2);
+        assert_eq!(player.resources.deuterium, 0);
+    }
+}
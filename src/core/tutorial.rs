@@ -0,0 +1,203 @@
+use bevy::prelude::*;
+use bevy_egui::egui::{Align2, Color32, Order, RichText};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::core::map::icon::Icon;
+use crate::core::map::map::Map;
+use crate::core::map::planet::PlanetId;
+use crate::core::messages::MessageMsg;
+use crate::core::missions::SendMissionMsg;
+use crate::core::player::Player;
+use crate::core::ui::locale::tr;
+use crate::core::ui::systems::UiState;
+use crate::core::units::buildings::Building;
+use crate::core::units::Unit;
+
+/// Steps of the guided first game, in the order they must be completed
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TutorialStep {
+    #[default]
+    SelectPlanet,
+    BuildMine,
+    SendScout,
+    Attack,
+}
+
+impl TutorialStep {
+    pub fn title(&self) -> &str {
+        match self {
+            TutorialStep::SelectPlanet => "Select your home planet",
+            TutorialStep::BuildMine => "Build a mine",
+            TutorialStep::SendScout => "Send a scout",
+            TutorialStep::Attack => "Launch an attack",
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        match self {
+            TutorialStep::SelectPlanet => {
+                "Click on your home planet (highlighted on the map) or press space to center \
+                the map on it. The overview on the left shows its resources and units."
+            },
+            TutorialStep::BuildMine => {
+                "Open the shop (B) and buy a Metal Mine, a Crystal Mine or a Deuterium \
+                Synthesizer on your home planet. Mines increase the resources you receive \
+                every turn."
+            },
+            TutorialStep::SendScout => {
+                "Buy a Probe in the shop, end the turn (ctrl + enter) and open the mission \
+                panel (M). Choose the Spy objective and send the probe to a nearby planet to \
+                reveal its units."
+            },
+            TutorialStep::Attack => {
+                "Build some combat ships and send them on an Attack mission. Check the \
+                combat report afterwards to see how the battle went."
+            },
+        }
+    }
+
+    pub fn next(&self) -> Option<Self> {
+        match self {
+            TutorialStep::SelectPlanet => Some(TutorialStep::BuildMine),
+            TutorialStep::BuildMine => Some(TutorialStep::SendScout),
+            TutorialStep::SendScout => Some(TutorialStep::Attack),
+            TutorialStep::Attack => None,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        *self as usize + 1
+    }
+}
+
+/// State machine of the guided first game. Only inserted in new single player
+/// games with the tutorial enabled, and removed when finished or skipped
+#[derive(Resource, Default)]
+pub struct Tutorial {
+    pub step: TutorialStep,
+}
+
+impl Tutorial {
+    pub const STEPS: usize = 4;
+
+    /// Whether the current step's goal has been reached
+    fn is_completed(
+        &self,
+        state: &UiState,
+        map: &Map,
+        player: &Player,
+        missions: &[&SendMissionMsg],
+    ) -> bool {
+        let home = player.home_planet;
+
+        match self.step {
+            TutorialStep::SelectPlanet => state.planet_selected == Some(home),
+            TutorialStep::BuildMine => map.get(home).buy.iter().any(|u| {
+                matches!(
+                    u,
+                    Unit::Building(
+                        Building::MetalMine
+                            | Building::CrystalMine
+                            | Building::DeuteriumSynthesizer
+                    )
+                )
+            }),
+            TutorialStep::SendScout => missions.iter().any(|m| m.mission.objective == Icon::Spy),
+            TutorialStep::Attack => missions.iter().any(|m| m.mission.objective == Icon::Attack),
+        }
+    }
+
+    /// Planet to highlight on the map for the current step
+    fn target(&self, player: &Player) -> Option<PlanetId> {
+        match self.step {
+            TutorialStep::SelectPlanet | TutorialStep::BuildMine => Some(player.home_planet),
+            _ => None,
+        }
+    }
+}
+
+pub fn update_tutorial(
+    mut commands: Commands,
+    tutorial: Option<ResMut<Tutorial>>,
+    state: Res<UiState>,
+    map: Res<Map>,
+    player: Res<Player>,
+    mut send_mission_msg: MessageReader<SendMissionMsg>,
+    mut message: MessageWriter<MessageMsg>,
+) {
+    // Always read the messages, so old missions don't complete later steps
+    let missions = send_mission_msg.read().collect::<Vec<_>>();
+
+    let Some(mut tutorial) = tutorial else {
+        return;
+    };
+
+    if tutorial.is_completed(&state, &map, &player, &missions) {
+        if let Some(next) = tutorial.step.next() {
+            message
+                .write(MessageMsg::info(format!("Tutorial: {} completed.", tutorial.step.title())));
+            tutorial.step = next;
+        } else {
+            message.write(MessageMsg::info(tr("tutorial.completed")));
+            commands.remove_resource::<Tutorial>();
+        }
+    }
+}
+
+pub fn highlight_tutorial(
+    mut gizmos: Gizmos,
+    tutorial: Option<Res<Tutorial>>,
+    map: Res<Map>,
+    player: Res<Player>,
+    time: Res<Time>,
+) {
+    let Some(target) = tutorial.and_then(|t| t.target(&player)) else {
+        return;
+    };
+
+    // Pulse the ring around the planet to draw the player's attention
+    let planet = map.get(target);
+    let pulse = 1. + 0.1 * (time.elapsed_secs() * 4.).sin();
+    gizmos.circle_2d(planet.position, planet.size() * 0.8 * pulse, Color::srgb(1., 0.85, 0.2));
+}
+
+pub fn draw_tutorial(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    tutorial: Option<Res<Tutorial>>,
+) {
+    let Some(tutorial) = tutorial else {
+        return;
+    };
+
+    egui::Area::new("tutorial".into())
+        .order(Order::Foreground)
+        .anchor(Align2::CENTER_TOP, [0., 60.])
+        .show(contexts.ctx_mut().unwrap(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.set_max_width(420.);
+
+                ui.label(
+                    RichText::new(format!(
+                        "Tutorial ({}/{}): {}",
+                        tutorial.step.index(),
+                        Tutorial::STEPS,
+                        tutorial.step.title()
+                    ))
+                    .strong()
+                    .color(Color32::from_rgb(255, 215, 50)),
+                );
+                ui.label(tutorial.step.description());
+
+                ui.add_space(5.);
+
+                if ui.button("Skip tutorial").clicked() {
+                    commands.remove_resource::<Tutorial>();
+                }
+            });
+        });
+}
+
+pub fn exit_tutorial(mut commands: Commands) {
+    commands.remove_resource::<Tutorial>();
+}
@@ -1,10 +1,15 @@
-use bevy_egui::egui::{Color32, Vec2};
+use std::collections::BTreeMap;
 
-use crate::core::constants::OWN_COLOR;
+use bevy_egui::egui::{Color32, FontFamily, FontId, TextStyle, Vec2};
+
+use crate::core::constants::{LARGE_TEXT_SCALE, OWN_COLOR};
 use crate::core::ui::aesthetics::Aesthetics;
 use crate::utils::ToColor32;
 
-pub struct NordDark;
+pub struct NordDark {
+    /// Larger text and stronger contrast for readability
+    pub large_text: bool,
+}
 
 impl Aesthetics for NordDark {
     fn name(&self) -> &str {
@@ -16,7 +21,11 @@ impl Aesthetics for NordDark {
     }
 
     fn bg_primary_color_visuals(&self) -> Color32 {
-        Color32::from_rgb(14, 21, 26)
+        if self.large_text {
+            Color32::from_rgb(4, 6, 8)
+        } else {
+            Color32::from_rgb(14, 21, 26)
+        }
     }
 
     fn bg_secondary_color_visuals(&self) -> Color32 {
@@ -32,11 +41,19 @@ impl Aesthetics for NordDark {
     }
 
     fn bg_contrast_color_visuals(&self) -> Color32 {
-        Color32::from_rgb(59, 66, 82)
+        if self.large_text {
+            Color32::from_rgb(136, 192, 208)
+        } else {
+            Color32::from_rgb(59, 66, 82)
+        }
     }
 
     fn fg_primary_text_color_visuals(&self) -> Option<Color32> {
-        Some(Color32::from_rgb(216, 222, 233))
+        if self.large_text {
+            Some(Color32::WHITE)
+        } else {
+            Some(Color32::from_rgb(216, 222, 233))
+        }
     }
 
     fn fg_warn_text_color_visuals(&self) -> Color32 {
@@ -69,4 +86,21 @@ impl Aesthetics for NordDark {
     fn rounding_visuals(&self) -> u8 {
         6
     }
+
+    fn custom_text_styles(&self) -> BTreeMap<TextStyle, FontId> {
+        let scale = if self.large_text {
+            LARGE_TEXT_SCALE
+        } else {
+            1.
+        };
+
+        [
+            (TextStyle::Small, FontId::new(18. * scale, FontFamily::Proportional)),
+            (TextStyle::Body, FontId::new(23. * scale, FontFamily::Proportional)),
+            (TextStyle::Button, FontId::new(20. * scale, FontFamily::Proportional)),
+            (TextStyle::Heading, FontId::new(40. * scale, FontFamily::Proportional)),
+            (TextStyle::Monospace, FontId::new(30., FontFamily::Monospace)),
+        ]
+        .into()
+    }
 }
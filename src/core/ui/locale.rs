@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use bevy::prelude::*;
+
+use crate::core::messages::MessageMsg;
+
+/// File with the translated strings, relative to the working directory
+pub const LOCALE_FILE: &str = "locale/strings.ron";
+
+/// Built-in (English) user-facing strings, keyed by their id. Arguments are
+/// referenced by position, e.g. `{0}`
+const STRINGS: [(&str, &str); 14] = [
+    ("turn", "Turn"),
+    ("turn.hover", "Current turn in the game."),
+    ("owned", "Planets colonized / Max. colonizable"),
+    (
+        "owned.hover",
+        "The current number of planets colonized (owned) and the maximum number of planets \
+        than can be colonized this game. A spots is only if an owned planet is abandoned, \
+        conquered or destroyed.",
+    ),
+    ("production", "Production: +{0}"),
+    ("upkeep", "Fleet upkeep: -{0}"),
+    ("intel.turn", "{0} ({1})"),
+    ("intel.hover", "Intelligence from turn {0}."),
+    ("battle", "Battle of {0}"),
+    ("shop.production", "Production"),
+    ("shop.fields", "Fields"),
+    ("shop.capacity", "{0}: {1}/{2}"),
+    ("turn.started", "Turn {0} started."),
+    ("tutorial.completed", "Tutorial completed. Good luck, commander!"),
+];
+
+static OVERRIDES: OnceLock<HashMap<String, String>> = OnceLock::new();
+
+/// Return the string with id `id` from the translation, falling back to the
+/// built-in string if it isn't translated. Unknown ids are returned as is
+fn lookup<'a>(id: &'a str, overrides: Option<&'a HashMap<String, String>>) -> &'a str {
+    if let Some(text) = overrides.and_then(|o| o.get(id)) {
+        return text.as_str();
+    }
+
+    STRINGS.iter().find(|(key, _)| *key == id).map_or(id, |(_, text)| text)
+}
+
+/// Return the (translated) string with id `id`. Unknown ids are returned as is
+pub fn tr(id: &'static str) -> &'static str {
+    lookup(id, OVERRIDES.get())
+}
+
+/// Return the (translated) string with id `id`, filling in its arguments
+pub fn trf(id: &'static str, args: &[&dyn ToString]) -> String {
+    args.iter().enumerate().fold(tr(id).to_string(), |text, (i, arg)| {
+        text.replace(&format!("{{{i}}}"), &arg.to_string())
+    })
+}
+
+/// Load the translated strings at startup. Invalid files are ignored, keeping
+/// the built-in strings
+pub fn load_locale(mut message: MessageWriter<MessageMsg>) {
+    let Ok(text) = std::fs::read_to_string(LOCALE_FILE) else {
+        return; // No translation installed
+    };
+
+    match ron::from_str::<HashMap<String, String>>(&text) {
+        Ok(strings) => {
+            if let Some(id) = strings.keys().find(|id| !STRINGS.iter().any(|(k, _)| k == id)) {
+                warn!("Unknown string id in {LOCALE_FILE}: {id}.");
+            }
+
+            info!("Loaded {} translated strings from {LOCALE_FILE}.", strings.len());
+            let _ = OVERRIDES.set(strings);
+        },
+        Err(e) => {
+            let e = format!("Failed to parse {LOCALE_FILE}: {e}.");
+            error!("{e}");
+            message.write(MessageMsg::error(e));
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lookup_falls_back_to_english() {
+        let overrides = HashMap::from([("turn".to_string(), "Tour".to_string())]);
+        assert_eq!(lookup("turn", Some(&overrides)), "Tour");
+        assert_eq!(lookup("battle", Some(&overrides)), "Battle of {0}");
+        assert_eq!(lookup("turn", None), "Turn");
+
+        // Unknown ids are shown as is
+        assert_eq!(lookup("unknown.id", Some(&overrides)), "unknown.id");
+    }
+
+    #[test]
+    fn trf_fills_in_the_arguments() {
+        assert_eq!(trf("turn.started", &[&7]), "Turn 7 started.");
+        assert_eq!(trf("intel.turn", &[&"Phobos", &3]), "Phobos (3)");
+        assert_eq!(trf("shop.capacity", &[&"Fleet", &4, &10]), "Fleet: 4/10");
+
+        // Missing arguments leave their placeholder
+        assert_eq!(trf("intel.turn", &[&"Phobos"]), "Phobos ({1})");
+    }
+}
@@ -1,4 +1,5 @@
 pub mod aesthetics;
 pub mod dark;
+pub mod locale;
 pub mod systems;
 pub mod utils;
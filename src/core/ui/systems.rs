@@ -1,14 +1,17 @@
 use std::collections::{HashMap, HashSet};
 
+use bevy::asset::RenderAssetUsages;
 use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
 use bevy_egui::egui::epaint::text::{FontInsert, FontPriority, InsertFontFamily};
 use bevy_egui::egui::load::SizedTexture;
 use bevy_egui::egui::{
     emath, Align, Align2, Color32, ComboBox, CursorIcon, FontData, FontFamily, Layout, Order,
-    Response, RichText, ScrollArea, Sense, Separator, Slider, Stroke, StrokeKind, TextStyle, Ui,
-    UiBuilder,
+    Response, RichText, ScrollArea, Sense, Separator, Slider, Stroke, StrokeKind, Style, TextStyle,
+    TextWrapMode, Ui, UiBuilder,
 };
 use bevy_egui::{egui, EguiContexts, EguiTextureHandle};
+use bevy_renet::renet::RenetClient;
 use itertools::Itertools;
 use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
@@ -18,20 +21,28 @@ use crate::core::combat::combat::CombatUnit;
 use crate::core::combat::report::{MissionReport, ReportId, RoundReport, Side};
 use crate::core::combat::stats::CombatStats;
 use crate::core::constants::{
-    BG2_COLOR, ENEMY_COLOR, OWN_COLOR, PROBES_PER_PRODUCTION_LEVEL, PS_SHIELD_PER_LEVEL,
-    SHIELD_COLOR,
+    BG2_COLOR, CARGO_INVESTMENT_FACTOR, FACTORY_PRODUCTION_FACTOR, MAX_BUY_WEIGHT, MAX_NOTE_LENGTH,
+    MAX_PLANET_NAME_LENGTH, MAX_VETERANCY, MIN_UI_HEIGHT, MIN_UI_WIDTH, MIN_WINDOW_HEIGHT,
+    MIN_WINDOW_WIDTH, OWN_COLOR, PHALANX_DISTANCE, PHALANX_SCAN_DEUTERIUM, PS_SHIELD_PER_LEVEL,
+    RADAR_DISTANCE, SHIELD_COLOR, SHIPYARD_PRODUCTION_FACTOR, SILO_CAPACITY_FACTOR,
+    SPECIALIZATION_COOLDOWN, VETERANCY_BONUS,
 };
+use crate::core::diplomacy::{Diplomacy, DiplomacyAction, DiplomacyMsg, Relation};
 use crate::core::map::icon::Icon;
 use crate::core::map::map::Map;
-use crate::core::map::planet::{Planet, PlanetId};
-use crate::core::messages::MessageMsg;
-use crate::core::missions::{BombingRaid, Mission, MissionId, Missions, SendMissionMsg};
-use crate::core::player::{PlanetInfo, Player};
-use crate::core::resources::ResourceName;
-use crate::core::settings::Settings;
+use crate::core::map::planet::{AutoEconomy, Planet, PlanetId, Specialization, Stance};
+use crate::core::messages::{MessageHistory, MessageMsg};
+use crate::core::missions::{BombingRaid, Mission, MissionId, Missions, SendMissionMsg, Targeting};
+use crate::core::network::PlayerList;
+use crate::core::persistence::Replay;
+use crate::core::player::{NoteTag, PlanetInfo, Player, Roster};
+use crate::core::resources::{ResourceName, Resources};
+use crate::core::settings::{ResourceBias, Settings};
 use crate::core::states::GameState;
+use crate::core::turns::{TurnSnapshot, TurnSummary};
 use crate::core::ui::aesthetics::Aesthetics;
 use crate::core::ui::dark::NordDark;
+use crate::core::ui::locale::{tr, trf};
 use crate::core::ui::utils::{toggle, CustomResponse, CustomUi, ImageIds};
 use crate::core::units::buildings::Building;
 use crate::core::units::defense::Defense;
@@ -59,11 +70,67 @@ pub enum MissionTab {
     MissionReports,
 }
 
+/// Entry of the encyclopedia panel
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum EncyclopediaEntry {
+    Unit(Unit),
+    Objective(Icon),
+    Resource(ResourceName),
+}
+
+impl EncyclopediaEntry {
+    pub fn all() -> Vec<Self> {
+        Unit::all()
+            .into_iter()
+            .flatten()
+            .map(EncyclopediaEntry::Unit)
+            .chain(Icon::iter().filter(|i| i.is_mission()).map(EncyclopediaEntry::Objective))
+            .chain(ResourceName::iter().map(EncyclopediaEntry::Resource))
+            .collect()
+    }
+
+    pub fn category(&self) -> &str {
+        match self {
+            EncyclopediaEntry::Unit(Unit::Building(_)) => "Buildings",
+            EncyclopediaEntry::Unit(Unit::Ship(_)) => "Fleet",
+            EncyclopediaEntry::Unit(Unit::Defense(_)) => "Defenses",
+            EncyclopediaEntry::Objective(_) => "Mission objectives",
+            EncyclopediaEntry::Resource(_) => "Resources",
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            EncyclopediaEntry::Unit(u) => u.to_name(),
+            EncyclopediaEntry::Objective(i) => i.to_name(),
+            EncyclopediaEntry::Resource(r) => r.to_name(),
+        }
+    }
+
+    pub fn image(&self) -> String {
+        match self {
+            EncyclopediaEntry::Unit(u) => u.to_lowername(),
+            EncyclopediaEntry::Objective(i) => i.to_lowername(),
+            EncyclopediaEntry::Resource(r) => r.to_lowername(),
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        match self {
+            EncyclopediaEntry::Unit(u) => u.description(),
+            EncyclopediaEntry::Objective(i) => i.description(),
+            EncyclopediaEntry::Resource(r) => r.description(),
+        }
+    }
+}
+
 #[derive(Resource, Default)]
 pub struct UiState {
     pub planet_hover: Option<PlanetId>,
     pub planet_selected: Option<PlanetId>,
     pub to_selected: bool,
+    /// Reset the zoom while moving the camera to the selected planet
+    pub reset_zoom: bool,
     pub shop: Shop,
     pub lab: (ResourceName, ResourceName),
     pub lab_amount: usize,
@@ -72,16 +139,40 @@ pub struct UiState {
     pub mission: bool,
     pub mission_tab: MissionTab,
     pub mission_info: Mission,
-    pub jump_gate_history: bool,
+    pub mission_fill: usize,
+    pub evacuate: Option<PlanetId>,
+    pub evacuate_abandon: bool,
+    /// Planet waiting for the player to confirm its abandonment
+    pub abandon: Option<PlanetId>,
+    /// Last choice between the Jump Gate and sublight travel per (origin, destination)
+    pub jump_gate_routes: HashMap<(PlanetId, PlanetId), bool>,
     pub mission_hover: Option<MissionId>,
     pub mission_report: Option<MissionId>,
     pub combat_report: Option<ReportId>,
     pub combat_report_total: bool,
+    /// Whether the total statistics of the combat report are divided by its number of rounds
+    pub combat_report_average: bool,
     pub combat_report_round: usize,
     pub combat_report_hover: Option<(Unit, Side)>,
+    /// Units excluded from the statistics shown in the combat report
+    pub combat_report_filter: HashSet<(Unit, Side)>,
     pub in_combat: Option<ReportId>,
     pub combat_round: usize,
     pub end_turn: bool,
+    pub snapshot: Option<TurnSnapshot>,
+    pub turn_summary: Option<TurnSummary>,
+    /// Planets that a detected enemy mission reaches next turn
+    pub imminent_attacks: Vec<PlanetId>,
+    pub diplomacy: bool,
+    pub notifications: bool,
+    pub player_list: bool,
+    pub encyclopedia: bool,
+    pub encyclopedia_search: String,
+    pub encyclopedia_entry: Option<EncyclopediaEntry>,
+    /// New name being typed for the selected planet
+    pub rename: Option<String>,
+    /// Last mission sent by the player, used to resend its composition
+    pub last_mission: Option<Mission>,
 }
 
 fn draw_panel<R>(
@@ -149,10 +240,16 @@ fn draw_army_grid(
                 }
             } else if report.mission.owner == player.id
                 && side == Side::Defender
-                && report.scout_probes > (unit.production() - 1) * PROBES_PER_PRODUCTION_LEVEL
+                && report.reveals(unit)
             {
                 // Even if attacker lost combat, he can see enemy starting units with scouts
                 total.to_string()
+            } else if report.planet.controlled == Some(player.id)
+                && side == Side::Attacker
+                && report.leaks(unit)
+            {
+                // A defender that lost learns part of the attacker's army (intel leak)
+                total.to_string()
             } else {
                 "?".to_string()
             };
@@ -183,6 +280,34 @@ fn draw_army_grid(
     });
 }
 
+/// Per-shot outcome of a unit against every enemy unit type in the combat
+fn draw_combat_matchups(ui: &mut Ui, unit: &Unit, targets: &[Unit], veterancy: usize) {
+    ui.set_max_width(480.);
+    ui.label(unit.to_name());
+
+    if unit.damage() == 0 || targets.is_empty() {
+        return;
+    }
+
+    ui.separator();
+
+    for target in targets {
+        let (_, shield_damage, hull_damage, shots) = shot_outcome(unit, target, veterancy);
+        let again = unit.rapid_fire().get(target).map_or(0, |rf| 100 - (*rf).min(100));
+
+        ui.small(format!(
+            "vs {}: {shield_damage} shield + {hull_damage} hull damage per shot, {shots} \
+            shot{} to destroy, {again}% chance to fire again.",
+            target.to_name(),
+            if shots == 1 {
+                ""
+            } else {
+                "s"
+            }
+        ));
+    }
+}
+
 fn draw_combat_army_grid(
     ui: &mut Ui,
     name: &str,
@@ -191,6 +316,8 @@ fn draw_combat_army_grid(
     units: Vec<Unit>,
     side: Side,
     color: Color,
+    veterancy: usize,
+    show_hover: bool,
     images: &ImageIds,
 ) -> bool {
     let (own, mut enemy) = match side {
@@ -198,12 +325,27 @@ fn draw_combat_army_grid(
         Side::Defender => (&round.defender, round.attacker.clone()),
     };
 
+    // Enemy units this side can shoot at, to show the matchups on hover
+    let mut targets = vec![];
+    for cu in enemy.iter() {
+        if !cu.unit.is_building() && !cu.unit.is_missile() && !targets.contains(&cu.unit) {
+            targets.push(cu.unit);
+        }
+    }
+
     if let Some((u, s)) = &state.combat_report_hover {
         if *s != side {
             enemy = enemy.into_iter().filter(|cu| cu.unit == *u).collect::<Vec<_>>();
         }
     }
 
+    let enemy_side = if side == Side::Attacker {
+        Side::Defender
+    } else {
+        Side::Attacker
+    };
+    enemy.retain(|cu| !state.combat_report_filter.contains(&(cu.unit, enemy_side.clone())));
+
     let total_ps = round.buildings.amount(&Unit::planetary_shield()) * PS_SHIELD_PER_LEVEL;
 
     let n_columns = if name.contains("building") {
@@ -246,21 +388,58 @@ fn draw_combat_army_grid(
             let hovering_crawler =
                 matches!(state.combat_report_hover, Some((Unit::Defense(Defense::Crawler), _)));
 
+            let key = (unit, side.clone());
+            let excluded = state.combat_report_filter.contains(&key);
+
             ui.add_enabled_ui(
                 state
                     .combat_report_hover
                     .as_ref()
                     .map_or(true, |(u, s)| (*s != side || *u == Unit::crawler()) || *u == unit),
                 |ui| {
+                    if excluded {
+                        ui.set_opacity(0.4);
+                    }
+
                     let response = ui
                         .add_image(images.get(unit.to_lowername()), [70.; 2])
-                        .on_hover_small_ext(unit.to_name());
+                        .interact(Sense::click());
+
+                    let response = if show_hover && !unit.is_building() {
+                        response
+                            .on_hover_ui(|ui| draw_combat_matchups(ui, &unit, &targets, veterancy))
+                    } else {
+                        response.on_hover_small_ext(unit.to_name())
+                    };
 
                     if response.hovered() && !unit.is_building() {
                         any_hovered = true;
                         state.combat_report_hover = Some((unit, side.clone()));
                     }
 
+                    if !unit.is_building() {
+                        if response.clicked() {
+                            if excluded {
+                                state.combat_report_filter.remove(&key);
+                            } else {
+                                state.combat_report_filter.insert(key.clone());
+                            }
+                        }
+
+                        ui.add_text_on_image(
+                            if excluded {
+                                "☐"
+                            } else {
+                                "☑"
+                            }
+                            .to_string(),
+                            Color32::WHITE,
+                            TextStyle::Small,
+                            response.rect.left_top() + egui::Vec2::new(2., 3.),
+                            Align2::LEFT_TOP,
+                        );
+                    }
+
                     let text = if hovering_crawler && side == Side::Defender {
                         if n_repaired > 0 {
                             Some(format!("❤{n_repaired}"))
@@ -419,7 +598,14 @@ fn draw_combat_army_grid(
     any_hovered
 }
 
-fn draw_resources(ui: &mut Ui, settings: &Settings, map: &Map, player: &Player, images: &ImageIds) {
+fn draw_resources(
+    ui: &mut Ui,
+    settings: &Settings,
+    map: &Map,
+    player: &Player,
+    missions: &Vec<Mission>,
+    images: &ImageIds,
+) {
     ui.add_space(10.);
 
     // Measure total horizontal width required
@@ -433,18 +619,14 @@ fn draw_resources(ui: &mut Ui, settings: &Settings, map: &Map, player: &Player,
         text += &player.resources.get(&r).to_string();
     }
 
-    let size_x = ui
-        .painter()
-        .layout_no_wrap(text, TextStyle::Heading.resolve(ui.style()), Color32::WHITE)
-        .size()
-        .x
+    let size_x = ui.text_width(text, TextStyle::Heading)
         + 80.
         + 35. * 3.
         + 65. * 5.
         + ui.spacing().item_spacing.x * 12.5;
 
     ui.horizontal_centered(|ui| {
-        ui.add_space((ui.available_width() - size_x) * 0.5);
+        ui.add_centering_space(size_x);
 
         let response = ui
             .scope(|ui| {
@@ -460,9 +642,15 @@ fn draw_resources(ui: &mut Ui, settings: &Settings, map: &Map, player: &Player,
                         ui.add_image(images.get("turn"), [130., 90.]);
                     });
                     ui.vertical(|ui| {
-                        ui.label("Turn");
+                        ui.label(tr("turn"));
                         ui.separator();
-                        ui.small("Current turn in the game.");
+                        ui.small(tr("turn.hover"));
+                        ui.add_space(5.);
+                        ui.small(format!(
+                            "Victory ({}): {}",
+                            settings.victory.to_title(),
+                            settings.victory.description()
+                        ));
                     });
                 });
             });
@@ -484,13 +672,9 @@ fn draw_resources(ui: &mut Ui, settings: &Settings, map: &Map, player: &Player,
                         ui.add_image(images.get("owned"), [130., 90.]);
                     });
                     ui.vertical(|ui| {
-                        ui.label("Planets colonized / Max. colonizable");
+                        ui.label(tr("owned"));
                         ui.separator();
-                        ui.small(
-                            "The current number of planets colonized (owned) and the maximum \
-                            number of planets than can be colonized this game. A spots is only \
-                            if an owned planet is abandoned, conquered or destroyed.",
-                        );
+                        ui.small(tr("owned.hover"));
                     });
                 });
             });
@@ -518,9 +702,11 @@ fn draw_resources(ui: &mut Ui, settings: &Settings, map: &Map, player: &Player,
                             ui.separator();
                             ui.scope(|ui| {
                                 ui.style_mut().interaction.selectable_labels = true;
-                                ui.small(format!(
-                                    "Production: +{}",
-                                    player.resource_production(&map.planets).get(&resource)
+                                ui.small(trf(
+                                    "production",
+                                    &[&player
+                                        .resource_production(&map.planets, settings)
+                                        .get(&resource)],
                                 ))
                                 .on_hover_cursor(CursorIcon::Default)
                                 .on_hover_text_at_pointer(
@@ -540,7 +726,16 @@ fn draw_resources(ui: &mut Ui, settings: &Settings, map: &Map, player: &Player,
                                     .small(),
                                 );
                             });
+                            if settings.upkeep && resource == ResourceName::Deuterium {
+                                ui.small(trf("upkeep", &[&player.upkeep(&map.planets, missions)]))
+                                    .on_hover_small(
+                                    "Deuterium paid every turn to maintain your ships. Ships on \
+                                    planets whose upkeep can't be paid are mothballed and can't \
+                                    be sent on missions until there is enough deuterium again.",
+                                );
+                            }
                             ui.small(resource.description());
+                            draw_economy_graph(ui, player, &resource);
                         });
                     });
                 });
@@ -549,17 +744,135 @@ fn draw_resources(ui: &mut Ui, settings: &Settings, map: &Map, player: &Player,
     });
 }
 
+/// Line graph of a resource's production and stockpile over the last turns
+fn draw_economy_graph(ui: &mut Ui, player: &Player, resource: &ResourceName) {
+    let (production, stockpile): (Vec<_>, Vec<_>) = player
+        .economy
+        .iter()
+        .map(|r| (r.production.get(resource), r.stockpile.get(resource)))
+        .unzip();
+
+    if production.len() < 2 {
+        return;
+    }
+
+    ui.add_space(5.);
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(250., 70.), Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_stroke(rect, 2., Stroke::new(1., Color32::DARK_GRAY), StrokeKind::Inside);
+
+    let max = production.iter().chain(&stockpile).max().copied().unwrap_or_default().max(1);
+    let line = |values: &Vec<usize>, color: Color32| {
+        let points = values
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                egui::pos2(
+                    rect.left() + rect.width() * i as f32 / (values.len() - 1) as f32,
+                    rect.bottom() - rect.height() * *v as f32 / max as f32,
+                )
+            })
+            .collect();
+
+        painter.add(egui::Shape::line(points, Stroke::new(1.5, color)));
+    };
+
+    line(&stockpile, Color32::LIGHT_BLUE);
+    line(&production, Color32::LIGHT_GREEN);
+
+    ui.horizontal(|ui| {
+        ui.label(RichText::new("Production").small().color(Color32::LIGHT_GREEN));
+        ui.label(RichText::new("Stockpile").small().color(Color32::LIGHT_BLUE));
+        ui.small(format!(
+            "(turns {}-{})",
+            player.economy[0].turn,
+            player.economy.back().unwrap().turn
+        ));
+    });
+}
+
 fn draw_planet_overview(
     ui: &mut Ui,
     id: PlanetId,
     map: &mut Map,
     player: &mut Player,
     settings: &Settings,
+    state: &mut UiState,
     message: &mut MessageWriter<MessageMsg>,
     images: &ImageIds,
 ) {
     let (n_owned, n_max_owned) = player.planets_owned(&map, &settings);
 
+    // Owned planets where newly built ships can be deployed to
+    let rally_targets = map
+        .planets()
+        .into_iter()
+        .filter(|p| player.owns(p) && p.id != id)
+        .map(|p| (p.id, player.planet_name(p).to_string()))
+        .sorted_by(|a, b| a.1.cmp(&b.1))
+        .collect::<Vec<_>>();
+
+    // Closest controlled planet where the fleet can be evacuated to
+    let position = map.get(id).position;
+    let shelter = map
+        .planets
+        .iter()
+        .filter(|p| p.id != id && player.controls(p) && !p.is_destroyed)
+        .min_by(|a, b| a.position.distance(position).total_cmp(&b.position.distance(position)))
+        .cloned();
+
+    // Moon orbiting this planet (or planet this moon orbits)
+    let companion = map.companion(id).cloned();
+
+    // Controlled planets that can send their fleet here: (name, duration, fuel, jump)
+    let target = map.get(id);
+    let launch_points = map
+        .planets
+        .iter()
+        .filter(|p| p.id != id && player.controls(p) && p.has_fleet() && !p.mothballed)
+        .map(|p| {
+            let mission = Mission::new(
+                settings.turn,
+                player.id,
+                p,
+                target,
+                if player.owns(target) {
+                    Icon::Deploy
+                } else {
+                    Icon::Attack
+                },
+                p.army
+                    .iter()
+                    .filter(|(u, c)| u.is_ship() && **c > 0)
+                    .map(|(u, c)| (*u, *c))
+                    .collect(),
+                BombingRaid::None,
+                false,
+                false,
+                None,
+            );
+
+            // Deploys between owned planets with Jump Gates jump if there's capacity left
+            let jump = player.owns(p)
+                && player.owns(target)
+                && p.army.amount(&Unit::Building(Building::JumpGate)) > 0
+                && target.army.amount(&Unit::Building(Building::JumpGate)) > 0
+                && p.jump_gate + mission.jump_cost() <= p.max_jump_capacity();
+
+            let (duration, fuel) = mission.travel_cost(map, jump);
+            (player.planet_name(p).to_string(), duration, fuel, jump)
+        })
+        .sorted_by_key(|(_, duration, fuel, _)| (*duration, *fuel))
+        .collect::<Vec<_>>();
+
+    // Owned planet whose Sensor Phalanx can scan this planet (unused ones first)
+    let scanner = map
+        .planets
+        .iter()
+        .filter(|p| player.owns(p) && p.in_phalanx_range(map.get(id)))
+        .min_by_key(|p| p.phalanx_scanned)
+        .map(|p| (p.id, p.phalanx_scanned));
+
     let planet = map.get_mut(id);
 
     ui.add_space(19.);
@@ -573,7 +886,42 @@ fn draw_planet_overview(
     // Now overlay elements on top
     ui.scope_builder(UiBuilder::new().max_rect(rect.shrink(5.)), |ui| {
         ui.vertical_centered(|ui| {
-            ui.heading(&planet.name);
+            if let Some(name) = state.rename.as_mut() {
+                let response = ui.add(
+                    egui::TextEdit::singleline(name)
+                        .char_limit(MAX_PLANET_NAME_LENGTH)
+                        .desired_width(200.)
+                        .horizontal_align(Align::Center)
+                        .font(TextStyle::Heading),
+                );
+                response.request_focus();
+
+                if response.lost_focus() {
+                    if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                        if let Err(e) = planet.rename(name) {
+                            message.write(MessageMsg::warning(e));
+                        }
+                    }
+                    state.rename = None;
+                }
+            } else if player.owns(planet) {
+                if ui
+                    .add(
+                        egui::Label::new(RichText::new(player.planet_name(planet)).heading())
+                            .sense(Sense::click()),
+                    )
+                    .on_hover_cursor(CursorIcon::Text)
+                    .on_hover_small_ext(
+                        "Double-click to rename the planet. Other players keep seeing its \
+                        original name.",
+                    )
+                    .double_clicked()
+                {
+                    state.rename = Some(player.planet_name(planet).to_string());
+                }
+            } else {
+                ui.heading(&planet.name);
+            }
         });
 
         ui.add_space(10.);
@@ -589,6 +937,76 @@ fn draw_planet_overview(
                 }
             ))
             .on_hover_small(planet.kind.description());
+            if planet.is_moon() {
+                ui.small("🏗 Lunar buildings only").on_hover_small(format!(
+                    "Moons can only build: {}. Defenses can't be built on a moon.",
+                    Unit::lunar_buildings().iter().map(|u| u.to_name()).join(", ")
+                ));
+            }
+            if let Some(companion) = &companion {
+                let response = ui
+                    .add(
+                        egui::Label::new(
+                            RichText::new(if planet.is_moon() {
+                                format!("🪐 Orbits: {}", companion.name)
+                            } else {
+                                format!("🌙 Moon: {}", companion.name)
+                            })
+                            .small(),
+                        )
+                        .sense(Sense::click()),
+                    )
+                    .on_hover_cursor(CursorIcon::PointingHand)
+                    .on_hover_small(
+                        "Click to select it. Right-click to deploy all ships to it. Deploy \
+                        missions between a planet and its moon cost no fuel.",
+                    );
+
+                if response.clicked() {
+                    state.planet_selected = Some(companion.id);
+                    state.to_selected = true;
+                } else if response.secondary_clicked()
+                    && player.controls(planet)
+                    && player.controls(companion)
+                    && planet.has_fleet()
+                    && !player.spectator
+                {
+                    state.mission = true;
+                    state.mission_tab = MissionTab::NewMission;
+                    state.mission_info = Mission::new(
+                        settings.turn,
+                        player.id,
+                        planet,
+                        companion,
+                        Icon::Deploy,
+                        planet
+                            .army
+                            .iter()
+                            .filter(|(u, c)| u.is_ship() && **c > 0)
+                            .map(|(u, c)| (*u, *c))
+                            .collect(),
+                        BombingRaid::None,
+                        false,
+                        false,
+                        None,
+                    );
+                    state.planet_selected = None;
+                }
+            }
+            if !planet.is_moon() && settings.resource_bias != ResourceBias::None {
+                ui.small(format!(
+                    "⛏ Reserves: {}",
+                    ResourceName::iter()
+                        .zip(planet.kind.abundance())
+                        .map(|(r, a)| format!("{}{}", r.to_name(), ["▼", "■", "▲"][a]))
+                        .join(" ")
+                ))
+                .on_hover_small(
+                    "Resources this kind of planet is typically rich (▲) or poor (▼) in. The \
+                    planet's production per building level is shown when hovering over its \
+                    buildings.",
+                );
+            }
             ui.small(format!(
                 "📐 Diameter: {}km ({:.0}%)",
                 format_thousands(planet.diameter),
@@ -612,10 +1030,272 @@ fn draw_planet_overview(
                 planet.position.y.round()
             ))
             .on_hover_small_ext("Position of the planet relative to the system's center.");
+
+            if !launch_points.is_empty() {
+                ui.small(format!("🚀 Launch points: {}", launch_points.len())).on_hover_ui(|ui| {
+                    draw_launch_points(ui, &launch_points, player.resources.deuterium)
+                });
+            }
+
+            if player.controls(planet) && planet.mothballed {
+                ui.small("💤 Mothballed").on_hover_small(format!(
+                    "The fleet upkeep of {} deuterium couldn't be paid. The ships on this planet \
+                    still defend it, but can't be sent on missions.",
+                    planet.upkeep()
+                ));
+            }
+
+            if player.controls(planet) && planet.veterancy > 0 && planet.army.combat_ships() > 0 {
+                ui.small(format!("🎖 Veterancy: {}/{}", planet.veterancy, MAX_VETERANCY))
+                    .on_hover_small(format!(
+                        "The ships stationed on this planet deal {}% extra damage.",
+                        planet.veterancy * VETERANCY_BONUS
+                    ));
+            }
         });
     });
 
+    let note_rect =
+        egui::Rect::from_min_size(rect.left_top() + egui::vec2(15., 50.), egui::vec2(190., 60.));
+
+    ui.scope_builder(UiBuilder::new().max_rect(note_rect), |ui| {
+        let note = player.notes.entry(planet.id).or_default();
+
+        let color = |tag: NoteTag| {
+            if tag == NoteTag::None {
+                Color32::WHITE
+            } else {
+                tag.color().to_color32()
+            }
+        };
+
+        ui.spacing_mut().item_spacing.y = 6.;
+
+        ComboBox::from_id_salt("note tag")
+            .width(110.)
+            .selected_text(
+                RichText::new(format!("🏷 {}", note.tag.to_name())).color(color(note.tag)),
+            )
+            .show_ui(ui, |ui| {
+                for tag in NoteTag::iter() {
+                    ui.selectable_value(
+                        &mut note.tag,
+                        tag,
+                        RichText::new(tag.to_name()).small().color(color(tag)),
+                    )
+                    .on_hover_cursor(CursorIcon::PointingHand);
+                }
+            })
+            .response
+            .on_hover_cursor(CursorIcon::PointingHand)
+            .on_hover_small("Color tag shown as a ring around the planet on the map.");
+
+        ui.add(
+            egui::TextEdit::singleline(&mut note.text)
+                .char_limit(MAX_NOTE_LENGTH)
+                .desired_width(180.)
+                .font(TextStyle::Small)
+                .hint_text("Add a note..."),
+        )
+        .on_hover_small("Personal note on this planet. Only you can see it.");
+    });
+
+    // Don't store empty notes
+    player.notes.retain(|_, n| !n.is_empty());
+
+    if player.owns(planet) && !planet.is_moon() {
+        let auto_rect = egui::Rect::from_min_size(
+            rect.left_top() + egui::vec2(15., 110.),
+            egui::vec2(320., 30.),
+        );
+
+        ui.scope_builder(UiBuilder::new().max_rect(auto_rect), |ui| {
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.x = 6.;
+
+                let mut enabled = planet.auto_economy.is_some();
+                ui.small("⚙ Auto-economy:");
+                if ui
+                    .add(toggle(&mut enabled))
+                    .on_hover_small_ext(
+                        "Every turn, save part of this planet's production to automatically \
+                        upgrade its cheapest resource building. Upgrades are only bought when \
+                        the savings and your resources cover the price.",
+                    )
+                    .clicked()
+                {
+                    planet.auto_economy = enabled.then(AutoEconomy::default);
+                }
+
+                if let Some(auto) = planet.auto_economy.as_mut() {
+                    ui.style_mut().drag_value_text_style = TextStyle::Small;
+                    ui.add(egui::DragValue::new(&mut auto.share).range(1..=100).suffix("%"))
+                        .on_hover_small("Percentage of the planet's production saved every turn.");
+                    ui.add(
+                        egui::DragValue::new(&mut auto.max_level)
+                            .range(1..=Building::MAX_LEVEL)
+                            .prefix("lvl "),
+                    )
+                    .on_hover_small("Level up to which the resource buildings are upgraded.");
+
+                    let mut balanced = auto.ratio.is_some();
+                    if ui
+                        .add(toggle(&mut balanced))
+                        .on_hover_small(format!(
+                            "Upgrade the resource building that brings the planet's production \
+                        closest to the target ratio ({}) instead of the cheapest one. The \
+                        ratio can be changed in the settings.",
+                            settings.resource_ratio.iter().join(":")
+                        ))
+                        .clicked()
+                    {
+                        auto.ratio = balanced.then_some(settings.resource_ratio);
+                    }
+                }
+            });
+        });
+    }
+
+    if player.controls(planet) {
+        let alert_rect = egui::Rect::from_min_size(
+            rect.left_top()
+                + egui::vec2(
+                    15.,
+                    if player.owns(planet) && !planet.is_moon() {
+                        145.
+                    } else {
+                        110.
+                    },
+                ),
+            egui::vec2(260., 30.),
+        );
+
+        ui.scope_builder(UiBuilder::new().max_rect(alert_rect), |ui| {
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.x = 6.;
+                ui.small("🚨 Defensive alert:");
+                ui.add(toggle(&mut planet.alert)).on_hover_small_ext(
+                    "When a detected enemy mission targets this planet, fleets deploying from \
+                    it to other planets are recalled to defend it. The recall costs the fuel \
+                    for the way back, and only happens if there is enough deuterium.",
+                );
+            });
+        });
+    }
+
+    if player.controls(planet) {
+        let offset = if player.owns(planet) && !planet.is_moon() {
+            72.
+        } else {
+            37.
+        };
+
+        let stance_rect = egui::Rect::from_min_size(
+            rect.right_bottom() - egui::vec2(160., offset),
+            egui::vec2(150., 30.),
+        );
+
+        ui.scope_builder(UiBuilder::new().max_rect(stance_rect), |ui| {
+            ComboBox::from_id_salt("stance")
+                .selected_text(format!("Stance: {}", planet.stance.to_title()))
+                .show_ui(ui, |ui| {
+                    for stance in Stance::iter() {
+                        ui.selectable_value(&mut planet.stance, stance, stance.to_title())
+                            .on_hover_cursor(CursorIcon::PointingHand)
+                            .on_hover_small(stance.description());
+                    }
+                })
+                .response
+                .on_hover_cursor(CursorIcon::PointingHand)
+                .on_hover_small_ext(planet.stance.description());
+        });
+    }
+
     if !planet.is_moon() {
+        if player.owns(planet) {
+            let spec_rect = egui::Rect::from_min_size(
+                rect.left_bottom() + egui::vec2(70., -37.),
+                egui::vec2(170., 30.),
+            );
+
+            let can_change = planet.can_specialize(settings.turn);
+
+            ui.scope_builder(UiBuilder::new().max_rect(spec_rect), |ui| {
+                ui.add_enabled_ui(can_change, |ui| {
+                    ComboBox::from_id_salt("specialization")
+                        .selected_text(format!("Focus: {}", planet.specialization.to_title()))
+                        .show_ui(ui, |ui| {
+                            for spec in Specialization::iter() {
+                                let affordable = player.resources >= spec.price();
+
+                                let response = ui
+                                    .add_enabled_ui(affordable, |ui| {
+                                        ui.selectable_label(
+                                            planet.specialization == spec,
+                                            spec.to_title(),
+                                        )
+                                    })
+                                    .inner
+                                    .on_hover_cursor(CursorIcon::PointingHand)
+                                    .on_hover_small(spec.description())
+                                    .on_disabled_hover_small("Not enough resources.");
+
+                                if response.clicked() {
+                                    planet.specialize(spec, settings.turn, &mut player.resources);
+                                }
+                            }
+                        })
+                        .response
+                        .on_hover_cursor(CursorIcon::PointingHand)
+                        .on_hover_small(format!(
+                            "{} Changing the specialization costs {} metal and {} crystal, and \
+                            can only be done once every {} turns.",
+                            planet.specialization.description(),
+                            Specialization::Economy.price().metal,
+                            Specialization::Economy.price().crystal,
+                            SPECIALIZATION_COOLDOWN,
+                        ))
+                        .on_disabled_hover_small_ext(format!(
+                            "The specialization can be changed again in turn {}.",
+                            planet.specialization_turn.unwrap_or_default()
+                                + SPECIALIZATION_COOLDOWN
+                        ));
+                });
+            });
+
+            let rally_rect = egui::Rect::from_min_size(
+                rect.right_bottom() - egui::vec2(160., 37.),
+                egui::vec2(150., 30.),
+            );
+
+            ui.scope_builder(UiBuilder::new().max_rect(rally_rect), |ui| {
+                let rally_name = planet
+                    .rally
+                    .and_then(|r| rally_targets.iter().find(|(id, _)| *id == r))
+                    .map(|(_, name)| name.as_str())
+                    .unwrap_or("None");
+
+                ComboBox::from_id_salt("rally")
+                    .selected_text(format!("Rally: {rally_name}"))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut planet.rally, None, "None")
+                            .on_hover_cursor(CursorIcon::PointingHand);
+
+                        for (id, name) in &rally_targets {
+                            ui.selectable_value(&mut planet.rally, Some(*id), name)
+                                .on_hover_cursor(CursorIcon::PointingHand);
+                        }
+                    })
+                    .response
+                    .on_hover_cursor(CursorIcon::PointingHand)
+                    .on_hover_small_ext(
+                        "Ships built on this planet are automatically deployed to the rally \
+                        planet at the end of the turn, as long as there is enough deuterium to \
+                        send them.",
+                    );
+            });
+        }
+
         let owned = player.owns(planet) && player.home_planet != planet.id;
         let controlled = player.controls(planet) && !player.owns(planet);
 
@@ -643,36 +1323,13 @@ fn draw_planet_overview(
                 ui.add_image_painter(images.get("abandon"), rect);
 
                 if response.clicked() {
-                    planet.abandon();
-
-                    // Inject hidden report to show last_info that the planet is abandoned
-                    if planet.controlled == None {
-                        let mission = Mission::from_mission(
-                            settings.turn,
-                            player.id,
-                            planet,
-                            planet,
-                            &Mission::default(),
-                        );
-
-                        player.reports.push(MissionReport {
-                            id: rand::random(),
-                            turn: settings.turn,
-                            mission,
-                            planet: planet.clone(),
-                            scout_probes: 0,
-                            surviving_attacker: Army::new(),
-                            surviving_defender: Army::new(),
-                            planet_colonized: false,
-                            planet_destroyed: false,
-                            destination_owned: None,
-                            destination_controlled: None,
-                            combat_report: None,
-                            hidden: true,
-                        });
+                    if settings.confirm_abandon {
+                        state.abandon = Some(planet.id);
+                    } else {
+                        player.abandon(planet, settings.turn);
+                        message
+                            .write(MessageMsg::info(format!("Planet {} abandoned.", planet.name)));
                     }
-
-                    message.write(MessageMsg::info(format!("Planet {} abandoned.", planet.name)));
                 }
             });
         } else if controlled {
@@ -697,33 +1354,222 @@ fn draw_planet_overview(
                     if response.clicked() {
                         *planet.army.entry(Unit::colony_ship()).or_insert(1) -= 1;
                         planet.colonize(player.id);
+                        planet.queue_build_order(settings.build_order, &mut player.resources);
                         message
                             .write(MessageMsg::info(format!("Planet {} colonized.", planet.name)));
                     }
                 },
             );
         }
-    }
-}
 
-fn draw_overview(ui: &mut Ui, planet: &Planet, images: &ImageIds) {
-    ui.add_space(17.);
+        if player.controls(planet) && !player.spectator {
+            let rect = rect.translate(egui::vec2(size.x + 10., 0.));
 
-    ui.horizontal(|ui| {
-        let text = &planet.name;
-        let size_x = ui
-            .painter()
-            .layout_no_wrap(text.clone(), TextStyle::Small.resolve(ui.style()), Color32::WHITE)
-            .size()
-            .x;
+            ui.add_enabled_ui(planet.has_fleet() && shelter.is_some(), |ui| {
+                let mut response = ui
+                    .interact(rect, ui.id().with("evacuate"), Sense::click())
+                    .on_hover_small_ext(
+                        "Evacuate all ships on this planet with a Deploy mission to the closest \
+                        controlled planet. The fuel cost is shown in the mission panel. Defenses \
+                        can't move and are left behind.",
+                    )
+                    .on_disabled_hover_small_ext(if shelter.is_none() {
+                        "There is no other controlled planet to evacuate to."
+                    } else {
+                        "There are no ships on this planet to evacuate."
+                    });
 
-        ui.spacing_mut().item_spacing.x = 7.;
-        ui.add_space((ui.available_width() - size_x - 27.) * 0.5);
-        ui.add_image(images.get("overview"), [20.; 2]);
-        ui.small(text);
-    });
+                if response.enabled() {
+                    response = response.on_hover_cursor(CursorIcon::PointingHand);
+                }
 
-    ui.add_space(10.);
+                ui.add_image_painter(images.get("deploy"), rect);
+
+                if response.clicked() {
+                    if let Some(shelter) = &shelter {
+                        state.mission = true;
+                        state.mission_tab = MissionTab::NewMission;
+                        state.mission_info = Mission::new(
+                            settings.turn,
+                            player.id,
+                            planet,
+                            shelter,
+                            Icon::Deploy,
+                            planet
+                                .army
+                                .iter()
+                                .filter(|(u, c)| u.is_ship() && **c > 0)
+                                .map(|(u, c)| (*u, *c))
+                                .collect(),
+                            BombingRaid::None,
+                            false,
+                            false,
+                            None,
+                        );
+                        state.evacuate = Some(planet.id);
+                        state.evacuate_abandon = false;
+                        state.planet_selected = None;
+                    }
+                }
+            });
+        }
+    }
+
+    if let Some((scanner_id, scanned)) =
+        scanner.filter(|_| !player.controls(planet) && !player.spectator)
+    {
+        let size = egui::vec2(40., 40.);
+        let pos = rect.left_bottom() - egui::vec2(-20., size.y + 7.);
+        let rect = egui::Rect::from_min_size(pos, size);
+
+        let affordable = player.resources.deuterium >= PHALANX_SCAN_DEUTERIUM;
+        let scan = ui
+            .add_enabled_ui(!scanned && affordable, |ui| {
+                let mut response = ui
+                    .interact(rect, ui.id().with("scan"), Sense::click())
+                    .on_hover_small_ext(format!(
+                        "Scan this planet with the Sensor Phalanx of {} for {} deuterium. The \
+                        scan reveals the units with production <= the Phalanx's level.",
+                        map.get(scanner_id).name,
+                        PHALANX_SCAN_DEUTERIUM
+                    ))
+                    .on_disabled_hover_small_ext(if scanned {
+                        "The Sensor Phalanx can only scan once per turn."
+                    } else {
+                        "Not enough deuterium."
+                    });
+
+                if response.enabled() {
+                    response = response.on_hover_cursor(CursorIcon::PointingHand);
+                }
+
+                ui.add_image_painter(images.get("sensor phalanx"), rect);
+
+                response.clicked()
+            })
+            .inner;
+
+        if scan {
+            let target = map.get(id).clone();
+            if player.phalanx_scan(map.get_mut(scanner_id), &target, settings.turn) {
+                message.write(MessageMsg::info(format!("Planet {} scanned.", target.name)));
+            }
+        }
+    }
+}
+
+/// Draw a bar with the makeup of a fleet, weighted by the value of the ships.
+/// Queued ships are drawn with a lighter shade
+/// Planets that can send their whole fleet to the selected planet, fastest first
+fn draw_launch_points(ui: &mut Ui, points: &[(String, usize, usize, bool)], deuterium: usize) {
+    ui.small("Travel time and fuel when sending each planet's whole fleet:");
+    ui.add_space(5.);
+
+    egui::Grid::new("launch_points").striped(true).spacing([20., 4.]).show(ui, |ui| {
+        ui.small("Planet");
+        ui.small("Turns");
+        ui.small("Fuel");
+        ui.end_row();
+
+        for (name, duration, fuel, jump) in points {
+            ui.small(name);
+            ui.small(if *jump {
+                format!("{duration} ⚡")
+            } else {
+                duration.to_string()
+            });
+            if *fuel > deuterium {
+                ui.colored_label(Color32::RED, RichText::new(fuel.fmt()).small());
+            } else {
+                ui.small(fuel.fmt());
+            }
+            ui.end_row();
+        }
+    });
+
+    ui.add_space(5.);
+    ui.small("⚡ Jumps through a Jump Gate. Fuel in red can't currently be afforded.");
+}
+
+fn draw_fleet_bar(ui: &mut Ui, army: &Army, queued: &Army) {
+    let value = |unit: &Unit| {
+        let price = unit.price();
+        price.metal + price.crystal + price.deuterium
+    };
+
+    let ships = Unit::ships();
+    let total = ships.iter().map(|u| (army.amount(u) + queued.amount(u)) * value(u)).sum::<usize>();
+
+    ui.add_space(4.);
+
+    ui.horizontal(|ui| {
+        ui.add_space(15.);
+
+        let (rect, _) =
+            ui.allocate_exact_size(egui::vec2(ui.available_width() - 15., 8.), Sense::hover());
+        ui.painter().rect_filled(rect, 2., BG2_COLOR.to_color32());
+
+        let mut x = rect.left();
+        for (i, unit) in ships.iter().enumerate() {
+            for (n, is_queued) in [(army.amount(unit), false), (queued.amount(unit), true)] {
+                if n == 0 {
+                    continue;
+                }
+
+                let fraction = (n * value(unit)) as f32 / total as f32;
+                let segment = egui::Rect::from_min_size(
+                    egui::pos2(x, rect.top()),
+                    egui::vec2(rect.width() * fraction, rect.height()),
+                );
+
+                let hue = i as f32 / ships.len() as f32;
+                let alpha = if is_queued {
+                    0.4
+                } else {
+                    1.
+                };
+                ui.painter().rect_filled(
+                    segment,
+                    0.,
+                    Color32::from(egui::ecolor::Hsva::new(hue, 0.7, 0.9, alpha)),
+                );
+
+                ui.interact(segment, ui.id().with(("fleet bar", i, is_queued)), Sense::hover())
+                    .on_hover_small(format!(
+                        "{}{}: {n} ({:.0}%)",
+                        unit.to_name(),
+                        if is_queued {
+                            " (queued)"
+                        } else {
+                            ""
+                        },
+                        fraction * 100.
+                    ));
+
+                x += segment.width();
+            }
+        }
+    });
+
+    ui.add_space(4.);
+}
+
+fn draw_overview(ui: &mut Ui, planet: &Planet, images: &ImageIds) {
+    ui.add_space(17.);
+
+    ui.horizontal(|ui| {
+        let text = &planet.name;
+        let size_x = ui.text_width(text, TextStyle::Small);
+
+        ui.spacing_mut().item_spacing.x = 7.;
+        ui.add_centering_space(size_x + 27.);
+        ui.add_image(images.get("overview"), [20.; 2]);
+        ui.small_wrapped(text);
+    });
+
+    draw_fleet_bar(ui, &planet.army, &planet.buy.iter().copied().counts());
+
+    ui.add_space(2.);
 
     ui.horizontal(|ui| {
         ui.spacing_mut().item_spacing = emath::Vec2::new(7., 4.);
@@ -760,20 +1606,19 @@ fn draw_report_overview(ui: &mut Ui, planet: &Planet, info: &PlanetInfo, images:
     ui.add_space(17.);
 
     ui.horizontal(|ui| {
-        let text = format!("{} ({})", planet.name, info.turn);
-        let size_x = ui
-            .painter()
-            .layout_no_wrap(text.clone(), TextStyle::Small.resolve(ui.style()), Color32::WHITE)
-            .size()
-            .x;
-
-        ui.add_space((ui.available_width() - size_x) * 0.5);
-        ui.small(text);
+        let text = trf("intel.turn", &[&planet.name, &info.turn]);
+        let size_x = ui.text_width(&text, TextStyle::Small);
+
+        ui.add_centering_space(size_x);
+        ui.small_wrapped(text);
     })
     .response
-    .on_hover_small(format!("Intelligence from turn {}.", info.turn));
+    .on_hover_small(trf("intel.hover", &[&info.turn]));
 
-    ui.add_space(10.);
+    // Only the known ships are shown
+    draw_fleet_bar(ui, &info.army, &Army::new());
+
+    ui.add_space(2.);
 
     ui.horizontal(|ui| {
         ui.spacing_mut().item_spacing = emath::Vec2::new(7., 4.);
@@ -809,6 +1654,111 @@ fn draw_report_overview(ui: &mut Ui, planet: &Planet, info: &PlanetInfo, images:
     });
 }
 
+fn draw_army_comparison(
+    ui: &mut Ui,
+    origin: &Planet,
+    destination: &Planet,
+    info: &PlanetInfo,
+    images: &ImageIds,
+) {
+    // Sum the combat stats of an army, returning whether some amounts are unknown
+    let stats = |units: Vec<Unit>, army: &Army| {
+        let mut unknown = false;
+        let mut total = [0usize; 4];
+        for unit in units.iter().filter(|u| !u.is_building() && !u.is_missile()) {
+            if let Some(n) = army.get(unit) {
+                total[0] += n;
+                total[1] += n * unit.hull();
+                total[2] += n * unit.shield();
+                total[3] += n * unit.damage();
+            } else {
+                unknown = true;
+            }
+        }
+        (total, unknown)
+    };
+
+    // Defenses can't leave the planet, so only compare the fleet
+    let (mine, _) = stats(Unit::ships(), &origin.army);
+    let (enemy, unknown) = stats(Unit::all_valid(destination.is_moon()).concat(), &info.army);
+
+    ui.add_space(15.);
+
+    ui.vertical_centered(|ui| {
+        ui.small(format!("{} vs {} ({})", origin.name, destination.name, info.turn))
+            .on_hover_small(trf("intel.hover", &[&info.turn]));
+    });
+
+    ui.add_space(10.);
+
+    let fmt = |n: usize, unknown: bool| {
+        if unknown {
+            format!("{}+?", n.fmt())
+        } else {
+            n.fmt()
+        }
+    };
+
+    ui.horizontal(|ui| {
+        ui.add_space(60.);
+        egui::Grid::new("army_comparison").striped(false).num_columns(3).spacing([40., 6.]).show(
+            ui,
+            |ui| {
+                for (i, (image, name)) in [
+                    ("fleet", "Units"),
+                    ("hull", "Hull"),
+                    ("shield", "Shield"),
+                    ("damage", "Damage"),
+                ]
+                .iter()
+                .enumerate()
+                {
+                    ui.horizontal(|ui| {
+                        ui.add_image(images.get(*image), [20., 20.]);
+                        ui.small(*name);
+                    });
+                    ui.small(mine[i].fmt());
+                    ui.small(fmt(enemy[i], unknown));
+                    ui.end_row();
+                }
+            },
+        );
+    });
+
+    ui.add_space(10.);
+
+    // Rough estimate of each side's strength: firepower times durability
+    let power = |s: [usize; 4]| (s[3] * (s[1] + s[2])) as f32;
+    let ratio = power(mine) / power(enemy).max(1.);
+
+    let (text, color) = if ratio >= 1.5 {
+        ("Favorable", Color32::from_rgb(0, 200, 0))
+    } else if ratio > 0.67 {
+        ("Even", Color32::from_rgb(220, 180, 0))
+    } else {
+        ("Unfavorable", Color32::from_rgb(220, 0, 0))
+    };
+
+    ui.vertical_centered(|ui| {
+        ui.label(
+            RichText::new(format!(
+                "{text}{}",
+                if unknown {
+                    " (?)"
+                } else {
+                    ""
+                }
+            ))
+            .small()
+            .color(color),
+        )
+        .on_hover_small(
+            "Quick comparison of the fleet on the selected planet against the last known army \
+            on the hovered planet. This is not a combat simulation.",
+        );
+    });
+}
+
 fn draw_mission_fleet_hover(
     ui: &mut Ui,
     mission: &Mission,
@@ -876,11 +1826,13 @@ fn draw_mission_fleet_hover(
 
 fn draw_new_mission(
     ui: &mut Ui,
+    missions: &Vec<Mission>,
     send_mission: &mut MessageWriter<SendMissionMsg>,
     settings: &Settings,
     state: &mut UiState,
     map: &mut Map,
     player: &mut Player,
+    diplomacy: &Diplomacy,
     is_hovered: bool,
     keyboard: &ButtonInput<KeyCode>,
     images: &ImageIds,
@@ -896,8 +1848,10 @@ fn draw_new_mission(
     }
 
     // Recalculate position (in case origin changed)
-    state.mission_info =
-        Mission::from_mission(settings.turn, player.id, origin, destination, &state.mission_info);
+    state.mission_info = Mission {
+        cargo: state.mission_info.cargo,
+        ..Mission::from_mission(settings.turn, player.id, origin, destination, &state.mission_info)
+    };
 
     if state.mission_info.objective == Icon::Colonize && n_owned >= n_max_owned {
         state.mission_info.objective = Icon::Deploy;
@@ -930,10 +1884,38 @@ fn draw_new_mission(
         _ => Unit::ships(),
     };
 
+    // Apply the Jump Gate choice first, so the duration and fuel are always up to date
+    let jump_cost = state.mission_info.jump_cost();
+    let (has_gates, can_jump) = state.mission_info.jump_gate_access(map, player);
+    let route = (origin.id, destination.id);
+    let (duration, fuel) = state.mission_info.select_travel(map, player, &state.jump_gate_routes);
+
+    // New home defense reservation of the origin planet
+    let mut reserve: Option<Army> = None;
+
     let speed = state.mission_info.speed();
     let distance = state.mission_info.distance(map);
-    let duration = state.mission_info.duration(map);
-    let fuel = state.mission_info.fuel_consumption(map);
+    let base_fuel = state.mission_info.base_fuel_consumption(map);
+
+    // Only deploy missions to owned planets carry cargo, limited by the fleet's
+    // capacity and the stockpile (the host applies the same limits)
+    let capacity = if state.mission_info.objective == Icon::Deploy && player.owns(destination) {
+        state.mission_info.cargo_capacity()
+    } else {
+        0
+    };
+
+    let mut remaining = capacity;
+    for resource in ResourceName::iter() {
+        let mut available = player.resources.get(&resource);
+        if resource == ResourceName::Deuterium {
+            available = available.saturating_sub(fuel);
+        }
+
+        let amount = state.mission_info.cargo.get_mut(&resource);
+        *amount = (*amount).min(available).min(remaining);
+        remaining -= *amount;
+    }
 
     ui.add_space(10.);
 
@@ -1002,7 +1984,8 @@ fn draw_new_mission(
                     ui.cell(50., |ui| ui.allocate_exact_size([50.; 2].into(), Sense::click()));
 
                 response = response.on_hover_cursor(CursorIcon::PointingHand).on_hover_small(
-                    "Click to select all units on the origin planet. Right-click to unselect all.",
+                    "Click to select all units on the origin planet, except the ones reserved \
+                    for home defense. Right-click to unselect all.",
                 );
 
                 let image = if response.hovered() && !response.is_pointer_button_down_on() {
@@ -1014,8 +1997,7 @@ fn draw_new_mission(
                 ui.add_image_painter(image, rect);
 
                 if response.clicked() {
-                    state.mission_info.army =
-                        army.iter().map(|u| (*u, origin.army.amount(u))).collect();
+                    state.mission_info.army = origin.select_available(&army, 100);
                 } else if response.secondary_clicked() {
                     state.mission_info.army.clear();
                 }
@@ -1097,7 +2079,8 @@ fn draw_new_mission(
                                             .on_disabled_hover_small(unit.to_name());
 
                                         if response.clicked() {
-                                            *state.mission_info.army.entry(*unit).or_insert(0) = n;
+                                            *state.mission_info.army.entry(*unit).or_insert(0) =
+                                                origin.available(unit);
                                         }
 
                                         if response.secondary_clicked() {
@@ -1112,6 +2095,17 @@ fn draw_new_mission(
                                             Align2::LEFT_BOTTOM,
                                         );
 
+                                        let reserved = origin.reserved.amount(unit).min(n);
+                                        if reserved > 0 {
+                                            ui.add_text_on_image(
+                                                format!("🛡{reserved}"),
+                                                Color32::LIGHT_BLUE,
+                                                TextStyle::Small,
+                                                response.rect.right_top(),
+                                                Align2::RIGHT_TOP,
+                                            );
+                                        }
+
                                         ui.style_mut().drag_value_text_style = TextStyle::Body;
                                         ui.spacing_mut().interact_size.x = 50.;
                                         let value =
@@ -1127,6 +2121,115 @@ fn draw_new_mission(
                         }
                     },
                 );
+
+                ui.add_space(10.);
+
+                // Quick selection of a part of the available units
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 8.;
+                    ui.spacing_mut().button_padding = egui::Vec2::new(6., 4.);
+
+                    if ui
+                        .add(egui::Button::new(RichText::new("Half").small()))
+                        .on_hover_cursor(CursorIcon::PointingHand)
+                        .on_hover_small("Select half of every unit on the origin planet.")
+                        .clicked()
+                    {
+                        state.mission_info.army = origin.select_available(&army, 50);
+                    }
+
+                    if ui
+                        .add(egui::Button::new(RichText::new("Keep garrison").small()))
+                        .on_hover_cursor(CursorIcon::PointingHand)
+                        .on_hover_small(
+                            "Select all units except one of every combat ship, leaving a \
+                            minimal garrison to keep control of the origin planet.",
+                        )
+                        .clicked()
+                    {
+                        state.mission_info.army = army
+                            .iter()
+                            .map(|u| {
+                                let n = origin.available(u);
+                                (
+                                    *u,
+                                    if u.is_combat_ship() && origin.reserved.amount(u) == 0 {
+                                        n.saturating_sub(1)
+                                    } else {
+                                        n
+                                    },
+                                )
+                            })
+                            .collect();
+                    }
+
+                    let response = ui
+                        .add(egui::Button::new(RichText::new("🛡 Reserve").small()))
+                        .on_hover_cursor(CursorIcon::PointingHand)
+                        .on_hover_small(
+                            "Reserve the units that are not selected as home defense. Reserved \
+                            units are left behind by the select all and quick selection actions. \
+                            Right-click to clear the reservation.",
+                        );
+
+                    if response.clicked() {
+                        reserve = Some(origin.reserve_unselected(&army, &state.mission_info.army));
+                    } else if response.secondary_clicked() {
+                        reserve = Some(Army::new());
+                    }
+
+                    ui.style_mut().drag_value_text_style = TextStyle::Body;
+                    ui.spacing_mut().interact_size.x = 50.;
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut state.mission_fill)
+                                .speed(1)
+                                .range(0..=100)
+                                .suffix("%"),
+                        )
+                        .on_hover_small(
+                            "Select this percentage of every unit on the origin planet, \
+                            rounded to the nearest whole unit.",
+                        )
+                        .changed()
+                    {
+                        state.mission_info.army =
+                            origin.select_available(&army, state.mission_fill);
+                    }
+                });
+
+                if capacity > 0 {
+                    ui.add_space(5.);
+
+                    ui.horizontal(|ui| {
+                        ui.spacing_mut().item_spacing.x = 8.;
+
+                        ui.small(format!("📦 {}/{}", state.mission_info.cargo.total(), capacity))
+                            .on_hover_small(format!(
+                                "Resources carried by the fleet. They are taken from your \
+                                stockpile at the end of the turn (as far as it allows) and \
+                                invested in the destination on arrival, where every \
+                                {CARGO_INVESTMENT_FACTOR} resources raise the production per \
+                                mine level by 1. Resources in transit can't be plundered, but \
+                                are lost if the fleet is defeated or the planet changed hands.",
+                            ));
+
+                        ui.style_mut().drag_value_text_style = TextStyle::Small;
+                        ui.spacing_mut().interact_size.x = 45.;
+                        for resource in ResourceName::iter() {
+                            let cargo = state.mission_info.cargo;
+                            let max = cargo.get(&resource) + capacity - cargo.total();
+
+                            ui.add_image(images.get(resource.to_lowername()), [15.; 2]);
+                            ui.add(
+                                egui::DragValue::new(state.mission_info.cargo.get_mut(&resource))
+                                    .speed(10)
+                                    .range(0..=max),
+                            )
+                            .on_hover_small(resource.to_name());
+                        }
+                    });
+                }
             });
 
             ui.add_space(15.);
@@ -1246,8 +2349,19 @@ fn draw_new_mission(
                         )
                     }
                 ));
-                ui.small(format!("⛽ Fuel consumption: {fuel}"))
-                    .on_hover_small("Amount of deuterium it costs to send this mission.");
+                if state.mission_info.is_orbit_deploy(map) {
+                    ui.small("⛽ Fuel consumption: 0").on_hover_small(
+                        "Deploying ships between a planet and its moon costs no fuel.",
+                    );
+                } else if fuel < base_fuel {
+                    ui.small(format!("⛽ Fuel consumption: {fuel} ({base_fuel})")).on_hover_small(
+                        "Amount of deuterium it costs to send this mission. The value between \
+                        brackets is the consumption without the reduction from Tankers.",
+                    );
+                } else {
+                    ui.small(format!("⛽ Fuel consumption: {fuel}"))
+                        .on_hover_small("Amount of deuterium it costs to send this mission.");
+                }
 
                 if matches!(
                     state.mission_info.objective,
@@ -1324,76 +2438,228 @@ fn draw_new_mission(
                     if bombers == 0 {
                         state.mission_info.bombing = BombingRaid::None;
                     }
-                }
 
-                if state.mission_info.objective == Icon::Deploy {
-                    if player.owns(origin)
-                        && player.owns(destination)
-                        && origin.army.amount(&Unit::Building(Building::JumpGate)) > 0
-                        && destination.army.amount(&Unit::Building(Building::JumpGate)) > 0
+                    ui.horizontal(|ui| {
+                        ui.small("🎯 Targeting:");
+
+                        ui.style_mut().spacing.button_padding.y = 1.5;
+                        ui.style_mut().text_styles.get_mut(&TextStyle::Button).unwrap().size = 18.;
+
+                        ComboBox::from_id_salt("targeting")
+                            .width(125.)
+                            .selected_text(state.mission_info.targeting.to_name())
+                            .show_ui(ui, |ui| {
+                                for item in Targeting::iter() {
+                                    ui.style_mut().spacing.button_padding.y = 1.5;
+                                    ui.style_mut().spacing.item_spacing.y = 5.;
+
+                                    ui.selectable_value(
+                                        &mut state.mission_info.targeting,
+                                        item,
+                                        RichText::new(item.to_name()).small(),
+                                    )
+                                    .on_hover_cursor(CursorIcon::PointingHand)
+                                    .on_hover_small(item.description());
+                                }
+                            })
+                            .response
+                            .on_hover_cursor(CursorIcon::PointingHand);
+                    })
+                    .response
+                    .on_hover_small(
+                        "Which enemy units the fleet shoots at. Rapid fire shots follow the same \
+                        policy.",
+                    );
+
+                    if let Some(info) = player
+                        .last_info(destination, missions)
+                        .filter(|i| i.controlled && i.army.has_army())
                     {
-                        let jump_cost = state.mission_info.jump_cost();
-                        let can_jump = origin.jump_gate + jump_cost <= origin.max_jump_capacity();
+                        ui.collapsing(RichText::new("⚡ Rapid fire preview").small(), |ui| {
+                            draw_rapid_fire_preview(ui, &state.mission_info.army, &info, images);
+                        });
+                    }
+                }
 
-                        if !can_jump {
-                            state.mission_info.jump_gate = false;
-                        } else if state.mission_info.jump_gate != state.jump_gate_history {
-                            state.mission_info.jump_gate = state.jump_gate_history;
-                        }
+                if matches!(state.mission_info.objective, Icon::Deploy | Icon::MissileStrike) {
+                    state.mission_info.return_to = None;
+                } else {
+                    let controlled = map
+                        .planets
+                        .iter()
+                        .filter(|p| state.mission_info.can_return_to(p, map))
+                        .sorted_by(|a, b| a.name.cmp(&b.name))
+                        .collect::<Vec<_>>();
+
+                    // The planet could have been lost (or the origin changed) since it was selected
+                    if !controlled.iter().any(|p| Some(p.id) == state.mission_info.return_to) {
+                        state.mission_info.return_to = None;
+                    }
+
+                    ui.horizontal(|ui| {
+                        ui.small("🏠 Return to:");
+
+                        ui.style_mut().spacing.button_padding.y = 1.5;
+                        ui.style_mut().text_styles.get_mut(&TextStyle::Button).unwrap().size = 18.;
+
+                        ComboBox::from_id_salt("return to")
+                            .width(125.)
+                            .selected_text(
+                                state
+                                    .mission_info
+                                    .return_to
+                                    .map_or("Origin", |id| map.get(id).name.as_str()),
+                            )
+                            .show_ui(ui, |ui| {
+                                ui.style_mut().spacing.button_padding.y = 1.5;
+                                ui.style_mut().spacing.item_spacing.y = 5.;
+
+                                ui.selectable_value(
+                                    &mut state.mission_info.return_to,
+                                    None,
+                                    RichText::new("Origin").small(),
+                                )
+                                .on_hover_cursor(CursorIcon::PointingHand);
+
+                                for planet in controlled {
+                                    ui.selectable_value(
+                                        &mut state.mission_info.return_to,
+                                        Some(planet.id),
+                                        RichText::new(&planet.name).small(),
+                                    )
+                                    .on_hover_cursor(CursorIcon::PointingHand);
+                                }
+                            })
+                            .response
+                            .on_hover_cursor(CursorIcon::PointingHand);
+                    })
+                    .response
+                    .on_hover_small(
+                        "Planet the surviving fleet returns to after the mission. Only planets \
+                        no farther from the target than the origin can be chosen. If the planet \
+                        is no longer under your control, the fleet returns to the planet of \
+                        origin or, if that one is lost as well, to the nearest planet you \
+                        control.",
+                    );
+                }
 
+                if has_gates {
+                    ui.add_enabled_ui(can_jump, |ui| {
                         ui.horizontal(|ui| {
                             ui.small(format!(
                                 "🌀 Jump Gate ({}/{}):",
                                 jump_cost,
-                                origin.max_jump_capacity() - origin.jump_gate
+                                origin.max_jump_capacity().saturating_sub(origin.jump_gate)
                             ));
                             if ui.add(toggle(&mut state.mission_info.jump_gate)).clicked() {
-                                state.jump_gate_history = !state.jump_gate_history;
+                                state.jump_gate_routes.insert(route, state.mission_info.jump_gate);
+                                ui.ctx().request_repaint();
                             }
                         })
                         .response
                         .on_hover_small(
                             "Whether to send this mission through the Jump Gate. Missions \
-                                through the Jump Gate always take 1 turn and cost no fuel. The \
-                                armies total jump cost can't surpass the Gate's limit.",
+                            through the Jump Gate always take 1 turn and cost no fuel. The \
+                            armies total jump cost can't surpass the Gate's limit. The choice \
+                            is remembered for this route.",
+                        )
+                        .on_disabled_hover_small(
+                            "The army's jump cost surpasses the Gate's remaining capacity.",
                         );
-                    }
-                } else {
-                    state.mission_info.jump_gate = false;
-                }
+                    });
+
+                    // Preview the trade-off between both ways to travel
+                    let (jump_duration, jump_fuel) = state.mission_info.travel_cost(map, true);
+                    let (sublight_duration, sublight_fuel) =
+                        state.mission_info.travel_cost(map, false);
+                    ui.small(format!(
+                        "   Jump: {jump_duration} turn, {jump_fuel} fuel, {jump_cost} capacity"
+                    ));
+                    ui.small(format!(
+                        "   Sublight: {sublight_duration} turn{}, {sublight_fuel} fuel",
+                        if sublight_duration == 1 {
+                            ""
+                        } else {
+                            "s"
+                        }
+                    ));
+                }
+
+                if let Some(owner) = destination.controlled.filter(|&c| {
+                    state.mission_info.objective != Icon::Deploy
+                        && diplomacy.is_protected(player.id, c, settings.turn + 1)
+                }) {
+                    ui.colored_label(
+                        Color32::RED,
+                        RichText::new(format!(
+                            "⚠ You have a treaty with player {owner}. The mission will be called \
+                            off."
+                        ))
+                        .small(),
+                    );
+                }
+
+                if state.evacuate == Some(origin.id) && state.mission_info.objective == Icon::Deploy
+                {
+                    let defenses = origin
+                        .army
+                        .iter()
+                        .filter_map(|(u, c)| u.is_defense().then_some(c))
+                        .sum::<usize>();
+
+                    if defenses > 0 {
+                        ui.colored_label(
+                            Color32::RED,
+                            RichText::new(format!("⚠ {defenses} defenses are left behind."))
+                                .small(),
+                        );
+                    }
+
+                    if player.owns(origin)
+                        && origin.id != player.home_planet
+                        && origin.buy.is_empty()
+                    {
+                        ui.horizontal(|ui| {
+                            ui.small("🏳 Abandon planet:");
+                            ui.add(toggle(&mut state.evacuate_abandon));
+                        })
+                        .response
+                        .on_hover_small(
+                            "Whether to abandon the planet after the fleet leaves. The \
+                            buildings on the planet remain. Defenses on the planet are \
+                            destroyed.",
+                        );
+                    }
+                }
             });
         });
 
         ui.with_layout(Layout::bottom_up(Align::Max), |ui| {
             ui.add_space(60.);
 
+            // Recompute the fuel in case the Jump Gate was toggled this frame
+            let fuel = state.mission_info.fuel_consumption(map);
+
             let army_check = state.mission_info.army.has_army();
             let fuel_check = player.resources.get(&ResourceName::Deuterium) >= fuel;
-            let objective_check = match state.mission_info.objective {
-                Icon::Deploy => state.mission_info.army.iter().any(|(u, c)| u.is_ship() && *c > 0),
-                Icon::Colonize => state.mission_info.army.amount(&Unit::colony_ship()) > 0,
-                Icon::Attack => {
-                    state.mission_info.army.iter().any(|(u, n)| *n > 0 && u.is_combat_ship())
-                },
-                Icon::Spy => {
-                    state.mission_info.army.amount(&Unit::probe()) == state.mission_info.total()
-                },
-                Icon::MissileStrike => {
-                    state.mission_info.army.amount(&Unit::interplanetary_missile())
-                        == state.mission_info.total()
-                },
-                Icon::Destroy => state.mission_info.army.amount(&Unit::war_sun()) > 0,
-                _ => unreachable!(),
-            };
+            let mothball_check = !origin.mothballed
+                || !state.mission_info.army.iter().any(|(u, c)| u.is_ship() && *c > 0);
+            let objective_check = state.mission_info.meets_objective();
+            let send_check = army_check && fuel_check && mothball_check && objective_check;
 
             ui.horizontal(|ui| {
                 ui.add_space(40.);
 
-                ui.add_enabled_ui(army_check && fuel_check && objective_check, |ui| {
+                ui.add_enabled_ui(send_check, |ui| {
                     let response =
                         ui.add_custom_button("Send mission", images).on_disabled_hover_ui(|ui| {
                             if !army_check {
                                 ui.small("No ships selected for the mission.");
+                            } else if !mothball_check {
+                                ui.small(
+                                    "The ships on this planet are mothballed since their upkeep \
+                                    couldn't be paid.",
+                                );
                             } else if !fuel_check {
                                 ui.small("Not enough fuel (deuterium) for the mission.");
                             } else {
@@ -1406,30 +2672,42 @@ fn draw_new_mission(
                     if response.clicked()
                         || (response.enabled() && keyboard.just_pressed(KeyCode::Enter))
                     {
-                        let mission = Mission::from_mission(
-                            settings.turn,
-                            player.id,
-                            origin,
-                            destination,
-                            &state.mission_info,
-                        );
+                        let mission = Mission {
+                            cargo: state.mission_info.cargo,
+                            ..Mission::from_mission(
+                                settings.turn,
+                                player.id,
+                                origin,
+                                destination,
+                                &state.mission_info,
+                            )
+                        };
 
-                        send_mission.write(SendMissionMsg::new(mission));
+                        state.last_mission = Some(mission.clone());
+                        send_mission.write(SendMissionMsg {
+                            abandon: state.evacuate == Some(origin.id) && state.evacuate_abandon,
+                            ..SendMissionMsg::new(mission)
+                        });
                         state.planet_selected = None;
                         state.mission = false;
                         state.mission_info = Mission::default();
+                        state.evacuate = None;
                     }
                 });
             });
         });
     }
+
+    if let Some(reserved) = reserve {
+        map.get_mut(state.mission_info.origin).reserved = reserved;
+    }
 }
 
 fn draw_active_missions(
     ui: &mut Ui,
     missions: Vec<&Mission>,
     state: &mut UiState,
-    map: &Map,
+    map: &mut Map,
     player: &Player,
     is_hovered: bool,
     images: &ImageIds,
@@ -1449,6 +2727,8 @@ fn draw_active_missions(
 
     ui.add_space(30.);
 
+    let mut intercept = None;
+
     ScrollArea::vertical()
         .max_width(ui.available_width() - 45.)
         .max_height(ui.available_height() - 50.)
@@ -1578,6 +2858,45 @@ fn draw_active_missions(
 
                             action(resp3, resp4, destination, &mut changed_hover, state);
 
+                            // Detected enemy fleets can be intercepted by the target's fleet
+                            if mission.owner != player.id
+                                && mission.objective != Icon::MissileStrike
+                                && player.controls(destination)
+                                && mission.is_seen_by_radar(map, player).is_some()
+                            {
+                                ui.cell(90., |ui| {
+                                    ui.add_space(15.);
+
+                                    let selected = destination.intercept == Some(mission.id);
+
+                                    ui.add_enabled_ui(destination.army.combat_ships() > 0, |ui| {
+                                        let response = ui
+                                            .add(egui::Button::selectable(
+                                                selected,
+                                                RichText::new("Intercept").small(),
+                                            ))
+                                            .on_hover_cursor(CursorIcon::PointingHand)
+                                            .on_hover_small(
+                                                "Sortie the combat ships of the destination \
+                                                planet to fight this fleet the turn before it \
+                                                arrives. Fleets faster than the interceptors \
+                                                can't be caught.",
+                                            )
+                                            .on_disabled_hover_small(
+                                                "There are no combat ships on the destination \
+                                                planet to intercept this fleet.",
+                                            );
+
+                                        if response.clicked() {
+                                            intercept = Some((
+                                                destination.id,
+                                                (!selected).then_some(mission.id),
+                                            ));
+                                        }
+                                    });
+                                });
+                            }
+
                             ui.end_row();
                         }
 
@@ -1590,13 +2909,19 @@ fn draw_active_missions(
                 );
             });
         });
+
+    if let Some((planet_id, mission_id)) = intercept {
+        map.get_mut(planet_id).intercept = mission_id;
+    }
 }
 
 fn draw_mission_reports(
     ui: &mut Ui,
     state: &mut UiState,
+    settings: &Settings,
     map: &Map,
     player: &Player,
+    roster: &Roster,
     is_hovered: bool,
     images: &ImageIds,
 ) {
@@ -1831,11 +3156,7 @@ fn draw_mission_reports(
             ui.horizontal(|ui| {
                 ui.visuals_mut().widgets.noninteractive.bg_stroke.width = 6.;
 
-                let (a_color, d_color) = if report.mission.owner == player.id {
-                    (OWN_COLOR, ENEMY_COLOR)
-                } else {
-                    (ENEMY_COLOR, OWN_COLOR)
-                };
+                let (a_color, d_color) = roster.report_colors(report, player, settings);
 
                 ui.vertical(|ui| {
                     ui.set_width(140.);
@@ -1934,6 +3255,23 @@ fn draw_mission_reports(
                         });
                     }
 
+                    if report.plunder.total() > 0 {
+                        ui.horizontal(|ui| {
+                            ui.spacing_mut().item_spacing.x = 4.0;
+                            ui.add_space(10.);
+                            ui.small("Plundered:");
+                            for resource in ResourceName::iter() {
+                                ui.add_image(images.get(resource.to_lowername()), [25., 18.]);
+                                ui.small(report.plunder.get(&resource).to_string());
+                            }
+                        })
+                        .response
+                        .on_hover_small_ext(
+                            "Resources the attacker took from the defender's stockpile, limited \
+                            by the cargo capacity of the surviving fleet.",
+                        );
+                    }
+
                     ui.with_layout(Layout::bottom_up(Align::Max), |ui| {
                         if report.combat_report.is_some()
                             && report.can_see(&Side::Attacker, player.id)
@@ -1959,6 +3297,8 @@ fn draw_mission(
     state: &mut UiState,
     map: &mut Map,
     player: &mut Player,
+    diplomacy: &Diplomacy,
+    roster: &Roster,
     is_hovered: bool,
     keyboard: &ButtonInput<KeyCode>,
     images: &ImageIds,
@@ -1976,11 +3316,13 @@ fn draw_mission(
     match state.mission_tab {
         MissionTab::NewMission => draw_new_mission(
             ui,
+            missions,
             send_mission,
             settings,
             state,
             map,
             player,
+            diplomacy,
             is_hovered,
             keyboard,
             images,
@@ -2004,16 +3346,76 @@ fn draw_mission(
             images,
         ),
         MissionTab::MissionReports => {
-            draw_mission_reports(ui, state, map, player, is_hovered, images)
+            draw_mission_reports(ui, state, settings, map, player, roster, is_hovered, images)
         },
     }
 }
 
+/// Launched, intercepted and hitting missiles of a missile strike, with the hits per target
+fn draw_missile_summary(ui: &mut Ui, round: &RoundReport, images: &ImageIds) {
+    let launched = round.n_missiles();
+    let intercepted = round.missiles_shot();
+
+    ui.add_space(15.);
+    ui.label("Missiles");
+    ui.separator();
+
+    egui::Grid::new("missile_grid").num_columns(2).spacing([8., 4.]).show(ui, |ui| {
+        for (label, value, hover) in [
+            ("Launched", launched, "Interplanetary Missiles launched at the planet."),
+            (
+                "Intercepted",
+                intercepted,
+                "Interplanetary Missiles destroyed by Antiballistic Missiles before impact.",
+            ),
+            ("Hit", launched - intercepted, "Interplanetary Missiles that reached their target."),
+            (
+                "Antiballistic",
+                round.antiballistic_fired,
+                "Antiballistic Missiles fired by the defender. Each of them can intercept at \
+                most one Interplanetary Missile.",
+            ),
+        ] {
+            ui.small(label).on_hover_small(hover);
+            ui.small(value.to_string());
+            ui.end_row();
+        }
+    });
+
+    // Hits and kills per targeted defense
+    let mut targets: HashMap<Unit, (usize, usize)> = HashMap::new();
+    for shot in round.attacker.iter().flat_map(|cu| &cu.shots).filter(|s| !s.missed) {
+        if let Some(unit) = shot.unit {
+            let entry = targets.entry(unit).or_default();
+            entry.0 += 1;
+            entry.1 += shot.killed as usize;
+        }
+    }
+
+    if !targets.is_empty() {
+        ui.add_space(10.);
+
+        for unit in Unit::defenses().iter().filter(|u| targets.contains_key(u)) {
+            let (hits, destroyed) = targets[unit];
+            ui.horizontal(|ui| {
+                ui.add_image(images.get(unit.to_lowername()), [25., 25.]);
+                ui.small(format!("{hits} 💀{destroyed}")).on_hover_small(format!(
+                    "{}: hit {hits} times, {destroyed} destroyed.",
+                    unit.to_name()
+                ));
+            });
+        }
+    }
+}
+
 fn draw_combat_report(
     ui: &mut Ui,
     state: &mut UiState,
     map: &Map,
     player: &Player,
+    roster: &Roster,
+    settings: &mut Settings,
+    next_game_state: &mut NextState<GameState>,
     images: &ImageIds,
 ) {
     let report = player.reports.iter().find(|r| r.id == state.combat_report.unwrap()).unwrap();
@@ -2034,6 +3436,11 @@ fn draw_combat_report(
         ui.add_space(5.);
         ui.small(&origin.name);
 
+        if report.mission.veterancy > 0 {
+            ui.small(format!("🎖{}", report.mission.veterancy))
+                .on_hover_small("Veterancy of the attacking fleet.");
+        }
+
         ui.add_space(25.);
 
         ui.add_image(images.get(report.mission.objective.to_lowername()), [25.; 2]);
@@ -2042,6 +3449,11 @@ fn draw_combat_report(
 
         ui.add_space(25.);
 
+        if report.planet.veterancy > 0 {
+            ui.small(format!("🎖{}", report.planet.veterancy))
+                .on_hover_small("Veterancy of the defending fleet.");
+        }
+
         ui.small(&destination.name);
         ui.add_space(5.);
         let resp = ui.add_image(images.get(destination.image()), [35., 35.]);
@@ -2068,6 +3480,14 @@ fn draw_combat_report(
                 ui.add_space(10.);
 
                 ui.small(format!("Round: {}/{}", state.combat_report_round, combat.rounds.len()));
+
+                if combat.round_cap {
+                    ui.add_space(10.);
+                    ui.small("⏱ Draw").on_hover_small(
+                        "The combat was ended by the maximum number of rounds. The surviving \
+                        attackers return to their origin.",
+                    );
+                }
             });
 
             ui.add_space(30.);
@@ -2079,10 +3499,36 @@ fn draw_combat_report(
 
             ui.add_space(10.);
 
-            ui.small("Total:")
+            ui.small("Total:");
+
+            ui.add_space(30.);
+
+            ui.add_enabled_ui(state.combat_report_total, |ui| {
+                ui.add(toggle(&mut state.combat_report_average)).on_hover_small(
+                    "If enabled, the total damage, repairs and kills are divided by the number \
+                    of rounds, to compare the efficiency of battles with a different length.",
+                );
+
+                ui.add_space(10.);
+
+                ui.small("Per round:");
+            });
         });
     });
 
+    // Divide the aggregated statistics by the number of rounds in the averaged view
+    let average = state.combat_report_total && state.combat_report_average;
+    let aggregate = |n: usize| {
+        if average {
+            match (n as f32).safe_div(combat.rounds.len() as f32) {
+                n if n >= 1_000. => (n.round() as usize).fmt(),
+                n => format!("{n:.1}"),
+            }
+        } else {
+            n.fmt()
+        }
+    };
+
     let round = if state.combat_report_total {
         let mut rr = combat.rounds.iter().fold(RoundReport::default(), |mut rr, r| {
             rr.attacker.extend(r.attacker.clone());
@@ -2102,6 +3548,15 @@ fn draw_combat_report(
         combat.rounds.get(state.combat_report_round - 1).unwrap().clone()
     };
 
+    // Explain how the shots are resolved when detailed hover info is enabled
+    let formula = |text: &str, details: &str| {
+        if settings.show_hover {
+            format!("{text}\n\n{details}")
+        } else {
+            text.to_string()
+        }
+    };
+
     let draw_stats = |ui: &mut Ui, units: Vec<&CombatUnit>, side: Side| {
         let shots = units.iter().flat_map(|u| &u.shots).collect::<Vec<_>>();
 
@@ -2131,9 +3586,9 @@ fn draw_combat_report(
         let rapid_fire = shots.iter().filter(|a| a.rapid_fire).count();
         let enemies_killed = shots.iter().filter(|a| a.killed).count();
 
-        let draw_row = |ui: &mut Ui, icon: &str, val: String, hover: &str| {
+        let draw_row = |ui: &mut Ui, icon: &str, val: String, hover: String| {
             ui.vertical_centered(|ui| {
-                ui.label(icon).on_hover_small(hover);
+                ui.label(icon).on_hover_small(&hover);
             });
             ui.label(if units.is_empty() {
                 "--".to_string()
@@ -2147,37 +3602,80 @@ fn draw_combat_report(
         egui::Grid::new("stats_grid").striped(false).num_columns(2).spacing([2., 6.]).show(
             ui,
             |ui| {
-                draw_row(ui, "🛡", shield_damage.fmt(), "Damage dealt to shields.");
-                draw_row(ui, "🔰", hull_damage.fmt(), "Damage dealt to hulls.");
+                draw_row(
+                    ui,
+                    "🛡",
+                    aggregate(shield_damage),
+                    formula(
+                        "Damage dealt to shields.",
+                        "Every shot first hits the target's shield: shield damage = \
+                        min(damage - shield piercing, remaining shield). Shields regenerate \
+                        at the start of every round.",
+                    ),
+                );
+                draw_row(
+                    ui,
+                    "🔰",
+                    aggregate(hull_damage),
+                    formula(
+                        "Damage dealt to hulls.",
+                        "The rest of the shot hits the hull: hull damage = min(damage - \
+                        shield damage, remaining hull). A unit is destroyed when its hull \
+                        reaches 0. Hull damage persists between rounds.",
+                    ),
+                );
                 if side == Side::Attacker {
-                    draw_row(ui, "🌐", ps_damage.fmt(), "Damage dealt to the planetary shield.");
+                    draw_row(
+                        ui,
+                        "🌐",
+                        aggregate(ps_damage),
+                        formula(
+                            "Damage dealt to the planetary shield.",
+                            "While the planetary shield is up, shots aimed at defenses (except \
+                            the Space Dock) and bombs hit the planetary shield instead, up to \
+                            its remaining points. The planetary shield doesn't regenerate \
+                            during the combat.",
+                        ),
+                    );
                 }
                 draw_row(
                     ui,
                     "⚔",
-                    (shield_damage + hull_damage + ps_damage).fmt(),
-                    "Total damage dealt.",
+                    aggregate(shield_damage + hull_damage + ps_damage),
+                    formula(
+                        "Total damage dealt.",
+                        "Damage per shot = unit damage × (100% + veterancy bonus) for ships.",
+                    ),
                 );
                 if side == Side::Defender {
                     draw_row(
                         ui,
                         "❤",
-                        total_repaired.to_string(),
-                        "Total hull points repaired by Crawlers.",
+                        aggregate(total_repaired),
+                        "Total hull points repaired by Crawlers.".to_string(),
                     );
                 }
                 draw_row(
                     ui,
                     "❌",
                     format!("{:.0}%", (shots_missed as f32).safe_div(u_shots.len() as f32) * 100.),
-                    "Percentage of shots that missed a target. A shot misses when it \
-                    fires on a unit that was already destroyed that round.",
+                    formula(
+                        "Percentage of shots that missed a target. A shot misses when it \
+                        fires on a unit that was already destroyed that round.",
+                        "Targets are picked among all enemy units alive at the start of the \
+                        round, so the damage of a missed shot is lost.",
+                    ),
                 );
                 draw_row(
                     ui,
                     "🔥",
                     format!("{:.0}%", (rapid_fire as f32).safe_div(u_shots.len() as f32) * 100.),
-                    "Percentage of shots that gained rapid fire.",
+                    formula(
+                        "Percentage of shots that gained rapid fire.",
+                        "After every shot, a unit fires again at a new target with a \
+                        probability of 100% - its rapid fire against the unit it just shot. \
+                        Without rapid fire against that unit, it stops firing.",
+                    ),
                 );
                 if report.mission.objective == Icon::MissileStrike && side == Side::Defender {
                     draw_row(
@@ -2188,7 +3686,8 @@ fn draw_combat_report(
                             (missiles_hit as f32).safe_div(m_shots.len() as f32) * 100.
                         ),
                         "Percentage of Antiballistic Missiles that intercepted an \
-                        incoming Interplanetary Missile.",
+                        incoming Interplanetary Missile."
+                            .to_string(),
                     );
                 }
                 if report.mission.bombing != BombingRaid::None && side == Side::Attacker {
@@ -2196,16 +3695,21 @@ fn draw_combat_report(
                         ui,
                         "💣",
                         format!("{:.0}%", (bombs_hit as f32).safe_div(b_shots.len() as f32) * 100.),
-                        "Percentage of bombs that hit enemy buildings.",
+                        "Percentage of bombs that hit enemy buildings.".to_string(),
                     );
                 }
-                draw_row(ui, "💀", enemies_killed.fmt(), "Number of enemy units destroyed.");
+                draw_row(
+                    ui,
+                    "💀",
+                    aggregate(enemies_killed),
+                    "Number of enemy units destroyed.".to_string(),
+                );
                 if report.mission.objective == Icon::Destroy && side == Side::Attacker {
                     draw_row(
                         ui,
                         "☠",
                         format!("{:.0}%", round.destroy_probability * 100.),
-                        "Probability of successfully destroying the planet.",
+                        "Probability of successfully destroying the planet.".to_string(),
                     );
                 }
             },
@@ -2216,11 +3720,7 @@ fn draw_combat_report(
 
     let (attacker_w, defender_w) = (ui.available_width() * 0.3, ui.available_width() * 0.6);
 
-    let (attack_c, defend_c) = if report.mission.owner == player.id {
-        (OWN_COLOR, ENEMY_COLOR)
-    } else {
-        (ENEMY_COLOR, OWN_COLOR)
-    };
+    let (attack_c, defend_c) = roster.report_colors(report, player, settings);
 
     ui.horizontal(|ui| {
         ui.add_space(40.);
@@ -2263,10 +3763,15 @@ fn draw_combat_report(
                                 .combat_report_hover
                                 .as_ref()
                                 .map_or(true, |(u, s)| *u == cu.unit && *s == Side::Attacker)
+                                && !state.combat_report_filter.contains(&(cu.unit, Side::Attacker))
                         })
                         .collect::<Vec<_>>();
 
                     draw_stats(ui, units, Side::Attacker);
+
+                    if report.mission.objective == Icon::MissileStrike {
+                        draw_missile_summary(ui, &round, images);
+                    }
                 });
 
                 ui.vertical(|ui| {
@@ -2284,6 +3789,8 @@ fn draw_combat_report(
                         },
                         Side::Attacker,
                         attack_c,
+                        report.mission.veterancy,
+                        settings.show_hover,
                         images,
                     );
                     any_hovered = any_hovered || hovered;
@@ -2317,6 +3824,8 @@ fn draw_combat_report(
                                         Unit::ships(),
                                         Side::Defender,
                                         defend_c,
+                                        report.planet.veterancy,
+                                        settings.show_hover,
                                         images,
                                     )
                                 } else {
@@ -2349,6 +3858,8 @@ fn draw_combat_report(
                                         .collect(),
                                     Side::Defender,
                                     defend_c,
+                                    report.planet.veterancy,
+                                    settings.show_hover,
                                     images,
                                 )
                             } else {
@@ -2368,6 +3879,8 @@ fn draw_combat_report(
                                     vec![Unit::planetary_shield()],
                                     Side::Defender,
                                     defend_c,
+                                    report.planet.veterancy,
+                                    settings.show_hover,
                                     images,
                                 );
                             }
@@ -2403,6 +3916,8 @@ fn draw_combat_report(
                                     units,
                                     Side::Defender,
                                     defend_c,
+                                    report.planet.veterancy,
+                                    settings.show_hover,
                                     images,
                                 );
                             }
@@ -2419,7 +3934,9 @@ fn draw_combat_report(
                                 .filter(|cu| {
                                     state.combat_report_hover.as_ref().map_or(true, |(u, s)| {
                                         *u == cu.unit && *s == Side::Defender
-                                    })
+                                    }) && !state
+                                        .combat_report_filter
+                                        .contains(&(cu.unit, Side::Defender))
                                 })
                                 .collect::<Vec<_>>();
 
@@ -2441,11 +3958,25 @@ fn draw_combat_report(
             ui.add_space(40.);
             if ui.add_custom_button("Close details", images).clicked() {
                 state.combat_report = None;
+                state.combat_report_filter.clear();
             }
 
-            ui.add_space(310.);
+            if settings.auto_resolve {
+                ui.add_space(20.);
+                if ui.add_custom_button("Watch combat", images).clicked() {
+                    state.in_combat = state.combat_report.take();
+                    settings.combat_paused = false;
+                    next_game_state.set(GameState::Combat);
+                }
+                ui.add_space(110.);
+            } else {
+                ui.add_space(310.);
+            }
 
-            ui.small("Hover over a unit to show the statistics for that unit only.");
+            ui.small(
+                "Hover over a unit to show the statistics for that unit only. Click it to \
+                exclude it from the statistics.",
+            );
         });
     });
 }
@@ -2531,12 +4062,104 @@ fn draw_mission_info_hover(
     });
 }
 
+/// Effect of upgrading a building one level and, for economy buildings, the
+/// number of turns it takes to recover the upgrade's cost
+fn upgrade_effect(
+    building: &Building,
+    level: usize,
+    planet: &Planet,
+    settings: &Settings,
+) -> (String, Option<usize>) {
+    let unlocks = |units: Vec<Unit>| {
+        units
+            .iter()
+            .filter(|u| u.production() == level + 1)
+            .map(|u| u.to_name())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let resource = match building {
+        Building::MetalMine => Some(ResourceName::Metal),
+        Building::CrystalMine => Some(ResourceName::Crystal),
+        Building::DeuteriumSynthesizer => Some(ResourceName::Deuterium),
+        _ => None,
+    };
+
+    if let Some(resource) = resource {
+        let delta = planet.production_increase(&resource) * settings.difficulty.production() / 100;
+
+        let price = Unit::Building(*building).price();
+        let cost = price.metal + price.crystal + price.deuterium;
+
+        return (
+            format!("Next level: +{delta} {} per turn.", resource.to_lowername()),
+            (delta > 0).then(|| cost.div_ceil(delta)),
+        );
+    }
+
+    let effect = match building {
+        Building::LunarBase => "Next level: +1 field on the moon.".to_string(),
+        Building::DemolitionNexus => {
+            "Next level: one more lunar building level destroyed on conquest.".to_string()
+        },
+        Building::Shipyard | Building::Factory => {
+            let limit = if *building == Building::Shipyard {
+                SHIPYARD_PRODUCTION_FACTOR
+            } else {
+                FACTORY_PRODUCTION_FACTOR
+            };
+
+            let unlocked = unlocks(building.gated_units());
+            format!(
+                "Next level: +{limit} production limit{}.",
+                if unlocked.is_empty() {
+                    String::new()
+                } else {
+                    format!(", unlocks {unlocked}")
+                }
+            )
+        },
+        Building::MissileSilo => {
+            let unlocked = unlocks(building.gated_units());
+            format!(
+                "Next level: +{SILO_CAPACITY_FACTOR} missile slots{}.",
+                if unlocked.is_empty() {
+                    String::new()
+                } else {
+                    format!(", unlocks {unlocked}")
+                }
+            )
+        },
+        Building::PlanetaryShield => format!("Next level: +{PS_SHIELD_PER_LEVEL} shield."),
+        Building::Reactor => "Next level: -10% fuel consumption.".to_string(),
+        Building::SensorPhalanx => {
+            format!("Next level: +{PHALANX_DISTANCE} AU range, sees production {}.", level + 1)
+        },
+        Building::JumpGate => {
+            format!("Next level: +{FACTORY_PRODUCTION_FACTOR} ships transported per turn.")
+        },
+        Building::Laboratory => format!(
+            "Next level: conversion rate 1:{}.",
+            1. + 0.5 * 5usize.saturating_sub(level + 1) as f32
+        ),
+        Building::OrbitalRadar => {
+            format!("Next level: +{RADAR_DISTANCE} AU range, sees production {}.", level + 1)
+        },
+        _ => unreachable!(),
+    };
+
+    (effect, None)
+}
+
 fn draw_unit_hover(
     ui: &mut Ui,
     unit: &Unit,
     count: usize,
     state: &mut UiState,
     player: &mut Player,
+    planet: &Planet,
+    settings: &Settings,
     msg: Option<String>,
     images: &ImageIds,
 ) {
@@ -2568,6 +4191,35 @@ fn draw_unit_hover(
 
             ui.small(unit.description());
 
+            if !unit.is_building() && *unit != Unit::space_dock() {
+                ui.add_space(5.);
+                ui.small(
+                    "Right-click to buy 5 units. Shift/ctrl + click to buy as many as possible.",
+                );
+            }
+
+            if let Unit::Building(building) = unit {
+                if count < Building::MAX_LEVEL {
+                    let (effect, payback) = upgrade_effect(building, count, planet, settings);
+
+                    ui.add_space(5.);
+                    ui.small(effect);
+
+                    if let Some(turns) = payback {
+                        ui.small(format!(
+                            "Pays back its cost in ≈{turns} turn{}.",
+                            if turns == 1 {
+                                ""
+                            } else {
+                                "s"
+                            }
+                        ));
+                    }
+
+                    draw_unlocked_units(ui, building, count, images);
+                }
+            }
+
             ui.add_space(10.);
 
             ui.spacing_mut().item_spacing.y = 0.;
@@ -2576,49 +4228,8 @@ fn draw_unit_hover(
                 ui.separator();
             }
 
-            let stat_hover = |ui: &mut Ui, stat: &CombatStats| {
-                ui.set_width(500.);
-                ui.horizontal(|ui| {
-                    ui.vertical(|ui| {
-                        ui.add_image(images.get(stat.to_lowername()), [130., 90.]);
-                    });
-                    ui.vertical(|ui| {
-                        ui.label(stat.to_name());
-                        ui.separator();
-                        ui.small(stat.description());
-                    });
-                });
-            };
-
             if !unit.is_building() {
-                for (i, row) in CombatStats::iter()
-                    .filter(|c| *c != CombatStats::RapidFire)
-                    .collect::<Vec<CombatStats>>()
-                    .chunks(3)
-                    .enumerate()
-                {
-                    if i == 0 || row.iter().any(|s| unit.get_stat(s) != "---") {
-                        egui::Grid::new(ui.auto_id_with(format!("row_{:?}", row[0])))
-                            .spacing([20., 0.])
-                            .striped(false)
-                            .show(ui, |ui| {
-                                for stat in row {
-                                    ui.horizontal(|ui| {
-                                        ui.set_width(150.);
-                                        ui.style_mut().interaction.selectable_labels = true;
-
-                                        ui.add_image(images.get(stat.to_lowername()), [70., 45.]);
-                                        ui.label(unit.get_stat(&stat))
-                                            .on_hover_cursor(CursorIcon::Default);
-                                    })
-                                    .response
-                                    .on_hover_ui(|ui| stat_hover(ui, stat));
-                                }
-                            });
-                    }
-
-                    ui.spacing_mut().item_spacing.y = 10.;
-                }
+                draw_combat_stats(ui, unit, images);
             } else if *unit == Unit::Building(Building::Laboratory) && count > 0 {
                 let (from, to) = &mut state.lab;
 
@@ -2694,84 +4305,519 @@ fn draw_unit_hover(
                 });
             }
 
-            if !unit.rapid_fire().is_empty() {
-                ui.separator();
-                ui.small(CombatStats::RapidFire.to_name())
-                    .on_hover_ui(|ui| stat_hover(ui, &CombatStats::RapidFire));
-
-                egui::Grid::new("rapid_fire").spacing([10., 10.]).striped(false).show(ui, |ui| {
-                    let mut counter = 0;
-                    for rf_unit in Unit::all().iter().flatten() {
-                        if let Some(rf) = unit.rapid_fire().get(rf_unit) {
-                            ui.horizontal(|ui| {
-                                ui.set_width(115.);
-                                ui.spacing_mut().item_spacing.x = 8.;
-
-                                ui.add_image(images.get(rf_unit.to_lowername()), [45., 45.]);
-                                ui.small(format!("{}%", rf));
-                            })
-                            .response
-                            .on_hover_text(RichText::new(rf_unit.to_name()).small());
-
-                            counter += 1;
-                            if counter % 4 == 0 {
-                                ui.end_row();
-                            }
-                        }
-                    }
-                });
-            }
+            draw_rapid_fire(ui, unit, images);
         });
     });
 }
 
-fn draw_shop(
-    ui: &mut Ui,
-    state: &mut UiState,
-    settings: &Settings,
-    player: &mut Player,
-    planet: &mut Planet,
-    images: &ImageIds,
-) {
-    ui.spacing_mut().item_spacing = emath::Vec2::new(4., 4.);
-
-    ui.add_space(4.);
+/// Units gated by an industrial building, comparing the current level with the next
+fn draw_unlocked_units(ui: &mut Ui, building: &Building, level: usize, images: &ImageIds) {
+    let units = building
+        .gated_units()
+        .into_iter()
+        .filter(|u| u.production() <= level + 1)
+        .collect::<Vec<_>>();
 
-    if planet.is_moon() && state.shop == Shop::Defenses {
-        state.shop = Shop::default();
+    if units.is_empty() {
+        return;
     }
 
-    let (current, max, idx) = match state.shop {
-        Shop::Buildings => (planet.fields_consumed(), planet.max_fields(), 0),
-        Shop::Fleet => (planet.fleet_production(), planet.max_fleet_production(), 1),
-        Shop::Defenses => (planet.battery_production(), planet.max_battery_production(), 2),
-    };
+    ui.add_space(5.);
+    ui.horizontal_wrapped(|ui| {
+        ui.spacing_mut().item_spacing.x = 4.;
 
-    ui.horizontal(|ui| {
-        ui.add_space(45.);
-        ui.add_image(images.get(state.shop.to_lowername()), [20., 20.]);
-        ui.small(state.shop.to_name());
+        for unit in units {
+            let unlocked = unit.production() > level;
 
-        if state.shop != Shop::Buildings || planet.is_moon() {
-            ui.with_layout(Layout::right_to_left(Align::Min), |ui| {
-                ui.add_space(45.);
-                ui.small(format!(
-                    "{}: {}/{}",
-                    if planet.is_moon() {
-                        "Fields"
-                    } else {
-                        "Production"
-                    },
-                    current,
-                    max
-                ));
+            ui.scope(|ui| {
+                // Units unlocked by the next level are greyed out
+                if unlocked {
+                    ui.set_opacity(0.4);
+                }
+
+                ui.add_image(images.get(unit.to_lowername()), [35.; 2]);
+            })
+            .response
+            .on_hover_text(
+                RichText::new(if unlocked {
+                    format!("{} (unlocked at level {})", unit.to_name(), level + 1)
+                } else {
+                    unit.to_name()
+                })
+                .small(),
+            );
+        }
+    });
+}
+
+fn draw_stat_hover(ui: &mut Ui, stat: &CombatStats, images: &ImageIds) {
+    ui.set_width(500.);
+    ui.horizontal(|ui| {
+        ui.vertical(|ui| {
+            ui.add_image(images.get(stat.to_lowername()), [130., 90.]);
+        });
+        ui.vertical(|ui| {
+            ui.label(stat.to_name());
+            ui.separator();
+            ui.small(stat.description());
+        });
+    });
+}
+
+/// Grid with the combat stats of a ship or defense
+fn draw_combat_stats(ui: &mut Ui, unit: &Unit, images: &ImageIds) {
+    for (i, row) in CombatStats::iter()
+        .filter(|c| *c != CombatStats::RapidFire)
+        .collect::<Vec<CombatStats>>()
+        .chunks(3)
+        .enumerate()
+    {
+        if i == 0 || row.iter().any(|s| unit.get_stat(s) != "---") {
+            egui::Grid::new(ui.auto_id_with(format!("row_{:?}", row[0])))
+                .spacing([20., 0.])
+                .striped(false)
+                .show(ui, |ui| {
+                    for stat in row {
+                        ui.horizontal(|ui| {
+                            ui.set_width(150.);
+                            ui.style_mut().interaction.selectable_labels = true;
+
+                            ui.add_image(images.get(stat.to_lowername()), [70., 45.]);
+                            ui.label(unit.get_stat(&stat)).on_hover_cursor(CursorIcon::Default);
+                        })
+                        .response
+                        .on_hover_ui(|ui| draw_stat_hover(ui, stat, images));
+                    }
+                });
+        }
+
+        ui.spacing_mut().item_spacing.y = 10.;
+    }
+}
+
+/// Grid with the units a ship or defense has rapid fire against
+fn draw_rapid_fire(ui: &mut Ui, unit: &Unit, images: &ImageIds) {
+    if !unit.rapid_fire().is_empty() {
+        ui.separator();
+        ui.small(CombatStats::RapidFire.to_name())
+            .on_hover_ui(|ui| draw_stat_hover(ui, &CombatStats::RapidFire, images));
+
+        egui::Grid::new("rapid_fire").spacing([10., 10.]).striped(false).show(ui, |ui| {
+            let mut counter = 0;
+            for rf_unit in Unit::all().iter().flatten() {
+                if let Some(rf) = unit.rapid_fire().get(rf_unit) {
+                    ui.horizontal(|ui| {
+                        ui.set_width(115.);
+                        ui.spacing_mut().item_spacing.x = 8.;
+
+                        ui.add_image(images.get(rf_unit.to_lowername()), [45., 45.]);
+                        ui.small(format!("{}%", rf));
+                    })
+                    .response
+                    .on_hover_ui(|ui| draw_matchup_hover(ui, unit, rf_unit, 0));
+
+                    counter += 1;
+                    if counter % 4 == 0 {
+                        ui.end_row();
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// Outcome of a single shot of a unit on an undamaged target, following the
+/// combat resolution: (damage, shield damage, hull damage, shots to destroy)
+fn shot_outcome(unit: &Unit, target: &Unit, veterancy: usize) -> (usize, usize, usize, usize) {
+    let mut damage = unit.damage();
+    if unit.is_ship() {
+        damage = damage * (100 + VETERANCY_BONUS * veterancy) / 100;
+    }
+
+    let pierced = damage * unit.shield_piercing() / 100;
+    let shot = |shield: usize| {
+        let shield_damage = (damage - pierced).min(shield);
+        (shield_damage, damage - shield_damage)
+    };
+
+    let (shield_damage, hull_damage) = shot(target.shield());
+
+    // Shields only regenerate at the start of the next round
+    let (mut shield, mut hull, mut shots) = (target.shield(), target.hull(), 0);
+    while hull > 0 && damage > 0 && shots < 1000 {
+        let (s, h) = shot(shield);
+        shield -= s;
+        hull = hull.saturating_sub(h);
+        shots += 1;
+    }
+
+    (damage, shield_damage, hull_damage.min(target.hull()), shots)
+}
+
+/// Detailed explanation of how the shots of a unit on a target are resolved
+fn draw_matchup_hover(ui: &mut Ui, unit: &Unit, target: &Unit, veterancy: usize) {
+    ui.set_max_width(420.);
+
+    ui.label(format!("{} ➡ {}", unit.to_name(), target.to_name()));
+    ui.separator();
+
+    let (damage, shield_damage, hull_damage, shots) = shot_outcome(unit, target, veterancy);
+    let piercing = unit.shield_piercing();
+
+    ui.small(if unit.is_ship() && veterancy > 0 {
+        format!(
+            "Damage per shot: {} × {}% veterancy = {damage}.",
+            unit.damage(),
+            100 + VETERANCY_BONUS * veterancy
+        )
+    } else {
+        format!("Damage per shot: {damage}.")
+    });
+
+    ui.small(if piercing > 0 {
+        format!(
+            "Shield: min({damage} - {piercing}% piercing, {}) = {shield_damage} absorbed.",
+            target.shield()
+        )
+    } else {
+        format!("Shield: min({damage}, {}) = {shield_damage} absorbed.", target.shield())
+    });
+    ui.small(format!(
+        "Hull: {damage} - {shield_damage} = {hull_damage} of {} hull points.",
+        target.hull()
+    ));
+
+    if shots > 0 && shots < 1000 {
+        ui.small(format!(
+            "Shots to destroy it within one round: {shots}. Shields regenerate at the start of \
+            every round, hull damage is permanent."
+        ));
+    }
+
+    ui.add_space(5.);
+
+    if let Some(rf) = unit.rapid_fire().get(target) {
+        let again = 100 - (*rf).min(100);
+        ui.small(format!(
+            "Rapid fire: after every shot on this unit, there is a {again}% (100% - {rf}%) chance \
+            to fire again at a new random target, i.e. ≈{:.1} extra shots if all targets were \
+            of this type.",
+            again as f32 / 100. / (1. - again as f32 / 100.)
+        ));
+    } else {
+        ui.small("No rapid fire: the unit stops firing after a shot on this unit.");
+    }
+
+    if target.is_defense()
+        && *target != Unit::space_dock()
+        && *unit != Unit::interplanetary_missile()
+    {
+        ui.small(format!(
+            "While the planetary shield is up ({PS_SHIELD_PER_LEVEL} per level), shots aimed \
+            at this unit hit the planetary shield instead."
+        ));
+    }
+
+    ui.small("A shot aimed at a unit already destroyed this round misses, and its damage is lost.");
+}
+
+/// Expected number of extra shots per round of a unit due to rapid fire, assuming
+/// every shot picks a random target of the army
+fn expected_extra_shots(unit: &Unit, enemy: &Army) -> f32 {
+    let targets = enemy.iter().filter(|(u, c)| !u.is_building() && **c > 0).collect::<Vec<_>>();
+    let total = targets.iter().map(|(_, c)| **c).sum::<usize>();
+
+    if total == 0 {
+        return 0.;
+    }
+
+    // Probability to fire again after every shot
+    let rapid_fire = unit.rapid_fire();
+    let again = targets
+        .iter()
+        .map(|(u, c)| {
+            let rf = *rapid_fire.get(u).unwrap_or(&100) as f32 / 100.;
+            **c as f32 / total as f32 * (1. - rf).max(0.)
+        })
+        .sum::<f32>();
+
+    again / (1. - again)
+}
+
+/// Which of the selected units have rapid fire against the known enemy army
+fn draw_rapid_fire_preview(ui: &mut Ui, army: &Army, info: &PlanetInfo, images: &ImageIds) {
+    ui.small(format!("Based on the intelligence from turn {}.", info.turn));
+
+    let mut any = false;
+    for (unit, count) in army.iter().filter(|(u, c)| **c > 0 && !u.rapid_fire().is_empty()) {
+        let rapid_fire = unit.rapid_fire();
+
+        // Units missing from the intelligence could be on the planet or not
+        let known =
+            rapid_fire.keys().filter(|u| info.army.amount(u) > 0).sorted().collect::<Vec<_>>();
+        let unknown =
+            rapid_fire.keys().filter(|u| !info.army.contains_key(u)).sorted().collect::<Vec<_>>();
+
+        if known.is_empty() && unknown.is_empty() {
+            continue;
+        }
+
+        any = true;
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 4.;
+
+            ui.add_image(images.get(unit.to_lowername()), [30., 30.])
+                .on_hover_small(unit.to_name());
+            ui.small(format!(
+                "+{:.1} shots/round:",
+                expected_extra_shots(unit, &info.army) * *count as f32
+            ))
+            .on_hover_small(
+                "Estimated extra shots per round of all units of this type against the known \
+                enemy army, assuming random targeting.",
+            );
+
+            for target in known {
+                ui.add_image(images.get(target.to_lowername()), [25., 25.])
+                    .on_hover_small(format!("{} ({}%)", target.to_name(), rapid_fire[target]));
+            }
+
+            if !unknown.is_empty() {
+                ui.small("?").on_hover_small(format!(
+                    "Rapid fire against units with unknown presence: {}.",
+                    unknown.iter().map(|u| u.to_name()).join(", ")
+                ));
+            }
+        });
+    }
+
+    if !any {
+        ui.small("None of the selected units have rapid fire against the enemy's units.");
+    }
+}
+
+/// Turns needed to build a queue like the one of a shop category with the current
+/// capacity and income, and the binding constraint. The queue itself is built at
+/// the end of the turn, so this is how often a batch this size can be repeated
+fn build_turns(queue: &[&Unit], max: usize, production: &Resources) -> (usize, &'static str) {
+    let total = queue.iter().map(|u| u.production()).sum::<usize>();
+    let price: Resources = queue.iter().map(|u| u.price()).sum();
+
+    let production_turns = if max == 0 {
+        usize::MAX
+    } else {
+        total.div_ceil(max).max(1)
+    };
+
+    // Turns of income required to pay for the queue
+    let resource_turns = ResourceName::iter()
+        .map(|r| match (price.get(&r), production.get(&r)) {
+            (0, _) => 0,
+            (_, 0) => usize::MAX,
+            (p, i) => p.div_ceil(i),
+        })
+        .max()
+        .unwrap_or(0);
+
+    if resource_turns > production_turns {
+        (resource_turns, "income")
+    } else {
+        (production_turns, "production capacity")
+    }
+}
+
+/// Checks whether a unit can be bought on a planet, in the order
+/// (resources, required level, maximum level, production capacity)
+fn shop_checks(unit: &Unit, planet: &Planet, player: &Player) -> (bool, bool, bool, bool) {
+    let count = planet.army.amount(unit);
+
+    let resources_check = player.resources >= unit.price();
+    let (level_check, building_check) = match unit {
+        Unit::Building(_) => (true, count < Building::MAX_LEVEL),
+        Unit::Ship(s) => {
+            (s.production() <= planet.army.amount(&Unit::Building(Building::Shipyard)), true)
+        },
+        Unit::Defense(d) if d.is_missile() => {
+            (d.production() <= planet.army.amount(&Unit::Building(Building::MissileSilo)), true)
+        },
+        Unit::Defense(d) => {
+            (d.production() <= planet.army.amount(&Unit::Building(Building::Factory)), true)
+        },
+    };
+
+    (resources_check, level_check, building_check, planet.capacity_left(unit) > 0)
+}
+
+fn draw_shop(
+    ui: &mut Ui,
+    state: &mut UiState,
+    settings: &Settings,
+    player: &mut Player,
+    planet: &mut Planet,
+    production: &Resources,
+    images: &ImageIds,
+) {
+    ui.spacing_mut().item_spacing = emath::Vec2::new(4., 4.);
+
+    ui.add_space(4.);
+
+    if planet.is_moon() && state.shop == Shop::Defenses {
+        state.shop = Shop::default();
+    }
+
+    let (current, max, idx) = match state.shop {
+        Shop::Buildings => (planet.fields_consumed(), planet.max_fields(), 0),
+        Shop::Fleet => (planet.fleet_production(), planet.max_fleet_production(), 1),
+        Shop::Defenses => (planet.battery_production(), planet.max_battery_production(), 2),
+    };
+
+    let units = &Unit::all_valid(planet.is_moon())[idx];
+
+    // What prevents buying more units of this category, considering only the
+    // units that are unlocked and below their maximum level
+    let checks = units
+        .iter()
+        .filter(|u| **u != Unit::space_dock())
+        .map(|u| shop_checks(u, planet, player))
+        .filter(|(_, level, building, _)| *level && *building)
+        .collect::<Vec<_>>();
+
+    let blocked = !checks.is_empty() && !checks.iter().any(|(r, _, _, p)| *r && *p);
+    let capacity_blocked = blocked && checks.iter().all(|(_, _, _, p)| !p);
+    let at_cap = current >= max || capacity_blocked;
+
+    let blocker = blocked.then(|| {
+        if !capacity_blocked {
+            (
+                "⚠ Not enough resources",
+                "None of the units that fit in the remaining production capacity can be \
+                afforded.",
+            )
+        } else if state.shop != Shop::Buildings {
+            (
+                "⚠ Production limit",
+                "The production capacity of this category is used up for this turn. Upgrade \
+                the production building to increase it.",
+            )
+        } else if planet.is_moon() && planet.fields_consumed() >= planet.max_fields() {
+            (
+                "⚠ No fields left",
+                "Every lunar building takes a field. Upgrade the Lunar Base to get more fields.",
+            )
+        } else {
+            ("⚠ All upgrades queued", "Every building can only be upgraded once per turn.")
+        }
+    });
+
+    ui.horizontal(|ui| {
+        ui.add_space(45.);
+        ui.add_image(images.get(state.shop.to_lowername()), [20., 20.]);
+        ui.small(format!("{} ({})", state.shop.to_name(), idx + 1))
+            .on_hover_small("Press the number key or Tab to switch between categories.");
+
+        if state.shop != Shop::Buildings && player.owns(planet) {
+            ui.add_space(15.);
+
+            let overflow = planet.overflow.map(|u| u.to_name()).unwrap_or("None".to_string());
+
+            ComboBox::from_id_salt("overflow")
+                .selected_text(RichText::new(format!("Overflow: {overflow}")).small())
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut planet.overflow, None, "None")
+                        .on_hover_cursor(CursorIcon::PointingHand);
+
+                    for unit in Unit::all_valid(planet.is_moon())[idx]
+                        .iter()
+                        .filter(|u| **u != Unit::space_dock())
+                    {
+                        ui.selectable_value(&mut planet.overflow, Some(*unit), unit.to_name())
+                            .on_hover_cursor(CursorIcon::PointingHand);
+                    }
+                })
+                .response
+                .on_hover_cursor(CursorIcon::PointingHand)
+                .on_hover_small_ext(
+                    "At the end of the turn, if nothing of this category was bought on the \
+                    planet, the idle production capacity is filled with the overflow unit, as \
+                    far as your resources allow.",
+                );
+        }
+
+        if state.shop != Shop::Buildings || planet.is_moon() || blocker.is_some() {
+            ui.with_layout(Layout::right_to_left(Align::Min), |ui| {
+                ui.add_space(45.);
+
+                if let Some((text, hover)) = blocker {
+                    ui.colored_label(Color32::RED, RichText::new(text).small())
+                        .on_hover_small(hover);
+                    ui.add_space(15.);
+                }
+
+                let queue = planet
+                    .buy
+                    .iter()
+                    .filter(|u| match state.shop {
+                        Shop::Buildings => u.is_building(),
+                        Shop::Fleet => u.is_ship(),
+                        Shop::Defenses => u.is_defense(),
+                    })
+                    .collect::<Vec<_>>();
+
+                if state.shop != Shop::Buildings && !queue.is_empty() {
+                    let (turns, constraint) = build_turns(&queue, max, production);
+                    let text = if turns == usize::MAX {
+                        format!("No {constraint}")
+                    } else {
+                        format!(
+                            "≈{turns} turn{}",
+                            if turns == 1 {
+                                ""
+                            } else {
+                                "s"
+                            }
+                        )
+                    };
+
+                    ui.small(text).on_hover_small(format!(
+                        "Queued units are built at the end of the turn. This is how often a \
+                        batch like this one can be built with the current production capacity \
+                        and income, limited by the {constraint}."
+                    ));
+                    ui.add_space(15.);
+                }
+
+                if state.shop != Shop::Buildings || planet.is_moon() {
+                    let text = RichText::new(trf(
+                        "shop.capacity",
+                        &[
+                            &tr(if planet.is_moon() {
+                                "shop.fields"
+                            } else {
+                                "shop.production"
+                            }),
+                            &current,
+                            &max,
+                        ],
+                    ))
+                    .small();
+
+                    if at_cap {
+                        ui.colored_label(Color32::RED, text);
+                    } else {
+                        ui.label(text);
+                    }
+                }
             });
         }
     });
 
     ui.add_space(10.);
 
-    for row in Unit::all_valid(planet.is_moon())[idx].chunks(5) {
+    // Units are always shown in two rows, scaling down the images if needed
+    let per_row = units.len().div_ceil(2).max(5);
+    let size = 650. / per_row as f32;
+
+    for row in units.chunks(per_row) {
         ui.horizontal(|ui| {
             ui.add_space(25.);
 
@@ -2779,45 +4825,20 @@ fn draw_shop(
                 let count = planet.army.amount(unit);
                 let bought = planet.buy.iter().filter(|u| *u == unit).count();
 
-                let resources_check = player.resources >= unit.price();
-                let (level_check, building_check, production_check) = match unit {
-                    Unit::Building(_) => (
-                        true,
-                        count < Building::MAX_LEVEL,
-                        !planet.buy.contains(unit)
-                            && (!planet.is_moon()
-                                || !unit.consumes_field()
-                                || planet.fields_consumed() < planet.max_fields()),
-                    ),
-                    Unit::Ship(s) => (
-                        s.production() <= planet.army.amount(&Unit::Building(Building::Shipyard)),
-                        true,
-                        planet.fleet_production() + s.production() <= planet.max_fleet_production(),
-                    ),
-                    Unit::Defense(d) if d.is_missile() => (
-                        d.production()
-                            <= planet.army.amount(&Unit::Building(Building::MissileSilo)),
-                        true,
-                        planet.battery_production() + d.production()
-                            <= planet.max_battery_production()
-                            && planet.missile_capacity() + bought < planet.max_missile_capacity(),
-                    ),
-                    Unit::Defense(d) => (
-                        d.production() <= planet.army.amount(&Unit::Building(Building::Factory)),
-                        true,
-                        planet.battery_production() + d.production()
-                            <= planet.max_battery_production()
-                            && (*d != Defense::SpaceDock || (count == 0 && bought == 0)),
-                    ),
-                };
+                // Queued building upgrades can be cancelled with a full refund
+                let queued = unit.is_building() && bought > 0;
+
+                let (resources_check, level_check, building_check, production_check) =
+                    shop_checks(unit, planet, player);
 
                 ui.add_enabled_ui(
-                    resources_check && level_check && building_check && production_check,
+                    queued
+                        || (resources_check && level_check && building_check && production_check),
                     |ui| {
                         ui.spacing_mut().button_padding = egui::Vec2::splat(2.);
 
                         let mut response =
-                            ui.add_image_button(images.get(unit.to_lowername()), [130., 130.]);
+                            ui.add_image_button(images.get(unit.to_lowername()), [size; 2]);
 
                         if ui.is_enabled() {
                             response = response.on_hover_cursor(CursorIcon::PointingHand);
@@ -2837,43 +4858,52 @@ fn draw_shop(
                             state.radar_hover = hovered.then_some(planet.id);
                         }
 
-                        if response.clicked() {
-                            player.resources -= unit.price();
-                            planet.buy.push(unit.clone());
+                        let stackable = !unit.is_building() && *unit != Unit::space_dock();
+                        let bulk =
+                            stackable && ui.input(|i| i.modifiers.shift || i.modifiers.command);
+
+                        if response.clicked() && !bulk {
+                            if queued {
+                                planet.cancel(unit, &mut player.resources);
+                            } else {
+                                player.resources -= unit.price();
+                                planet.buy.push(unit.clone());
+                            }
                         }
 
-                        if !unit.is_building()
-                            && *unit != Unit::space_dock()
-                            && response.secondary_clicked()
-                        {
-                            // Buy 5 new units (or maximum possible)
-                            let n = match unit {
-                                Unit::Ship(s) => {
-                                    let max_n_p = (planet.max_fleet_production()
-                                        - planet.fleet_production())
-                                        / s.production();
-                                    let max_n_r = (player.resources / s.price()).min();
-                                    5.min(max_n_p).min(max_n_r)
-                                },
-                                Unit::Defense(d) => {
-                                    let max_n_p = (planet.max_battery_production()
-                                        - planet.battery_production())
-                                        / d.production();
-                                    let max_n_r = (player.resources / d.price()).min();
-                                    let max_n_m = if d.is_missile() {
-                                        planet.max_missile_capacity() - planet.missile_capacity()
-                                    } else {
-                                        5
-                                    };
-                                    5.min(max_n_p).min(max_n_r).min(max_n_m)
-                                },
-                                _ => unreachable!(),
-                            };
+                        // Buy 5 new units with right-click, or as many as possible
+                        // with shift/ctrl + click
+                        let n = if bulk && response.clicked() {
+                            Some(usize::MAX)
+                        } else if stackable && response.secondary_clicked() {
+                            Some(5)
+                        } else {
+                            None
+                        };
 
+                        if let Some(n) = n {
+                            let n = n.min(planet.max_purchasable(unit, &player.resources));
                             player.resources -= unit.price() * n;
                             planet.buy.extend(vec![unit.clone(); n]);
                         }
 
+                        // Middle-click cycles the weight of the queued units
+                        let weighted = stackable && bought > 0;
+                        if weighted && response.middle_clicked() {
+                            let weight = planet.buy_weight(unit) % MAX_BUY_WEIGHT + 1;
+                            planet.buy_weights.insert(*unit, weight);
+                        }
+
+                        if weighted {
+                            ui.add_text_on_image(
+                                format!("w{}", planet.buy_weight(unit)),
+                                Color32::WHITE,
+                                TextStyle::Body,
+                                response.rect.right_top() - egui::Vec2::new(3., -3.),
+                                Align2::RIGHT_TOP,
+                            );
+                        }
+
                         if count > 0 {
                             let text = match unit {
                                 Unit::Building(Building::MissileSilo) => Some(format!(
@@ -2924,7 +4954,34 @@ fn draw_shop(
                         if settings.show_hover {
                             response
                                 .on_hover_ui(|ui| {
-                                    draw_unit_hover(ui, unit, count, state, player, None, &images);
+                                    draw_unit_hover(
+                                        ui,
+                                        unit,
+                                        count,
+                                        state,
+                                        player,
+                                        planet,
+                                        settings,
+                                        if queued {
+                                            Some(
+                                                "Upgrade queued. Click to cancel it and recover \
+                                                its cost."
+                                                    .to_string(),
+                                            )
+                                        } else if weighted {
+                                            Some(format!(
+                                                "Weight {}. If the production capacity drops \
+                                                below the queued production, it's split over \
+                                                the queued units in proportion to their \
+                                                weights, and the rest is refunded. Middle-click \
+                                                to change the weight.",
+                                                planet.buy_weight(unit)
+                                            ))
+                                        } else {
+                                            None
+                                        },
+                                        &images,
+                                    );
                                 })
                                 .on_disabled_hover_ui(|ui| {
                                     draw_unit_hover(
@@ -2933,6 +4990,8 @@ fn draw_shop(
                                         count,
                                         state,
                                         player,
+                                        planet,
+                                        settings,
                                         Some(if !resources_check {
                                             "Not enough resources.".to_string()
                                         } else if !building_check {
@@ -3005,21 +5064,12 @@ fn draw_combat_selection(
                     ui.horizontal_centered(|ui| {
                         ui.spacing_mut().item_spacing.x = 4.;
 
-                        let text = format!("Battle of {}", destination.name);
-                        let size_x = ui
-                            .painter()
-                            .layout_no_wrap(
-                                text.clone(),
-                                TextStyle::Small.resolve(ui.style()),
-                                Color32::WHITE,
-                            )
-                            .size()
-                            .x
-                            + 150.;
+                        let text = trf("battle", &[&destination.name]);
+                        let size_x = ui.text_width(&text, TextStyle::Small) + 150.;
 
-                        ui.add_space((ui.available_width() - size_x) * 0.5);
+                        ui.add_centering_space(size_x);
 
-                        ui.small(text);
+                        ui.small_wrapped(text);
 
                         ui.add_space(20.);
 
@@ -3058,19 +5108,837 @@ fn draw_combat_selection(
                     );
                 }
 
-                if response.clicked() {
-                    state.in_combat = Some(report.id);
-                    settings.combat_paused = false;
-                    next_game_state.set(GameState::Combat);
+                if response.clicked() {
+                    state.in_combat = Some(report.id);
+                    settings.combat_paused = false;
+                    next_game_state.set(GameState::Combat);
+                }
+            }
+        });
+    });
+}
+
+fn draw_lite_combat(
+    ui: &mut Ui,
+    state: &UiState,
+    map: &Map,
+    player: &Player,
+    roster: &Roster,
+    settings: &mut Settings,
+    next_game_state: &mut NextState<GameState>,
+) {
+    let report = player.reports.iter().find(|r| r.id == state.in_combat.unwrap()).unwrap();
+    let Some(combat) = report.combat_report.as_ref() else {
+        return;
+    };
+
+    let n_rounds = combat.rounds.len();
+    let round = &combat.rounds[state.combat_round.clamp(1, n_rounds) - 1];
+
+    ui.add_space(20.);
+
+    ui.vertical_centered(|ui| {
+        ui.heading(format!(
+            "Battle of {} - Round {}/{}",
+            map.get(report.mission.destination).name,
+            state.combat_round.max(1),
+            n_rounds
+        ));
+    });
+
+    ui.add_space(10.);
+
+    let (attack_c, defend_c) = roster.report_colors(report, player, settings);
+
+    let draw_side = |ui: &mut Ui, side: Side, color: Color| {
+        ui.vertical(|ui| {
+            ui.set_width(290.);
+            ui.colored_label(color.to_color32(), side.to_title());
+            ui.separator();
+
+            egui::Grid::new(format!("lite_{}", side.to_lowername()))
+                .striped(true)
+                .num_columns(4)
+                .spacing([20., 4.])
+                .show(ui, |ui| {
+                    ui.small("Unit");
+                    ui.small("Count").on_hover_small("Units alive at the start of the round.");
+                    ui.small("⚔").on_hover_small("Damage dealt this round.");
+                    ui.small("💀").on_hover_small("Enemy units destroyed this round.");
+                    ui.end_row();
+
+                    let units = round.units(&side);
+                    for unit in units.iter().map(|cu| cu.unit).unique() {
+                        let group = units.iter().filter(|cu| cu.unit == unit).collect::<Vec<_>>();
+                        let shots = group.iter().flat_map(|cu| &cu.shots).collect::<Vec<_>>();
+
+                        ui.small(unit.to_name());
+                        ui.small(group.len().fmt());
+                        ui.small(
+                            shots
+                                .iter()
+                                .map(|s| {
+                                    s.shield_damage + s.hull_damage + s.planetary_shield_damage
+                                })
+                                .sum::<usize>()
+                                .fmt(),
+                        );
+                        ui.small(shots.iter().filter(|s| s.killed).count().fmt());
+                        ui.end_row();
+                    }
+                });
+        });
+    };
+
+    ui.horizontal_top(|ui| {
+        ui.add_space(40.);
+        ScrollArea::vertical().max_height(330.).show(ui, |ui| {
+            ui.horizontal_top(|ui| {
+                draw_side(ui, Side::Attacker, attack_c);
+                ui.add_space(20.);
+                draw_side(ui, Side::Defender, defend_c);
+            });
+        });
+    });
+
+    ui.with_layout(Layout::bottom_up(Align::Center), |ui| {
+        ui.add_space(35.);
+        ui.horizontal(|ui| {
+            ui.add_space(220.);
+            ui.spacing_mut().item_spacing.x = 10.;
+
+            if ui
+                .add(egui::Button::new(RichText::new(if settings.combat_paused {
+                    "Play"
+                } else {
+                    "Pause"
+                })))
+                .on_hover_cursor(CursorIcon::PointingHand)
+                .clicked()
+            {
+                settings.combat_paused = !settings.combat_paused;
+            }
+
+            ui.small(format!("{}x", settings.combat_speed))
+                .on_hover_small("Use the arrow keys to change the combat speed.");
+
+            if ui
+                .add(egui::Button::new(
+                    RichText::new(format!("Setup: {}", settings.combat_setup.to_title())).small(),
+                ))
+                .on_hover_cursor(CursorIcon::PointingHand)
+                .on_hover_small(
+                    "Length of the fly-in animation at the start of the next combats. Click to \
+                    cycle between full, short and skipped.",
+                )
+                .clicked()
+            {
+                settings.combat_setup = settings.combat_setup.next();
+            }
+
+            if ui
+                .add(egui::Button::new("Exit combat"))
+                .on_hover_cursor(CursorIcon::PointingHand)
+                .clicked()
+            {
+                // Auto-resolved combats are watched from the report, so return to the map
+                next_game_state.set(if settings.auto_resolve {
+                    GameState::Playing
+                } else {
+                    GameState::CombatMenu
+                });
+            }
+        });
+    });
+}
+
+fn draw_replay(ui: &mut Ui, replay: &mut Replay) {
+    let last = replay.turns.len() - 1;
+
+    ui.add_space(10.);
+
+    ui.vertical_centered(|ui| {
+        ui.small(format!(
+            "Replay (read-only) - Turn {} ({}/{})",
+            replay.turns[replay.index].turn,
+            replay.index + 1,
+            replay.turns.len()
+        ));
+    });
+
+    ui.add_space(5.);
+
+    ui.horizontal(|ui| {
+        ui.add_space(40.);
+        ui.spacing_mut().item_spacing.x = 8.;
+        ui.spacing_mut().button_padding = egui::Vec2::new(8., 4.);
+
+        let mut button = |ui: &mut Ui, text: &str, hover: &str| {
+            ui.add(egui::Button::new(RichText::new(text).small()))
+                .on_hover_cursor(CursorIcon::PointingHand)
+                .on_hover_small(hover)
+                .clicked()
+        };
+
+        if button(ui, "First", "Go to the first recorded turn.") {
+            replay.index = 0;
+        }
+        if button(ui, "Back", "Step one turn back.") {
+            replay.index = replay.index.saturating_sub(1);
+        }
+        if button(
+            ui,
+            if replay.playing {
+                "Pause"
+            } else {
+                "Play"
+            },
+            "Automatically advance through the turns.",
+        ) {
+            replay.playing = !replay.playing;
+            if replay.index == last {
+                replay.index = 0;
+            }
+        }
+        if button(ui, "Next", "Step one turn forward.") {
+            replay.index = (replay.index + 1).min(last);
+        }
+        if button(ui, "Last", "Go to the last recorded turn.") {
+            replay.index = last;
+        }
+
+        ui.add_space(10.);
+
+        ui.add(Slider::new(&mut replay.index, 0..=last).show_value(false))
+            .on_hover_cursor(CursorIcon::PointingHand)
+            .on_hover_small("Drag to scrub through the turns.");
+    });
+}
+
+fn draw_turn_summary(
+    ui: &mut Ui,
+    state: &mut UiState,
+    settings: &Settings,
+    map: &Map,
+    images: &ImageIds,
+) {
+    let summary = state.turn_summary.as_ref().unwrap();
+
+    ui.add_space(5.);
+
+    ui.vertical_centered(|ui| ui.label(format!("Summary of turn {}", settings.turn - 1)));
+
+    ui.add_space(10.);
+
+    ui.horizontal(|ui| {
+        ui.add_space(40.);
+        for resource in ResourceName::iter() {
+            ui.add_image(images.get(resource.to_lowername()), [40., 25.]);
+            ui.small(format!("+{}", summary.resources.get(&resource).fmt()));
+            ui.add_space(15.);
+        }
+    });
+
+    ui.add_space(10.);
+
+    let line = |ui: &mut Ui, image: String, text: String| {
+        ui.horizontal(|ui| {
+            ui.add_space(40.);
+            ui.add_image(images.get(image), [20., 20.]);
+            ui.small(text);
+        });
+    };
+
+    ScrollArea::vertical().max_height(190.).show(ui, |ui| {
+        ui.set_width(ui.available_width() - 30.);
+
+        line(ui, "deploy".to_string(), format!("Missions arrived: {}", summary.missions));
+        line(ui, "attack".to_string(), format!("Combats resolved: {}", summary.combats));
+
+        for (id, building, level) in &summary.buildings {
+            line(
+                ui,
+                building.to_lowername(),
+                format!("{} reached level {level} on {}.", building.to_name(), map.get(*id).name),
+            );
+        }
+
+        for id in &summary.gained {
+            let planet = map.get(*id);
+            line(ui, planet.image(), format!("Planet {} gained.", planet.name));
+        }
+
+        for id in &summary.lost {
+            let planet = map.get(*id);
+            line(ui, planet.image(), format!("Planet {} lost.", planet.name));
+        }
+    });
+
+    ui.with_layout(Layout::bottom_up(Align::Center), |ui| {
+        ui.add_space(30.);
+        if ui.add_custom_button("Close", images).clicked() {
+            state.turn_summary = None;
+        }
+    });
+}
+
+fn draw_abandon(
+    ui: &mut Ui,
+    state: &mut UiState,
+    settings: &Settings,
+    map: &mut Map,
+    player: &mut Player,
+    missions: &Missions,
+    message: &mut MessageWriter<MessageMsg>,
+    images: &ImageIds,
+) {
+    let id = state.abandon.unwrap();
+    let planet = map.get(id);
+
+    let count = |f: fn(&Unit) -> bool| {
+        planet.army.iter().filter_map(|(u, c)| f(u).then_some(*c)).sum::<usize>()
+    };
+    let outgoing = missions.iter().filter(|m| m.owner == player.id && m.origin == id).count();
+    let incoming = missions.iter().filter(|m| m.owner == player.id && m.destination == id).count();
+
+    ui.add_space(5.);
+
+    ui.vertical_centered(|ui| ui.label(format!("Abandon planet {}?", planet.name)));
+
+    ui.add_space(10.);
+
+    let line = |ui: &mut Ui, text: String| {
+        ui.horizontal(|ui| {
+            ui.add_space(40.);
+            ui.small(text);
+        });
+    };
+
+    line(ui, format!("Defenses destroyed: {}", count(Unit::is_defense)));
+    line(ui, format!("Building levels retained: {}", count(Unit::is_building)));
+    line(
+        ui,
+        if planet.has_fleet() {
+            format!(
+                "Ships left on the planet: {}. The planet stays under your control.",
+                count(Unit::is_ship)
+            )
+        } else {
+            "The planet is no longer under your control.".to_string()
+        },
+    );
+    line(
+        ui,
+        format!("Missions leaving the planet: {outgoing}. They continue to their destination."),
+    );
+    line(
+        ui,
+        format!("Missions towards the planet: {incoming}. Deploy missions still arrive to it."),
+    );
+
+    let name = planet.name.clone();
+
+    ui.with_layout(Layout::bottom_up(Align::Center), |ui| {
+        ui.add_space(30.);
+        ui.horizontal(|ui| {
+            ui.add_space(100.);
+            if ui.add_custom_button("Abandon", images).clicked() {
+                player.abandon(map.get_mut(id), settings.turn);
+                message.write(MessageMsg::info(format!("Planet {name} abandoned.")));
+                state.abandon = None;
+            }
+
+            ui.add_space(20.);
+            if ui.add_custom_button("Cancel", images).clicked() {
+                state.abandon = None;
+            }
+        });
+    });
+}
+
+fn draw_diplomacy(
+    ui: &mut Ui,
+    state: &mut UiState,
+    settings: &Settings,
+    map: &mut Map,
+    player: &Player,
+    diplomacy: &Diplomacy,
+    roster: &Roster,
+    diplomacy_msg: &mut MessageWriter<DiplomacyMsg>,
+    images: &ImageIds,
+) {
+    let others = map
+        .planets
+        .iter()
+        .filter_map(|p| p.owned.filter(|&o| o != player.id))
+        .unique()
+        .sorted()
+        .collect::<Vec<_>>();
+
+    ui.add_space(5.);
+
+    ui.vertical_centered(|ui| ui.label("Diplomacy"));
+
+    ui.add_space(10.);
+
+    let button = |ui: &mut Ui, text: &str, hover: &str| {
+        ui.add(egui::Button::new(RichText::new(text).small()))
+            .on_hover_cursor(CursorIcon::PointingHand)
+            .on_hover_small(hover)
+            .clicked()
+    };
+
+    ScrollArea::vertical().max_height(250.).show(ui, |ui| {
+        ui.set_width(ui.available_width() - 30.);
+
+        if others.is_empty() || player.spectator {
+            ui.horizontal(|ui| {
+                ui.add_space(40.);
+                ui.small("No other players in this game.");
+            });
+        }
+
+        for id in others.iter().filter(|_| !player.spectator) {
+            let treaty = diplomacy.get(player.id, *id);
+            let relation = diplomacy.relation(player.id, *id);
+
+            ui.horizontal(|ui| {
+                ui.add_space(40.);
+                ui.colored_label(
+                    roster.color(Some(*id), settings).to_color32(),
+                    RichText::new(format!("{}:", roster.name(*id))).small(),
+                );
+                if diplomacy.are_teammates(player.id, *id) {
+                    ui.small("Teammate").on_hover_small(
+                        "Teammates are allied for the whole game. The alliance can't be broken.",
+                    );
+                } else {
+                    ui.small(relation.to_name()).on_hover_small(relation.description());
+                }
+
+                if let Some(b) = treaty.and_then(|t| t.broken) {
+                    if diplomacy.is_protected(player.id, *id, settings.turn + 1) {
+                        ui.small(format!("(broken on turn {b})")).on_hover_small(
+                            "Attacks between both players are allowed from next turn.",
+                        );
+                    }
+                }
+            });
+
+            // The alliance between teammates is fixed
+            if !diplomacy.are_teammates(player.id, *id) {
+                ui.horizontal(|ui| {
+                    ui.add_space(55.);
+                    ui.spacing_mut().item_spacing.x = 8.;
+
+                    match treaty.and_then(|t| t.proposal) {
+                        Some((from, r)) if from == *id => {
+                            ui.small(format!("Proposes a {}.", r.to_title()));
+                            if button(ui, "Accept", r.description()) {
+                                diplomacy_msg
+                                    .write(DiplomacyMsg::new(DiplomacyAction::Accept(*id)));
+                            }
+                            if button(ui, "Decline", "Decline the proposal.") {
+                                diplomacy_msg
+                                    .write(DiplomacyMsg::new(DiplomacyAction::Decline(*id)));
+                            }
+                        },
+                        Some((_, r)) => {
+                            ui.small(format!("Proposed a {}.", r.to_title()));
+                        },
+                        None => {
+                            for r in
+                                Relation::iter().filter(|r| *r != Relation::War && *r != relation)
+                            {
+                                if button(
+                                    ui,
+                                    &format!("Propose {}", r.to_lowername()),
+                                    r.description(),
+                                ) {
+                                    diplomacy_msg
+                                        .write(DiplomacyMsg::new(DiplomacyAction::Propose(*id, r)));
+                                }
+                            }
+                        },
+                    }
+
+                    if relation != Relation::War
+                        && button(
+                            ui,
+                            "Break",
+                            "Break the treaty. Attacks are allowed after a delay of one turn.",
+                        )
+                    {
+                        diplomacy_msg.write(DiplomacyMsg::new(DiplomacyAction::Break(*id)));
+                    }
+                });
+            }
+
+            if relation == Relation::Alliance {
+                let (_, n_max_owned) = player.planets_owned(map, settings);
+
+                ui.horizontal(|ui| {
+                    ui.add_space(55.);
+                    ui.spacing_mut().item_spacing.x = 8.;
+
+                    let offered =
+                        map.planets.iter().find(|p| p.transfer == Some(*id)).map(|p| p.id);
+                    let mut selected = offered;
+
+                    ComboBox::from_id_salt(format!("gift {id}"))
+                        .width(140.)
+                        .selected_text(
+                            RichText::new(format!(
+                                "🎁 {}",
+                                selected.map_or("Gift a planet".to_string(), |p| map
+                                    .get(p)
+                                    .name
+                                    .clone())
+                            ))
+                            .small(),
+                        )
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut selected, None, RichText::new("None").small())
+                                .on_hover_cursor(CursorIcon::PointingHand);
+                            for planet in map
+                                .planets()
+                                .into_iter()
+                                .filter(|p| player.owns(p) && p.id != player.home_planet)
+                                .sorted_by(|a, b| a.name.cmp(&b.name))
+                            {
+                                ui.selectable_value(
+                                    &mut selected,
+                                    Some(planet.id),
+                                    RichText::new(&planet.name).small(),
+                                )
+                                .on_hover_cursor(CursorIcon::PointingHand);
+                            }
+                        })
+                        .response
+                        .on_hover_cursor(CursorIcon::PointingHand)
+                        .on_hover_small(
+                            "Offer one of your planets, with all its units, to this ally. The \
+                            planet is transferred at the end of the turn if the ally has not \
+                            reached the maximum number of owned planets.",
+                        );
+
+                    if selected != offered {
+                        map.planets
+                            .iter_mut()
+                            .filter(|p| p.transfer == Some(*id))
+                            .for_each(|p| p.transfer = None);
+                        if let Some(p) = selected {
+                            map.get_mut(p).transfer = Some(*id);
+                        }
+                    }
+
+                    if offered.is_some() {
+                        let ally_owned =
+                            map.planets().iter().filter(|p| p.owned == Some(*id)).count();
+                        if ally_owned >= n_max_owned {
+                            ui.small("(pending, no room)").on_hover_small(
+                                "The ally owns the maximum number of planets. The planet is \
+                                transferred once there is room for it.",
+                            );
+                        }
+                    }
+                });
+            }
+
+            ui.add_space(5.);
+        }
+    });
+
+    ui.with_layout(Layout::bottom_up(Align::Center), |ui| {
+        ui.add_space(30.);
+        if ui.add_custom_button("Close", images).clicked() {
+            state.diplomacy = false;
+        }
+    });
+}
+
+fn draw_notifications(
+    ui: &mut Ui,
+    state: &mut UiState,
+    history: &MessageHistory,
+    images: &ImageIds,
+) {
+    ui.add_space(5.);
+
+    ui.vertical_centered(|ui| ui.label("Notifications"));
+
+    ui.add_space(10.);
+
+    ScrollArea::vertical().max_height(250.).show(ui, |ui| {
+        ui.set_width(ui.available_width() - 30.);
+
+        if history.0.is_empty() {
+            ui.horizontal(|ui| {
+                ui.add_space(40.);
+                ui.small("No notifications yet.");
+            });
+        }
+
+        for notification in &history.0 {
+            ui.horizontal(|ui| {
+                ui.add_space(40.);
+                ui.colored_label(
+                    notification.level.color(),
+                    RichText::new(notification.level.icon()).small(),
+                );
+
+                let text = RichText::new(&notification.message).small();
+                if let Some(id) = notification.report {
+                    if ui
+                        .add(egui::Label::new(text.underline()).sense(Sense::click()))
+                        .on_hover_cursor(CursorIcon::PointingHand)
+                        .on_hover_small("Open the combat report.")
+                        .clicked()
+                    {
+                        state.combat_report = Some(id);
+                        state.combat_report_round = 1;
+                        state.notifications = false;
+                    }
+                } else {
+                    ui.label(text);
+                }
+            });
+        }
+    });
+
+    ui.with_layout(Layout::bottom_up(Align::Center), |ui| {
+        ui.add_space(30.);
+        if ui.add_custom_button("Close", images).clicked() {
+            state.notifications = false;
+        }
+    });
+}
+
+pub fn draw_notification_history(
+    mut contexts: EguiContexts,
+    mut state: ResMut<UiState>,
+    history: Res<MessageHistory>,
+    game_state: Res<State<GameState>>,
+    images: Res<ImageIds>,
+    window: Single<&Window>,
+) {
+    if !state.notifications || *game_state.get() != GameState::Playing {
+        return;
+    }
+
+    // The zoom factor was already set by draw_ui
+    let zoom = contexts.ctx_mut().unwrap().zoom_factor();
+    let (width, height) = (window.width() / zoom, window.height() / zoom);
+    let (window_w, window_h) = (500., 400.);
+
+    draw_panel(
+        &mut contexts,
+        "notifications",
+        "panel",
+        ((width - window_w) * 0.5, (height - window_h) * 0.5),
+        (window_w, window_h),
+        &images,
+        |ui| draw_notifications(ui, &mut state, &history, &images),
+    );
+}
+
+/// Banner shown on the map while the game is paused
+pub fn draw_pause_indicator(mut contexts: EguiContexts, settings: Res<Settings>) {
+    if !settings.paused {
+        return;
+    }
+
+    egui::Area::new("paused".into())
+        .order(Order::Foreground)
+        .anchor(Align2::CENTER_BOTTOM, [0., -80.])
+        .interactable(false)
+        .show(contexts.ctx_mut().unwrap(), |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.label(RichText::new("⏸ Paused (ctrl + P to resume)").strong());
+            });
+        });
+}
+
+/// Button in the corner of the map to center the camera on the home planet
+pub fn draw_home_button(
+    mut contexts: EguiContexts,
+    mut state: ResMut<UiState>,
+    player: Res<Player>,
+    settings: Res<Settings>,
+) {
+    if player.spectator {
+        return;
+    }
+
+    egui::Area::new("home button".into())
+        .order(Order::Middle)
+        .anchor(Align2::RIGHT_BOTTOM, [-10., -10.])
+        .show(contexts.ctx_mut().unwrap(), |ui| {
+            let mut response = ui.button(RichText::new("🏠").heading());
+
+            if settings.show_hover {
+                response = response.on_hover_small("Center the map on your home planet (home).");
+            }
+
+            if response.clicked() {
+                state.planet_selected = Some(player.home_planet);
+                state.to_selected = true;
+                state.reset_zoom = true;
+                state.mission = false;
+            }
+        });
+}
+
+fn draw_players(
+    ui: &mut Ui,
+    state: &mut UiState,
+    settings: &Settings,
+    player: &Player,
+    roster: &Roster,
+    player_list: &PlayerList,
+    disconnected: bool,
+    images: &ImageIds,
+) {
+    ui.add_space(5.);
+
+    ui.vertical_centered(|ui| ui.label("Players"));
+
+    ui.add_space(10.);
+
+    ScrollArea::vertical().max_height(250.).show(ui, |ui| {
+        ui.set_width(ui.available_width() - 30.);
+
+        if disconnected {
+            ui.horizontal(|ui| {
+                ui.add_space(40.);
+                ui.colored_label(
+                    Color32::RED,
+                    RichText::new("❌ Disconnected from the host.").small(),
+                );
+            });
+        } else if player_list.0.is_empty() {
+            ui.horizontal(|ui| {
+                ui.add_space(40.);
+                ui.small("No other players in this game.");
+            });
+        }
+
+        for status in &player_list.0 {
+            ui.horizontal(|ui| {
+                ui.add_space(40.);
+                ui.spacing_mut().item_spacing.x = 12.;
+
+                let color = if status.id == player.id {
+                    OWN_COLOR
+                } else {
+                    roster.color(Some(status.id), settings)
+                };
+
+                ui.colored_label(
+                    color.to_color32(),
+                    RichText::new(if status.id == player.id {
+                        format!("{} (you)", roster.name(status.id))
+                    } else {
+                        roster.name(status.id)
+                    })
+                    .small(),
+                );
+
+                if !status.connected {
+                    ui.colored_label(Color32::RED, RichText::new("❌ Disconnected").small());
+                    return;
+                }
+
+                if status.end_turn {
+                    ui.small("✔ Turn ended");
+                } else {
+                    ui.small("⏳ Playing");
                 }
-            }
-        });
+
+                match status.latency {
+                    Some(latency) => ui
+                        .small(format!("📶 {latency:.0} ms"))
+                        .on_hover_small("Round-trip time to the host."),
+                    None => ui.small("🏠 Host"),
+                };
+
+                ui.small(format!("🌎 {}", status.planets)).on_hover_small("Planets owned.");
+            });
+
+            ui.add_space(5.);
+        }
+    });
+
+    ui.with_layout(Layout::bottom_up(Align::Center), |ui| {
+        ui.add_space(30.);
+        if ui.add_custom_button("Close", images).clicked() {
+            state.player_list = false;
+        }
     });
 }
 
-pub fn set_ui_style(mut contexts: EguiContexts) {
+pub fn draw_player_list(
+    mut contexts: EguiContexts,
+    mut state: ResMut<UiState>,
+    settings: Res<Settings>,
+    player: Res<Player>,
+    roster: Res<Roster>,
+    player_list: Res<PlayerList>,
+    client: Option<Res<RenetClient>>,
+    game_state: Res<State<GameState>>,
+    images: Res<ImageIds>,
+    window: Single<&Window>,
+) {
+    if !state.player_list || *game_state.get() != GameState::Playing {
+        return;
+    }
+
+    // The zoom factor was already set by draw_ui
+    let zoom = contexts.ctx_mut().unwrap().zoom_factor();
+    let (width, height) = (window.width() / zoom, window.height() / zoom);
+    let (window_w, window_h) = (500., 400.);
+
+    let disconnected = client.is_some_and(|c| c.is_disconnected());
+
+    draw_panel(
+        &mut contexts,
+        "player list",
+        "panel",
+        ((width - window_w) * 0.5, (height - window_h) * 0.5),
+        (window_w, window_h),
+        &images,
+        |ui| {
+            draw_players(
+                ui,
+                &mut state,
+                &settings,
+                &player,
+                &roster,
+                &player_list,
+                disconnected,
+                &images,
+            )
+        },
+    );
+}
+
+fn ui_style(settings: &Settings) -> Style {
+    let mut style = NordDark {
+        large_text: settings.large_text,
+    }
+    .custom_style();
+
+    // Larger text wraps instead of overflowing the fixed-width panels
+    if settings.large_text {
+        style.wrap_mode = Some(TextWrapMode::Wrap);
+    }
+
+    style
+}
+
+pub fn set_ui_style(mut contexts: EguiContexts, settings: Res<Settings>) {
     let context = contexts.ctx_mut().unwrap();
-    context.set_style(NordDark.custom_style());
+    context.set_style(ui_style(&settings));
 
     context.add_font(FontInsert::new(
         "firasans",
@@ -3082,15 +5950,38 @@ pub fn set_ui_style(mut contexts: EguiContexts) {
     ));
 }
 
+/// Apply a change of the text size setting while playing
+pub fn update_ui_style(
+    mut contexts: EguiContexts,
+    settings: Res<Settings>,
+    mut large_text: Local<bool>,
+) {
+    if settings.large_text != *large_text {
+        *large_text = settings.large_text;
+        contexts.ctx_mut().unwrap().set_style(ui_style(&settings));
+    }
+}
+
 pub fn add_ui_images(
     mut contexts: EguiContexts,
     mut images: ResMut<ImageIds>,
+    mut image_assets: ResMut<Assets<Image>>,
     assets: Local<WorldAssets>,
 ) {
     for (k, v) in assets.images.iter() {
         let id = contexts.add_image(EguiTextureHandle::Strong(v.clone()));
-        images.0.insert(k, id);
+        images.images.insert(k, id);
     }
+
+    // Magenta texture that stands out for units or icons without art
+    let placeholder = image_assets.add(Image::new_fill(
+        Extent3d::default(),
+        TextureDimension::D2,
+        &[255, 0, 255, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    ));
+    images.placeholder = contexts.add_image(EguiTextureHandle::Strong(placeholder));
 }
 
 pub fn draw_ui(
@@ -3104,21 +5995,43 @@ pub fn draw_ui(
     mut settings: ResMut<Settings>,
     game_state: Res<State<GameState>>,
     mut next_game_state: ResMut<NextState<GameState>>,
+    replay: Option<ResMut<Replay>>,
+    diplomacy: Res<Diplomacy>,
+    roster: Res<Roster>,
+    mut diplomacy_msg: MessageWriter<DiplomacyMsg>,
     keyboard: Res<ButtonInput<KeyCode>>,
     images: Res<ImageIds>,
     window: Single<&Window>,
 ) {
-    let (width, height) = (window.width(), window.height());
+    // Scale the panels down when the window is smaller than the size they were designed for
+    let zoom = (window.width() / MIN_UI_WIDTH).min(window.height() / MIN_UI_HEIGHT).min(1.);
+    contexts.ctx_mut().unwrap().set_zoom_factor(zoom);
+
+    // Width and height in ui points (after zooming)
+    let (width, height) = (window.width() / zoom, window.height() / zoom);
+    let too_small = window.width() < MIN_WINDOW_WIDTH || window.height() < MIN_WINDOW_HEIGHT;
+
+    if too_small {
+        egui::Area::new("window too small".into())
+            .order(Order::Foreground)
+            .anchor(Align2::CENTER_BOTTOM, [0., -5.])
+            .show(contexts.ctx_mut().unwrap(), |ui| {
+                ui.label(
+                    RichText::new("The window is too small. Enlarge it to show all panels.")
+                        .color(Color32::ORANGE),
+                );
+            });
+    }
 
     if matches!(game_state.get(), GameState::Playing | GameState::GameMenu) {
         draw_panel(
             &mut contexts,
             "resources",
             "thin panel",
-            (window.width() * 0.5 - 625., window.height() * 0.01),
+            (width * 0.5 - 625., height * 0.01),
             (1250., 70.),
             &images,
-            |ui| draw_resources(ui, &settings, &map, &player, &images),
+            |ui| draw_resources(ui, &settings, &map, &player, &missions.0, &images),
         );
     }
 
@@ -3131,17 +6044,21 @@ pub fn draw_ui(
     // Store whether the next panel should be shown on the right side or not
     let right_side = if let Some(id) = state.planet_hover.or(state.planet_selected) {
         let right_side = state.planet_selected.is_some()
-            || window.cursor_position().map(|pos| pos.x < width * 0.5).unwrap_or_default();
+            || window.cursor_position().map(|pos| pos.x < window.width() * 0.5).unwrap_or_default();
 
         let planet = map.get(id);
 
-        let (window_w, window_h) = if planet.is_moon() {
-            (145., 630.)
+        // The height of the panel depends on the longest column of units
+        let n_rows = Unit::all_valid(planet.is_moon()).iter().map(|c| c.len()).max().unwrap_or(0);
+        let window_h = 90. + 54. * n_rows as f32;
+
+        let window_w = if planet.is_moon() {
+            145.
         } else {
-            (205., 630.)
+            205.
         };
 
-        let mut draw_planet_info = |contexts, id, map, player, extension| {
+        let mut draw_planet_info = |contexts, id, map, player, state, extension| {
             let (window_w2, window_h2) = (518., 216.);
 
             draw_panel(
@@ -3169,14 +6086,25 @@ pub fn draw_ui(
                 ),
                 (window_w2, window_h2),
                 &images,
-                |ui| draw_planet_overview(ui, id, map, player, &settings, &mut message, &images),
+                |ui| {
+                    draw_planet_overview(
+                        ui,
+                        id,
+                        map,
+                        player,
+                        &settings,
+                        state,
+                        &mut message,
+                        &images,
+                    )
+                },
             );
         };
 
         // Check whether there is a report on this planet
         let info = player.last_info(planet, &missions.0);
 
-        if player.controls(planet) || player.spectator {
+        if player.controls(planet) || player.spectator || settings.sandbox.reveal() {
             draw_panel(
                 &mut contexts,
                 "overview",
@@ -3194,7 +6122,7 @@ pub fn draw_ui(
                 |ui| draw_overview(ui, planet, &images),
             );
 
-            draw_planet_info(&mut contexts, id, &mut map, &mut player, true);
+            draw_planet_info(&mut contexts, id, &mut map, &mut player, &mut state, true);
             !right_side
         } else if let Some(info) = info {
             // Don't use has_army since no units is also valid information
@@ -3216,16 +6144,16 @@ pub fn draw_ui(
                     |ui| draw_report_overview(ui, planet, &info, &images),
                 );
 
-                draw_planet_info(&mut contexts, id, &mut map, &mut player, true);
+                draw_planet_info(&mut contexts, id, &mut map, &mut player, &mut state, true);
                 !right_side
             } else if !planet.is_destroyed {
-                draw_planet_info(&mut contexts, id, &mut map, &mut player, false);
+                draw_planet_info(&mut contexts, id, &mut map, &mut player, &mut state, false);
                 !right_side
             } else {
                 right_side
             }
         } else if !planet.is_destroyed {
-            draw_planet_info(&mut contexts, id, &mut map, &mut player, false);
+            draw_planet_info(&mut contexts, id, &mut map, &mut player, &mut state, false);
             !right_side
         } else {
             right_side
@@ -3234,10 +6162,10 @@ pub fn draw_ui(
         true
     };
 
-    if let Some(mission_id) = state.mission_hover {
+    if let Some(mission_id) = state.mission_hover.filter(|_| !too_small) {
         let mission = missions.get(mission_id);
 
-        let (window_w, window_h) = (110., 630.);
+        let (window_w, window_h) = (110., 90. + 54. * Unit::ships().len() as f32);
 
         draw_panel(
             &mut contexts,
@@ -3276,6 +6204,33 @@ pub fn draw_ui(
         );
     }
 
+    // Compare the selected planet's fleet with the army of a hovered enemy planet
+    if let (Some(selected), Some(hovered)) = (state.planet_selected, state.planet_hover) {
+        let (origin, destination) = (map.get(selected), map.get(hovered));
+
+        if !state.mission
+            && !too_small
+            && selected != hovered
+            && player.controls(origin)
+            && !player.controls(destination)
+            && !destination.is_destroyed
+        {
+            if let Some(info) = player.last_info(destination, &missions.0) {
+                let (window_w, window_h) = (420., 230.);
+
+                draw_panel(
+                    &mut contexts,
+                    "army comparison",
+                    "panel",
+                    (width * 0.5 - window_w * 0.5, height * 0.995 - window_h),
+                    (window_w, window_h),
+                    &images,
+                    |ui| draw_army_comparison(ui, origin, destination, &info, &images),
+                );
+            }
+        }
+    }
+
     if state.mission {
         state.end_turn = false;
 
@@ -3298,6 +6253,8 @@ pub fn draw_ui(
                     &mut state,
                     &mut map,
                     &mut player,
+                    &diplomacy,
+                    &roster,
                     is_hovered,
                     &keyboard,
                     &images,
@@ -3310,6 +6267,7 @@ pub fn draw_ui(
 
             // Hide shop if hovering another planet
             if !state.planet_hover.is_some_and(|planet_id| planet_id != id) {
+                let production = player.resource_production(&map.planets, &settings);
                 let planet = map.get_mut(id);
 
                 if player.owns(&planet) || (planet.is_moon() && player.controls(&planet)) {
@@ -3322,7 +6280,17 @@ pub fn draw_ui(
                         (width * 0.5 - window_w * 0.5, height * 0.995 - window_h),
                         (window_w, window_h),
                         &images,
-                        |ui| draw_shop(ui, &mut state, &settings, &mut player, planet, &images),
+                        |ui| {
+                            draw_shop(
+                                ui,
+                                &mut state,
+                                &settings,
+                                &mut player,
+                                planet,
+                                &production,
+                                &images,
+                            )
+                        },
                     );
                 }
             }
@@ -3338,10 +6306,124 @@ pub fn draw_ui(
             &mut contexts,
             "combat report",
             "panel",
-            (width * 0.5 - window_w * 0.5, height * 0.9 - window_h),
+            (width * 0.5 - window_w * 0.5, (height * 0.9 - window_h).max(0.)),
+            (window_w, window_h),
+            &images,
+            |ui| {
+                draw_combat_report(
+                    ui,
+                    &mut state,
+                    &map,
+                    &player,
+                    &roster,
+                    &mut settings,
+                    &mut next_game_state,
+                    &images,
+                )
+            },
+        );
+    }
+
+    if let Some(mut replay) = replay {
+        let (window_w, window_h) = (620., 110.);
+
+        draw_panel(
+            &mut contexts,
+            "replay",
+            "thin panel",
+            ((width - window_w) * 0.5, height * 0.99 - window_h),
+            (window_w, window_h),
+            &images,
+            |ui| draw_replay(ui, &mut replay),
+        );
+    }
+
+    if state.turn_summary.is_some() && *game_state.get() == GameState::Playing {
+        let (window_w, window_h) = (450., 400.);
+
+        draw_panel(
+            &mut contexts,
+            "turn summary",
+            "panel",
+            ((width - window_w) * 0.5, (height - window_h) * 0.5),
+            (window_w, window_h),
+            &images,
+            |ui| draw_turn_summary(ui, &mut state, &settings, &map, &images),
+        );
+    }
+
+    if state.abandon.is_some() && *game_state.get() == GameState::Playing {
+        let (window_w, window_h) = (450., 300.);
+
+        draw_panel(
+            &mut contexts,
+            "abandon",
+            "panel",
+            ((width - window_w) * 0.5, (height - window_h) * 0.5),
+            (window_w, window_h),
+            &images,
+            |ui| {
+                draw_abandon(
+                    ui,
+                    &mut state,
+                    &settings,
+                    &mut map,
+                    &mut player,
+                    &missions,
+                    &mut message,
+                    &images,
+                )
+            },
+        );
+    }
+
+    if state.diplomacy && *game_state.get() == GameState::Playing {
+        let (window_w, window_h) = (500., 400.);
+
+        draw_panel(
+            &mut contexts,
+            "diplomacy",
+            "panel",
+            ((width - window_w) * 0.5, (height - window_h) * 0.5),
+            (window_w, window_h),
+            &images,
+            |ui| {
+                draw_diplomacy(
+                    ui,
+                    &mut state,
+                    &settings,
+                    &mut map,
+                    &player,
+                    &diplomacy,
+                    &roster,
+                    &mut diplomacy_msg,
+                    &images,
+                )
+            },
+        );
+    }
+
+    if *game_state.get() == GameState::Combat && settings.lite_combat {
+        let (window_w, window_h) = (700., 500.);
+
+        draw_panel(
+            &mut contexts,
+            "lite combat",
+            "panel",
+            ((width - window_w) * 0.5, (height - window_h) * 0.5),
             (window_w, window_h),
             &images,
-            |ui| draw_combat_report(ui, &mut state, &map, &player, &images),
+            |ui| {
+                draw_lite_combat(
+                    ui,
+                    &state,
+                    &map,
+                    &player,
+                    &roster,
+                    &mut settings,
+                    &mut next_game_state,
+                )
+            },
         );
     }
 
@@ -3369,3 +6451,204 @@ pub fn draw_ui(
         );
     }
 }
+
+fn draw_encyclopedia_entry(
+    ui: &mut Ui,
+    entry: &EncyclopediaEntry,
+    planet: &Planet,
+    settings: &Settings,
+    images: &ImageIds,
+) {
+    ui.horizontal(|ui| {
+        ui.add_image(images.get(entry.image()), [120.; 2]);
+        ui.vertical(|ui| {
+            ui.label(entry.name());
+            ui.small(entry.category());
+        });
+    });
+
+    ui.separator();
+
+    if let EncyclopediaEntry::Unit(unit) = entry {
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 8.;
+
+            for resource in ResourceName::iter() {
+                ui.add_image(images.get(resource.to_lowername()), [50., 35.]);
+                ui.label(unit.price().get(&resource).to_string());
+                ui.add_space(20.);
+            }
+        });
+
+        match unit {
+            Unit::Ship(_) => ui.small(format!("Requires Shipyard level {}.", unit.production())),
+            Unit::Defense(_) if unit.is_missile() => {
+                ui.small(format!("Requires Missile Silo level {}.", unit.production()))
+            },
+            Unit::Defense(_) => ui.small(format!("Requires Factory level {}.", unit.production())),
+            _ => ui.small(format!("Maximum level {}.", Building::MAX_LEVEL)),
+        };
+
+        ui.separator();
+    }
+
+    ui.small(entry.description());
+
+    if let EncyclopediaEntry::Unit(unit) = entry {
+        ui.add_space(10.);
+
+        if let Unit::Building(building) = unit {
+            // The effects are shown for the first level on the player's home planet
+            let (effect, _) = upgrade_effect(building, 0, planet, settings);
+            ui.small(effect.replacen("Next level", "First level", 1));
+        } else {
+            ui.spacing_mut().item_spacing.y = 0.;
+            draw_combat_stats(ui, unit, images);
+            draw_rapid_fire(ui, unit, images);
+        }
+    }
+}
+
+fn draw_encyclopedia(
+    ui: &mut Ui,
+    state: &mut UiState,
+    planet: &Planet,
+    settings: &Settings,
+    images: &ImageIds,
+) {
+    ui.add_space(5.);
+
+    ui.vertical_centered(|ui| ui.label("Encyclopedia"));
+
+    ui.add_space(10.);
+
+    ui.horizontal(|ui| {
+        ui.add_space(40.);
+        ui.add(
+            egui::TextEdit::singleline(&mut state.encyclopedia_search)
+                .desired_width(250.)
+                .font(TextStyle::Small)
+                .hint_text("Search..."),
+        );
+    });
+
+    ui.add_space(10.);
+
+    let search = state.encyclopedia_search.to_lowercase();
+    let entries = EncyclopediaEntry::all()
+        .into_iter()
+        .filter(|e| {
+            search.is_empty()
+                || e.name().to_lowercase().contains(&search)
+                || e.description().to_lowercase().contains(&search)
+        })
+        .collect::<Vec<_>>();
+
+    ui.horizontal_top(|ui| {
+        ui.add_space(40.);
+
+        ScrollArea::vertical().id_salt("encyclopedia entries").max_height(520.).show(ui, |ui| {
+            ui.set_width(250.);
+
+            if entries.is_empty() {
+                ui.small("No entries found.");
+            }
+
+            for (category, group) in &entries.iter().chunk_by(|e| e.category()) {
+                ui.colored_label(Color32::GRAY, RichText::new(category).small());
+
+                for entry in group {
+                    if ui
+                        .selectable_label(
+                            state.encyclopedia_entry == Some(*entry),
+                            RichText::new(entry.name()).small(),
+                        )
+                        .clicked()
+                    {
+                        state.encyclopedia_entry = Some(*entry);
+                    }
+                }
+
+                ui.add_space(5.);
+            }
+        });
+
+        ui.separator();
+
+        ScrollArea::vertical().id_salt("encyclopedia entry").max_height(520.).show(ui, |ui| {
+            ui.set_width(ui.available_width() - 40.);
+
+            match state.encyclopedia_entry.filter(|e| entries.contains(e)) {
+                Some(entry) => draw_encyclopedia_entry(ui, &entry, planet, settings, images),
+                None => {
+                    ui.small("Select an entry on the left.");
+                },
+            }
+        });
+    });
+
+    ui.with_layout(Layout::bottom_up(Align::Center), |ui| {
+        ui.add_space(30.);
+        if ui.add_custom_button("Close", images).clicked() {
+            state.encyclopedia = false;
+        }
+    });
+}
+
+pub fn draw_encyclopedia_panel(
+    mut contexts: EguiContexts,
+    mut state: ResMut<UiState>,
+    settings: Res<Settings>,
+    map: Res<Map>,
+    player: Res<Player>,
+    game_state: Res<State<GameState>>,
+    images: Res<ImageIds>,
+    window: Single<&Window>,
+) {
+    if !state.encyclopedia || *game_state.get() != GameState::Playing {
+        return;
+    }
+
+    // The zoom factor was already set by draw_ui
+    let zoom = contexts.ctx_mut().unwrap().zoom_factor();
+    let (width, height) = (window.width() / zoom, window.height() / zoom);
+    let (window_w, window_h) = (1000., 700.);
+
+    draw_panel(
+        &mut contexts,
+        "encyclopedia",
+        "panel",
+        ((width - window_w) * 0.5, (height - window_h) * 0.5),
+        (window_w, window_h),
+        &images,
+        |ui| draw_encyclopedia(ui, &mut state, map.get(player.home_planet), &settings, &images),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INCOME: Resources = Resources {
+        metal: 1_000_000,
+        crystal: 1_000_000,
+        deuterium: 1_000_000,
+    };
+
+    #[test]
+    fn build_turns_bound_by_the_production() {
+        let fighter = Unit::Ship(Ship::LightFighter);
+        assert_eq!(build_turns(&[&fighter; 5], 5, &INCOME), (1, "production capacity"));
+        assert_eq!(build_turns(&[&fighter; 12], 5, &INCOME), (3, "production capacity"));
+        assert_eq!(build_turns(&[&fighter], 0, &INCOME), (usize::MAX, "production capacity"));
+    }
+
+    #[test]
+    fn build_turns_bound_by_the_resources() {
+        let cruiser = Unit::Ship(Ship::Cruiser);
+        // The metal of two cruisers (200) takes 8 turns to earn
+        let income = Resources::new(25, 30, 0);
+        assert_eq!(build_turns(&[&cruiser; 2], 10, &income), (8, "income"));
+        assert_eq!(build_turns(&[&cruiser], 10, &Resources::default()), (usize::MAX, "income"));
+    }
+}
@@ -1,19 +1,41 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
 
+use bevy::log::warn;
 use bevy::prelude::Resource;
 use bevy_egui::egui::load::SizedTexture;
 use bevy_egui::egui::*;
+use itertools::Itertools;
 
 use crate::core::constants::BG_COLOR;
 use crate::utils::ToColor32;
 
 #[derive(Resource, Default)]
-pub struct ImageIds(pub HashMap<&'static str, TextureId>);
+pub struct ImageIds {
+    pub images: HashMap<&'static str, TextureId>,
+    /// Texture returned for keys without an image
+    pub placeholder: TextureId,
+    /// Keys requested without an image, to warn only once per key
+    missing: Mutex<HashSet<String>>,
+}
 
 impl ImageIds {
     pub fn get(&self, key: impl Into<String>) -> TextureId {
-        let key = key.into().clone();
-        *self.0.get(key.as_str()).expect(format!("No image found with name: {}", key).as_str())
+        let key = key.into();
+        match self.images.get(key.as_str()) {
+            Some(id) => *id,
+            None => {
+                let mut missing = self.missing.lock().unwrap();
+                if missing.insert(key.clone()) {
+                    warn!(
+                        "No image found with name: {key}. Missing images: {}.",
+                        missing.iter().sorted().join(", ")
+                    );
+                }
+
+                self.placeholder
+            },
+        }
     }
 }
 
@@ -105,6 +127,9 @@ pub trait CustomUi {
         align: Align2,
     ) -> Rect;
     fn cell<R>(&mut self, width: f32, add_contents: impl FnOnce(&mut Ui) -> R) -> R;
+    fn text_width(&self, text: impl Into<String>, style: TextStyle) -> f32;
+    fn add_centering_space(&mut self, width: f32);
+    fn small_wrapped(&mut self, text: impl Into<RichText>) -> Response;
 }
 
 impl CustomUi for Ui {
@@ -214,4 +239,42 @@ impl CustomUi for Ui {
         })
         .inner
     }
+
+    /// Width of a text on a single line
+    fn text_width(&self, text: impl Into<String>, style: TextStyle) -> f32 {
+        self.painter()
+            .layout_no_wrap(text.into(), style.resolve(self.style()), Color32::WHITE)
+            .size()
+            .x
+    }
+
+    /// Space that centers content of `width`. Content wider than the available
+    /// space starts at the left instead of being pushed out of view
+    fn add_centering_space(&mut self, width: f32) {
+        self.add_space(((self.available_width() - width) * 0.5).max(0.));
+    }
+
+    /// Small label that wraps when it doesn't fit in the available width
+    fn small_wrapped(&mut self, text: impl Into<RichText>) -> Response {
+        self.add(Label::new(text.into().small()).wrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_keys_return_the_placeholder() {
+        let images = ImageIds {
+            images: HashMap::from([("probe", TextureId::User(1))]),
+            placeholder: TextureId::User(0),
+            ..Default::default()
+        };
+
+        assert_eq!(images.get("probe"), TextureId::User(1));
+        assert_eq!(images.get("unknown"), TextureId::User(0));
+        assert_eq!(images.get("unknown"), TextureId::User(0));
+        assert_eq!(images.missing.lock().unwrap().len(), 1);
+    }
 }
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use strum_macros::EnumIter;
 
 use crate::core::resources::Resources;
-use crate::core::units::{Description, Price};
+use crate::core::units::{Description, Price, Unit};
 
 #[derive(
     EnumIter, Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize,
@@ -28,6 +28,18 @@ pub enum Building {
 
 impl Building {
     pub const MAX_LEVEL: usize = 5;
+
+    /// Units that require a minimum level of this building to be produced
+    pub fn gated_units(&self) -> Vec<Unit> {
+        match self {
+            Building::Shipyard => Unit::ships(),
+            Building::Factory => Unit::defenses().into_iter().filter(|u| !u.is_missile()).collect(),
+            Building::MissileSilo => {
+                Unit::defenses().into_iter().filter(|u| u.is_missile()).collect()
+            },
+            _ => vec![],
+        }
+    }
 }
 
 impl Description for Building {
@@ -87,7 +99,9 @@ impl Description for Building {
                 "The Sensor Phalanx scans the space around a planet to detect enemy attacks. \
                 A Phalanx of level N scans the space at 0.8 * N AU from the planet, and it only \
                 sees units with production <= N. The objective of the enemy mission is not \
-                revealed. Spying missions are not detected by the Phalanx."
+                revealed. Spying missions are not detected by the Phalanx. Once per turn, the \
+                Phalanx can actively scan an enemy planet in range to reveal its units with \
+                production <= N, at the cost of 250 deuterium."
             },
             Building::JumpGate => {
                 "The Jump Gate enables rapid travel between two owned planets with jump gates \
@@ -5,14 +5,17 @@ use serde::{Deserialize, Serialize};
 use strum::IntoEnumIterator;
 
 use crate::core::combat::stats::CombatStats;
+use crate::core::constants::UPKEEP_PER_PRODUCTION;
 use crate::core::resources::Resources;
 use crate::core::units::buildings::Building;
 use crate::core::units::defense::Defense;
+use crate::core::units::modding::modded;
 use crate::core::units::ships::Ship;
 use crate::utils::NameFromEnum;
 
 pub mod buildings;
 pub mod defense;
+pub mod modding;
 pub mod ships;
 
 pub trait Description {
@@ -36,6 +39,45 @@ pub trait Combat {
     fn fuel_consumption(&self) -> usize {
         0
     }
+    /// Percentage of the damage that bypasses the target's shield
+    fn shield_piercing(&self) -> usize {
+        0
+    }
+}
+
+/// Family of a unit's weapon, used to pick its combat sounds
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WeaponClass {
+    Kinetic,
+    Missile,
+    Laser,
+    Ion,
+    Plasma,
+    Bomb,
+}
+
+impl WeaponClass {
+    pub fn fire_sound(&self) -> &'static str {
+        match self {
+            WeaponClass::Kinetic => "kinetic fire",
+            WeaponClass::Missile => "missile fire",
+            WeaponClass::Laser => "laser fire",
+            WeaponClass::Ion => "ion fire",
+            WeaponClass::Plasma => "plasma fire",
+            WeaponClass::Bomb => "bomb fire",
+        }
+    }
+
+    pub fn impact_sound(&self) -> &'static str {
+        match self {
+            WeaponClass::Kinetic => "kinetic impact",
+            WeaponClass::Missile => "missile impact",
+            WeaponClass::Laser => "laser impact",
+            WeaponClass::Ion => "ion impact",
+            WeaponClass::Plasma => "plasma impact",
+            WeaponClass::Bomb => "bomb impact",
+        }
+    }
 }
 
 pub type Army = HashMap<Unit, usize>;
@@ -44,6 +86,7 @@ pub trait Amount {
     fn amount(&self, unit: &Unit) -> usize;
     fn has_army(&self) -> bool;
     fn total_production(&self) -> usize;
+    fn combat_ships(&self) -> usize;
 }
 
 impl Amount for Army {
@@ -56,6 +99,9 @@ impl Amount for Army {
     fn total_production(&self) -> usize {
         self.iter().filter_map(|(u, c)| (*c > 0).then_some(u.production())).sum()
     }
+    fn combat_ships(&self) -> usize {
+        self.iter().filter_map(|(u, c)| u.is_combat_ship().then_some(*c)).sum()
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize, Deserialize)]
@@ -175,6 +221,31 @@ impl Unit {
         matches!(self, Unit::Building(_))
     }
 
+    /// Weapon of the unit, or None if it doesn't fire in combat
+    pub fn weapon_class(&self) -> Option<WeaponClass> {
+        match self {
+            Unit::Building(_) => None,
+            Unit::Ship(s) => match s {
+                Ship::Probe | Ship::ColonyShip | Ship::Tanker => None,
+                Ship::LightFighter | Ship::HeavyFighter => Some(WeaponClass::Laser),
+                Ship::Destroyer | Ship::Cruiser | Ship::Battleship => Some(WeaponClass::Kinetic),
+                Ship::IonFrigate => Some(WeaponClass::Ion),
+                Ship::Bomber => Some(WeaponClass::Bomb),
+                Ship::Dreadnought | Ship::WarSun => Some(WeaponClass::Plasma),
+            },
+            Unit::Defense(d) => match d {
+                Defense::SpaceDock => None,
+                Defense::Crawler | Defense::GaussCannon => Some(WeaponClass::Kinetic),
+                Defense::RocketLauncher
+                | Defense::AntiballisticMissile
+                | Defense::InterplanetaryMissile => Some(WeaponClass::Missile),
+                Defense::LightLaser | Defense::HeavyLaser => Some(WeaponClass::Laser),
+                Defense::IonCannon => Some(WeaponClass::Ion),
+                Defense::PlasmaTurret => Some(WeaponClass::Plasma),
+            },
+        }
+    }
+
     pub fn is_ship(&self) -> bool {
         matches!(self, Unit::Ship(_))
     }
@@ -209,7 +280,7 @@ impl Unit {
 
     pub fn is_combat_ship(&self) -> bool {
         match self {
-            Unit::Ship(s) if !matches!(s, Ship::Probe | Ship::ColonyShip) => true,
+            Unit::Ship(s) if !matches!(s, Ship::Probe | Ship::ColonyShip | Ship::Tanker) => true,
             _ => false,
         }
     }
@@ -222,6 +293,23 @@ impl Unit {
         }
     }
 
+    /// Resources the unit can plunder (only ships have cargo)
+    pub fn cargo(&self) -> usize {
+        match self {
+            Unit::Ship(s) => s.cargo(),
+            _ => 0,
+        }
+    }
+
+    /// Deuterium paid every turn to maintain the unit (only ships have upkeep)
+    pub fn upkeep(&self) -> usize {
+        if self.is_ship() {
+            self.production() * UPKEEP_PER_PRODUCTION
+        } else {
+            0
+        }
+    }
+
     pub fn get_stat(&self, stat: &CombatStats) -> String {
         let n = match stat {
             CombatStats::Hull => self.hull() as f32,
@@ -257,8 +345,15 @@ impl Unit {
     }
 }
 
+// The stats of units can be overridden by mods (see `modding`), so they
+// are always read through these impls instead of the ones of the variants
+
 impl Description for Unit {
     fn description(&self) -> &str {
+        if let Some(description) = modded(self, |s| s.description.as_deref()) {
+            return description;
+        }
+
         match self {
             Unit::Building(b) => b.description(),
             Unit::Ship(s) => s.description(),
@@ -269,6 +364,10 @@ impl Description for Unit {
 
 impl Price for Unit {
     fn price(&self) -> Resources {
+        if let Some(price) = modded(self, |s| s.price) {
+            return price;
+        }
+
         match self {
             Unit::Building(b) => b.price(),
             Unit::Ship(s) => s.price(),
@@ -279,6 +378,10 @@ impl Price for Unit {
 
 impl Combat for Unit {
     fn hull(&self) -> usize {
+        if let Some(hull) = modded(self, |s| s.hull) {
+            return hull;
+        }
+
         match self {
             Unit::Building(_) => 0,
             Unit::Ship(s) => s.hull(),
@@ -287,6 +390,10 @@ impl Combat for Unit {
     }
 
     fn shield(&self) -> usize {
+        if let Some(shield) = modded(self, |s| s.shield) {
+            return shield;
+        }
+
         match self {
             Unit::Building(_) => 0,
             Unit::Ship(s) => s.shield(),
@@ -295,6 +402,10 @@ impl Combat for Unit {
     }
 
     fn damage(&self) -> usize {
+        if let Some(damage) = modded(self, |s| s.damage) {
+            return damage;
+        }
+
         match self {
             Unit::Building(_) => 0,
             Unit::Ship(s) => s.damage(),
@@ -303,6 +414,10 @@ impl Combat for Unit {
     }
 
     fn rapid_fire(&self) -> HashMap<Unit, usize> {
+        if let Some(rapid_fire) = modded(self, |s| s.rapid_fire.clone()) {
+            return rapid_fire;
+        }
+
         match self {
             Unit::Building(_) => HashMap::new(),
             Unit::Ship(s) => s.rapid_fire(),
@@ -311,6 +426,10 @@ impl Combat for Unit {
     }
 
     fn speed(&self) -> f32 {
+        if let Some(speed) = modded(self, |s| s.speed) {
+            return speed;
+        }
+
         match self {
             Unit::Building(_) => 0.,
             Unit::Ship(s) => s.speed(),
@@ -319,10 +438,25 @@ impl Combat for Unit {
     }
 
     fn fuel_consumption(&self) -> usize {
+        if let Some(fuel_consumption) = modded(self, |s| s.fuel_consumption) {
+            return fuel_consumption;
+        }
+
         match self {
             Unit::Building(_) => 0,
             Unit::Ship(s) => s.fuel_consumption(),
             Unit::Defense(d) => d.fuel_consumption(),
         }
     }
+
+    fn shield_piercing(&self) -> usize {
+        if let Some(shield_piercing) = modded(self, |s| s.shield_piercing) {
+            return shield_piercing;
+        }
+
+        match self {
+            Unit::Ship(s) => s.shield_piercing(),
+            _ => 0,
+        }
+    }
 }
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use bevy::prelude::*;
+use ron::extensions::Extensions;
+use ron::Options;
+use serde::Deserialize;
+
+use crate::core::messages::MessageMsg;
+use crate::core::resources::Resources;
+use crate::core::units::Unit;
+
+/// File with the unit overrides, relative to the working directory
+pub const UNITS_FILE: &str = "mods/units.ron";
+
+/// Overridden stats of a unit. Missing fields keep their built-in value
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct UnitStats {
+    pub price: Option<Resources>,
+    pub hull: Option<usize>,
+    pub shield: Option<usize>,
+    pub damage: Option<usize>,
+    pub rapid_fire: Option<HashMap<Unit, usize>>,
+    pub speed: Option<f32>,
+    pub fuel_consumption: Option<usize>,
+    pub shield_piercing: Option<usize>,
+    pub description: Option<String>,
+}
+
+impl UnitStats {
+    fn validate(&self, unit: &Unit) -> Result<(), String> {
+        let name = unit.to_name();
+
+        if unit.is_building()
+            && (self.hull.is_some()
+                || self.shield.is_some()
+                || self.damage.is_some()
+                || self.rapid_fire.is_some()
+                || self.speed.is_some()
+                || self.fuel_consumption.is_some()
+                || self.shield_piercing.is_some())
+        {
+            return Err(format!("{name}: buildings only accept a price and a description."));
+        }
+
+        if self.hull == Some(0) {
+            return Err(format!("{name}: the hull must be larger than 0."));
+        }
+
+        if self.speed.is_some_and(|s| !s.is_finite() || s < 0.) {
+            return Err(format!("{name}: the speed must be a positive number."));
+        }
+
+        if self.shield_piercing.is_some_and(|p| p > 100) {
+            return Err(format!("{name}: the shield piercing must be a percentage (0-100)."));
+        }
+
+        // A rapid fire of 0% would make the unit shoot forever
+        if let Some((target, rf)) =
+            self.rapid_fire.iter().flatten().find(|(_, rf)| !(1..=100).contains(*rf))
+        {
+            return Err(format!(
+                "{name}: the rapid fire against {} must be between 1 and 100, got {rf}.",
+                target.to_name()
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+static OVERRIDES: OnceLock<HashMap<Unit, UnitStats>> = OnceLock::new();
+
+/// Return the overridden value of a unit's stat, if any
+pub fn modded<T>(unit: &Unit, stat: impl Fn(&'static UnitStats) -> Option<T>) -> Option<T> {
+    OVERRIDES.get()?.get(unit).and_then(stat)
+}
+
+/// Parse and validate the content of a unit overrides file
+pub fn parse_overrides(text: &str) -> Result<HashMap<Unit, UnitStats>, String> {
+    let overrides = Options::default()
+        .with_default_extension(Extensions::IMPLICIT_SOME)
+        .from_str::<HashMap<Unit, UnitStats>>(text)
+        .map_err(|e| format!("Failed to parse {UNITS_FILE}: {e}."))?;
+
+    for (unit, stats) in overrides.iter() {
+        stats.validate(unit).map_err(|e| format!("Invalid {UNITS_FILE}: {e}"))?;
+    }
+
+    Ok(overrides)
+}
+
+/// Load the unit overrides at startup. Invalid files are ignored, keeping the
+/// built-in stats for all units
+pub fn load_unit_overrides(mut message: MessageWriter<MessageMsg>) {
+    let Ok(text) = std::fs::read_to_string(UNITS_FILE) else {
+        return; // No mods installed
+    };
+
+    match parse_overrides(&text) {
+        Ok(overrides) => {
+            info!("Loaded stat overrides for {} units from {UNITS_FILE}.", overrides.len());
+            let _ = OVERRIDES.set(overrides);
+        },
+        Err(e) => {
+            error!("{e}");
+            message.write(MessageMsg::error(e));
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::units::buildings::Building;
+    use crate::core::units::defense::Defense;
+    use crate::core::units::ships::Ship;
+
+    #[test]
+    fn overrides_are_parsed() {
+        let overrides = parse_overrides(
+            r#"{
+                Ship(LightFighter): (
+                    price: (metal: 3000, crystal: 1000, deuterium: 0),
+                    damage: 60,
+                    rapid_fire: { Ship(Probe): 20 },
+                ),
+                Defense(RocketLauncher): (hull: 250),
+                Building(MetalMine): (description: "Extracts metal from the planet's crust."),
+            }"#,
+        )
+        .unwrap();
+
+        let fighter = &overrides[&Unit::Ship(Ship::LightFighter)];
+        assert_eq!(fighter.price, Some(Resources::new(3000, 1000, 0)));
+        assert_eq!(fighter.damage, Some(60));
+        assert_eq!(fighter.rapid_fire.as_ref().unwrap()[&Unit::probe()], 20);
+        assert_eq!(fighter.hull, None);
+
+        assert_eq!(overrides[&Unit::Defense(Defense::RocketLauncher)].hull, Some(250));
+        assert!(overrides[&Unit::Building(Building::MetalMine)].description.is_some());
+    }
+
+    #[test]
+    fn invalid_overrides_are_rejected() {
+        for text in [
+            "{ Building(MetalMine): (hull: 100) }",
+            "{ Ship(Cruiser): (hull: 0) }",
+            "{ Ship(Cruiser): (speed: -1.0) }",
+            "{ Ship(IonFrigate): (shield_piercing: 120) }",
+            "{ Ship(Cruiser): (rapid_fire: { Ship(Probe): 0 }) }",
+            "{ Ship(Cruiser): (armor: 10) }",
+            "{ Ship(Cruiser): (hull: 10 }",
+        ] {
+            assert!(parse_overrides(text).is_err(), "{text}");
+        }
+
+        assert!(parse_overrides("{}").unwrap().is_empty());
+    }
+}
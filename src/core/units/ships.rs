@@ -13,10 +13,12 @@ use crate::core::units::{Combat, Description, Price, Unit};
 pub enum Ship {
     Probe,
     ColonyShip,
+    Tanker,
     LightFighter,
     HeavyFighter,
     Destroyer,
     Cruiser,
+    IonFrigate,
     Bomber,
     Battleship,
     Dreadnought,
@@ -29,16 +31,36 @@ impl Ship {
         match self {
             Ship::Probe => 1,
             Ship::ColonyShip => 2,
+            Ship::Tanker => 2,
             Ship::LightFighter => 1,
             Ship::HeavyFighter => 1,
             Ship::Destroyer => 2,
             Ship::Cruiser => 3,
+            Ship::IonFrigate => 3,
             Ship::Bomber => 3,
             Ship::Battleship => 4,
             Ship::Dreadnought => 4,
             Ship::WarSun => 5,
         }
     }
+
+    /// Amount of resources the ship can carry back after plundering a planet
+    pub fn cargo(&self) -> usize {
+        match self {
+            Ship::Probe => 0,
+            Ship::ColonyShip => 50,
+            Ship::Tanker => 200,
+            Ship::LightFighter => 10,
+            Ship::HeavyFighter => 20,
+            Ship::Destroyer => 30,
+            Ship::Cruiser => 50,
+            Ship::IonFrigate => 40,
+            Ship::Bomber => 20,
+            Ship::Battleship => 80,
+            Ship::Dreadnought => 120,
+            Ship::WarSun => 300,
+        }
+    }
 }
 
 impl Description for Ship {
@@ -49,7 +71,9 @@ impl Description for Ship {
                 this ship only takes part in the first round of any attack (this behavior can be \
                 changed when sending a mission). After the first round, it reports on the enemy \
                 units (prior to any combat) and returns to the planet of origin. The more Probes \
-                survive, the better the intelligence. Probes don't have damage, but can be used \
+                survive, the better the intelligence. On spy missions, every arriving Probe \
+                gathers intelligence, even if it's destroyed afterwards. Every 5 Probes reveal \
+                units one production level higher. Probes don't have damage, but can be used \
                 as fodder in combat. Probes are the fastest ships in the game."
             },
             Ship::ColonyShip => {
@@ -58,6 +82,12 @@ impl Description for Ship {
                 Upon colonizing a planet, the ship is deconstructed. Colony ships are very slow \
                 and consume a lot of fuel."
             },
+            Ship::Tanker => {
+                "The Tanker is a support ship that carries extra deuterium for the fleet it \
+                travels with. Every Tanker in a mission reduces the fleet's fuel consumption, \
+                although each additional Tanker is less effective than the previous one. Tankers \
+                have no weapons and only serve as fodder in combat."
+            },
             Ship::LightFighter => {
                 "Given their relatively low armor and simple weapons systems, Light Fighters \
                 serve best as support ships in battle. Their agility and speed, paired with \
@@ -79,6 +109,12 @@ impl Description for Ship {
                 "Cruisers are the backbone of any military fleet. Heavy armor, powerful weapon \
                 systems, and a high speed make this ship a tough opponent to fight against."
             },
+            Ship::IonFrigate => {
+                "The Ion Frigate fires charged ion beams that partially pass through energy \
+                shields. A part of its damage always lands on the target's hull, which makes it \
+                very effective against heavily shielded units like Dreadnoughts, War Suns and \
+                Plasma Turrets. Its own armor is relatively light for a ship of its class."
+            },
             Ship::Bomber => {
                 "The Bomber is used primarily to destroy planetary buildings and defenses. Its \
                 high Rapid Fire against most defensive structures makes it very effective for \
@@ -112,10 +148,12 @@ impl Price for Ship {
         match self {
             Ship::Probe => Resources::new(0, 20, 0),
             Ship::ColonyShip => Resources::new(100, 400, 200),
+            Ship::Tanker => Resources::new(50, 100, 150),
             Ship::LightFighter => Resources::new(30, 15, 0),
             Ship::HeavyFighter => Resources::new(60, 30, 0),
             Ship::Destroyer => Resources::new(60, 50, 20),
             Ship::Cruiser => Resources::new(100, 90, 0),
+            Ship::IonFrigate => Resources::new(80, 140, 30),
             Ship::Bomber => Resources::new(80, 200, 35),
             Ship::Battleship => Resources::new(150, 170, 100),
             Ship::Dreadnought => Resources::new(250, 200, 150),
@@ -129,10 +167,12 @@ impl Combat for Ship {
         match self {
             Ship::Probe => 10,
             Ship::ColonyShip => 0,
+            Ship::Tanker => 200,
             Ship::LightFighter => 30,
             Ship::HeavyFighter => 100,
             Ship::Destroyer => 150,
             Ship::Cruiser => 350,
+            Ship::IonFrigate => 250,
             Ship::Bomber => 350,
             Ship::Battleship => 500,
             Ship::Dreadnought => 700,
@@ -144,10 +184,12 @@ impl Combat for Ship {
         match self {
             Ship::Probe => 0,
             Ship::ColonyShip => 0,
+            Ship::Tanker => 10,
             Ship::LightFighter => 2,
             Ship::HeavyFighter => 6,
             Ship::Destroyer => 10,
             Ship::Cruiser => 20,
+            Ship::IonFrigate => 20,
             Ship::Bomber => 40,
             Ship::Battleship => 40,
             Ship::Dreadnought => 60,
@@ -159,10 +201,12 @@ impl Combat for Ship {
         match self {
             Ship::Probe => 0,
             Ship::ColonyShip => 0,
+            Ship::Tanker => 0,
             Ship::LightFighter => 5,
             Ship::HeavyFighter => 15,
             Ship::Destroyer => 30,
             Ship::Cruiser => 70,
+            Ship::IonFrigate => 50,
             Ship::Bomber => 70,
             Ship::Battleship => 90,
             Ship::Dreadnought => 100,
@@ -172,7 +216,7 @@ impl Combat for Ship {
 
     fn rapid_fire(&self) -> HashMap<Unit, usize> {
         match self {
-            Ship::Probe | Ship::ColonyShip => HashMap::new(),
+            Ship::Probe | Ship::ColonyShip | Ship::Tanker => HashMap::new(),
             Ship::LightFighter | Ship::HeavyFighter => {
                 HashMap::from([(Unit::Ship(Ship::Probe), 80)])
             },
@@ -183,6 +227,11 @@ impl Combat for Ship {
                 (Unit::Defense(Defense::RocketLauncher), 70),
             ]),
             Ship::Cruiser => HashMap::from([(Unit::Ship(Ship::Probe), 80)]),
+            Ship::IonFrigate => HashMap::from([
+                (Unit::Ship(Ship::Probe), 80),
+                (Unit::Ship(Ship::Dreadnought), 70),
+                (Unit::Defense(Defense::PlasmaTurret), 70),
+            ]),
             Ship::Bomber => HashMap::from([
                 (Unit::Ship(Ship::Probe), 80),
                 (Unit::Defense(Defense::Crawler), 80),
@@ -228,10 +277,12 @@ impl Combat for Ship {
         match self {
             Ship::Probe => 2.7,
             Ship::ColonyShip => 1.5,
+            Ship::Tanker => 2.0,
             Ship::LightFighter => 2.4,
             Ship::HeavyFighter => 2.4,
             Ship::Destroyer => 2.2,
             Ship::Cruiser => 2.0,
+            Ship::IonFrigate => 2.0,
             Ship::Bomber => 1.7,
             Ship::Battleship => 1.9,
             Ship::Dreadnought => 1.8,
@@ -243,14 +294,23 @@ impl Combat for Ship {
         match self {
             Ship::Probe => 1,
             Ship::ColonyShip => 10,
+            Ship::Tanker => 4,
             Ship::LightFighter => 2,
             Ship::HeavyFighter => 3,
             Ship::Destroyer => 3,
             Ship::Cruiser => 6,
+            Ship::IonFrigate => 5,
             Ship::Bomber => 7,
             Ship::Battleship => 8,
             Ship::Dreadnought => 9,
             Ship::WarSun => 12,
         }
     }
+
+    fn shield_piercing(&self) -> usize {
+        match self {
+            Ship::IonFrigate => 40,
+            _ => 0,
+        }
+    }
 }
@@ -55,6 +55,9 @@ fn main() {
     .add_plugins((EguiPlugin::default(), MessagesPlugin::default(), AudioPlugin, TweeningPlugin))
     .add_plugins(GamePlugin);
 
+    #[cfg(any(debug_assertions, feature = "diagnostics"))]
+    app.add_plugins(core::diagnostics::DiagnosticsOverlayPlugin);
+
     #[cfg(target_os = "windows")]
     app.add_systems(Startup, set_window_icon);
 